@@ -0,0 +1,343 @@
+/*! Block devices, for reading and writing virtual devices.
+ *
+ * - [`BlockDevice`] is the I/O model used by readers, such as the import
+ *   scanner, so they can read from files, memory, or other storage.
+ * - [`MemoryDevice`] is an in-memory device, for tests and images already in
+ *   memory.
+ * - With the `std` feature, [`std::fs::File`] is a block device.
+ */
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::endian::FieldPath;
+use crate::phys::{Label, LabelDecode};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A block device, such as a disk, partition, or file of a virtual device.
+ *
+ * - Reads and writes are at byte offsets, and are exact: either all the
+ *   bytes are transferred, or an error is returned.
+ */
+pub trait BlockDevice {
+    /** Returns the size of the device in bytes.
+     *
+     * # Errors
+     *
+     * Returns [`BlockDeviceError`] if the size cannot be determined.
+     */
+    fn size(&self) -> Result<u64, BlockDeviceError>;
+
+    /// Returns the logical sector size of the device in bytes.
+    fn sector_size(&self) -> usize {
+        512
+    }
+
+    /** Reads `buf.len()` bytes at `offset`.
+     *
+     * # Errors
+     *
+     * Returns [`BlockDeviceError`] if the read is out of bounds, or fails.
+     */
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), BlockDeviceError>;
+
+    /** Writes `buf` at `offset`.
+     *
+     * - The default implementation is read only.
+     *
+     * # Errors
+     *
+     * Returns [`BlockDeviceError`] if the device is read only, the write is out
+     * of bounds, or fails.
+     */
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), BlockDeviceError> {
+        let _ = (offset, buf);
+        Err(BlockDeviceError::ReadOnly {})
+    }
+}
+
+/** Reads and decodes a label of a block device.
+ *
+ * - `index` of the label, from 0 to 3.
+ * - The label is read into `buf`, which is kept by the caller, so that
+ *   configurations can be decoded from it.
+ *
+ * # Errors
+ *
+ * Returns [`BlockDeviceError`] if the device is too small, or the read fails.
+ */
+pub fn read_label<D: BlockDevice + ?Sized>(
+    device: &D,
+    index: usize,
+    buf: &mut [u8; Label::LENGTH],
+) -> Result<LabelDecode, BlockDeviceError> {
+    let size = device.size()?;
+    let offsets = match Label::offsets(size) {
+        Ok(v) => v,
+        Err(_) => return Err(BlockDeviceError::TooSmall { size: size }),
+    };
+    let offset = offsets[index];
+
+    device.read_at(offset, buf)?;
+
+    let mut path = FieldPath::new();
+    path.push_index("label", index);
+
+    Ok(LabelDecode::from_bytes_at(buf, offset, &path))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** An in-memory block device.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::{BlockDevice, MemoryDevice};
+ *
+ * let mut device = MemoryDevice::new(vec![0u8; 4096]);
+ * device.write_at(512, &[1, 2, 3]).unwrap();
+ *
+ * let mut buf = [0u8; 4];
+ * device.read_at(511, &mut buf).unwrap();
+ * assert_eq!(buf, [0, 1, 2, 3]);
+ *
+ * assert_eq!(device.size().unwrap(), 4096);
+ * assert!(device.read_at(4094, &mut buf).is_err());
+ * ```
+ */
+#[derive(Debug)]
+pub struct MemoryDevice<T> {
+    data: T,
+    sector_size: usize,
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> MemoryDevice<T> {
+    /// Creates a [`MemoryDevice`] with 512 byte sectors.
+    pub fn new(data: T) -> MemoryDevice<T> {
+        MemoryDevice::with_sector_size(data, 512)
+    }
+
+    /// Creates a [`MemoryDevice`] with a sector size.
+    pub fn with_sector_size(data: T, sector_size: usize) -> MemoryDevice<T> {
+        MemoryDevice {
+            data: data,
+            sector_size: sector_size,
+        }
+    }
+
+    /// Returns the bytes of the device.
+    pub fn data(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Returns the mutable bytes of the device.
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        self.data.as_mut()
+    }
+
+    /// Returns the range of a transfer, if it is in bounds.
+    fn range(&self, offset: u64, length: usize) -> Result<(usize, usize), BlockDeviceError> {
+        let size = self.data.as_ref().len();
+        let start = offset as usize;
+
+        match start.checked_add(length) {
+            Some(end) if offset <= size as u64 && end <= size => Ok((start, end)),
+            _ => Err(BlockDeviceError::OutOfBounds {
+                offset: offset,
+                length: length,
+                size: size as u64,
+            }),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> BlockDevice for MemoryDevice<T> {
+    fn size(&self) -> Result<u64, BlockDeviceError> {
+        Ok(self.data.as_ref().len() as u64)
+    }
+
+    fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), BlockDeviceError> {
+        let (start, end) = self.range(offset, buf.len())?;
+        buf.copy_from_slice(&self.data.as_ref()[start..end]);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), BlockDeviceError> {
+        let (start, end) = self.range(offset, buf.len())?;
+        self.data.as_mut()[start..end].copy_from_slice(buf);
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A file, or an operating system block device, as a [`BlockDevice`].
+ *
+ * - The size is found by seeking to the end, so it works for block devices,
+ *   which have a metadata length of 0.
+ * - On targets other than unix and windows, reads and writes seek, and then
+ *   read or write, so they are not atomic when the file is shared between
+ *   threads.
+ */
+#[cfg(feature = "std")]
+impl BlockDevice for File {
+    fn size(&self) -> Result<u64, BlockDeviceError> {
+        use std::io::{Seek, SeekFrom};
+
+        let mut file = self;
+        Ok(file.seek(SeekFrom::End(0))?)
+    }
+
+    #[cfg(unix)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), BlockDeviceError> {
+        use std::os::unix::fs::FileExt;
+
+        Ok(self.read_exact_at(buf, offset)?)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), BlockDeviceError> {
+        use std::os::windows::fs::FileExt;
+
+        let mut done = 0;
+        while done < buf.len() {
+            match self.seek_read(&mut buf[done..], offset + done as u64)? {
+                0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                n => done += n,
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), BlockDeviceError> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(file.read_exact(buf)?)
+    }
+
+    #[cfg(unix)]
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), BlockDeviceError> {
+        use std::os::unix::fs::FileExt;
+
+        Ok(self.write_all_at(buf, offset)?)
+    }
+
+    #[cfg(windows)]
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), BlockDeviceError> {
+        use std::os::windows::fs::FileExt;
+
+        let mut done = 0;
+        while done < buf.len() {
+            match self.seek_write(&buf[done..], offset + done as u64)? {
+                0 => return Err(io::Error::from(io::ErrorKind::WriteZero).into()),
+                n => done += n,
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), BlockDeviceError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        self.seek(SeekFrom::Start(offset))?;
+        Ok(self.write_all(buf)?)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum BlockDeviceError {
+    /** I/O error.
+     *
+     * - `err` - Error.
+     */
+    #[cfg(feature = "std")]
+    IoError { err: io::Error },
+
+    /** Transfer is out of bounds of the device.
+     *
+     * - `offset` - Byte offset of transfer.
+     * - `length` - Byte length of transfer.
+     * - `size`   - Size of device.
+     */
+    OutOfBounds {
+        offset: u64,
+        length: usize,
+        size: u64,
+    },
+
+    /// Device is read only.
+    ReadOnly {},
+
+    /** Device is too small for labels.
+     *
+     * - `size` - Size of device.
+     */
+    TooSmall { size: u64 },
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for BlockDeviceError {
+    fn from(value: io::Error) -> Self {
+        BlockDeviceError::IoError { err: value }
+    }
+}
+
+impl fmt::Display for BlockDeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            BlockDeviceError::IoError { err } => {
+                write!(f, "Block device I/O error: {err}")
+            }
+            BlockDeviceError::OutOfBounds {
+                offset,
+                length,
+                size,
+            } => {
+                write!(
+                    f,
+                    "Block device error: transfer of {length} bytes at offset {offset} is out of bounds of size {size}"
+                )
+            }
+            BlockDeviceError::ReadOnly {} => {
+                write!(f, "Block device error: read only")
+            }
+            BlockDeviceError::TooSmall { size } => {
+                write!(f, "Block device error: size {size} is too small for labels")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for BlockDeviceError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            BlockDeviceError::IoError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
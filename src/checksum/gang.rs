@@ -0,0 +1,239 @@
+/*! Gang header checksum.
+ *
+ * - The `gang_header` checksum is embedded at the tail end of a gang header,
+ *   like the [`crate::checksum::label`] checksum.
+ * - It uses `sha256`, where the checksum endian encoding is specified using the magic.
+ * - The checksum is calculated over the entire block (including the tail).
+ * - When calculating the checksum, the checksum in the tail is set to the
+ *   verifier of the block pointer to the gang header.
+ *
+ * C reference: `zio_checksum_gang_verifier`
+ */
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::checksum::{sha_256_digest, sha_256_digest_slices};
+use crate::endian::{DecodeError, EncodeError, Encoder, Endian};
+use crate::phys::{ChecksumTail, ChecksumValue, Dva};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Returns the verifier of a gang header.
+ *
+ * - `dva` is the first DVA of the block pointer to the gang header, even if
+ *   the gang header is read from another DVA.
+ * - `birth_txg` is the physical birth transaction group of the block
+ *   pointer, or the logical birth transaction group if it is zero.
+ */
+pub fn gang_verifier(dva: &Dva, birth_txg: u64) -> ChecksumValue {
+    ChecksumValue {
+        words: [dva.vdev as u64, dva.offset_bytes(), birth_txg, 0],
+    }
+}
+
+/** Compute the checksum of the `data` gang header and encode it at the end of
+ * `data`.
+ *
+ * - `verifier` is included in checksum computation (see [`gang_verifier`])
+ * - `endian` specifies the checksum encoding
+ *
+ * # Errors
+ *
+ * Returns [`GangChecksumError`] if slice is too short.
+ */
+pub fn gang_checksum(
+    data: &mut [u8],
+    verifier: &ChecksumValue,
+    endian: Endian,
+) -> Result<(), GangChecksumError> {
+    // Check length.
+    let length = data.len();
+    if length < ChecksumTail::LENGTH {
+        return Err(GangChecksumError::InvalidLength { length: length });
+    }
+
+    // Encode tail.
+    let tail = ChecksumTail {
+        endian: endian,
+        value: ChecksumValue {
+            words: verifier.words,
+        },
+    };
+    let tail_bytes = &mut data[length - ChecksumTail::LENGTH..length];
+    tail.to_bytes(tail_bytes.try_into().unwrap())?;
+
+    // Compute checksum.
+    let checksum = sha_256_digest(data);
+
+    // Encode tail.
+    let tail = ChecksumTail {
+        endian: endian,
+        value: ChecksumValue { words: checksum },
+    };
+    let tail_bytes = &mut data[length - ChecksumTail::LENGTH..length];
+    tail.to_bytes(tail_bytes.try_into().unwrap())?;
+
+    Ok(())
+}
+
+/** Verify the checksum of the `data` gang header.
+ *
+ * - `verifier` is included in checksum computation (see [`gang_verifier`])
+ *
+ * # Errors
+ *
+ * Returns [`GangVerifyError`] if slice is too short, invalid magic, or
+ * mismatched checksum.
+ */
+pub fn gang_verify(data: &[u8], verifier: &ChecksumValue) -> Result<(), GangVerifyError> {
+    // Check length.
+    let length = data.len();
+    if length < ChecksumTail::LENGTH {
+        return Err(GangVerifyError::InvalidLength { length: length });
+    }
+
+    // Decode ChecksumTail.
+    let tail = &data[length - ChecksumTail::LENGTH..length];
+    let tail = ChecksumTail::from_bytes(tail.try_into().unwrap())?;
+
+    // Create verifier checksum.
+    let verifier_checksum = &mut [0; 32];
+
+    // Create encoder.
+    let mut encoder = Encoder::to_bytes(verifier_checksum, tail.endian);
+
+    // Encode verifier.
+    for word in verifier.words {
+        encoder.put_u64(word)?;
+    }
+
+    // Compute checksum.
+    let slices = &[
+        &data[0..length - verifier_checksum.len()],
+        verifier_checksum,
+    ];
+    let computed_checksum = sha_256_digest_slices(slices);
+
+    // Compare checksum.
+    if tail.value.words == computed_checksum {
+        Ok(())
+    } else {
+        Err(GangVerifyError::Mismatch {})
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum GangChecksumError {
+    /** Endian pack error.
+     *
+     * - `err` - [`EncodeError`]
+     */
+    EndianEncodeError { err: EncodeError },
+
+    /** Invalid length.
+     *
+     * - `length` - Length of data.
+     */
+    InvalidLength { length: usize },
+}
+
+impl From<EncodeError> for GangChecksumError {
+    fn from(value: EncodeError) -> Self {
+        GangChecksumError::EndianEncodeError { err: value }
+    }
+}
+
+impl fmt::Display for GangChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GangChecksumError::EndianEncodeError { err } => {
+                write!(f, "Gang checksum Endian pack error: {err}")
+            }
+            GangChecksumError::InvalidLength { length } => {
+                write!(f, "Gang checksum error: invalid length {length}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for GangChecksumError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GangChecksumError::EndianEncodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum GangVerifyError {
+    /** Endian pack error.
+     *
+     * - `err` - [`EncodeError`]
+     */
+    EndianEncodeError { err: EncodeError },
+
+    /** Endian unpack error.
+     *
+     * - `err` - [`DecodeError`]
+     */
+    EndianDecodeError { err: DecodeError },
+
+    /** Invalid length.
+     *
+     * - `length` - Length of data.
+     */
+    InvalidLength { length: usize },
+
+    /** Checksum mismatch. */
+    Mismatch {},
+}
+
+impl From<EncodeError> for GangVerifyError {
+    fn from(value: EncodeError) -> Self {
+        GangVerifyError::EndianEncodeError { err: value }
+    }
+}
+
+impl From<DecodeError> for GangVerifyError {
+    fn from(value: DecodeError) -> Self {
+        GangVerifyError::EndianDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for GangVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GangVerifyError::EndianEncodeError { err } => {
+                write!(f, "Gang verify Endian pack error: {err}")
+            }
+            GangVerifyError::EndianDecodeError { err } => {
+                write!(f, "Gang verify Endian unpack error: {err}")
+            }
+            GangVerifyError::InvalidLength { length } => {
+                write!(f, "Gang verify error: invalid length {length}")
+            }
+            GangVerifyError::Mismatch {} => write!(f, "Gang verify checksum mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for GangVerifyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GangVerifyError::EndianEncodeError { err } => Some(err),
+            GangVerifyError::EndianDecodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+/*! Block decompression.
+ */
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::compression::{
+    gzip_decompress, lz4_decompress, lzjb_decompress, zle_decompress, zstd_decompress,
+};
+use crate::phys::CompressionType;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses the physical data `src` of a block into its logical data
+ * `dst`.
+ *
+ * - `dst` is the logical size of the block.
+ * - `src` may be rounded up to the sector size.
+ * - Data that is not compressed is copied.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if the compression is not supported, or the
+ * data is invalid.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::compression::block_decompress;
+ * use zfs::phys::CompressionType;
+ *
+ * let mut dst = [0u8; 12];
+ *
+ * // LZ4 literals "abc", and a match of 8 bytes at offset 3, then a literal.
+ * let src = [0, 0, 0, 8, 0x34, b'a', b'b', b'c', 3, 0, 0x10, b'x', 0, 0];
+ * block_decompress(&src, &mut dst, CompressionType::Lz4).unwrap();
+ * assert_eq!(&dst, b"abcabcabcabx");
+ *
+ * // LZJB literals "abc", and a match of 9 bytes at offset 3.
+ * let src = [0x08, b'a', b'b', b'c', 0x18, 3];
+ * block_decompress(&src, &mut dst, CompressionType::Lzjb).unwrap();
+ * assert_eq!(&dst, b"abcabcabcabc");
+ *
+ * // Gzip (zlib) of "abcabcabcabc".
+ * let src = [0x78, 0x9c, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x00, 0x1d, 0xe0, 0x04, 0x99];
+ * block_decompress(&src, &mut dst, CompressionType::Gzip6).unwrap();
+ * assert_eq!(&dst, b"abcabcabcabc");
+ *
+ * // ZLE literals "abc", and nine zeroes.
+ * let src = [2, b'a', b'b', b'c', 72];
+ * block_decompress(&src, &mut dst, CompressionType::Zle).unwrap();
+ * assert_eq!(&dst, b"abc\0\0\0\0\0\0\0\0\0");
+ *
+ * // Zstd of "abcabcabcabc", a raw block, after a ZFS header of a frame of
+ * // 25 bytes.
+ * let src = [
+ *     0, 0, 0, 25, 3, 0, 0x29, 0x0a, 0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0x61, 0x00, 0x00, 0x61,
+ *     0x62, 0x63, 0x61, 0x62, 0x63, 0x61, 0x62, 0x63, 0x61, 0x62, 0x63, 0x7f, 0x07, 0x79, 0x96,
+ * ];
+ * block_decompress(&src, &mut dst, CompressionType::Zstd).unwrap();
+ * assert_eq!(&dst, b"abcabcabcabc");
+ *
+ * // Not compressed.
+ * block_decompress(b"abcdefghijkl", &mut dst, CompressionType::Off).unwrap();
+ * assert_eq!(&dst, b"abcdefghijkl");
+ * ```
+ */
+pub fn block_decompress(
+    src: &[u8],
+    dst: &mut [u8],
+    compression: CompressionType,
+) -> Result<(), DecompressError> {
+    match compression {
+        CompressionType::Off => {
+            if src.len() < dst.len() {
+                return Err(DecompressError::EndOfInput {});
+            }
+            dst.copy_from_slice(&src[0..dst.len()]);
+            Ok(())
+        }
+        CompressionType::Empty => {
+            dst.fill(0);
+            Ok(())
+        }
+        CompressionType::Gzip1
+        | CompressionType::Gzip2
+        | CompressionType::Gzip3
+        | CompressionType::Gzip4
+        | CompressionType::Gzip5
+        | CompressionType::Gzip6
+        | CompressionType::Gzip7
+        | CompressionType::Gzip8
+        | CompressionType::Gzip9 => gzip_decompress(src, dst),
+        CompressionType::Lz4 => lz4_decompress(src, dst),
+        CompressionType::Lzjb => lzjb_decompress(src, dst),
+        CompressionType::Zle => zle_decompress(src, dst),
+        CompressionType::Zstd => zstd_decompress(src, dst),
+        _ => Err(DecompressError::Unsupported {
+            compression: compression,
+        }),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum DecompressError {
+    /** Checksum of the decompressed data does not match.
+     *
+     * - `expected` - Checksum of the compressed data.
+     * - `actual`   - Checksum of the decompressed data.
+     */
+    ChecksumMismatch { expected: u32, actual: u32 },
+
+    /// Compressed data ended before the end of the decompressed data.
+    EndOfInput {},
+
+    /// Decompressed data is longer than the output.
+    EndOfOutput {},
+
+    /** Invalid block type of compressed data.
+     *
+     * - `block_type` - Block type.
+     */
+    InvalidBlockType { block_type: u8 },
+
+    /// Invalid Huffman code, FSE table, or sequence of compressed data.
+    InvalidCode {},
+
+    /** Invalid header of compressed data.
+     *
+     * - `header` - Header.
+     */
+    InvalidHeader { header: u16 },
+
+    /** Invalid length of compressed data.
+     *
+     * - `length` - Length.
+     * - `max`    - Maximum length.
+     */
+    InvalidLength { length: usize, max: usize },
+
+    /** Invalid magic number of compressed data.
+     *
+     * - `magic` - Magic number.
+     */
+    InvalidMagic { magic: u32 },
+
+    /** Match is before the start of the decompressed data.
+     *
+     * - `offset` - Offset of the match.
+     */
+    InvalidMatchOffset { offset: usize },
+
+    /** Length of a stored block does not match its complement.
+     *
+     * - `length`     - Length.
+     * - `complement` - Complement of length.
+     */
+    InvalidStoredLength { length: u16, complement: u16 },
+
+    /** Compression is not supported.
+     *
+     * - `compression` - [`CompressionType`]
+     */
+    Unsupported { compression: CompressionType },
+}
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecompressError::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Decompress error: checksum mismatch, expected {expected:#010x}, actual {actual:#010x}"
+                )
+            }
+            DecompressError::EndOfInput {} => {
+                write!(f, "Decompress error: end of input")
+            }
+            DecompressError::EndOfOutput {} => {
+                write!(f, "Decompress error: end of output")
+            }
+            DecompressError::InvalidBlockType { block_type } => {
+                write!(f, "Decompress error: invalid block type {block_type}")
+            }
+            DecompressError::InvalidCode {} => {
+                write!(f, "Decompress error: invalid code")
+            }
+            DecompressError::InvalidHeader { header } => {
+                write!(f, "Decompress error: invalid header {header:#06x}")
+            }
+            DecompressError::InvalidLength { length, max } => {
+                write!(f, "Decompress error: invalid length {length}, max {max}")
+            }
+            DecompressError::InvalidMagic { magic } => {
+                write!(f, "Decompress error: invalid magic {magic:#010x}")
+            }
+            DecompressError::InvalidMatchOffset { offset } => {
+                write!(f, "Decompress error: invalid match offset {offset}")
+            }
+            DecompressError::InvalidStoredLength { length, complement } => {
+                write!(
+                    f,
+                    "Decompress error: invalid stored length {length}, complement {complement}"
+                )
+            }
+            DecompressError::Unsupported { compression } => {
+                write!(f, "Decompress error: unsupported compression {compression}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DecompressError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,543 @@
+/*! Gzip decompression.
+ *
+ * - C reference: `gzip_decompress`, which calls zlib `uncompress`.
+ * - Despite the name, ZFS gzip blocks are zlib streams, and not gzip files.
+ *
+ * ```text
+ * +--------------------------+---+
+ * | CMF (method 8, window)   | 1 |
+ * +--------------------------+---+
+ * | FLG (check, no dict)     | 1 |
+ * +--------------------------+---+
+ * | deflate blocks           |   |
+ * +--------------------------+---+
+ * | Adler-32 (big endian)    | 4 |
+ * +--------------------------+---+
+ * ```
+ *
+ * Each deflate block starts with a bit for the last block, and two bits of
+ * block type:
+ * - 0: Stored, with a byte aligned length, and its complement.
+ * - 1: Literals, lengths, and distances with fixed Huffman codes.
+ * - 2: Literals, lengths, and distances with Huffman codes that are encoded
+ *   at the start of the block.
+ *
+ * Bits are read from the least significant bit of each byte, and Huffman
+ * codes are read from their most significant bit.
+ */
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::compression::DecompressError;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Compression method of a zlib header (deflate).
+const METHOD_DEFLATE: u8 = 8;
+
+/// Maximum window size of a zlib header, as a power of 2 minus 8.
+const WINDOW_MAX: u8 = 7;
+
+/// Flag of a zlib header for a preset dictionary.
+const FLAG_DICT: u8 = 0x20;
+
+/// Maximum number of bits of a Huffman code.
+const MAX_BITS: usize = 15;
+
+/// Maximum number of literal and length codes.
+const MAX_LITERAL_CODES: usize = 288;
+
+/// Maximum number of distance codes.
+const MAX_DISTANCE_CODES: usize = 30;
+
+/// Number of literal and length codes of a fixed block.
+const FIXED_LITERAL_CODES: usize = 288;
+
+/// Number of code length codes.
+const CODE_LENGTH_CODES: usize = 19;
+
+/// End of block symbol.
+const END_OF_BLOCK: u16 = 256;
+
+/// Order of the code lengths of the code length codes.
+const CODE_LENGTH_ORDER: [usize; CODE_LENGTH_CODES] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Base of lengths, for symbols 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+/// Extra bits of lengths, for symbols 257 to 285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base of distances, for symbols 0 to 29.
+const DISTANCE_BASE: [u16; MAX_DISTANCE_CODES] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// Extra bits of distances, for symbols 0 to 29.
+const DISTANCE_EXTRA: [u8; MAX_DISTANCE_CODES] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Largest prime below 2^16, for Adler-32.
+const ADLER_MOD: u32 = 65521;
+
+/// Largest number of bytes before an Adler-32 sum may overflow a [`u32`].
+const ADLER_BLOCK: usize = 5552;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses ZFS gzip `src` into `dst`.
+ *
+ * - `src` may have bytes after the end of the zlib stream.
+ * - The decompressed data may be shorter than `dst`.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if the header, a block, or the checksum is
+ * invalid, a block is past the end of `src` or `dst`, or a match is before
+ * the start of `dst`.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::compression::gzip_decompress;
+ *
+ * // zlib stream of "abcabcabcabc", with a fixed Huffman block.
+ * let src = [
+ *     0x78, 0x9c, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x00, 0x1d, 0xe0, 0x04, 0x99,
+ * ];
+ * let mut dst = [0u8; 12];
+ * gzip_decompress(&src, &mut dst).unwrap();
+ * assert_eq!(&dst, b"abcabcabcabc");
+ *
+ * // Damaged checksum.
+ * let mut src = src;
+ * src[12] ^= 1;
+ * assert!(gzip_decompress(&src, &mut dst).is_err());
+ * ```
+ */
+pub fn gzip_decompress(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    ////////////////////////////////
+    // Decode header.
+    if src.len() < 2 {
+        return Err(DecompressError::EndOfInput {});
+    }
+
+    let cmf = src[0];
+    let flg = src[1];
+    let header = u16::from_be_bytes([cmf, flg]);
+    if (cmf & 0x0f) != METHOD_DEFLATE
+        || (cmf >> 4) > WINDOW_MAX
+        || (flg & FLAG_DICT) != 0
+        || !header.is_multiple_of(31)
+    {
+        return Err(DecompressError::InvalidHeader { header: header });
+    }
+
+    let mut bits = BitReader {
+        src: src,
+        pos: 2,
+        buf: 0,
+        count: 0,
+    };
+    let mut dst_pos = 0;
+
+    ////////////////////////////////
+    // Decode blocks.
+    loop {
+        let last = bits.get(1)?;
+        let block_type = bits.get(2)?;
+
+        match block_type {
+            0 => inflate_stored(&mut bits, dst, &mut dst_pos)?,
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                inflate_codes(&mut bits, dst, &mut dst_pos, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                inflate_codes(&mut bits, dst, &mut dst_pos, &literals, &distances)?;
+            }
+            _ => {
+                return Err(DecompressError::InvalidBlockType {
+                    block_type: block_type as u8,
+                })
+            }
+        }
+
+        if last == 1 {
+            break;
+        }
+    }
+
+    ////////////////////////////////
+    // Verify checksum.
+    let expected = u32::from_be_bytes(bits.get_aligned_bytes(4)?.try_into().unwrap());
+    let actual = adler32(&dst[0..dst_pos]);
+    if expected != actual {
+        return Err(DecompressError::ChecksumMismatch {
+            expected: expected,
+            actual: actual,
+        });
+    }
+
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Reads bits of a deflate stream, from the least significant bit of each byte.
+struct BitReader<'a> {
+    src: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    /** Gets `need` bits, at most 16.
+     */
+    fn get(&mut self, need: u32) -> Result<u32, DecompressError> {
+        while self.count < need {
+            if self.pos >= self.src.len() {
+                return Err(DecompressError::EndOfInput {});
+            }
+            self.buf |= (self.src[self.pos] as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+
+        let value = self.buf & ((1 << need) - 1);
+        self.buf >>= need;
+        self.count -= need;
+
+        Ok(value)
+    }
+
+    /** Drops the bits left in the current byte, and gets `length` bytes.
+     */
+    fn get_aligned_bytes(&mut self, length: usize) -> Result<&[u8], DecompressError> {
+        self.buf = 0;
+        self.count = 0;
+
+        if length > self.src.len() - self.pos {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        self.pos += length;
+        Ok(&self.src[self.pos - length..self.pos])
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A canonical Huffman code.
+ *
+ * - `counts` - Number of symbols of each code length.
+ * - `symbols` - Symbols, ordered by code length, then by value.
+ */
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: [u16; MAX_LITERAL_CODES],
+}
+
+impl Huffman {
+    /** Constructs the code of the code lengths of symbols, where a length of
+     * zero is an unused symbol.
+     *
+     * - Returns the code, and the number of unused codes, which is zero for a
+     *   complete code.
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError::InvalidCode`] if there are more symbols than
+     * codes.
+     */
+    fn from_lengths(lengths: &[u8]) -> Result<(Huffman, i32), DecompressError> {
+        let mut code = Huffman {
+            counts: [0; MAX_BITS + 1],
+            symbols: [0; MAX_LITERAL_CODES],
+        };
+
+        for length in lengths {
+            code.counts[*length as usize] += 1;
+        }
+
+        // No codes.
+        if code.counts[0] as usize == lengths.len() {
+            return Ok((code, 0));
+        }
+
+        // Check that the lengths do not have more symbols than codes.
+        let mut left: i32 = 1;
+        for count in &code.counts[1..] {
+            left <<= 1;
+            left -= *count as i32;
+            if left < 0 {
+                return Err(DecompressError::InvalidCode {});
+            }
+        }
+
+        // Offsets of the first symbol of each length.
+        let mut offsets = [0; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + code.counts[length];
+        }
+
+        for (symbol, length) in lengths.iter().enumerate() {
+            let length = *length as usize;
+            if length != 0 {
+                code.symbols[offsets[length] as usize] = symbol as u16;
+                offsets[length] += 1;
+            }
+        }
+
+        Ok((code, left))
+    }
+
+    /** Constructs the code of the code lengths of symbols, where a length of
+     * zero is an unused symbol.
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError::InvalidCode`] if there are more symbols than
+     * codes, or fewer, unless there is only one symbol.
+     */
+    fn from_lengths_complete(lengths: &[u8]) -> Result<Huffman, DecompressError> {
+        let (code, left) = Huffman::from_lengths(lengths)?;
+
+        if left > 0 && lengths.len() - code.counts[0] as usize != 1 {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        Ok(code)
+    }
+
+    /** Decodes a symbol.
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError::InvalidCode`] if the bits are not a code.
+     */
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, DecompressError> {
+        // Code of the current length, first code of the length, and index of
+        // the first symbol of the length.
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for length in 1..=MAX_BITS {
+            code |= bits.get(1)? as i32;
+
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(DecompressError::InvalidCode {})
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Copies a stored block.
+ */
+fn inflate_stored(
+    bits: &mut BitReader,
+    dst: &mut [u8],
+    dst_pos: &mut usize,
+) -> Result<(), DecompressError> {
+    let header = bits.get_aligned_bytes(4)?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let complement = u16::from_le_bytes([header[2], header[3]]);
+    if length != !complement {
+        return Err(DecompressError::InvalidStoredLength {
+            length: length,
+            complement: complement,
+        });
+    }
+
+    let length = length as usize;
+    if length > dst.len() - *dst_pos {
+        return Err(DecompressError::EndOfOutput {});
+    }
+
+    let data = bits.get_aligned_bytes(length)?;
+    dst[*dst_pos..*dst_pos + length].copy_from_slice(data);
+    *dst_pos += length;
+
+    Ok(())
+}
+
+/** Constructs the codes of a fixed block.
+ */
+fn fixed_codes() -> Result<(Huffman, Huffman), DecompressError> {
+    let mut lengths = [0; FIXED_LITERAL_CODES];
+    lengths[0..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..288].fill(8);
+
+    let (literals, _) = Huffman::from_lengths(&lengths)?;
+    let (distances, _) = Huffman::from_lengths(&[5; MAX_DISTANCE_CODES])?;
+
+    Ok((literals, distances))
+}
+
+/** Decodes the codes of a dynamic block.
+ */
+fn dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman), DecompressError> {
+    ////////////////////////////////
+    // Decode counts.
+    let literal_count = bits.get(5)? as usize + 257;
+    let distance_count = bits.get(5)? as usize + 1;
+    let code_length_count = bits.get(4)? as usize + 4;
+
+    if literal_count > MAX_LITERAL_CODES - 2 || distance_count > MAX_DISTANCE_CODES {
+        return Err(DecompressError::InvalidCode {});
+    }
+
+    ////////////////////////////////
+    // Decode the code of the code lengths.
+    let mut lengths = [0; CODE_LENGTH_CODES];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        lengths[*index] = bits.get(3)? as u8;
+    }
+
+    let (code_lengths, left) = Huffman::from_lengths(&lengths)?;
+    if left != 0 {
+        return Err(DecompressError::InvalidCode {});
+    }
+
+    ////////////////////////////////
+    // Decode the code lengths of the literal and distance codes.
+    let total = literal_count + distance_count;
+    let mut lengths = [0; MAX_LITERAL_CODES + MAX_DISTANCE_CODES];
+    let mut index = 0;
+
+    while index < total {
+        let symbol = code_lengths.decode(bits)?;
+
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                if index == 0 {
+                    return Err(DecompressError::InvalidCode {});
+                }
+                (lengths[index - 1], 3 + bits.get(2)? as usize)
+            }
+            17 => (0, 3 + bits.get(3)? as usize),
+            _ => (0, 11 + bits.get(7)? as usize),
+        };
+
+        if repeat > total - index {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        lengths[index..index + repeat].fill(length);
+        index += repeat;
+    }
+
+    // The end of block symbol must have a code.
+    if lengths[END_OF_BLOCK as usize] == 0 {
+        return Err(DecompressError::InvalidCode {});
+    }
+
+    ////////////////////////////////
+    // Construct codes.
+    let literals = Huffman::from_lengths_complete(&lengths[0..literal_count])?;
+    let distances = Huffman::from_lengths_complete(&lengths[literal_count..total])?;
+
+    Ok((literals, distances))
+}
+
+/** Decodes the literals and matches of a block with Huffman codes.
+ */
+fn inflate_codes(
+    bits: &mut BitReader,
+    dst: &mut [u8],
+    dst_pos: &mut usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), DecompressError> {
+    loop {
+        let symbol = literals.decode(bits)?;
+
+        ////////////////////////////////
+        // Literal.
+        if symbol < END_OF_BLOCK {
+            if *dst_pos >= dst.len() {
+                return Err(DecompressError::EndOfOutput {});
+            }
+            dst[*dst_pos] = symbol as u8;
+            *dst_pos += 1;
+            continue;
+        }
+
+        if symbol == END_OF_BLOCK {
+            return Ok(());
+        }
+
+        ////////////////////////////////
+        // Match.
+        let symbol = (symbol - END_OF_BLOCK - 1) as usize;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(DecompressError::InvalidCode {});
+        }
+        let length = LENGTH_BASE[symbol] as usize + bits.get(LENGTH_EXTRA[symbol] as u32)? as usize;
+
+        let symbol = distances.decode(bits)? as usize;
+        if symbol >= DISTANCE_BASE.len() {
+            return Err(DecompressError::InvalidCode {});
+        }
+        let offset =
+            DISTANCE_BASE[symbol] as usize + bits.get(DISTANCE_EXTRA[symbol] as u32)? as usize;
+
+        if offset > *dst_pos {
+            return Err(DecompressError::InvalidMatchOffset { offset: offset });
+        }
+        if length > dst.len() - *dst_pos {
+            return Err(DecompressError::EndOfOutput {});
+        }
+
+        // Matches may overlap the output, so copy one byte at a time.
+        for _ in 0..length {
+            dst[*dst_pos] = dst[*dst_pos - offset];
+            *dst_pos += 1;
+        }
+    }
+}
+
+/** Computes the Adler-32 checksum of `data`.
+ */
+fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    for block in data.chunks(ADLER_BLOCK) {
+        for byte in block {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= ADLER_MOD;
+        b %= ADLER_MOD;
+    }
+
+    (b << 16) | a
+}
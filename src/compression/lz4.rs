@@ -0,0 +1,151 @@
+/*! LZ4 decompression.
+ *
+ * - C reference: `lz4_decompress_zfs`, `LZ4_uncompress_unknownOutputSize`
+ * - ZFS prefixes the LZ4 block with its length, because the physical size
+ *   of the block is rounded up to sectors.
+ *
+ * ```text
+ * +--------------------+--------+
+ * | length (big endian)|      4 |
+ * +--------------------+--------+
+ * |          LZ4 block | length |
+ * +--------------------+--------+
+ * ```
+ *
+ * Each sequence of the LZ4 block has literals, followed by a match, except
+ * for the last sequence, which only has literals.
+ *
+ * ```text
+ * +---------------------------+-------+
+ * | token                     |     1 |
+ * +---------------------------+-------+
+ * | literal length (optional) |    0+ |
+ * +---------------------------+-------+
+ * | literals                  |    0+ |
+ * +---------------------------+-------+
+ * | offset (little endian)    |     2 |
+ * +---------------------------+-------+
+ * | match length (optional)   |    0+ |
+ * +---------------------------+-------+
+ *
+ * token: literal length (4 bits) | match length - 4 (4 bits)
+ * ```
+ *
+ * A length of 15 in the token is followed by bytes that are added to it,
+ * until a byte that is not 255.
+ */
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::compression::DecompressError;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Minimum length of a match.
+const MATCH_MIN: usize = 4;
+
+/// Length in a token that is followed by more length bytes.
+const LENGTH_MORE: usize = 15;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses ZFS LZ4 `src` into `dst`.
+ *
+ * - `src` starts with the length of the LZ4 block.
+ * - The decompressed data may be shorter than `dst`.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if the length is invalid, a sequence is past
+ * the end of `src` or `dst`, or a match is before the start of `dst`.
+ */
+pub fn lz4_decompress(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    ////////////////////////////////
+    // Decode length.
+    if src.len() < 4 {
+        return Err(DecompressError::EndOfInput {});
+    }
+
+    let length = u32::from_be_bytes(src[0..4].try_into().unwrap()) as usize;
+    if length > src.len() - 4 {
+        return Err(DecompressError::InvalidLength {
+            length: length,
+            max: src.len() - 4,
+        });
+    }
+
+    let src = &src[4..4 + length];
+    let mut src_pos = 0;
+    let mut dst_pos = 0;
+
+    // Use macros for cleaner code.
+    macro_rules! next {
+        () => {{
+            if src_pos >= src.len() {
+                return Err(DecompressError::EndOfInput {});
+            }
+            src_pos += 1;
+            src[src_pos - 1] as usize
+        }};
+    }
+
+    macro_rules! length {
+        ($length:expr) => {{
+            let mut length = $length;
+            if length == LENGTH_MORE {
+                loop {
+                    let more = next!();
+                    length += more;
+                    if more != 255 {
+                        break;
+                    }
+                }
+            }
+            length
+        }};
+    }
+
+    loop {
+        let token = next!();
+
+        ////////////////////////////////
+        // Copy literals.
+        let literals = length!(token >> 4);
+        if literals > src.len() - src_pos {
+            return Err(DecompressError::EndOfInput {});
+        }
+        if literals > dst.len() - dst_pos {
+            return Err(DecompressError::EndOfOutput {});
+        }
+
+        dst[dst_pos..dst_pos + literals].copy_from_slice(&src[src_pos..src_pos + literals]);
+        src_pos += literals;
+        dst_pos += literals;
+
+        ////////////////////////////////
+        // The last sequence only has literals.
+        if src_pos == src.len() {
+            break;
+        }
+
+        ////////////////////////////////
+        // Copy match.
+        let offset = next!() | (next!() << 8);
+        if offset == 0 || offset > dst_pos {
+            return Err(DecompressError::InvalidMatchOffset { offset: offset });
+        }
+
+        let length = length!(token & 0x0f) + MATCH_MIN;
+        if length > dst.len() - dst_pos {
+            return Err(DecompressError::EndOfOutput {});
+        }
+
+        // Matches may overlap the output, so copy one byte at a time.
+        for _ in 0..length {
+            dst[dst_pos] = dst[dst_pos - offset];
+            dst_pos += 1;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+/*! LZJB decompression.
+ *
+ * - C reference: `lzjb_decompress`
+ * - A copy map byte has one bit for each of the next eight items, starting
+ *   from the lowest bit. An item is either a literal byte, or a two byte
+ *   match of a length and an offset back into the output.
+ *
+ * ```text
+ *        1                   0
+ *  5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0
+ * +-------------------------------+
+ * | length (6)|    offset (10)    |
+ * +-------------------------------+
+ *
+ * length: match length - 3
+ * ```
+ */
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::compression::DecompressError;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Bits of the match length.
+const MATCH_BITS: usize = 6;
+
+/// Minimum length of a match.
+const MATCH_MIN: usize = 3;
+
+/// Mask of the match offset.
+const OFFSET_MASK: usize = (1 << (16 - MATCH_BITS)) - 1;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses LZJB `src` into `dst`.
+ *
+ * - Decompresses until `dst` is full.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if `src` ends before `dst` is full, or a match
+ * is before the start of `dst`.
+ */
+pub fn lzjb_decompress(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    let mut src_pos = 0;
+    let mut dst_pos = 0;
+    let mut copy_map = 0;
+    let mut copy_mask = 1 << 7;
+
+    // Use macros for cleaner code.
+    macro_rules! next {
+        () => {{
+            if src_pos >= src.len() {
+                return Err(DecompressError::EndOfInput {});
+            }
+            src_pos += 1;
+            src[src_pos - 1] as usize
+        }};
+    }
+
+    while dst_pos < dst.len() {
+        ////////////////////////////////
+        // Get the next copy map.
+        copy_mask <<= 1;
+        if copy_mask == 1 << 8 {
+            copy_mask = 1;
+            copy_map = next!();
+        }
+
+        ////////////////////////////////
+        // Copy a match or a literal.
+        if (copy_map & copy_mask) != 0 {
+            let a = next!();
+            let b = next!();
+            let length = (a >> (8 - MATCH_BITS)) + MATCH_MIN;
+            let offset = ((a << 8) | b) & OFFSET_MASK;
+
+            if offset > dst_pos {
+                return Err(DecompressError::InvalidMatchOffset { offset: offset });
+            }
+
+            // Matches may overlap the output, so copy one byte at a time.
+            let end = dst.len().min(dst_pos + length);
+            while dst_pos < end {
+                dst[dst_pos] = dst[dst_pos - offset];
+                dst_pos += 1;
+            }
+        } else {
+            dst[dst_pos] = next!() as u8;
+            dst_pos += 1;
+        }
+    }
+
+    Ok(())
+}
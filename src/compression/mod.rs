@@ -0,0 +1,13 @@
+pub mod block;
+pub mod gzip;
+pub mod lz4;
+pub mod lzjb;
+pub mod zle;
+pub mod zstd;
+
+pub use block::{block_decompress, DecompressError};
+pub use gzip::gzip_decompress;
+pub use lz4::lz4_decompress;
+pub use lzjb::lzjb_decompress;
+pub use zle::zle_decompress;
+pub use zstd::{zstd_decompress, ZstdHeader};
@@ -0,0 +1,74 @@
+/*! ZLE (zero length encoding) decompression.
+ *
+ * - C reference: `zle_decompress`
+ * - Each run starts with a length byte. Lengths up to [`ZLE_LEVEL`] are
+ *   followed by that many literal bytes, and longer lengths are runs of
+ *   zeroes.
+ *
+ * ```text
+ * +-------+----------------------------+
+ * | 0..63 | (length + 1) literal bytes |
+ * +-------+----------------------------+
+ * | 64..  | (length + 1 - 64) zeroes   |
+ * +-------+----------------------------+
+ * ```
+ */
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::compression::DecompressError;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Maximum length of a literal run, which is the level of ZLE compression.
+pub const ZLE_LEVEL: usize = 64;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses ZLE `src` into `dst`.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if a run is past the end of `src` or `dst`, or
+ * `dst` is not filled.
+ */
+pub fn zle_decompress(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    let mut src_pos = 0;
+    let mut dst_pos = 0;
+
+    while src_pos < src.len() && dst_pos < dst.len() {
+        let length = 1 + src[src_pos] as usize;
+        src_pos += 1;
+
+        if length <= ZLE_LEVEL {
+            ////////////////////////////////
+            // Copy literal bytes.
+            if src_pos + length > src.len() {
+                return Err(DecompressError::EndOfInput {});
+            }
+            if dst_pos + length > dst.len() {
+                return Err(DecompressError::EndOfOutput {});
+            }
+
+            dst[dst_pos..dst_pos + length].copy_from_slice(&src[src_pos..src_pos + length]);
+            src_pos += length;
+            dst_pos += length;
+        } else {
+            ////////////////////////////////
+            // Fill zeroes.
+            let length = length - ZLE_LEVEL;
+            if dst_pos + length > dst.len() {
+                return Err(DecompressError::EndOfOutput {});
+            }
+
+            dst[dst_pos..dst_pos + length].fill(0);
+            dst_pos += length;
+        }
+    }
+
+    if dst_pos != dst.len() {
+        return Err(DecompressError::EndOfInput {});
+    }
+
+    Ok(())
+}
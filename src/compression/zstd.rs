@@ -0,0 +1,1393 @@
+/*! Zstd decompression.
+ *
+ * - C reference: `zfs_zstd_decompress_level`, which calls
+ *   `ZSTD_decompressDCtx`.
+ * - The Zstandard frame format is described in RFC 8878.
+ * - A ZFS zstd block starts with a header, which is followed by a Zstandard
+ *   frame.
+ *
+ * ```text
+ * +--------------------------------------+---+
+ * | Length of frame (big endian)         | 4 |
+ * +--------------------------------------+---+
+ * | Version (24 bits), level (8 bits)    | 4 |
+ * | (big endian)                         |   |
+ * +--------------------------------------+---+
+ * | Zstandard frame                      |   |
+ * +--------------------------------------+---+
+ * ```
+ *
+ * A frame starts with a magic number, and a header, which is followed by
+ * blocks, and an optional checksum of the decompressed data. Each block
+ * starts with a 3 byte header of a bit for the last block, two bits of block
+ * type, and the length of the block:
+ * - 0: Raw, with bytes that are copied.
+ * - 1: RLE, with a byte that is repeated for the length of the block.
+ * - 2: Compressed, with literals, then sequences of a literal length, a match
+ *   length, and an offset.
+ *
+ * Literals are raw, RLE, or compressed with a Huffman code, in one or four
+ * streams. The codes of the sequences are compressed with FSE (finite state
+ * entropy) tables. Huffman and FSE streams are read backwards, from the most
+ * significant bit of the last byte. Tables may be repeated from the previous
+ * block of the frame.
+ *
+ * Dictionaries are not supported, because ZFS does not use them.
+ */
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::compression::DecompressError;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Length of the ZFS zstd header.
+const HEADER_LENGTH: usize = 8;
+
+/// Magic number of a Zstandard frame.
+const FRAME_MAGIC: u32 = 0xfd2fb528;
+
+/// Frame header descriptor bit that must be zero.
+const FRAME_RESERVED: u8 = 0x08;
+
+/// Frame header descriptor bit for a frame with a single segment.
+const FRAME_SINGLE_SEGMENT: u8 = 0x20;
+
+/// Frame header descriptor bit for a checksum of the decompressed data.
+const FRAME_CHECKSUM: u8 = 0x04;
+
+/// Maximum length of the decompressed data of a block.
+const BLOCK_MAX: usize = 128 * 1024;
+
+/// Maximum number of bits of a Huffman code.
+const HUFFMAN_MAX_BITS: u32 = 11;
+
+/// Maximum number of Huffman weights that are encoded.
+const HUFFMAN_MAX_WEIGHTS: usize = 255;
+
+/// Maximum accuracy of the FSE table of Huffman weights.
+const HUFFMAN_WEIGHTS_ACCURACY_MAX: u32 = 6;
+
+/// Number of Huffman weight symbols of an FSE table.
+const HUFFMAN_WEIGHT_CODES: usize = 13;
+
+/// Maximum number of states of an FSE table.
+const FSE_TABLE_MAX: usize = 1 << 9;
+
+/// Number of literal length codes.
+const LITERAL_LENGTH_CODES: usize = 36;
+
+/// Number of match length codes.
+const MATCH_LENGTH_CODES: usize = 53;
+
+/// Number of offset codes.
+const OFFSET_CODES: usize = 32;
+
+/// Maximum accuracy of an FSE table of literal length codes.
+const LITERAL_LENGTH_ACCURACY_MAX: u32 = 9;
+
+/// Maximum accuracy of an FSE table of match length codes.
+const MATCH_LENGTH_ACCURACY_MAX: u32 = 9;
+
+/// Maximum accuracy of an FSE table of offset codes.
+const OFFSET_ACCURACY_MAX: u32 = 8;
+
+/// Base of literal lengths, for codes 0 to 35.
+const LITERAL_LENGTH_BASE: [u32; LITERAL_LENGTH_CODES] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 20, 22, 24, 28, 32, 40, 48, 64,
+    0x80, 0x100, 0x200, 0x400, 0x800, 0x1000, 0x2000, 0x4000, 0x8000, 0x10000,
+];
+
+/// Extra bits of literal lengths, for codes 0 to 35.
+const LITERAL_LENGTH_EXTRA: [u8; LITERAL_LENGTH_CODES] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 4, 6, 7, 8, 9, 10, 11,
+    12, 13, 14, 15, 16,
+];
+
+/// Base of match lengths, for codes 0 to 52.
+const MATCH_LENGTH_BASE: [u32; MATCH_LENGTH_CODES] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27,
+    28, 29, 30, 31, 32, 33, 34, 35, 37, 39, 41, 43, 47, 51, 59, 67, 83, 99, 0x83, 0x103, 0x203,
+    0x403, 0x803, 0x1003, 0x2003, 0x4003, 0x8003, 0x10003,
+];
+
+/// Extra bits of match lengths, for codes 0 to 52.
+const MATCH_LENGTH_EXTRA: [u8; MATCH_LENGTH_CODES] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    1, 1, 1, 1, 2, 2, 3, 3, 4, 4, 5, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+];
+
+/// Predefined distribution of literal length codes, with an accuracy of 6.
+const LITERAL_LENGTH_DEFAULT: [i16; LITERAL_LENGTH_CODES] = [
+    4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 3, 2, 1, 1, 1, 1, 1,
+    -1, -1, -1, -1,
+];
+
+/// Predefined distribution of match length codes, with an accuracy of 6.
+const MATCH_LENGTH_DEFAULT: [i16; MATCH_LENGTH_CODES] = [
+    1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1, -1, -1,
+];
+
+/// Predefined distribution of offset codes, with an accuracy of 5.
+const OFFSET_DEFAULT: [i16; 29] = [
+    1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1,
+];
+
+/// Initial repeated offsets of a frame.
+const REPEAT_OFFSETS: [usize; 3] = [1, 4, 8];
+
+/// Prime 1 of XXH64.
+const XXH_PRIME_1: u64 = 0x9e3779b185ebca87;
+
+/// Prime 2 of XXH64.
+const XXH_PRIME_2: u64 = 0xc2b2ae3d27d4eb4f;
+
+/// Prime 3 of XXH64.
+const XXH_PRIME_3: u64 = 0x165667b19e3779f9;
+
+/// Prime 4 of XXH64.
+const XXH_PRIME_4: u64 = 0x85ebca77c2b2ae63;
+
+/// Prime 5 of XXH64.
+const XXH_PRIME_5: u64 = 0x27d4eb2f165667c5;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Header of a ZFS zstd block.
+ */
+#[derive(Debug)]
+pub struct ZstdHeader {
+    /// Length of the Zstandard frame after the header.
+    pub length: u32,
+
+    /// Version of zstd that compressed the block.
+    pub version: u32,
+
+    /// Compression level, as a ZFS zstd level.
+    pub level: u8,
+}
+
+impl ZstdHeader {
+    /** Decodes a [`ZstdHeader`].
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError`] if `src` is shorter than the header, or the
+     * length of the frame is past the end of `src`.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::compression::ZstdHeader;
+     *
+     * // Frame of 13 bytes, compressed by zstd 1.5.6 at level 3.
+     * let src = [0, 0, 0, 13, 3, 0, 0x29, 0x0a];
+     * let mut src = src.to_vec();
+     * src.resize(src.len() + 13, 0);
+     *
+     * let header = ZstdHeader::from_bytes(&src).unwrap();
+     * assert_eq!(header.length, 13);
+     * assert_eq!(header.version, 10506);
+     * assert_eq!(header.level, 3);
+     *
+     * // Frame past the end.
+     * assert!(ZstdHeader::from_bytes(&src[0..20]).is_err());
+     * ```
+     */
+    pub fn from_bytes(src: &[u8]) -> Result<ZstdHeader, DecompressError> {
+        if src.len() < HEADER_LENGTH {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let length = u32::from_be_bytes(src[0..4].try_into().unwrap());
+        let version_level = u32::from_be_bytes(src[4..8].try_into().unwrap());
+
+        let max = src.len() - HEADER_LENGTH;
+        if length as usize > max {
+            return Err(DecompressError::InvalidLength {
+                length: length as usize,
+                max: max,
+            });
+        }
+
+        Ok(ZstdHeader {
+            length: length,
+            version: version_level & 0x00ffffff,
+            level: (version_level >> 24) as u8,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decompresses ZFS zstd `src` into `dst`.
+ *
+ * - `src` starts with a [`ZstdHeader`], and may have bytes after the end of
+ *   the frame.
+ * - The decompressed data may be shorter than `dst`.
+ * - The checksum of the decompressed data is verified, if the frame has one.
+ *
+ * # Errors
+ *
+ * Returns [`DecompressError`] if the header, a block, a table, or the checksum
+ * is invalid, a block is past the end of `src` or `dst`, or a match is before
+ * the start of `dst`.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::compression::zstd_decompress;
+ *
+ * // ZFS header of a frame of 44 bytes, compressed by zstd 1.5.6 at level 3.
+ * let mut src = vec![0, 0, 0, 44, 3, 0, 0x29, 0x0a];
+ *
+ * // Zstandard frame with Huffman coded literals, one sequence, and a
+ * // checksum.
+ * src.extend_from_slice(&[
+ *     0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0xfd, 0x00, 0x00, 0xb2, 0xc1, 0x05,
+ *     0x0b, 0xd0, 0xe7, 0x5a, 0x04, 0x6f, 0x77, 0x00, 0x23, 0x4d, 0x94, 0x16,
+ *     0xe5, 0x0c, 0x25, 0x2d, 0xd3, 0xcb, 0x2e, 0xef, 0x32, 0x96, 0x69, 0x01,
+ *     0x00, 0x6c, 0x32, 0xc7, 0xb6, 0x9a, 0x38, 0x9e,
+ * ]);
+ *
+ * let mut dst = [0u8; 46];
+ * zstd_decompress(&src, &mut dst).unwrap();
+ * assert_eq!(&dst, b"the cat sat on the mat, the cat sat on the hat");
+ *
+ * // Damaged checksum.
+ * let mut damaged = src.clone();
+ * damaged[51] ^= 1;
+ * assert!(zstd_decompress(&damaged, &mut dst).is_err());
+ * ```
+ */
+pub fn zstd_decompress(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    let header = ZstdHeader::from_bytes(src)?;
+    let frame = &src[HEADER_LENGTH..HEADER_LENGTH + header.length as usize];
+
+    decompress_frame(frame, dst)
+}
+
+/** Decompresses a Zstandard frame `src` into `dst`.
+ */
+fn decompress_frame(src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+    ////////////////////////////////
+    // Decode frame header.
+    if src.len() < 5 {
+        return Err(DecompressError::EndOfInput {});
+    }
+
+    let magic = u32::from_le_bytes(src[0..4].try_into().unwrap());
+    if magic != FRAME_MAGIC {
+        return Err(DecompressError::InvalidMagic { magic: magic });
+    }
+
+    let descriptor = src[4];
+    if (descriptor & FRAME_RESERVED) != 0 {
+        return Err(DecompressError::InvalidHeader {
+            header: descriptor as u16,
+        });
+    }
+
+    let single_segment = (descriptor & FRAME_SINGLE_SEGMENT) != 0;
+    let window_length = if single_segment { 0 } else { 1 };
+    let dictionary_length = [0, 1, 2, 4][(descriptor & 0x03) as usize];
+    let content_length = match descriptor >> 6 {
+        0 => usize::from(single_segment),
+        1 => 2,
+        2 => 4,
+        _ => 8,
+    };
+
+    let mut pos = 5 + window_length;
+    if pos + dictionary_length + content_length > src.len() {
+        return Err(DecompressError::EndOfInput {});
+    }
+
+    // A dictionary identifier of zero is no dictionary.
+    let dictionary = get_le(&src[pos..pos + dictionary_length]);
+    if dictionary != 0 {
+        return Err(DecompressError::InvalidHeader {
+            header: descriptor as u16,
+        });
+    }
+    pos += dictionary_length + content_length;
+
+    ////////////////////////////////
+    // Decode blocks.
+    let mut decoder = BlockDecoder {
+        literals: [0; BLOCK_MAX],
+        huffman: None,
+        literal_lengths: None,
+        offsets: None,
+        match_lengths: None,
+        repeat_offsets: REPEAT_OFFSETS,
+    };
+    let mut dst_pos = 0;
+
+    loop {
+        if src.len() - pos < 3 {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let block_header = get_le(&src[pos..pos + 3]) as usize;
+        let last = (block_header & 1) != 0;
+        let block_type = ((block_header >> 1) & 0x03) as u8;
+        let length = block_header >> 3;
+        pos += 3;
+
+        // RLE blocks have one byte, and the length is of the decompressed data.
+        let src_length = if block_type == 1 { 1 } else { length };
+        if src_length > src.len() - pos {
+            return Err(DecompressError::EndOfInput {});
+        }
+        let block = &src[pos..pos + src_length];
+
+        match block_type {
+            0 | 1 => {
+                if length > dst.len() - dst_pos {
+                    return Err(DecompressError::EndOfOutput {});
+                }
+                if block_type == 0 {
+                    dst[dst_pos..dst_pos + length].copy_from_slice(block);
+                } else {
+                    dst[dst_pos..dst_pos + length].fill(block[0]);
+                }
+                dst_pos += length;
+            }
+            2 => {
+                if length > BLOCK_MAX {
+                    return Err(DecompressError::InvalidLength {
+                        length: length,
+                        max: BLOCK_MAX,
+                    });
+                }
+                decoder.decompress(block, dst, &mut dst_pos)?;
+            }
+            _ => {
+                return Err(DecompressError::InvalidBlockType {
+                    block_type: block_type,
+                })
+            }
+        }
+        pos += src_length;
+
+        if last {
+            break;
+        }
+    }
+
+    ////////////////////////////////
+    // Verify checksum.
+    if (descriptor & FRAME_CHECKSUM) != 0 {
+        if src.len() - pos < 4 {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let expected = u32::from_le_bytes(src[pos..pos + 4].try_into().unwrap());
+        let actual = xxh64(&dst[0..dst_pos]) as u32;
+        if expected != actual {
+            return Err(DecompressError::ChecksumMismatch {
+                expected: expected,
+                actual: actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** State of the compressed blocks of a frame.
+ *
+ * - `literals` - Decompressed literals of the current block.
+ * - `huffman` - Huffman table of the last block with compressed literals.
+ * - `literal_lengths`, `offsets`, `match_lengths` - FSE tables of the last
+ *   block with sequences.
+ * - `repeat_offsets` - Most recent offsets, most recent first.
+ */
+struct BlockDecoder {
+    literals: [u8; BLOCK_MAX],
+    huffman: Option<HuffmanTable>,
+    literal_lengths: Option<FseTable>,
+    offsets: Option<FseTable>,
+    match_lengths: Option<FseTable>,
+    repeat_offsets: [usize; 3],
+}
+
+impl BlockDecoder {
+    /** Decompresses a compressed block.
+     */
+    fn decompress(
+        &mut self,
+        src: &[u8],
+        dst: &mut [u8],
+        dst_pos: &mut usize,
+    ) -> Result<(), DecompressError> {
+        let (literal_count, pos) = self.decode_literals(src)?;
+        self.decode_sequences(&src[pos..], literal_count, dst, dst_pos)
+    }
+
+    /** Decodes the literals of a block.
+     *
+     * - Returns the number of literals, and the length of the literals
+     *   section.
+     */
+    fn decode_literals(&mut self, src: &[u8]) -> Result<(usize, usize), DecompressError> {
+        if src.is_empty() {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let literals_type = src[0] & 0x03;
+        let size_format = (src[0] >> 2) & 0x03;
+
+        ////////////////////////////////
+        // Raw and RLE literals.
+        if literals_type < 2 {
+            let header_length = match size_format {
+                0 | 2 => 1,
+                1 => 2,
+                _ => 3,
+            };
+            if src.len() < header_length {
+                return Err(DecompressError::EndOfInput {});
+            }
+
+            let header = get_le(&src[0..header_length]) as usize;
+            let count = if header_length == 1 {
+                header >> 3
+            } else {
+                header >> 4
+            };
+            if count > BLOCK_MAX {
+                return Err(DecompressError::InvalidLength {
+                    length: count,
+                    max: BLOCK_MAX,
+                });
+            }
+
+            let src_length = if literals_type == 0 { count } else { 1 };
+            if src_length > src.len() - header_length {
+                return Err(DecompressError::EndOfInput {});
+            }
+
+            let data = &src[header_length..header_length + src_length];
+            if literals_type == 0 {
+                self.literals[0..count].copy_from_slice(data);
+            } else {
+                self.literals[0..count].fill(data[0]);
+            }
+
+            return Ok((count, header_length + src_length));
+        }
+
+        ////////////////////////////////
+        // Compressed literals, with a new Huffman table, or the previous one.
+        let (header_length, length_bits, streams) = match size_format {
+            0 => (3, 10, 1),
+            1 => (3, 10, 4),
+            2 => (4, 14, 4),
+            _ => (5, 18, 4),
+        };
+        if src.len() < header_length {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let header = get_le(&src[0..header_length]);
+        let mask = (1 << length_bits) - 1;
+        let count = ((header >> 4) & mask) as usize;
+        let compressed_length = ((header >> (4 + length_bits)) & mask) as usize;
+
+        if count > BLOCK_MAX {
+            return Err(DecompressError::InvalidLength {
+                length: count,
+                max: BLOCK_MAX,
+            });
+        }
+        if compressed_length > src.len() - header_length {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let mut data = &src[header_length..header_length + compressed_length];
+        if literals_type == 2 {
+            let (table, table_length) = HuffmanTable::from_bytes(data)?;
+            self.huffman = Some(table);
+            data = &data[table_length..];
+        }
+
+        let table = match &self.huffman {
+            Some(v) => v,
+            None => return Err(DecompressError::InvalidCode {}),
+        };
+        let literals = &mut self.literals[0..count];
+
+        if streams == 1 {
+            table.decode_stream(data, literals)?;
+        } else {
+            // Jump table of the lengths of the first three streams.
+            if data.len() < 6 {
+                return Err(DecompressError::EndOfInput {});
+            }
+
+            // The first three streams decode a quarter of the literals, rounded
+            // up, and the last stream decodes the rest.
+            let segment = count.div_ceil(4);
+            if segment * 3 > count {
+                return Err(DecompressError::InvalidCode {});
+            }
+
+            let mut stream_pos = 6;
+            for index in 0..4 {
+                let stream_length = if index < 3 {
+                    u16::from_le_bytes([data[2 * index], data[2 * index + 1]]) as usize
+                } else {
+                    data.len() - stream_pos
+                };
+                if stream_length > data.len() - stream_pos {
+                    return Err(DecompressError::EndOfInput {});
+                }
+
+                let start = segment * index;
+                let end = if index < 3 { start + segment } else { count };
+                table.decode_stream(
+                    &data[stream_pos..stream_pos + stream_length],
+                    &mut literals[start..end],
+                )?;
+                stream_pos += stream_length;
+            }
+        }
+
+        Ok((count, header_length + compressed_length))
+    }
+
+    /** Decodes and executes the sequences of a block.
+     */
+    fn decode_sequences(
+        &mut self,
+        src: &[u8],
+        literal_count: usize,
+        dst: &mut [u8],
+        dst_pos: &mut usize,
+    ) -> Result<(), DecompressError> {
+        ////////////////////////////////
+        // Decode number of sequences.
+        if src.is_empty() {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let (count, mut pos) = match src[0] {
+            0..=127 => (src[0] as usize, 1),
+            128..=254 => {
+                if src.len() < 2 {
+                    return Err(DecompressError::EndOfInput {});
+                }
+                ((((src[0] - 128) as usize) << 8) + src[1] as usize, 2)
+            }
+            255 => {
+                if src.len() < 3 {
+                    return Err(DecompressError::EndOfInput {});
+                }
+                (get_le(&src[1..3]) as usize + 0x7f00, 3)
+            }
+        };
+
+        let mut literal_pos = 0;
+
+        if count > 0 {
+            ////////////////////////////////
+            // Decode tables.
+            if src.len() <= pos {
+                return Err(DecompressError::EndOfInput {});
+            }
+
+            let modes = src[pos];
+            pos += 1;
+            if (modes & 0x03) != 0 {
+                return Err(DecompressError::InvalidCode {});
+            }
+
+            pos += FseTable::from_mode(
+                &mut self.literal_lengths,
+                modes >> 6,
+                &src[pos..],
+                &LITERAL_LENGTH_DEFAULT,
+                6,
+                LITERAL_LENGTH_CODES,
+                LITERAL_LENGTH_ACCURACY_MAX,
+            )?;
+            pos += FseTable::from_mode(
+                &mut self.offsets,
+                (modes >> 4) & 0x03,
+                &src[pos..],
+                &OFFSET_DEFAULT,
+                5,
+                OFFSET_CODES,
+                OFFSET_ACCURACY_MAX,
+            )?;
+            pos += FseTable::from_mode(
+                &mut self.match_lengths,
+                (modes >> 2) & 0x03,
+                &src[pos..],
+                &MATCH_LENGTH_DEFAULT,
+                6,
+                MATCH_LENGTH_CODES,
+                MATCH_LENGTH_ACCURACY_MAX,
+            )?;
+
+            let (literal_lengths, offsets, match_lengths) =
+                match (&self.literal_lengths, &self.offsets, &self.match_lengths) {
+                    (Some(a), Some(b), Some(c)) => (a, b, c),
+                    _ => return Err(DecompressError::InvalidCode {}),
+                };
+
+            ////////////////////////////////
+            // Decode sequences.
+            let mut bits = BackwardBitReader::from_bytes(&src[pos..])?;
+
+            let mut literal_length_state = bits.get(literal_lengths.accuracy_log) as usize;
+            let mut offset_state = bits.get(offsets.accuracy_log) as usize;
+            let mut match_length_state = bits.get(match_lengths.accuracy_log) as usize;
+
+            for index in 0..count {
+                let literal_length_code = literal_lengths.entries[literal_length_state].symbol;
+                let offset_code = offsets.entries[offset_state].symbol;
+                let match_length_code = match_lengths.entries[match_length_state].symbol;
+
+                // Extra bits are read in the order offset, match length,
+                // literal length.
+                let offset_value = (1 << offset_code) + bits.get(offset_code as u32) as usize;
+                let match_length = MATCH_LENGTH_BASE[match_length_code as usize] as usize
+                    + bits.get(MATCH_LENGTH_EXTRA[match_length_code as usize] as u32) as usize;
+                let literal_length = LITERAL_LENGTH_BASE[literal_length_code as usize] as usize
+                    + bits.get(LITERAL_LENGTH_EXTRA[literal_length_code as usize] as u32) as usize;
+
+                // States are updated in the order literal length, match
+                // length, offset, except after the last sequence.
+                if index + 1 < count {
+                    literal_length_state =
+                        literal_lengths.next_state(literal_length_state, &mut bits);
+                    match_length_state = match_lengths.next_state(match_length_state, &mut bits);
+                    offset_state = offsets.next_state(offset_state, &mut bits);
+                }
+
+                if bits.is_overflowed() {
+                    return Err(DecompressError::EndOfInput {});
+                }
+
+                let offset = repeat_offset(&mut self.repeat_offsets, offset_value, literal_length)?;
+
+                // Literals.
+                if literal_length > literal_count - literal_pos {
+                    return Err(DecompressError::InvalidCode {});
+                }
+                if literal_length > dst.len() - *dst_pos {
+                    return Err(DecompressError::EndOfOutput {});
+                }
+                dst[*dst_pos..*dst_pos + literal_length]
+                    .copy_from_slice(&self.literals[literal_pos..literal_pos + literal_length]);
+                literal_pos += literal_length;
+                *dst_pos += literal_length;
+
+                // Match.
+                if offset > *dst_pos {
+                    return Err(DecompressError::InvalidMatchOffset { offset: offset });
+                }
+                if match_length > dst.len() - *dst_pos {
+                    return Err(DecompressError::EndOfOutput {});
+                }
+
+                if offset >= match_length {
+                    dst.copy_within(
+                        *dst_pos - offset..*dst_pos - offset + match_length,
+                        *dst_pos,
+                    );
+                    *dst_pos += match_length;
+                } else {
+                    // Matches may overlap the output, so copy one byte at a
+                    // time.
+                    for _ in 0..match_length {
+                        dst[*dst_pos] = dst[*dst_pos - offset];
+                        *dst_pos += 1;
+                    }
+                }
+            }
+
+            // The bits of the sequences must all be read.
+            if !bits.is_empty() {
+                return Err(DecompressError::InvalidCode {});
+            }
+        }
+
+        ////////////////////////////////
+        // Copy the literals after the last sequence.
+        let length = literal_count - literal_pos;
+        if length > dst.len() - *dst_pos {
+            return Err(DecompressError::EndOfOutput {});
+        }
+        dst[*dst_pos..*dst_pos + length]
+            .copy_from_slice(&self.literals[literal_pos..literal_count]);
+        *dst_pos += length;
+
+        Ok(())
+    }
+}
+
+/** Computes the offset of a sequence, and updates the repeated offsets.
+ *
+ * - Offset values 1 to 3 are repeated offsets, which are shifted by one
+ *   for a sequence without literals.
+ */
+fn repeat_offset(
+    repeats: &mut [usize; 3],
+    offset_value: usize,
+    literal_length: usize,
+) -> Result<usize, DecompressError> {
+    if offset_value > 3 {
+        let offset = offset_value - 3;
+        repeats[2] = repeats[1];
+        repeats[1] = repeats[0];
+        repeats[0] = offset;
+        return Ok(offset);
+    }
+
+    let index = offset_value - usize::from(literal_length != 0);
+    if index == 0 {
+        return Ok(repeats[0]);
+    }
+
+    let offset = if index < 3 {
+        repeats[index]
+    } else {
+        repeats[0] - 1
+    };
+    if offset == 0 {
+        return Err(DecompressError::InvalidMatchOffset { offset: offset });
+    }
+
+    if index > 1 {
+        repeats[2] = repeats[1];
+    }
+    repeats[1] = repeats[0];
+    repeats[0] = offset;
+
+    Ok(offset)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Reads bits of a forward stream, from the least significant bit of each byte.
+struct BitReader<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    /** Gets `count` bits, at most 32, where bits past the end are zero.
+     */
+    fn get(&mut self, count: u32) -> u32 {
+        let value = get_bits(self.src, self.pos, count);
+        self.pos += count as usize;
+        value as u32
+    }
+}
+
+/// Reads bits of a backward stream, from the most significant bit of the last
+/// byte, after the padding to the first set bit.
+struct BackwardBitReader<'a> {
+    src: &'a [u8],
+    pos: isize,
+}
+
+impl BackwardBitReader<'_> {
+    /** Constructs a reader of `src`.
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError`] if `src` is empty, or the last byte is zero.
+     */
+    fn from_bytes(src: &[u8]) -> Result<BackwardBitReader<'_>, DecompressError> {
+        let last = match src.last() {
+            Some(v) => *v,
+            None => return Err(DecompressError::EndOfInput {}),
+        };
+        if last == 0 {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        let padding = last.leading_zeros() as isize + 1;
+
+        Ok(BackwardBitReader {
+            src: src,
+            pos: src.len() as isize * 8 - padding,
+        })
+    }
+
+    /** Gets `count` bits, at most 32, where bits before the start are zero.
+     */
+    fn get(&mut self, count: u32) -> u64 {
+        self.pos -= count as isize;
+
+        if self.pos >= 0 {
+            return get_bits(self.src, self.pos as usize, count);
+        }
+
+        // Bits before the start are the least significant bits.
+        let missing = -self.pos as u32;
+        if missing >= count {
+            return 0;
+        }
+        get_bits(self.src, 0, count - missing) << missing
+    }
+
+    /** Returns true if all the bits have been read.
+     */
+    fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /** Returns true if bits before the start have been read.
+     */
+    fn is_overflowed(&self) -> bool {
+        self.pos < 0
+    }
+}
+
+/** Gets `count` bits of `src` at bit `pos`, at most 64, where bits past the
+ * end are zero.
+ */
+fn get_bits(src: &[u8], pos: usize, count: u32) -> u64 {
+    let mut value = 0;
+    let mut got = 0;
+    let mut pos = pos;
+
+    while got < count {
+        let index = pos / 8;
+        if index >= src.len() {
+            break;
+        }
+
+        let shift = (pos % 8) as u32;
+        let take = (8 - shift).min(count - got);
+        let bits = ((src[index] >> shift) as u64) & ((1 << take) - 1);
+
+        value |= bits << got;
+        got += take;
+        pos += take as usize;
+    }
+
+    value
+}
+
+/** Gets a little endian value of at most 8 bytes.
+ */
+fn get_le(src: &[u8]) -> u64 {
+    src.iter()
+        .rev()
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** An entry of a Huffman table.
+ *
+ * - `symbol` - Symbol.
+ * - `bits` - Number of bits of the code.
+ */
+#[derive(Clone, Copy, Default)]
+struct HuffmanEntry {
+    symbol: u8,
+    bits: u8,
+}
+
+/** A Huffman table, indexed by the next `max_bits` bits of a stream.
+ */
+struct HuffmanTable {
+    max_bits: u32,
+    entries: [HuffmanEntry; 1 << HUFFMAN_MAX_BITS],
+}
+
+impl HuffmanTable {
+    /** Decodes a Huffman table from its weights, which are either FSE
+     * compressed, or 4 bits each.
+     *
+     * - Returns the table, and the length of its description.
+     */
+    fn from_bytes(src: &[u8]) -> Result<(HuffmanTable, usize), DecompressError> {
+        if src.is_empty() {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let header = src[0] as usize;
+        let mut weights = [0; HUFFMAN_MAX_WEIGHTS];
+
+        ////////////////////////////////
+        // Weights of 4 bits each.
+        if header >= 128 {
+            let count = header - 127;
+            let length = count.div_ceil(2);
+            if length > src.len() - 1 {
+                return Err(DecompressError::EndOfInput {});
+            }
+
+            for (index, weight) in weights.iter_mut().take(count).enumerate() {
+                let byte = src[1 + index / 2];
+                *weight = if index % 2 == 0 {
+                    byte >> 4
+                } else {
+                    byte & 0x0f
+                };
+            }
+
+            return Ok((HuffmanTable::from_weights(&weights[0..count])?, 1 + length));
+        }
+
+        ////////////////////////////////
+        // Weights compressed with an FSE table, and two interleaved states.
+        if header > src.len() - 1 {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        let data = &src[1..1 + header];
+        let (table, table_length) =
+            FseTable::from_bytes(data, HUFFMAN_WEIGHT_CODES, HUFFMAN_WEIGHTS_ACCURACY_MAX)?;
+
+        let mut bits = BackwardBitReader::from_bytes(&data[table_length..])?;
+        let mut states = [
+            bits.get(table.accuracy_log) as usize,
+            bits.get(table.accuracy_log) as usize,
+        ];
+        let mut count = 0;
+
+        // Decode until the stream overflows, then decode the other state.
+        loop {
+            let state = count % 2;
+            if count >= HUFFMAN_MAX_WEIGHTS {
+                return Err(DecompressError::InvalidCode {});
+            }
+            weights[count] = table.entries[states[state]].symbol;
+            count += 1;
+            states[state] = table.next_state(states[state], &mut bits);
+
+            if bits.is_overflowed() {
+                if count >= HUFFMAN_MAX_WEIGHTS {
+                    return Err(DecompressError::InvalidCode {});
+                }
+                weights[count] = table.entries[states[1 - state]].symbol;
+                count += 1;
+                break;
+            }
+        }
+
+        Ok((HuffmanTable::from_weights(&weights[0..count])?, 1 + header))
+    }
+
+    /** Constructs a Huffman table from the weights of its symbols.
+     *
+     * - The weight of the last symbol is implied, so that the weights sum to a
+     *   power of 2.
+     * - A weight of zero is an unused symbol, otherwise the number of bits of
+     *   a code is `max_bits + 1 - weight`.
+     */
+    fn from_weights(weights: &[u8]) -> Result<HuffmanTable, DecompressError> {
+        ////////////////////////////////
+        // Compute the weight of the last symbol.
+        let mut sum: u32 = 0;
+        for weight in weights {
+            if *weight as u32 > HUFFMAN_MAX_BITS {
+                return Err(DecompressError::InvalidCode {});
+            }
+            if *weight > 0 {
+                sum += 1 << (*weight - 1);
+            }
+        }
+        if sum == 0 {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        let max_bits = u32::BITS - sum.leading_zeros();
+        if max_bits > HUFFMAN_MAX_BITS {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        let left = (1 << max_bits) - sum;
+        if !left.is_power_of_two() {
+            return Err(DecompressError::InvalidCode {});
+        }
+        let last_weight = (u32::BITS - left.leading_zeros()) as u8;
+
+        ////////////////////////////////
+        // Count the codes of each number of bits.
+        let bits_of = |weight: u8| {
+            if weight == 0 {
+                0
+            } else {
+                max_bits + 1 - weight as u32
+            }
+        };
+
+        let mut counts = [0; HUFFMAN_MAX_BITS as usize + 1];
+        for weight in weights.iter().chain([last_weight].iter()) {
+            counts[bits_of(*weight) as usize] += 1;
+        }
+
+        ////////////////////////////////
+        // Longer codes are first, and each code fills the entries of its
+        // prefix.
+        let mut table = HuffmanTable {
+            max_bits: max_bits,
+            entries: [HuffmanEntry::default(); 1 << HUFFMAN_MAX_BITS],
+        };
+
+        let mut starts = [0; HUFFMAN_MAX_BITS as usize + 1];
+        let mut start = 0;
+        for bits in (1..=max_bits).rev() {
+            starts[bits as usize] = start;
+            let end = start + (counts[bits as usize] << (max_bits - bits));
+            for entry in &mut table.entries[start..end] {
+                entry.bits = bits as u8;
+            }
+            start = end;
+        }
+
+        for (symbol, weight) in weights.iter().chain([last_weight].iter()).enumerate() {
+            let bits = bits_of(*weight);
+            if bits == 0 {
+                continue;
+            }
+
+            let start = starts[bits as usize];
+            let end = start + (1 << (max_bits - bits));
+            for entry in &mut table.entries[start..end] {
+                entry.symbol = symbol as u8;
+            }
+            starts[bits as usize] = end;
+        }
+
+        Ok(table)
+    }
+
+    /** Decodes a stream of Huffman codes into `dst`.
+     *
+     * # Errors
+     *
+     * Returns [`DecompressError::InvalidCode`] if the stream does not have
+     * exactly `dst.len()` codes.
+     */
+    fn decode_stream(&self, src: &[u8], dst: &mut [u8]) -> Result<(), DecompressError> {
+        let mut bits = BackwardBitReader::from_bytes(src)?;
+        let mask = (1 << self.max_bits) - 1;
+        let mut state = bits.get(self.max_bits) as usize;
+
+        for byte in dst.iter_mut() {
+            let entry = self.entries[state];
+            *byte = entry.symbol;
+            state = ((state << entry.bits) | bits.get(entry.bits as u32) as usize) & mask;
+        }
+
+        // The stream ends with the last code, at the start of the state.
+        if bits.pos != -(self.max_bits as isize) {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** An entry of an FSE table.
+ *
+ * - `symbol` - Symbol.
+ * - `bits` - Number of bits to read for the next state.
+ * - `base` - Base of the next state.
+ */
+#[derive(Clone, Copy, Default)]
+struct FseEntry {
+    symbol: u8,
+    bits: u8,
+    base: u16,
+}
+
+/** An FSE table, of `1 << accuracy_log` states.
+ */
+#[derive(Clone, Copy)]
+struct FseTable {
+    accuracy_log: u32,
+    entries: [FseEntry; FSE_TABLE_MAX],
+}
+
+impl FseTable {
+    /** Constructs the table of a sequences section for its mode.
+     *
+     * - 0: Predefined distribution `default` of accuracy `default_log`.
+     * - 1: RLE of one symbol.
+     * - 2: FSE table description.
+     * - 3: Table of the previous block.
+     * - Returns the number of bytes read.
+     */
+    fn from_mode(
+        table: &mut Option<FseTable>,
+        mode: u8,
+        src: &[u8],
+        default: &[i16],
+        default_log: u32,
+        max_symbols: usize,
+        max_accuracy: u32,
+    ) -> Result<usize, DecompressError> {
+        match mode {
+            0 => {
+                *table = Some(FseTable::from_counts(default, default_log)?);
+                Ok(0)
+            }
+            1 => {
+                if src.is_empty() {
+                    return Err(DecompressError::EndOfInput {});
+                }
+                let mut rle = FseTable {
+                    accuracy_log: 0,
+                    entries: [FseEntry::default(); FSE_TABLE_MAX],
+                };
+                rle.entries[0].symbol = src[0];
+                if src[0] as usize >= max_symbols {
+                    return Err(DecompressError::InvalidCode {});
+                }
+                *table = Some(rle);
+                Ok(1)
+            }
+            2 => {
+                let (fse, length) = FseTable::from_bytes(src, max_symbols, max_accuracy)?;
+                *table = Some(fse);
+                Ok(length)
+            }
+            _ => match table {
+                Some(_) => Ok(0),
+                None => Err(DecompressError::InvalidCode {}),
+            },
+        }
+    }
+
+    /** Decodes an FSE table description.
+     *
+     * - Returns the table, and the length of its description.
+     */
+    fn from_bytes(
+        src: &[u8],
+        max_symbols: usize,
+        max_accuracy: u32,
+    ) -> Result<(FseTable, usize), DecompressError> {
+        let mut bits = BitReader { src: src, pos: 0 };
+
+        let accuracy_log = bits.get(4) + 5;
+        if accuracy_log > max_accuracy {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        let mut counts = [0i16; MATCH_LENGTH_CODES];
+        let mut remaining: i32 = 1 << accuracy_log;
+        let mut symbols = 0;
+
+        while remaining > 0 {
+            if symbols >= max_symbols {
+                return Err(DecompressError::InvalidCode {});
+            }
+
+            // Values that are below the threshold are one bit shorter.
+            let count_bits = u32::BITS - ((remaining + 1) as u32).leading_zeros();
+            let mut value = bits.get(count_bits);
+            let lower_mask = (1 << (count_bits - 1)) - 1;
+            let threshold = (1 << count_bits) - 1 - (remaining + 1) as u32;
+
+            if (value & lower_mask) < threshold {
+                bits.pos -= 1;
+                value &= lower_mask;
+            } else if value > lower_mask {
+                value -= threshold;
+            }
+
+            // A count of -1 is less than 1, and counts as 1.
+            let count = value as i32 - 1;
+            remaining -= count.abs();
+            counts[symbols] = count as i16;
+            symbols += 1;
+
+            // A count of zero is followed by 2 bit repeats of more zeroes.
+            if count == 0 {
+                loop {
+                    let repeat = bits.get(2) as usize;
+                    if repeat > max_symbols - symbols {
+                        return Err(DecompressError::InvalidCode {});
+                    }
+                    symbols += repeat;
+                    if repeat != 3 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if remaining != 0 {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        let length = bits.pos.div_ceil(8);
+        if length > src.len() {
+            return Err(DecompressError::EndOfInput {});
+        }
+
+        Ok((
+            FseTable::from_counts(&counts[0..symbols], accuracy_log)?,
+            length,
+        ))
+    }
+
+    /** Constructs an FSE table from the counts of its symbols, which sum to
+     * `1 << accuracy_log`.
+     *
+     * - Symbols with a count of -1 have one state at the end of the table.
+     * - Other symbols are spread through the table, skipping the states at
+     *   the end.
+     */
+    fn from_counts(counts: &[i16], accuracy_log: u32) -> Result<FseTable, DecompressError> {
+        let size = 1 << accuracy_log;
+        let mut table = FseTable {
+            accuracy_log: accuracy_log,
+            entries: [FseEntry::default(); FSE_TABLE_MAX],
+        };
+
+        // Next state of each symbol, which starts at its count.
+        let mut next = [0u16; MATCH_LENGTH_CODES];
+
+        let mut high = size;
+        for (symbol, count) in counts.iter().enumerate() {
+            if *count == -1 {
+                high -= 1;
+                table.entries[high].symbol = symbol as u8;
+                next[symbol] = 1;
+            }
+        }
+
+        let step = (size >> 1) + (size >> 3) + 3;
+        let mask = size - 1;
+        let mut pos = 0;
+
+        for (symbol, count) in counts.iter().enumerate() {
+            if *count <= 0 {
+                continue;
+            }
+
+            next[symbol] = *count as u16;
+            for _ in 0..*count {
+                table.entries[pos].symbol = symbol as u8;
+                loop {
+                    pos = (pos + step) & mask;
+                    if pos < high {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if pos != 0 {
+            return Err(DecompressError::InvalidCode {});
+        }
+
+        for entry in &mut table.entries[0..size] {
+            let state = next[entry.symbol as usize];
+            next[entry.symbol as usize] += 1;
+
+            let bits = accuracy_log - (u16::BITS - 1 - state.leading_zeros());
+            entry.bits = bits as u8;
+            entry.base = (state << bits) - size as u16;
+        }
+
+        Ok(table)
+    }
+
+    /** Gets the next state after `state`.
+     */
+    fn next_state(&self, state: usize, bits: &mut BackwardBitReader) -> usize {
+        let entry = self.entries[state];
+        entry.base as usize + bits.get(entry.bits as u32) as usize
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Computes the XXH64 checksum of `data`, with a seed of zero.
+ */
+fn xxh64(data: &[u8]) -> u64 {
+    let round = |acc: u64, input: u64| {
+        acc.wrapping_add(input.wrapping_mul(XXH_PRIME_2))
+            .rotate_left(31)
+            .wrapping_mul(XXH_PRIME_1)
+    };
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+
+    let mut stripes = data.chunks_exact(32);
+    let mut hash = if data.len() >= 32 {
+        let mut acc = [
+            XXH_PRIME_1.wrapping_add(XXH_PRIME_2),
+            XXH_PRIME_2,
+            0,
+            0u64.wrapping_sub(XXH_PRIME_1),
+        ];
+
+        for stripe in &mut stripes {
+            for (index, value) in acc.iter_mut().enumerate() {
+                *value = round(*value, read_u64(&stripe[8 * index..8 * index + 8]));
+            }
+        }
+
+        let mut hash = acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18));
+        for value in acc {
+            hash = (hash ^ round(0, value))
+                .wrapping_mul(XXH_PRIME_1)
+                .wrapping_add(XXH_PRIME_4);
+        }
+        hash
+    } else {
+        XXH_PRIME_5
+    };
+
+    hash = hash.wrapping_add(data.len() as u64);
+
+    let mut rest = stripes.remainder();
+    while rest.len() >= 8 {
+        hash ^= round(0, read_u64(&rest[0..8]));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME_1)
+            .wrapping_add(XXH_PRIME_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        let value = u32::from_le_bytes(rest[0..4].try_into().unwrap()) as u64;
+        hash ^= value.wrapping_mul(XXH_PRIME_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(XXH_PRIME_2)
+            .wrapping_add(XXH_PRIME_3);
+        rest = &rest[4..];
+    }
+    for byte in rest {
+        hash ^= (*byte as u64).wrapping_mul(XXH_PRIME_5);
+        hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME_1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(XXH_PRIME_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(XXH_PRIME_3);
+    hash ^= hash >> 32;
+
+    hash
+}
@@ -8,6 +8,8 @@ use core::fmt;
 use core::result::Result;
 use core::result::Result::{Err, Ok};
 
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 #[cfg(feature = "std")]
 use std::error;
 
@@ -40,9 +42,191 @@ pub const SWAP: Endian = Endian::Big;
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/** A field of a [`FieldPath`].
+ *
+ * - `name`  - Name of field.
+ * - `index` - Index into field, if it is an array.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub index: Option<usize>,
+}
+
+/** A path of fields, such as `label[2].uber_block[17].ptr.dva[1]`.
+ *
+ * - Has a fixed capacity, so that it does not need to allocate.
+ * - Fields pushed past the capacity are counted, but not stored, and are
+ *   displayed as `...`.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::FieldPath;
+ *
+ * let mut path = FieldPath::new();
+ * path.push_index("label", 2);
+ * path.push_index("uber_block", 17);
+ * path.push("ptr");
+ * path.push_index("dva", 1);
+ *
+ * assert_eq!(format!("{path}"), "label[2].uber_block[17].ptr.dva[1]");
+ *
+ * path.pop();
+ * assert_eq!(format!("{path}"), "label[2].uber_block[17].ptr");
+ * ```
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct FieldPath {
+    fields: [Field; FieldPath::CAPACITY],
+    depth: usize,
+}
+
+impl FieldPath {
+    /// Maximum number of fields stored.
+    pub const CAPACITY: usize = 8;
+
+    /// Instantiates an empty [`FieldPath`].
+    pub const fn new() -> FieldPath {
+        FieldPath {
+            fields: [Field {
+                name: "",
+                index: None,
+            }; FieldPath::CAPACITY],
+            depth: 0,
+        }
+    }
+
+    /// Returns the number of fields in the path.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns true if the path has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.depth == 0
+    }
+
+    /// Returns the stored fields of the path.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields[0..self.depth.min(FieldPath::CAPACITY)]
+    }
+
+    /// Pushes a field.
+    pub fn push(&mut self, name: &'static str) {
+        self.push_field(Field {
+            name: name,
+            index: None,
+        });
+    }
+
+    /// Pushes an array field, with the index into the array.
+    pub fn push_index(&mut self, name: &'static str, index: usize) {
+        self.push_field(Field {
+            name: name,
+            index: Some(index),
+        });
+    }
+
+    /// Pushes a [`Field`].
+    pub fn push_field(&mut self, field: Field) {
+        if self.depth < FieldPath::CAPACITY {
+            self.fields[self.depth] = field;
+        }
+        self.depth += 1;
+    }
+
+    /// Pops the last field. Does nothing for an empty path.
+    pub fn pop(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+impl Default for FieldPath {
+    fn default() -> Self {
+        FieldPath::new()
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, field) in self.fields().iter().enumerate() {
+            if idx > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", field.name)?;
+            if let Some(index) = field.index {
+                write!(f, "[{index}]")?;
+            }
+        }
+
+        if self.depth > FieldPath::CAPACITY {
+            write!(f, "...")?;
+        }
+
+        Ok(())
+    }
+}
+
+/** Location of decoded bytes.
+ *
+ * - `offset` - Absolute byte offset, such as the offset in the device.
+ * - `path`   - Path of the field being decoded.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Location {
+    pub offset: u64,
+    pub path: FieldPath,
+}
+
+impl Location {
+    /** Instantiates a [`Location`].
+     */
+    pub const fn new(offset: u64, path: FieldPath) -> Location {
+        Location {
+            offset: offset,
+            path: path,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = self.offset;
+        if self.path.is_empty() {
+            write!(f, "offset {offset}")
+        } else {
+            write!(f, "offset {offset} ({})", self.path)
+        }
+    }
+}
+
+/** [`Location`] of a decode error.
+ *
+ * - With the `alloc` feature, the [`Location`] is boxed, so that errors that
+ *   carry one stay small.
+ * - Created with `location.into()`, and read like a [`Location`].
+ */
+#[cfg(feature = "alloc")]
+pub type ErrorLocation = Box<Location>;
+
+/** [`Location`] of a decode error.
+ *
+ * - With the `alloc` feature, the [`Location`] is boxed, so that errors that
+ *   carry one stay small.
+ * - Created with `location.into()`, and read like a [`Location`].
+ */
+#[cfg(not(feature = "alloc"))]
+pub type ErrorLocation = Location;
+
+////////////////////////////////////////////////////////////////////////////////
+
 type U16Decoder = fn(bytes: [u8; 2]) -> u16;
 type U32Decoder = fn(bytes: [u8; 4]) -> u32;
 type U64Decoder = fn(bytes: [u8; 8]) -> u64;
+type U128Decoder = fn(bytes: [u8; 16]) -> u128;
 
 /** Decoder for an [`Endian`] type. */
 struct EndianDecoder {
@@ -50,6 +234,7 @@ struct EndianDecoder {
     get_u16: U16Decoder,
     get_u32: U32Decoder,
     get_u64: U64Decoder,
+    get_u128: U128Decoder,
 }
 
 /** [`Endian::Big`] decoder. */
@@ -58,6 +243,7 @@ const BIG_ENDIAN_DECODER: EndianDecoder = EndianDecoder {
     get_u16: u16::from_be_bytes,
     get_u32: u32::from_be_bytes,
     get_u64: u64::from_be_bytes,
+    get_u128: u128::from_be_bytes,
 };
 
 /** [`Endian::Little`] decoder. */
@@ -66,6 +252,7 @@ const LITTLE_ENDIAN_DECODER: EndianDecoder = EndianDecoder {
     get_u16: u16::from_le_bytes,
     get_u32: u32::from_le_bytes,
     get_u64: u64::from_le_bytes,
+    get_u128: u128::from_le_bytes,
 };
 
 /** A binary decoder.
@@ -74,6 +261,9 @@ pub struct Decoder<'a> {
     data: &'a [u8],
     offset: Cell<usize>,
     decoder: EndianDecoder,
+    base: u64,
+    path: Cell<FieldPath>,
+    last: Cell<usize>,
 }
 
 impl fmt::Debug for Decoder<'_> {
@@ -83,6 +273,7 @@ impl fmt::Debug for Decoder<'_> {
             .field("length", &self.data.len())
             .field("offset", &self.offset.get())
             .field("endian", &self.decoder.endian)
+            .field("location", &self.location())
             .finish()
     }
 }
@@ -113,6 +304,42 @@ impl Decoder<'_> {
      * ```
      */
     pub fn from_bytes(data: &[u8], endian: Endian) -> Decoder {
+        Decoder::from_bytes_at(data, endian, Location::default())
+    }
+
+    /** Initializes a [`Decoder`] based on the supplied [`Endian`] value, for
+     * data at the [`Location`].
+     *
+     * - `location.offset` is the absolute offset of the start of `data`.
+     * - `location.path` is the path of the field that `data` is for.
+     *
+     * After an error, [`Decoder::location`] reports the absolute offset of
+     * the value that failed to decode, and the path, including the fields
+     * pushed with [`Decoder::push_field`].
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Decoder, Endian, FieldPath, Location};
+     *
+     * let mut path = FieldPath::new();
+     * path.push_index("label", 2);
+     *
+     * let data = &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc];
+     * let decoder = Decoder::from_bytes_at(data, Endian::Big, Location::new(4096, path));
+     *
+     * decoder.push_field("guid");
+     * decoder.get_u32().unwrap();
+     * assert!(decoder.get_u32().is_err());
+     *
+     * let location = decoder.location();
+     * assert_eq!(location.offset, 4100);
+     * assert_eq!(format!("{}", location.path), "label[2].guid");
+     * ```
+     */
+    pub fn from_bytes_at(data: &[u8], endian: Endian, location: Location) -> Decoder<'_> {
         Decoder {
             data: data,
             offset: Cell::new(0),
@@ -120,6 +347,9 @@ impl Decoder<'_> {
                 Endian::Big => BIG_ENDIAN_DECODER,
                 Endian::Little => LITTLE_ENDIAN_DECODER,
             },
+            base: location.offset,
+            path: Cell::new(location.path),
+            last: Cell::new(0),
         }
     }
 
@@ -197,8 +427,25 @@ impl Decoder<'_> {
      * ```
      */
     pub fn from_u64_magic(data: &[u8], magic: u64) -> Result<Decoder, DecodeError> {
+        Decoder::from_u64_magic_at(data, magic, Location::default())
+    }
+
+    /** Initializes a [`Decoder`] based on the expected magic value, for data
+     * at the [`Location`].
+     *
+     * See [`Decoder::from_bytes_at`] for `location`.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes, or magic is invalid.
+     */
+    pub fn from_u64_magic_at(
+        data: &[u8],
+        magic: u64,
+        location: Location,
+    ) -> Result<Decoder<'_>, DecodeError> {
         // Initialize decoder assuming little endian.
-        let mut decoder = Decoder::from_bytes(data, Endian::Little);
+        let mut decoder = Decoder::from_bytes_at(data, Endian::Little, location);
 
         // Try to get the magic.
         let data_magic = decoder.get_u64()?;
@@ -228,6 +475,9 @@ impl Decoder<'_> {
      * Returns [`DecodeError`] if there are not enough bytes to decode.
      */
     fn check_need(&self, count: usize) -> Result<(), DecodeError> {
+        // Keep track of the start of the last value, for the location.
+        self.last.set(self.offset.get());
+
         if self.len() >= count {
             Ok(())
         } else {
@@ -239,6 +489,51 @@ impl Decoder<'_> {
         }
     }
 
+    /** Returns the [`Location`] of the last decoded value.
+     *
+     * - The offset is the absolute offset of the last value decoded, or
+     *   attempted to be decoded. After an error, it is the offset of the
+     *   value that failed to decode.
+     * - The path includes the fields that are pushed, and not yet popped.
+     */
+    pub fn location(&self) -> Location {
+        Location {
+            offset: self.base + self.last.get() as u64,
+            path: self.path.get(),
+        }
+    }
+
+    /** Pushes a field to the path of the [`Location`].
+     *
+     * Decoders of nested structures push a field before decoding it, and pop
+     * it after decoding it successfully, so that on error the path is of the
+     * field that failed to decode.
+     */
+    pub fn push_field(&self, name: &'static str) {
+        let mut path = self.path.get();
+        path.push(name);
+        self.path.set(path);
+    }
+
+    /** Pushes an array field, with the index into the array, to the path of
+     * the [`Location`].
+     *
+     * See [`Decoder::push_field`].
+     */
+    pub fn push_index(&self, name: &'static str, index: usize) {
+        let mut path = self.path.get();
+        path.push_index(name, index);
+        self.path.set(path);
+    }
+
+    /** Pops the last field from the path of the [`Location`].
+     */
+    pub fn pop_field(&self) {
+        let mut path = self.path.get();
+        path.pop();
+        self.path.set(path);
+    }
+
     /** Returns the source data length.
      *
      * Remains unchanged while decoding values.
@@ -382,6 +677,12 @@ impl Decoder<'_> {
         }
 
         if x != 0 {
+            // Point at the first non-zero byte.
+            let first = self.data[offset..offset + count]
+                .iter()
+                .position(|b| *b != 0);
+            self.last.set(offset + first.unwrap_or_default());
+
             return Err(DecodeError::NonZeroPadding {});
         }
 
@@ -459,6 +760,23 @@ impl Decoder<'_> {
         Ok(<[u8; 8]>::try_from(&self.data[start..end]).unwrap())
     }
 
+    /** Returns 16 bytes.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     */
+    pub fn get_16_bytes(&self) -> Result<[u8; 16], DecodeError> {
+        self.check_need(16)?;
+
+        let start = self.offset.get();
+        let end = start + 16;
+
+        self.offset.set(end);
+
+        Ok(<[u8; 16]>::try_from(&self.data[start..end]).unwrap())
+    }
+
     /** Decodes bytes.
      *
      * [`Endian`] does not matter for order of decoded bytes.
@@ -678,6 +996,223 @@ impl Decoder<'_> {
     pub fn get_u64(&self) -> Result<u64, DecodeError> {
         Ok((self.decoder.get_u64)(self.get_8_bytes()?))
     }
+
+    /** Decodes a [`u128`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Decoder, Endian};
+     *
+     * // Some bytes.
+     * let data = &[
+     *     0xf2, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+     *     0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88,
+     * ];
+     *
+     * // Create decoder.
+     * let decoder = Decoder::from_bytes(data, Endian::Big);
+     *
+     * // Get values.
+     * assert_eq!(
+     *     decoder.get_u128().unwrap(),
+     *     0xf23456789abcdef01122334455667788
+     * );
+     * ```
+     */
+    pub fn get_u128(&self) -> Result<u128, DecodeError> {
+        Ok((self.decoder.get_u128)(self.get_16_bytes()?))
+    }
+
+    /** Decodes an [`i8`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     */
+    pub fn get_i8(&self) -> Result<i8, DecodeError> {
+        Ok(self.get_u8()? as i8)
+    }
+
+    /** Decodes an [`i16`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     */
+    pub fn get_i16(&self) -> Result<i16, DecodeError> {
+        Ok(self.get_u16()? as i16)
+    }
+
+    /** Decodes an [`i32`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     */
+    pub fn get_i32(&self) -> Result<i32, DecodeError> {
+        Ok(self.get_u32()? as i32)
+    }
+
+    /** Decodes an [`i64`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Decoder, Endian};
+     *
+     * // Some bytes.
+     * let data = &[
+     *     0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+     *     0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+     * ];
+     *
+     * // Create decoder.
+     * let decoder = Decoder::from_bytes(data, Endian::Big);
+     * assert_eq!(decoder.get_i64().unwrap(), -2);
+     *
+     * // Create decoder.
+     * let decoder = Decoder::from_bytes(&data[8..16], Endian::Little);
+     * assert_eq!(decoder.get_i64().unwrap(), -2);
+     * ```
+     */
+    pub fn get_i64(&self) -> Result<i64, DecodeError> {
+        Ok(self.get_u64()? as i64)
+    }
+
+    /** Decodes an [`i128`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode.
+     */
+    pub fn get_i128(&self) -> Result<i128, DecodeError> {
+        Ok(self.get_u128()? as i128)
+    }
+
+    /** Decodes an array of N [`u64`].
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode. Nothing
+     * is decoded on error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Decoder, Endian};
+     *
+     * // Some bytes.
+     * let data = &[
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+     * ];
+     *
+     * // Create decoder.
+     * let decoder = Decoder::from_bytes(data, Endian::Big);
+     *
+     * // Get values.
+     * let a: [u64; 2] = decoder.get_u64_array().unwrap();
+     * assert_eq!(a, [1, 2]);
+     *
+     * // Need 8 more bytes.
+     * assert!(decoder.get_u64_array::<2>().is_err());
+     * assert_eq!(decoder.len(), 8);
+     * ```
+     */
+    pub fn get_u64_array<const N: usize>(&self) -> Result<[u64; N], DecodeError> {
+        let mut values = [0; N];
+        self.get_u64_slice(&mut values)?;
+        Ok(values)
+    }
+
+    /** Decodes [`u16`] values to fill the `values` slice.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode. Nothing
+     * is decoded on error.
+     */
+    pub fn get_u16_slice(&self, values: &mut [u16]) -> Result<(), DecodeError> {
+        self.check_need(values.len() * 2)?;
+
+        for value in values.iter_mut() {
+            *value = self.get_u16()?;
+        }
+
+        Ok(())
+    }
+
+    /** Decodes [`u32`] values to fill the `values` slice.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode. Nothing
+     * is decoded on error.
+     */
+    pub fn get_u32_slice(&self, values: &mut [u32]) -> Result<(), DecodeError> {
+        self.check_need(values.len() * 4)?;
+
+        for value in values.iter_mut() {
+            *value = self.get_u32()?;
+        }
+
+        Ok(())
+    }
+
+    /** Decodes [`u64`] values to fill the `values` slice.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError`] if there are not enough bytes to decode. Nothing
+     * is decoded on error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Decoder, Endian};
+     *
+     * // Some bytes.
+     * let data = &[
+     *     0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     *     0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     * ];
+     *
+     * // Create decoder.
+     * let decoder = Decoder::from_bytes(data, Endian::Little);
+     *
+     * // Get values.
+     * let mut values = [0; 2];
+     * decoder.get_u64_slice(&mut values).unwrap();
+     * assert_eq!(values, [1, 2]);
+     * ```
+     */
+    pub fn get_u64_slice(&self, values: &mut [u64]) -> Result<(), DecodeError> {
+        self.check_need(values.len() * 8)?;
+
+        for value in values.iter_mut() {
+            *value = self.get_u64()?;
+        }
+
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -685,6 +1220,7 @@ impl Decoder<'_> {
 type U16Encoder = fn(value: u16) -> [u8; 2];
 type U32Encoder = fn(value: u32) -> [u8; 4];
 type U64Encoder = fn(value: u64) -> [u8; 8];
+type U128Encoder = fn(value: u128) -> [u8; 16];
 
 /** Encoder for an [`Endian`] type. */
 struct EndianEncoder {
@@ -692,6 +1228,7 @@ struct EndianEncoder {
     put_u16: U16Encoder,
     put_u32: U32Encoder,
     put_u64: U64Encoder,
+    put_u128: U128Encoder,
 }
 
 /** [`Endian::Big`] encoder. */
@@ -700,6 +1237,7 @@ const BIG_ENDIAN_ENCODER: EndianEncoder = EndianEncoder {
     put_u16: u16::to_be_bytes,
     put_u32: u32::to_be_bytes,
     put_u64: u64::to_be_bytes,
+    put_u128: u128::to_be_bytes,
 };
 
 /** [`Endian::Little`] encoder. */
@@ -708,6 +1246,7 @@ const LITTLE_ENDIAN_ENCODER: EndianEncoder = EndianEncoder {
     put_u16: u16::to_le_bytes,
     put_u32: u32::to_le_bytes,
     put_u64: u64::to_le_bytes,
+    put_u128: u128::to_le_bytes,
 };
 
 /** A binary encoder.
@@ -887,6 +1426,45 @@ impl Encoder<'_> {
         self.offset
     }
 
+    /** Seeks to an offset, to overwrite values that were encoded.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if the offset is past the end of the data.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Encoder, Endian};
+     *
+     * let data = &mut [0; 6];
+     * let mut encoder = Encoder::to_bytes(data, Endian::Little);
+     *
+     * encoder.put_u16(1).unwrap();
+     * encoder.put_u32(2).unwrap();
+     *
+     * // Overwrite first value.
+     * encoder.seek(0).unwrap();
+     * encoder.put_u16(3).unwrap();
+     *
+     * assert_eq!(data, &[0x03, 0x00, 0x02, 0x00, 0x00, 0x00]);
+     * ```
+     */
+    pub fn seek(&mut self, offset: usize) -> Result<(), EncodeError> {
+        if offset > self.data.len() {
+            return Err(EncodeError::InvalidOffset {
+                offset: offset,
+                length: self.data.len(),
+            });
+        }
+
+        self.offset = offset;
+
+        Ok(())
+    }
+
     /** Encodes 2 bytes.
      *
      * # Errors
@@ -944,6 +1522,25 @@ impl Encoder<'_> {
         Ok(())
     }
 
+    /** Encodes 16 bytes.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    fn put_16_bytes(&mut self, data: [u8; 16]) -> Result<(), EncodeError> {
+        self.check_need(16)?;
+
+        let start = self.offset;
+        let end = start + 16;
+
+        self.offset = end;
+
+        self.data[start..end].copy_from_slice(&data);
+
+        Ok(())
+    }
+
     /** Encodes bytes.
      *
      * # Errors
@@ -1026,6 +1623,139 @@ impl Encoder<'_> {
 
         Ok(())
     }
+
+    /** Encodes a [`u128`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_u128(&mut self, value: u128) -> Result<(), EncodeError> {
+        self.put_16_bytes((self.encoder.put_u128)(value))
+    }
+
+    /** Encodes an [`i8`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i8(&mut self, value: i8) -> Result<(), EncodeError> {
+        self.put_u8(value as u8)
+    }
+
+    /** Encodes an [`i16`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i16(&mut self, value: i16) -> Result<(), EncodeError> {
+        self.put_u16(value as u16)
+    }
+
+    /** Encodes an [`i32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i32(&mut self, value: i32) -> Result<(), EncodeError> {
+        self.put_u32(value as u32)
+    }
+
+    /** Encodes an [`i64`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i64(&mut self, value: i64) -> Result<(), EncodeError> {
+        self.put_u64(value as u64)
+    }
+
+    /** Encodes an [`i128`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i128(&mut self, value: i128) -> Result<(), EncodeError> {
+        self.put_u128(value as u128)
+    }
+
+    /** Encodes a slice of [`u16`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available. Nothing
+     * is encoded on error.
+     */
+    pub fn put_u16_slice(&mut self, values: &[u16]) -> Result<(), EncodeError> {
+        self.check_need(values.len() * 2)?;
+
+        for value in values {
+            self.put_u16(*value)?;
+        }
+
+        Ok(())
+    }
+
+    /** Encodes a slice of [`u32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available. Nothing
+     * is encoded on error.
+     */
+    pub fn put_u32_slice(&mut self, values: &[u32]) -> Result<(), EncodeError> {
+        self.check_need(values.len() * 4)?;
+
+        for value in values {
+            self.put_u32(*value)?;
+        }
+
+        Ok(())
+    }
+
+    /** Encodes a slice of [`u64`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available. Nothing
+     * is encoded on error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Encoder, Endian};
+     *
+     * let data = &mut [0; 16];
+     * let mut encoder = Encoder::to_bytes(data, Endian::Little);
+     *
+     * encoder.put_u64_slice(&[1, 2]).unwrap();
+     * assert!(encoder.put_u64_slice(&[3]).is_err());
+     *
+     * assert_eq!(
+     *     data,
+     *     &[
+     *         0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     *         0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     *     ]
+     * );
+     * ```
+     */
+    pub fn put_u64_slice(&mut self, values: &[u64]) -> Result<(), EncodeError> {
+        self.check_need(values.len() * 8)?;
+
+        for value in values {
+            self.put_u64(*value)?;
+        }
+
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -1111,6 +1841,13 @@ pub enum EncodeError {
         length: usize,
         count: usize,
     },
+
+    /** Invalid offset is past data.
+     *
+     * - `offset` - Byte offset of data.
+     * - `length` - Total length of data.
+     */
+    InvalidOffset { offset: usize, length: usize },
 }
 
 impl fmt::Display for EncodeError {
@@ -1126,6 +1863,9 @@ impl fmt::Display for EncodeError {
                     "Endian end of output at offset {offset}, need {count} bytes, total length {length}"
                 )
             }
+            EncodeError::InvalidOffset { offset, length } => {
+                write!(f, "Endian invalid offset {offset}, total length {length}")
+            }
         }
     }
 }
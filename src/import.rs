@@ -0,0 +1,725 @@
+/*! Pool import planning from a set of devices.
+ *
+ * - C reference: `zpool_find_import`, `vdev_uberblock_load`
+ *
+ * Each device is scanned for its four labels. The label with a valid
+ * configuration and the highest transaction group is used for the device,
+ * and the devices are grouped by pool GUID.
+ *
+ * For each pool:
+ * - The configuration with the highest transaction group is used for the
+ *   pool.
+ * - For each top level vdev, the configuration with the highest transaction
+ *   group of its devices is used for the top level vdev tree.
+ * - The active uber block is the most recent valid uber block of all the
+ *   devices, see [`UberBlock::compare`]. It can be limited to a maximum
+ *   transaction group, to rewind the pool like `zpool import -T`.
+ * - The sum of the GUIDs of the vdevs is checked against the active uber
+ *   block.
+ * - Top level vdevs and leaf vdevs that are not found, and devices with an
+ *   older configuration, are reported. Holes, which are placeholders for
+ *   removed log vdevs, are not missing.
+ *
+ * Devices that only have spare or level 2 cache labels, are not part of a
+ * pool configuration, and are reported as unlabeled. Devices with label
+ * configurations that cannot be decoded are reported as invalid, with the
+ * decode error.
+ *
+ * Devices are read using [`BlockDevice`].
+ */
+use core::fmt;
+use core::iter;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use std::boxed::Box;
+use std::error;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::block_device::{read_label, BlockDevice, BlockDeviceError};
+use crate::nv;
+use crate::phys::{
+    Label, NvPairs, Pool, PoolDecodeError, UberBlock, Vdev, VdevDecodeError, VdevTree,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Result of scanning the labels of a device.
+enum DeviceScan {
+    /// The device has at least one valid label configuration.
+    Device(Box<ImportDevice>),
+
+    /// The device has labels, but no configuration that can be decoded.
+    Invalid(PoolDecodeError),
+
+    /// The device has no valid label configuration.
+    Unlabeled,
+}
+
+/** A device with at least one valid label configuration.
+ */
+#[derive(Debug)]
+pub struct ImportDevice {
+    /// Index of the device in the scanned devices.
+    pub index: usize,
+
+    /// GUID of the leaf vdev.
+    pub guid: u64,
+
+    /// GUID of the pool.
+    pub pool_guid: u64,
+
+    /// GUID of the top level vdev.
+    pub top_guid: u64,
+
+    /// Transaction group of the configuration.
+    pub txg: u64,
+
+    /// Index of the label with the configuration.
+    pub label: usize,
+
+    /// Number of labels with a valid configuration.
+    pub label_count: usize,
+
+    /// NV pairs payload of the configuration.
+    pub config: Vec<u8>,
+
+    /// Best valid uber block of all the labels.
+    pub uber_block: Option<UberBlock>,
+}
+
+impl ImportDevice {
+    /** Scans the labels of a device.
+     *
+     * - Returns [`DeviceScan::Invalid`] with the last error, if the device
+     *   has label configurations with valid checksums, but none of them can be
+     *   decoded.
+     * - `max_txg` limits the transaction group of the uber block.
+     *
+     * # Errors
+     *
+     * Returns [`ImportError`] if the device cannot be read.
+     */
+    fn from_device<D: BlockDevice + ?Sized>(
+        index: usize,
+        block_device: &D,
+        max_txg: Option<u64>,
+    ) -> Result<DeviceScan, ImportError> {
+        let mut device: Option<ImportDevice> = None;
+        let mut config_error: Option<PoolDecodeError> = None;
+        let mut label_count = 0;
+        let mut best_uber_block: Option<UberBlock> = None;
+
+        let mut buf = vec![0u8; Label::LENGTH];
+        let bytes: &mut [u8; Label::LENGTH] = buf.as_mut_slice().try_into().unwrap();
+
+        for label_index in 0..Label::COUNT {
+            let label = match read_label(block_device, label_index, bytes) {
+                Ok(v) => v,
+                Err(BlockDeviceError::TooSmall { size: _ }) => return Ok(DeviceScan::Unlabeled),
+                Err(err) => return Err(err.into()),
+            };
+
+            ////////////////////////////////
+            // Keep the best uber block, if it is in this label.
+            // The best uber block so far is first, at index 0.
+            let uber_blocks = iter::once(best_uber_block.as_ref())
+                .chain(label.uber_blocks.iter().map(|v| v.as_ref().ok()));
+            if let Some(index) = UberBlock::select(uber_blocks, max_txg).filter(|v| *v > 0) {
+                best_uber_block = label
+                    .uber_blocks
+                    .into_iter()
+                    .nth(index - 1)
+                    .and_then(|v| v.ok());
+            }
+
+            ////////////////////////////////
+            // Decode configuration.
+            if label.nv_pairs.is_err() {
+                continue;
+            }
+
+            let config = &bytes[NvPairs::OFFSET..NvPairs::OFFSET + NvPairs::PAYLOAD_LENGTH];
+            let decoder = match nv::Decoder::from_bytes(config) {
+                Ok(v) => v,
+                Err(err) => {
+                    config_error = Some(err.into());
+                    continue;
+                }
+            };
+            let pool = match Pool::from_decoder(&decoder) {
+                Ok(v) => v,
+                Err(err) => {
+                    config_error = Some(err);
+                    continue;
+                }
+            };
+
+            label_count += 1;
+
+            ////////////////////////////////
+            // Keep the configuration with the highest transaction group.
+            let is_better = match &device {
+                Some(v) => pool.txg > v.txg,
+                None => true,
+            };
+
+            if is_better {
+                device = Some(ImportDevice {
+                    index: index,
+                    guid: pool.guid,
+                    pool_guid: pool.pool_guid,
+                    top_guid: pool.top_guid,
+                    txg: pool.txg,
+                    label: label_index,
+                    label_count: 0,
+                    config: config.to_vec(),
+                    uber_block: None,
+                });
+            }
+        }
+
+        let mut device = match (device, config_error) {
+            (Some(v), _) => v,
+            (None, Some(err)) => return Ok(DeviceScan::Invalid(err)),
+            (None, None) => return Ok(DeviceScan::Unlabeled),
+        };
+        device.label_count = label_count;
+        device.uber_block = best_uber_block;
+
+        Ok(DeviceScan::Device(Box::new(device)))
+    }
+
+    /** Decodes the configuration of the device.
+     *
+     * # Errors
+     *
+     * Returns [`ImportError`] in case of an error.
+     */
+    pub fn pool(&self) -> Result<Pool<'_>, ImportError> {
+        let decoder = nv::Decoder::from_bytes(&self.config)?;
+        Ok(Pool::from_decoder(&decoder)?)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A top level vdev found in the device configurations.
+ */
+#[derive(Debug)]
+pub struct ImportTopLevel {
+    /// ID of the top level vdev.
+    pub id: u64,
+
+    /// GUID of the top level vdev.
+    pub guid: u64,
+
+    /// Transaction group of the configuration used for the vdev tree.
+    pub txg: u64,
+
+    /// Index into [`ImportPool::devices`] of the configuration.
+    pub device: usize,
+
+    /// Sum of the GUIDs of the top level vdev tree.
+    pub guid_sum: u64,
+
+    /// GUIDs of the leaf vdevs of the top level vdev tree.
+    pub leaf_guids: Vec<u64>,
+}
+
+/// Collects the GUIDs of the leaf vdevs that are backed by a device.
+fn collect_leaf_guids(
+    vdev_tree: &VdevTree,
+    leaf_guids: &mut Vec<u64>,
+) -> Result<(), VdevDecodeError> {
+    match vdev_tree.vdev.children() {
+        Some(children) => {
+            for child in children.iter() {
+                collect_leaf_guids(&child?, leaf_guids)?;
+            }
+        }
+        None => match vdev_tree.vdev {
+            Vdev::Hole() | Vdev::Indirect(_) => (),
+            _ => leaf_guids.push(vdev_tree.guid),
+        },
+    }
+
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A pool assembled from the devices with the same pool GUID.
+ */
+#[derive(Debug)]
+pub struct ImportPool {
+    /// GUID of the pool.
+    pub pool_guid: u64,
+
+    /// Name of the pool, from the most recent configuration.
+    pub name: String,
+
+    /// Transaction group of the most recent configuration.
+    pub txg: u64,
+
+    /// Index into [`ImportPool::devices`] of the most recent configuration.
+    pub config_device: usize,
+
+    /// Devices of the pool.
+    pub devices: Vec<ImportDevice>,
+
+    /// Top level vdevs found, ordered by ID.
+    pub top_levels: Vec<ImportTopLevel>,
+
+    /// IDs of top level vdevs that are holes, see [`crate::phys::PoolHoles`].
+    pub holes: Vec<u64>,
+
+    /** IDs of top level vdevs that are not found, other than holes.
+     *
+     * - Indirect vdevs, which are top level vdevs that were removed, have no
+     *   devices, and so no labels. They are reported as missing, and their
+     *   GUIDs are not in [`ImportPool::guid_sum`]. Only the configuration in
+     *   the MOS tells them apart from top level vdevs that are not found.
+     */
+    pub missing_top_levels: Vec<u64>,
+
+    /// GUIDs of leaf vdevs that are not found.
+    pub missing_devices: Vec<u64>,
+
+    /// Indices into [`ImportPool::devices`] of devices with an older
+    /// configuration than their top level vdev, or that are no longer part
+    /// of their top level vdev tree.
+    pub stale_devices: Vec<usize>,
+
+    /// Index into [`ImportPool::devices`] of the active uber block.
+    pub uber_block_device: Option<usize>,
+
+    /** Sum of the GUIDs of the pool and of the top level vdevs found.
+     *
+     * - Holes have a GUID of zero, so they do not change the sum.
+     */
+    pub guid_sum: u64,
+}
+
+impl ImportPool {
+    /** Assembles a pool from its devices.
+     *
+     * # Errors
+     *
+     * Returns [`ImportError`] in case of an error.
+     */
+    fn from_devices(devices: Vec<ImportDevice>) -> Result<ImportPool, ImportError> {
+        ////////////////////////////////
+        // Find the most recent configuration.
+        let mut config_device = 0;
+        for (idx, device) in devices.iter().enumerate() {
+            if device.txg > devices[config_device].txg {
+                config_device = idx;
+            }
+        }
+
+        let pool = devices[config_device].pool()?;
+        let pool_guid = pool.pool_guid;
+
+        ////////////////////////////////
+        // Find the most recent configuration of each top level vdev.
+        let mut top_levels: Vec<ImportTopLevel> = Vec::new();
+
+        for (idx, device) in devices.iter().enumerate() {
+            match top_levels.iter_mut().find(|t| t.guid == device.top_guid) {
+                Some(top_level) => {
+                    if device.txg > top_level.txg {
+                        top_level.txg = device.txg;
+                        top_level.device = idx;
+                    }
+                }
+                None => top_levels.push(ImportTopLevel {
+                    id: 0,
+                    guid: device.top_guid,
+                    txg: device.txg,
+                    device: idx,
+                    guid_sum: 0,
+                    leaf_guids: Vec::new(),
+                }),
+            }
+        }
+
+        ////////////////////////////////
+        // Decode the top level vdev trees.
+        for top_level in top_levels.iter_mut() {
+            let config = devices[top_level.device].pool()?;
+
+            top_level.id = config.vdev_tree.id;
+            top_level.guid_sum = config.vdev_tree.guid_sum()?;
+            collect_leaf_guids(&config.vdev_tree, &mut top_level.leaf_guids)?;
+        }
+
+        top_levels.sort_by_key(|t| t.id);
+
+        ////////////////////////////////
+        // Find missing top level vdevs, which are not holes.
+        let mut holes = Vec::new();
+        if let Some(pool_holes) = &pool.holes {
+            for hole in pool_holes.iter() {
+                holes.push(hole?);
+            }
+        }
+
+        let vdev_children = pool.vdev_children.unwrap_or(top_levels.len() as u64);
+        let missing_top_levels = (0..vdev_children)
+            .filter(|id| !holes.contains(id) && !top_levels.iter().any(|t| t.id == *id))
+            .collect();
+
+        ////////////////////////////////
+        // Find missing and stale devices.
+        let mut missing_devices = Vec::new();
+        for top_level in top_levels.iter() {
+            for guid in top_level.leaf_guids.iter() {
+                if !devices.iter().any(|d| d.guid == *guid) {
+                    missing_devices.push(*guid);
+                }
+            }
+        }
+
+        let mut stale_devices = Vec::new();
+        for (idx, device) in devices.iter().enumerate() {
+            let top_level = top_levels
+                .iter()
+                .find(|t| t.guid == device.top_guid)
+                .unwrap();
+
+            if device.txg < top_level.txg || !top_level.leaf_guids.contains(&device.guid) {
+                stale_devices.push(idx);
+            }
+        }
+
+        ////////////////////////////////
+        // Find the active uber block.
+        let uber_block_device =
+            UberBlock::select(devices.iter().map(|d| d.uber_block.as_ref()), None);
+
+        ////////////////////////////////
+        // Sum the GUIDs.
+        let guid_sum = top_levels
+            .iter()
+            .fold(pool_guid, |sum, t| sum.wrapping_add(t.guid_sum));
+
+        Ok(ImportPool {
+            pool_guid: pool_guid,
+            name: pool.name.into(),
+            txg: pool.txg,
+            config_device: config_device,
+            devices: devices,
+            top_levels: top_levels,
+            holes: holes,
+            missing_top_levels: missing_top_levels,
+            missing_devices: missing_devices,
+            stale_devices: stale_devices,
+            uber_block_device: uber_block_device,
+            guid_sum: guid_sum,
+        })
+    }
+
+    /// Returns the active uber block.
+    pub fn uber_block(&self) -> Option<&UberBlock> {
+        self.uber_block_device
+            .and_then(|idx| self.devices[idx].uber_block.as_ref())
+    }
+
+    /** Returns true if the GUID sum of the active uber block matches the
+     * vdevs found.
+     *
+     * - A pool with indirect vdevs does not match, because their GUIDs are
+     *   not found, see [`ImportPool::missing_top_levels`].
+     */
+    pub fn is_guid_sum_valid(&self) -> bool {
+        match self.uber_block() {
+            Some(uber_block) => uber_block.guid_sum == self.guid_sum,
+            None => false,
+        }
+    }
+
+    /** Returns true if all the top level vdevs and devices are found, and the
+     * GUID sum is valid.
+     */
+    pub fn is_complete(&self) -> bool {
+        self.missing_top_levels.is_empty()
+            && self.missing_devices.is_empty()
+            && self.is_guid_sum_valid()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Result of scanning a set of devices for pools.
+ */
+#[derive(Debug)]
+pub struct ImportScan {
+    /// Pools found, in the order of their first device.
+    pub pools: Vec<ImportPool>,
+
+    /// Indices of devices without a valid label configuration.
+    pub unlabeled: Vec<usize>,
+
+    /// Indices of devices with label configurations that have valid
+    /// checksums but cannot be decoded, and the decode error of the last one.
+    pub invalid: Vec<(usize, PoolDecodeError)>,
+}
+
+impl ImportScan {
+    /** Scans the devices for pools.
+     *
+     * - `devices` are the block devices to scan, in any order.
+     *
+     * # Errors
+     *
+     * Returns [`ImportError`] in case of an error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::block_device::MemoryDevice;
+     * use zfs::checksum::label_checksum;
+     * use zfs::endian::Endian;
+     * use zfs::import::ImportScan;
+     * use zfs::nv::{Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Label, NvPairs, UberBlock, UberBlockRing};
+     *
+     * // Builds a device of a mirror, with a configuration and an uber block. The
+     * // pool had a log vdev, which was removed, so top level vdev 1 is a hole.
+     * fn device(guid: u64, txg: u64, guid_sum: u64) -> MemoryDevice<Vec<u8>> {
+     *     let text = format!(r#"{{
+     *         "version": {{ "uint64": 5000 }},
+     *         "name": {{ "string": "tank" }},
+     *         "state": {{ "uint64": 0 }},
+     *         "txg": {{ "uint64": {txg} }},
+     *         "pool_guid": {{ "uint64": 10 }},
+     *         "top_guid": {{ "uint64": 20 }},
+     *         "guid": {{ "uint64": {guid} }},
+     *         "vdev_children": {{ "uint64": 2 }},
+     *         "hole_array": {{ "uint64_array": [1] }},
+     *         "vdev_tree": {{ "nvlist": {{
+     *             "type": {{ "string": "mirror" }},
+     *             "id": {{ "uint64": 0 }},
+     *             "guid": {{ "uint64": 20 }},
+     *             "children": {{ "nvlist_array": [
+     *                 {{ "type": {{ "string": "file" }}, "id": {{ "uint64": 0 }},
+     *                   "guid": {{ "uint64": 21 }}, "path": {{ "string": "/a" }} }},
+     *                 {{ "type": {{ "string": "file" }}, "id": {{ "uint64": 1 }},
+     *                   "guid": {{ "uint64": 22 }}, "path": {{ "string": "/b" }} }}
+     *             ] }}
+     *         }} }}
+     *     }}"#);
+     *     let config = nv_json::to_bytes(&text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     *     let nv_pairs = NvPairs::from_config(&config).unwrap();
+     *
+     *     let mut data = vec![0u8; 4 * Label::LENGTH];
+     *     Label::to_device_bytes(&mut data, &nv_pairs, Endian::Little).unwrap();
+     *
+     *     let mut bytes = [0u8; UberBlock::LENGTH];
+     *     bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     *     label_checksum(&mut bytes, 0, Endian::Little).unwrap();
+     *     let mut uber_block = UberBlock::from_bytes(&bytes, 0).unwrap();
+     *     uber_block.txg = txg + 1;
+     *     uber_block.guid_sum = guid_sum;
+     *     let ring = UberBlockRing::default();
+     *     Label::write_uber_block(&mut data, ring, &uber_block, false).unwrap();
+     *
+     *     MemoryDevice::new(data)
+     * }
+     *
+     * let guid_sum = 10 + 20 + 21 + 22;
+     * let a = device(21, 8, guid_sum);
+     * let b = device(22, 5, guid_sum);
+     * let blank = MemoryDevice::new(vec![0u8; 4 * Label::LENGTH]);
+     *
+     * // A label with a valid checksum, but not a pool configuration.
+     * let text = r#"{ "name": { "string": "tank" } }"#;
+     * let config = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let mut data = vec![0u8; 4 * Label::LENGTH];
+     * Label::to_device_bytes(&mut data, &NvPairs::from_config(&config).unwrap(), Endian::Little)
+     *     .unwrap();
+     * let invalid = MemoryDevice::new(data);
+     *
+     * let scan = ImportScan::from_devices(&[&a, &blank, &b, &invalid]).unwrap();
+     * assert_eq!(scan.unlabeled, [1]);
+     * assert_eq!(scan.invalid.len(), 1);
+     * assert_eq!(scan.invalid[0].0, 3);
+     * assert_eq!(scan.pools.len(), 1);
+     *
+     * let pool = &scan.pools[0];
+     * assert_eq!(pool.name, "tank");
+     * assert_eq!(pool.txg, 8);
+     * assert_eq!(pool.uber_block().unwrap().txg, 9);
+     * assert!(pool.is_guid_sum_valid());
+     * assert!(pool.is_complete());
+     *
+     * // Top level vdev 1 is a hole, and is not missing.
+     * assert_eq!(pool.holes, [1]);
+     * assert!(pool.missing_top_levels.is_empty());
+     *
+     * // Device b has an older configuration than its mirror.
+     * assert_eq!(pool.stale_devices, [1]);
+     * assert_eq!(pool.devices[1].index, 2);
+     *
+     * // Scanning only device a, reports device b as missing.
+     * let scan = ImportScan::from_devices(&[&a]).unwrap();
+     * assert_eq!(scan.pools[0].missing_devices, [22]);
+     * assert!(!scan.pools[0].is_complete());
+     *
+     * // Rewind to the uber block of device b.
+     * let scan = ImportScan::from_devices_max_txg(&[&a, &b], Some(7)).unwrap();
+     * assert_eq!(scan.pools[0].uber_block().unwrap().txg, 6);
+     * assert_eq!(scan.pools[0].uber_block_device, Some(1));
+     * ```
+     */
+    pub fn from_devices<D: BlockDevice + ?Sized>(
+        devices: &[&D],
+    ) -> Result<ImportScan, ImportError> {
+        ImportScan::from_devices_max_txg(devices, None)
+    }
+
+    /** Scans the devices for pools, with a maximum transaction group for the
+     * active uber block.
+     *
+     * - `max_txg` rewinds the pools to an earlier transaction group, like
+     *   `zpool import -T`. Pools without an uber block at or before it have no
+     *   active uber block.
+     *
+     * # Errors
+     *
+     * Returns [`ImportError`] in case of an error.
+     */
+    pub fn from_devices_max_txg<D: BlockDevice + ?Sized>(
+        devices: &[&D],
+        max_txg: Option<u64>,
+    ) -> Result<ImportScan, ImportError> {
+        ////////////////////////////////
+        // Scan the labels of each device, and group them by pool.
+        let mut groups: Vec<Vec<ImportDevice>> = Vec::new();
+        let mut unlabeled = Vec::new();
+        let mut invalid = Vec::new();
+
+        for (index, block_device) in devices.iter().enumerate() {
+            let device = match ImportDevice::from_device(index, *block_device, max_txg)? {
+                DeviceScan::Device(v) => *v,
+                DeviceScan::Invalid(err) => {
+                    invalid.push((index, err));
+                    continue;
+                }
+                DeviceScan::Unlabeled => {
+                    unlabeled.push(index);
+                    continue;
+                }
+            };
+
+            match groups
+                .iter_mut()
+                .find(|g| g[0].pool_guid == device.pool_guid)
+            {
+                Some(group) => group.push(device),
+                None => groups.push(vec![device]),
+            }
+        }
+
+        ////////////////////////////////
+        // Assemble the pools.
+        let mut pools = Vec::new();
+        for group in groups {
+            pools.push(ImportPool::from_devices(group)?);
+        }
+
+        Ok(ImportScan {
+            pools: pools,
+            unlabeled: unlabeled,
+            invalid: invalid,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum ImportError {
+    /** Block device error.
+     *
+     * - `err` - Error.
+     */
+    BlockDeviceError { err: BlockDeviceError },
+
+    /** NV decoding error.
+     *
+     * - `err` - Error.
+     */
+    NvDecodeError { err: nv::DecodeError },
+
+    /** Pool decode error.
+     *
+     * - `err` - Error.
+     */
+    PoolDecodeError { err: PoolDecodeError },
+
+    /** Vdev decode error.
+     *
+     * - `err` - Error.
+     */
+    VdevDecodeError { err: VdevDecodeError },
+}
+
+impl From<BlockDeviceError> for ImportError {
+    fn from(value: BlockDeviceError) -> Self {
+        ImportError::BlockDeviceError { err: value }
+    }
+}
+
+impl From<nv::DecodeError> for ImportError {
+    fn from(value: nv::DecodeError) -> Self {
+        ImportError::NvDecodeError { err: value }
+    }
+}
+
+impl From<PoolDecodeError> for ImportError {
+    fn from(value: PoolDecodeError) -> Self {
+        ImportError::PoolDecodeError { err: value }
+    }
+}
+
+impl From<VdevDecodeError> for ImportError {
+    fn from(value: VdevDecodeError) -> Self {
+        ImportError::VdevDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::BlockDeviceError { err } => {
+                write!(f, "Import Block Device error: {err}")
+            }
+            ImportError::NvDecodeError { err } => {
+                write!(f, "Import NV decode error: {err}")
+            }
+            ImportError::PoolDecodeError { err } => {
+                write!(f, "Import Pool decode error: {err}")
+            }
+            ImportError::VdevDecodeError { err } => {
+                write!(f, "Import Vdev decode error: {err}")
+            }
+        }
+    }
+}
+
+impl error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ImportError::BlockDeviceError { err } => Some(err),
+            ImportError::NvDecodeError { err } => Some(err),
+            ImportError::PoolDecodeError { err } => Some(err),
+            ImportError::VdevDecodeError { err } => Some(err),
+        }
+    }
+}
@@ -1,10 +1,22 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[macro_use]
 extern crate num_derive;
 
+pub mod block_device;
 pub mod checksum;
+pub mod compression;
 pub mod endian;
+#[cfg(feature = "std")]
+pub mod import;
+pub mod mmp;
 pub mod nv;
+#[cfg(feature = "std")]
+pub mod nv_json;
 pub mod phys;
+#[cfg(feature = "alloc")]
+pub mod read;
 pub mod xdr;
@@ -0,0 +1,339 @@
+/*! Multihost (MMP) activity check.
+ *
+ * - C reference: `spa_activity_check`, `spa_activity_check_duration`
+ *
+ * A host with multihost enabled regularly writes uber blocks to the pool,
+ * even when there are no changes. Before touching a pool, the best uber
+ * block is observed, and observed again after the import delay. If it has
+ * changed, then another host is actively writing the pool.
+ *
+ * The caller waits between observations, so the check does not depend on a
+ * clock:
+ *
+ * 1. [`MmpWatch::from_devices`] observes the best uber block.
+ * 2. Wait for [`MmpWatch::import_delay_ns`].
+ * 3. [`MmpWatch::check_devices`] observes again, and reports the activity.
+ *
+ * The MMP write interval, sequence number, and fail intervals are in the uber
+ * block, see [`UberBlockMmp`]. The host ID is not in the uber block, and is
+ * from the `hostid` of the label configuration.
+ */
+use core::iter;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use crate::block_device::{read_label, BlockDevice, BlockDeviceError};
+use crate::nv;
+use crate::phys::{Label, PoolHost, UberBlock, UberBlockMmp};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Default MMP write interval in milliseconds (`zfs_multihost_interval`).
+pub const MMP_DEFAULT_INTERVAL_MS: u64 = 1000;
+
+/// Default number of intervals to wait on import (`zfs_multihost_import_intervals`).
+pub const MMP_DEFAULT_IMPORT_INTERVALS: u64 = 20;
+
+/// Safety factor in percent for the fail intervals of a remote host.
+pub const MMP_IMPORT_SAFETY_FACTOR: u64 = 200;
+
+const NANOSECONDS_PER_MILLISECOND: u64 = 1_000_000;
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+
+/** Returns the time to wait between observations of the uber block, in
+ * nanoseconds.
+ *
+ * - C reference: `spa_activity_check_duration`
+ * - If the remote host has fail intervals, it suspends the pool after that
+ *   many failed write intervals, so the delay is based on them.
+ * - Otherwise, the delay is a number of write intervals, with the MMP delay.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::mmp::import_delay_ns;
+ * use zfs::phys::UberBlockMmp;
+ *
+ * // Interval of 1000 ms, with 10 fail intervals.
+ * let mmp = UberBlockMmp { delay: 0, config: (10 << 48) | 0x04 | (1000 << 8) | 0x01 };
+ * assert_eq!(import_delay_ns(Some(&mmp)), 20_000_000_000);
+ *
+ * // No MMP, so default interval of 1000 ms, for 20 intervals.
+ * assert_eq!(import_delay_ns(None), 20_000_000_000);
+ * ```
+ */
+pub fn import_delay_ns(mmp: Option<&UberBlockMmp>) -> u64 {
+    let default_interval = MMP_DEFAULT_INTERVAL_MS * NANOSECONDS_PER_MILLISECOND;
+    let import_delay = NANOSECONDS_PER_SECOND.max(MMP_DEFAULT_IMPORT_INTERVALS * default_interval);
+
+    let mmp = match mmp {
+        Some(v) => v,
+        None => return import_delay,
+    };
+
+    match (mmp.interval(), mmp.fail_intervals()) {
+        (Some(interval), Some(fail_intervals)) if fail_intervals > 0 => {
+            fail_intervals
+                .saturating_mul(interval)
+                .saturating_mul(NANOSECONDS_PER_MILLISECOND)
+                .saturating_mul(MMP_IMPORT_SAFETY_FACTOR)
+                / 100
+        }
+        (Some(interval), _) => import_delay.max(
+            interval
+                .saturating_mul(NANOSECONDS_PER_MILLISECOND)
+                .saturating_add(mmp.delay)
+                .saturating_mul(MMP_DEFAULT_IMPORT_INTERVALS),
+        ),
+        (None, _) => import_delay.max(
+            default_interval
+                .saturating_add(mmp.delay)
+                .saturating_mul(MMP_DEFAULT_IMPORT_INTERVALS),
+        ),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** An observation of the best uber block of a pool.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MmpObservation {
+    pub txg: u64,
+    pub timestamp: u64,
+    pub sequence: Option<u64>,
+}
+
+impl MmpObservation {
+    /// Creates an [`MmpObservation`] of an uber block.
+    pub fn from_uber_block(uber_block: &UberBlock) -> MmpObservation {
+        MmpObservation {
+            txg: uber_block.txg,
+            timestamp: uber_block.timestamp,
+            sequence: uber_block.mmp.as_ref().and_then(|mmp| mmp.sequence()),
+        }
+    }
+}
+
+/** Result of an activity check.
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub enum MmpActivity {
+    /** The best uber block has changed, so another host is writing the pool.
+     *
+     * - `before` - First observation.
+     * - `after`  - Second observation.
+     */
+    Active {
+        before: Option<MmpObservation>,
+        after: Option<MmpObservation>,
+    },
+
+    /// The best uber block has not changed.
+    Inactive,
+}
+
+impl MmpActivity {
+    /// Returns true if another host is writing the pool.
+    pub fn is_active(&self) -> bool {
+        matches!(self, MmpActivity::Active { .. })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Watches the best uber block of a pool, to check for activity.
+ */
+#[derive(Debug)]
+pub struct MmpWatch {
+    /// Observation of the best uber block, or [`None`] if there is none.
+    pub observation: Option<MmpObservation>,
+
+    /// Time to wait before checking, in nanoseconds.
+    pub import_delay_ns: u64,
+
+    /// Host ID of the label configuration of the best uber block.
+    pub host_id: Option<u64>,
+
+    /// MMP values of the best uber block.
+    pub mmp: Option<UberBlockMmp>,
+}
+
+impl MmpWatch {
+    /** Starts watching an uber block.
+     *
+     * - `host_id` of the label configuration, if known.
+     */
+    pub fn from_uber_block(uber_block: Option<&UberBlock>, host_id: Option<u64>) -> MmpWatch {
+        let mmp = uber_block.and_then(|ub| ub.mmp.as_ref());
+
+        MmpWatch {
+            observation: uber_block.map(MmpObservation::from_uber_block),
+            import_delay_ns: import_delay_ns(mmp),
+            host_id: host_id,
+            mmp: mmp.map(|mmp| UberBlockMmp {
+                delay: mmp.delay,
+                config: mmp.config,
+            }),
+        }
+    }
+
+    /** Starts watching the best uber block of all the labels of the devices.
+     *
+     * - `devices` are read again for each observation.
+     * - Devices that are too small for labels are skipped.
+     *
+     * # Errors
+     *
+     * Returns [`BlockDeviceError`] if a device cannot be read.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::block_device::MemoryDevice;
+     * use zfs::checksum::label_checksum;
+     * use zfs::endian::Endian;
+     * use zfs::mmp::MmpWatch;
+     * use zfs::nv::{Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Label, NvPairs, UberBlock, UberBlockMmp, UberBlockRing};
+     *
+     * let text = r#"{ "hostid": { "uint64": 7 }, "hostname": { "string": "a" } }"#;
+     * let config = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let nv_pairs = NvPairs::from_config(&config).unwrap();
+     *
+     * let mut device = MemoryDevice::new(vec![0u8; 4 * Label::LENGTH]);
+     * Label::to_device_bytes(device.data_mut(), &nv_pairs, Endian::Little).unwrap();
+     *
+     * // Uber block with MMP sequence 1, interval 1000 ms, and 10 fail intervals.
+     * let mut bytes = [0u8; UberBlock::LENGTH];
+     * bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     * label_checksum(&mut bytes, 0, Endian::Little).unwrap();
+     * let mut uber_block = UberBlock::from_bytes(&bytes, 0).unwrap();
+     * uber_block.txg = 40;
+     * uber_block.mmp = Some(UberBlockMmp {
+     *     delay: 0,
+     *     config: (10 << 48) | 0x04 | (1 << 32) | 0x02 | (1000 << 8) | 0x01,
+     * });
+     *
+     * let ring = UberBlockRing::default();
+     * Label::write_uber_block(device.data_mut(), ring, &uber_block, true).unwrap();
+     *
+     * let watch = MmpWatch::from_devices(&[&device]).unwrap();
+     * assert_eq!(watch.host_id, Some(7));
+     * assert_eq!(watch.import_delay_ns, 20_000_000_000);
+     *
+     * // Nothing written after the import delay.
+     * assert!(!watch.check_devices(&[&device]).unwrap().is_active());
+     *
+     * // Another host writes an MMP uber block, with the next sequence number.
+     * uber_block.mmp.as_mut().unwrap().config += 1 << 32;
+     * Label::write_uber_block(device.data_mut(), ring, &uber_block, true).unwrap();
+     * assert!(watch.check_devices(&[&device]).unwrap().is_active());
+     * ```
+     */
+    pub fn from_devices<D: BlockDevice + ?Sized>(
+        devices: &[&D],
+    ) -> Result<MmpWatch, BlockDeviceError> {
+        let (uber_block, host_id) = best_uber_block(devices)?;
+        Ok(MmpWatch::from_uber_block(uber_block.as_ref(), host_id))
+    }
+
+    /** Checks an uber block against the first observation.
+     *
+     * - The uber block should be observed after [`MmpWatch::import_delay_ns`].
+     */
+    pub fn check(&self, uber_block: Option<&UberBlock>) -> MmpActivity {
+        let after = uber_block.map(MmpObservation::from_uber_block);
+
+        if after == self.observation {
+            MmpActivity::Inactive
+        } else {
+            MmpActivity::Active {
+                before: self.observation,
+                after: after,
+            }
+        }
+    }
+
+    /** Checks the best uber block of all the labels of the devices against the
+     * first observation.
+     *
+     * - The devices should be read after [`MmpWatch::import_delay_ns`].
+     *
+     * # Errors
+     *
+     * Returns [`BlockDeviceError`] if a device cannot be read.
+     */
+    pub fn check_devices<D: BlockDevice + ?Sized>(
+        &self,
+        devices: &[&D],
+    ) -> Result<MmpActivity, BlockDeviceError> {
+        let (uber_block, _) = best_uber_block(devices)?;
+        Ok(self.check(uber_block.as_ref()))
+    }
+
+    /** Returns true if the pool was last written by another host.
+     *
+     * - `host_id` of this host.
+     * - Returns false if the host ID of the pool is not known.
+     */
+    pub fn is_other_host(&self, host_id: u64) -> bool {
+        match self.host_id {
+            Some(v) => v != host_id,
+            None => false,
+        }
+    }
+}
+
+/** Returns the best uber block of all the labels of the devices, and the
+ * host ID of the label configuration with the best uber block.
+ */
+fn best_uber_block<D: BlockDevice + ?Sized>(
+    devices: &[&D],
+) -> Result<(Option<UberBlock>, Option<u64>), BlockDeviceError> {
+    let mut best: Option<UberBlock> = None;
+    let mut host_id: Option<u64> = None;
+    let mut bytes = [0u8; Label::LENGTH];
+
+    for device in devices {
+        for label_index in 0..Label::COUNT {
+            let label = match read_label(*device, label_index, &mut bytes) {
+                Ok(v) => v,
+                Err(BlockDeviceError::TooSmall { size: _ }) => break,
+                Err(err) => return Err(err),
+            };
+
+            // Get host ID of label configuration.
+            let label_host_id = match &label.nv_pairs {
+                Ok(nv_pairs) => match nv::Decoder::from_bytes(&nv_pairs.payload) {
+                    Ok(decoder) => match PoolHost::from_decoder(&decoder) {
+                        Ok(v) => v.map(|host| host.id),
+                        Err(_) => None,
+                    },
+                    Err(_) => None,
+                },
+                Err(_) => None,
+            };
+
+            // Keep the best uber block, if it is in this label.
+            // The best uber block so far is first, at index 0.
+            let uber_blocks =
+                iter::once(best.as_ref()).chain(label.uber_blocks.iter().map(|v| v.as_ref().ok()));
+            if let Some(index) = UberBlock::select(uber_blocks, None).filter(|v| *v > 0) {
+                best = label
+                    .uber_blocks
+                    .into_iter()
+                    .nth(index - 1)
+                    .and_then(|v| v.ok());
+                host_id = label_host_id;
+            }
+        }
+    }
+
+    Ok((best, host_id))
+}
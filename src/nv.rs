@@ -45,6 +45,14 @@
  *   such as `org.openzfs:blake3`
  * - [`DataType::BooleanValue`] has a count of 1, and an actual value that can
  *   be [`true`] or [`false`]
+ *
+ * Strict mode
+ * ===========
+ * [`Decoder::from_bytes`] is lenient, in order to decode real world labels.
+ * [`Decoder::from_bytes_strict`] additionally returns an error for:
+ * - Non-zero XDR padding bytes.
+ * - Non-zero bytes after the end of the list.
+ * - Nested lists, and arrays whose declared size is longer than their contents.
  */
 use core::cell::Cell;
 use core::fmt;
@@ -58,7 +66,7 @@ use std::error;
 extern crate strum;
 use enum_as_inner::EnumAsInner;
 
-use crate::endian::Endian;
+use crate::endian::{self, Endian};
 use crate::xdr;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -266,15 +274,16 @@ pub struct Decoder<'a> {
 
 #[derive(Debug)]
 pub struct NestedDecoder<'a> {
-    parent: &'a Decoder<'a>,
+    /// Data of the decoder that created this nested decoder.
+    parent: &'a [u8],
     decoder: Decoder<'a>,
     offset: usize,
     length: usize,
 }
 
-impl NestedDecoder<'_> {
+impl<'a> NestedDecoder<'a> {
     /// Gets the nested decoder.
-    pub fn get_decoder(&self) -> &Decoder {
+    pub fn get_decoder(&self) -> &Decoder<'a> {
         &self.decoder
     }
 }
@@ -305,6 +314,11 @@ impl<T> ArrayDecoder<'_, T> {
         }
     }
 
+    /// Returns true if there are no more elements to be decoded.
+    pub fn is_empty(&self) -> bool {
+        self.index.get() >= self.count
+    }
+
     /// Resets the decoder to the start of the data.
     pub fn reset(&self) {
         self.decoder.reset();
@@ -312,7 +326,22 @@ impl<T> ArrayDecoder<'_, T> {
     }
 }
 
-impl<'a> ArrayDecoder<'a, &str> {
+/// Clones the decoder, with its own position, so that the clone can be used
+/// independently.
+impl<T> Clone for ArrayDecoder<'_, T> {
+    fn clone(&self) -> Self {
+        ArrayDecoder {
+            decoder: self.decoder.clone(),
+            count: self.count,
+            index: self.index.clone(),
+            encoding: self.encoding,
+            endian: self.endian,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a> ArrayDecoder<'a, &'a str> {
     /** Returns the next element.
      *
      * - Call while [`ArrayDecoder::len`] is greater than 0.
@@ -321,7 +350,7 @@ impl<'a> ArrayDecoder<'a, &str> {
      *
      * Returns [`DecodeError`] on error.
      */
-    pub fn get(&self) -> Result<&str, DecodeError> {
+    pub fn get(&self) -> Result<&'a str, DecodeError> {
         let index = self.index.get();
 
         if index < self.count {
@@ -363,7 +392,7 @@ impl<'a> ArrayDecoder<'a, Decoder<'a>> {
      *
      * Returns [`DecodeError`] on error.
      */
-    pub fn get(&'a self) -> Result<Decoder<'a>, DecodeError> {
+    pub fn get(&self) -> Result<Decoder<'a>, DecodeError> {
         let index = self.index.get();
 
         if index < self.count {
@@ -374,11 +403,12 @@ impl<'a> ArrayDecoder<'a, Decoder<'a>> {
             let data = self.decoder.get_n_bytes(starting_length)?;
 
             // Create a temporary decoder.
-            let decoder = Decoder::from_partial(self.encoding, self.endian, data)?;
+            let decoder =
+                Decoder::from_partial(self.encoding, self.endian, data, self.decoder.is_strict())?;
 
             // Decode until end of list or error.
             loop {
-                match decoder.next_pair() {
+                match decoder.decode_pair(false) {
                     Ok(v) => match v {
                         Some(_) => continue,
                         None => break,
@@ -388,7 +418,7 @@ impl<'a> ArrayDecoder<'a, Decoder<'a>> {
             }
 
             // Compute number of bytes used for this list.
-            let bytes_used = starting_length - self.decoder.len();
+            let bytes_used = decoder.decoder.offset();
 
             // Rewind decoder back.
             self.decoder.rewind(starting_length)?;
@@ -397,7 +427,7 @@ impl<'a> ArrayDecoder<'a, Decoder<'a>> {
             let data = self.decoder.get_n_bytes(bytes_used)?;
 
             // Return decoder.
-            Decoder::from_partial(self.encoding, self.endian, data)
+            Decoder::from_partial(self.encoding, self.endian, data, self.decoder.is_strict())
         } else {
             Err(DecodeError::EndOfArray {})
         }
@@ -455,7 +485,7 @@ impl<'a> DecodedPair<'_> {
     }
 }
 
-impl Decoder<'_> {
+impl<'a> Decoder<'a> {
     /** Instantiates a NV list [`Decoder`] from a slice of bytes.
      *
      * # Errors.
@@ -463,6 +493,55 @@ impl Decoder<'_> {
      * Returns [`DecodeError`] on error.
      */
     pub fn from_bytes(data: &[u8]) -> Result<Decoder, DecodeError> {
+        Decoder::from_header(data, false)
+    }
+
+    /** Instantiates a strict NV list [`Decoder`] from a slice of bytes.
+     *
+     * See the module documentation for the additional checks.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError`] on error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::nv::Decoder;
+     *
+     * // An empty list, followed by garbage.
+     * let data = &[
+     *     0x01, 0x00, 0x00, 0x00, // XDR encoding, big endian
+     *     0x00, 0x00, 0x00, 0x00, // version
+     *     0x00, 0x00, 0x00, 0x01, // flags
+     *     0x00, 0x00, 0x00, 0x00, // encoded size
+     *     0x00, 0x00, 0x00, 0x00, // decoded size
+     *     0x00, 0x00, 0x00, 0xff, // garbage
+     * ];
+     *
+     * // Lenient decoder stops at the end of the list.
+     * let decoder = Decoder::from_bytes(data).unwrap();
+     * assert!(decoder.next_pair().unwrap().is_none());
+     *
+     * // Strict decoder checks the bytes after the end of the list.
+     * let decoder = Decoder::from_bytes_strict(data).unwrap();
+     * assert!(decoder.next_pair().is_err());
+     * ```
+     */
+    pub fn from_bytes_strict(data: &[u8]) -> Result<Decoder<'_>, DecodeError> {
+        Decoder::from_header(data, true)
+    }
+
+    /** Instantiates a NV list [`Decoder`] from a slice of bytes, starting with
+     * the NV list header.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError`] on error.
+     */
+    fn from_header(data: &[u8], strict: bool) -> Result<Decoder<'_>, DecodeError> {
         // Check that NvList header is not truncated.
         if data.len() < 4 {
             return Err(DecodeError::EndOfInput {
@@ -501,12 +580,12 @@ impl Decoder<'_> {
             });
         }
 
-        Decoder::from_partial(encoding, endian, rest)
+        Decoder::from_partial(encoding, endian, rest, strict)
     }
 
     /** Instantiates a nested NV list [`Decoder`] from a slice of bytes.
      *
-     * - Encoding, and endian must be the same as the parent list.
+     * - Encoding, endian, and strict must be the same as the parent list.
      *
      * # Errors.
      *
@@ -516,6 +595,7 @@ impl Decoder<'_> {
         encoding: Encoding,
         endian: Endian,
         data: &[u8],
+        strict: bool,
     ) -> Result<Decoder, DecodeError> {
         // Check encoding.
         match encoding {
@@ -525,7 +605,10 @@ impl Decoder<'_> {
 
         // NOTE: For XDR, it is always big endian, no matter what the endian
         //       field says.
-        let decoder = xdr::Decoder::from_bytes(data);
+        let decoder = match strict {
+            true => xdr::Decoder::from_bytes_strict(data),
+            false => xdr::Decoder::from_bytes(data),
+        };
 
         // NvList version.
         let version = decoder.get()?;
@@ -564,7 +647,110 @@ impl Decoder<'_> {
      *
      * Returns [`DecodeError`] on error.
      */
-    pub fn next_pair(&self) -> Result<Option<DecodedPair>, DecodeError> {
+    pub fn next_pair(&self) -> Result<Option<DecodedPair<'a>>, DecodeError> {
+        self.decode_pair(true)
+    }
+
+    /** Returns true if the decoder was created in strict mode.
+     */
+    pub fn is_strict(&self) -> bool {
+        self.decoder.is_strict()
+    }
+
+    /** Instantiates an XDR decoder for a value, with the same strict mode as
+     * this decoder.
+     */
+    fn xdr_decoder<'b>(&self, data: &'b [u8]) -> xdr::Decoder<'b> {
+        match self.decoder.is_strict() {
+            true => xdr::Decoder::from_bytes_strict(data),
+            false => xdr::Decoder::from_bytes(data),
+        }
+    }
+
+    /** Checks that the bytes after the end of the list are all zero.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError::TrailingGarbage`] on non-zero bytes.
+     */
+    fn check_trailing(&self) -> Result<(), DecodeError> {
+        let offset = self.decoder.offset();
+        let length = self.decoder.len();
+        let data = self.decoder.get_n_bytes(length)?;
+        self.decoder.rewind(length)?;
+
+        if data.iter().any(|x| *x != 0) {
+            return Err(DecodeError::TrailingGarbage {
+                offset: offset,
+                length: length,
+            });
+        }
+
+        Ok(())
+    }
+
+    /** Checks that the nested list `data` is exactly as long as its contents.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError::InvalidEncodedSize`] if the declared size is
+     * longer than the list.
+     */
+    fn check_nested_size(&self, data: &[u8]) -> Result<(), DecodeError> {
+        let decoder = Decoder::from_partial(self.encoding, self.endian, data, true)?;
+
+        // Decode until end of list.
+        while decoder.next_pair()?.is_some() {}
+
+        // Check for unused bytes.
+        if !decoder.decoder.is_empty() {
+            return Err(DecodeError::InvalidEncodedSize {
+                encoded_size: data.len(),
+                used: decoder.decoder.offset(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /** Checks that the array `data` is exactly as long as its `count` values,
+     * where `f` decodes one value.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError::InvalidEncodedSize`] if the declared size is
+     * longer than the values.
+     */
+    fn check_array_size<F>(&self, data: &[u8], count: usize, f: F) -> Result<(), DecodeError>
+    where
+        F: Fn(&xdr::Decoder) -> Result<(), DecodeError>,
+    {
+        let decoder = xdr::Decoder::from_bytes_strict(data);
+
+        for _ in 0..count {
+            f(&decoder)?;
+        }
+
+        if !decoder.is_empty() {
+            return Err(DecodeError::InvalidEncodedSize {
+                encoded_size: data.len(),
+                used: decoder.offset(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /** Gets the next [`DecodedPair`].
+     *
+     * - `end_of_data` is true when the end of this list is also the end of the
+     *   data, and trailing bytes are checked in strict mode.
+     *
+     * # Errors.
+     *
+     * Returns [`DecodeError`] on error.
+     */
+    fn decode_pair(&self, end_of_data: bool) -> Result<Option<DecodedPair<'a>>, DecodeError> {
         // Keep track of starting length, to verify encoded_size, and
         // construct nested NV List structures.
         let starting_length = self.decoder.len();
@@ -580,6 +766,9 @@ impl Decoder<'_> {
 
         // Check for end of list.
         if encoded_size == 0 && decoded_size == 0 {
+            if end_of_data && self.decoder.is_strict() {
+                self.check_trailing()?;
+            }
             return Ok(None);
         }
 
@@ -631,7 +820,7 @@ impl Decoder<'_> {
 
             DataType::ByteArray => DecodedDataValue::ByteArray(self.decoder.get_bytes()?),
             DataType::Int16Array => DecodedDataValue::Int16Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -639,7 +828,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Uint16Array => DecodedDataValue::Uint16Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -647,7 +836,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Int32Array => DecodedDataValue::Int32Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -655,7 +844,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Uint32Array => DecodedDataValue::Uint32Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -663,7 +852,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Int64Array => DecodedDataValue::Int64Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 8)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 8)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -671,7 +860,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Uint64Array => DecodedDataValue::Uint64Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 8)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 8)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -679,8 +868,16 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::StringArray => DecodedDataValue::StringArray(ArrayDecoder {
-                // TODO(cybojanek): Verify length of strings at this point?
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(bytes_rem)?),
+                decoder: {
+                    let data = self.decoder.get_n_bytes(bytes_rem)?;
+                    if self.decoder.is_strict() {
+                        self.check_array_size(data, element_count, |d| {
+                            d.get_str()?;
+                            Ok(())
+                        })?;
+                    }
+                    self.xdr_decoder(data)
+                },
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -693,17 +890,36 @@ impl Decoder<'_> {
             DataType::NvList => DecodedDataValue::NvList(NestedDecoder {
                 offset: self.decoder.offset(),
                 length: bytes_rem,
-                parent: self,
-                decoder: Decoder::from_partial(
-                    self.encoding,
-                    self.endian,
-                    // TODO(cybojanek): Verify length of list at this point?
-                    self.decoder.get_n_bytes(bytes_rem)?,
-                )?,
+                parent: self.decoder.data(),
+                decoder: {
+                    let data = self.decoder.get_n_bytes(bytes_rem)?;
+                    if self.decoder.is_strict() {
+                        self.check_nested_size(data)?;
+                    }
+                    Decoder::from_partial(
+                        self.encoding,
+                        self.endian,
+                        data,
+                        self.decoder.is_strict(),
+                    )?
+                },
             }),
             DataType::NvListArray => DecodedDataValue::NvListArray(ArrayDecoder {
-                // TODO(cybojanek): Verify length of list at this point?
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(bytes_rem)?),
+                decoder: {
+                    let data = self.decoder.get_n_bytes(bytes_rem)?;
+                    if self.decoder.is_strict() {
+                        self.check_array_size(data, element_count, |d| {
+                            let rest = d.get_n_bytes(d.len())?;
+                            d.rewind(rest.len())?;
+                            let list =
+                                Decoder::from_partial(self.encoding, self.endian, rest, true)?;
+                            while list.decode_pair(false)?.is_some() {}
+                            d.skip(list.decoder.offset())?;
+                            Ok(())
+                        })?;
+                    }
+                    self.xdr_decoder(data)
+                },
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -717,7 +933,7 @@ impl Decoder<'_> {
             DataType::Uint8 => DecodedDataValue::Uint8(self.decoder.get()?),
 
             DataType::BooleanArray => DecodedDataValue::BooleanArray(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -725,7 +941,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Int8Array => DecodedDataValue::Int8Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -733,7 +949,7 @@ impl Decoder<'_> {
                 phantom: PhantomData,
             }),
             DataType::Uint8Array => DecodedDataValue::Uint8Array(ArrayDecoder {
-                decoder: xdr::Decoder::from_bytes(self.decoder.get_n_bytes(element_count * 4)?),
+                decoder: self.xdr_decoder(self.decoder.get_n_bytes(element_count * 4)?),
                 count: element_count,
                 index: Cell::new(0),
                 endian: self.endian,
@@ -785,7 +1001,7 @@ impl Decoder<'_> {
      * Returns [`None`] if the pair is not found.
      * Resets the decoder prior to searching.
      */
-    pub fn find<'a, 'b>(&'a self, name: &'b str) -> Result<Option<DecodedPair<'a>>, DecodeError> {
+    pub fn find(&self, name: &str) -> Result<Option<DecodedPair<'a>>, DecodeError> {
         // Reset decoder to start.
         self.reset();
 
@@ -810,14 +1026,50 @@ impl Decoder<'_> {
      *
      * Returns [`None`] if the pair is not found.
      * Preserves main decoder offset.
+     *
+     * # Errors
+     *
+     * Returns [`DecodeError::NestedDecoderMismatch`] if the nested list was
+     * not decoded from the data of this decoder.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{DecodeError, Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     *
+     * let text = r#"{ "vdev_tree": { "nvlist": { "id": { "uint64": 7 } } } }"#;
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let other = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     *
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let pair = decoder.find("vdev_tree").unwrap().unwrap();
+     * let nested = pair.value.as_nv_list().unwrap();
+     *
+     * // The decoder can be moved.
+     * let decoders = vec![decoder];
+     * let id = decoders[0].find_nested(nested, "id").unwrap().unwrap();
+     * assert_eq!(*id.value.as_uint64().unwrap(), 7);
+     *
+     * // A decoder of other data, even if it is equal, does not match.
+     * let other = Decoder::from_bytes(&other).unwrap();
+     * assert!(matches!(
+     *     other.find_nested(nested, "id"),
+     *     Err(DecodeError::NestedDecoderMismatch {}),
+     * ));
+     * ```
      */
-    pub fn find_nested<'a, 'b, 'c>(
-        &'a self,
-        nested: &'b NestedDecoder,
-        name: &'c str,
+    pub fn find_nested(
+        &self,
+        nested: &NestedDecoder,
+        name: &str,
     ) -> Result<Option<DecodedPair<'a>>, DecodeError> {
-        // Check decoder matches.
-        if !std::ptr::eq(self, nested.parent) {
+        // Check decoder matches, by the pointer and length of its data, which
+        // do not change when the decoder is moved.
+        if !core::ptr::eq(self.decoder.data(), nested.parent) {
             return Err(DecodeError::NestedDecoderMismatch {});
         }
 
@@ -847,7 +1099,7 @@ impl Decoder<'_> {
             }
 
             // Get next pair.
-            match self.next_pair() {
+            match self.decode_pair(false) {
                 Ok(result) => {
                     pair = result;
 
@@ -938,35 +1190,6 @@ macro_rules! find_option_bool {
 
 pub(crate) use find_option_bool;
 
-macro_rules! find_option_nested {
-    ($decoder:expr, $nested:expr, $name:expr, $data_value_type:tt, $error:tt) => {{
-        // Find the pair.
-        let result = $decoder.find_nested($nested, $name)?;
-
-        match result {
-            Some(pair) => {
-                // Get the data type in case of an error.
-                let data_type = pair.data_type();
-
-                // Check the value type.
-                match pair.value {
-                    // It matches! Ok.
-                    nv::DecodedDataValue::$data_value_type(v) => Ok(Some(v)),
-                    // It does not match - error.
-                    _ => Err($error::ValueTypeMismatch {
-                        name: $name,
-                        data_type: data_type,
-                    }),
-                }
-            }
-            // If not found, then return None.
-            None => Ok(None),
-        }
-    }};
-}
-
-pub(crate) use find_option_nested;
-
 macro_rules! find {
     ($decoder:expr, $name:expr,  $data_value_type:tt, $error:tt) => {{
         // Find the pair, or return error that it is missing.
@@ -992,30 +1215,926 @@ macro_rules! find {
 
 pub(crate) use find;
 
-macro_rules! find_nested {
-    ($decoder:expr, $nested:expr, $name:expr,  $data_value_type:tt, $error:tt) => {{
-        // Find the pair, or return error that it is missing.
-        let pair = $decoder
-            .find_nested($nested, $name)?
-            .ok_or($error::MissingValue { name: $name })?;
+////////////////////////////////////////////////////////////////////////////////
 
-        // Get the data type in case of an error.
-        let data_type = pair.data_type();
+impl Decoder<'_> {
+    /** Writes the list in the indented `name: value` layout of `zdb -l`.
+     *
+     * - `indent` is the number of spaces before each top level name. `zdb -l`
+     *   uses an indent of 4.
+     * - Nested lists are indented by 4 more spaces.
+     * - Each element of a [`DataType::NvListArray`] is written as `name[N]:`,
+     *   followed by the nested list.
+     * - Resets the decoder prior to writing.
+     *
+     * # Errors
+     *
+     * Returns [`DumpError`] on decoding or formatting errors.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::nv::Decoder;
+     *
+     * let data = &[
+     *     0x01, 0x00, 0x00, 0x00, // XDR encoding, big endian
+     *     0x00, 0x00, 0x00, 0x00, // version
+     *     0x00, 0x00, 0x00, 0x01, // flags
+     *     // "name": String "tank"
+     *     0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00,
+     *     0x00, 0x00, 0x00, 0x04, 0x6e, 0x61, 0x6d, 0x65,
+     *     0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x01,
+     *     0x00, 0x00, 0x00, 0x04, 0x74, 0x61, 0x6e, 0x6b,
+     *     // "vdev_tree": NvList
+     *     0x00, 0x00, 0x00, 0x50, 0x00, 0x00, 0x00, 0x00,
+     *     0x00, 0x00, 0x00, 0x09, 0x76, 0x64, 0x65, 0x76,
+     *     0x5f, 0x74, 0x72, 0x65, 0x65, 0x00, 0x00, 0x00,
+     *     0x00, 0x00, 0x00, 0x13, 0x00, 0x00, 0x00, 0x01,
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+     *     //     "id": Uint64 7
+     *     0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00,
+     *     0x00, 0x00, 0x00, 0x02, 0x69, 0x64, 0x00, 0x00,
+     *     0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01,
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+     *     //     End of nested list.
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     *     // End of list.
+     *     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+     * ];
+     *
+     * let decoder = Decoder::from_bytes(data).unwrap();
+     *
+     * let mut output = String::new();
+     * decoder.dump(&mut output, 4).unwrap();
+     *
+     * assert_eq!(
+     *     output,
+     *     concat!(
+     *         "    name: 'tank'\n",
+     *         "    vdev_tree:\n",
+     *         "        id: 7\n",
+     *     )
+     * );
+     * ```
+     */
+    pub fn dump<W: fmt::Write>(&self, w: &mut W, indent: usize) -> Result<(), DumpError> {
+        self.reset();
 
-        // Check the value type.
-        match pair.value {
-            // It matches! Ok.
-            nv::DecodedDataValue::$data_value_type(v) => Ok(v),
-            // It does not match - error.
-            _ => Err($error::ValueTypeMismatch {
-                name: $name,
-                data_type: data_type,
-            }),
+        while let Some(pair) = self.next_pair()? {
+            let name = pair.name;
+
+            ////////////////////////////////
+            // Use macros for cleaner code.
+            macro_rules! dump_value {
+                ($fmt:literal, $value:expr) => {
+                    writeln!(
+                        w,
+                        concat!("{:indent$}{}: ", $fmt),
+                        "",
+                        name,
+                        $value,
+                        indent = indent
+                    )?
+                };
+            }
+
+            macro_rules! dump_array {
+                ($fmt:literal, $array:expr) => {{
+                    let array = $array;
+                    write!(w, "{:indent$}{}: ", "", name, indent = indent)?;
+                    while array.len() > 0 {
+                        write!(w, concat!($fmt, " "), array.get()?)?;
+                    }
+                    writeln!(w)?;
+                }};
+            }
+
+            ////////////////////////////////
+            // Write value.
+            match &pair.value {
+                DecodedDataValue::Boolean() => writeln!(w, "{:indent$}{}", "", name)?,
+
+                DecodedDataValue::Byte(v) => dump_value!("{}", v),
+                DecodedDataValue::Int16(v) => dump_value!("{}", v),
+                DecodedDataValue::Uint16(v) => dump_value!("{}", v),
+                DecodedDataValue::Int32(v) => dump_value!("{}", v),
+                DecodedDataValue::Uint32(v) => dump_value!("{}", v),
+                DecodedDataValue::Int64(v) => dump_value!("{}", v),
+                DecodedDataValue::Uint64(v) => dump_value!("{}", v),
+                DecodedDataValue::String(v) => dump_value!("'{}'", v),
+
+                DecodedDataValue::ByteArray(v) => {
+                    write!(w, "{:indent$}{}: ", "", name)?;
+                    for byte in v.iter() {
+                        write!(w, "{byte} ")?;
+                    }
+                    writeln!(w)?;
+                }
+                DecodedDataValue::Int16Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Uint16Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Int32Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Uint32Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Int64Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Uint64Array(v) => dump_array!("{}", v),
+                DecodedDataValue::StringArray(v) => dump_array!("'{}'", v),
+
+                DecodedDataValue::HrTime(v) => dump_value!("{}", v),
+
+                DecodedDataValue::NvList(v) => {
+                    writeln!(w, "{:indent$}{}:", "", name)?;
+                    v.get_decoder().dump(w, indent + 4)?;
+                }
+                DecodedDataValue::NvListArray(v) => {
+                    let mut index = 0;
+                    while !v.is_empty() {
+                        writeln!(w, "{:indent$}{}[{}]:", "", name, index)?;
+                        v.get()?.dump(w, indent + 4)?;
+                        index += 1;
+                    }
+                }
+
+                DecodedDataValue::BooleanValue(v) => dump_value!("{}", v),
+
+                DecodedDataValue::Int8(v) => dump_value!("{}", v),
+                DecodedDataValue::Uint8(v) => dump_value!("{}", v),
+
+                DecodedDataValue::BooleanArray(v) => dump_array!("{}", v),
+                DecodedDataValue::Int8Array(v) => dump_array!("{}", v),
+                DecodedDataValue::Uint8Array(v) => dump_array!("{}", v),
+
+                DecodedDataValue::Double(v) => dump_value!("{:.6}", v),
+            }
         }
-    }};
+
+        Ok(())
+    }
 }
 
-pub(crate) use find_nested;
+////////////////////////////////////////////////////////////////////////////////
+
+impl DataValue<'_> {
+    /// Gets the data type of the data value.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            DataValue::Boolean() => DataType::Boolean,
+
+            DataValue::Byte(_) => DataType::Byte,
+            DataValue::Int16(_) => DataType::Int16,
+            DataValue::Uint16(_) => DataType::Uint16,
+            DataValue::Int32(_) => DataType::Int32,
+            DataValue::Uint32(_) => DataType::Uint32,
+            DataValue::Int64(_) => DataType::Int64,
+            DataValue::Uint64(_) => DataType::Uint64,
+            DataValue::String(_) => DataType::String,
+
+            DataValue::ByteArray(_) => DataType::ByteArray,
+            DataValue::Int16Array(_) => DataType::Int16Array,
+            DataValue::Uint16Array(_) => DataType::Uint16Array,
+            DataValue::Int32Array(_) => DataType::Int32Array,
+            DataValue::Uint32Array(_) => DataType::Uint32Array,
+            DataValue::Int64Array(_) => DataType::Int64Array,
+            DataValue::Uint64Array(_) => DataType::Uint64Array,
+            DataValue::StringArray(_) => DataType::StringArray,
+
+            DataValue::HrTime(_) => DataType::HrTime,
+
+            DataValue::NvList(_) => DataType::NvList,
+            DataValue::NvListArray(_) => DataType::NvListArray,
+
+            DataValue::BooleanValue(_) => DataType::BooleanValue,
+
+            DataValue::Int8(_) => DataType::Int8,
+            DataValue::Uint8(_) => DataType::Uint8,
+
+            DataValue::BooleanArray(_) => DataType::BooleanArray,
+            DataValue::Int8Array(_) => DataType::Int8Array,
+            DataValue::Uint8Array(_) => DataType::Uint8Array,
+
+            DataValue::Double(_) => DataType::Double,
+        }
+    }
+
+    /// Gets the number of values.
+    pub fn count(&self) -> usize {
+        match self {
+            DataValue::Boolean() => 0,
+
+            DataValue::ByteArray(v) => v.len(),
+            DataValue::Int16Array(v) => v.len(),
+            DataValue::Uint16Array(v) => v.len(),
+            DataValue::Int32Array(v) => v.len(),
+            DataValue::Uint32Array(v) => v.len(),
+            DataValue::Int64Array(v) => v.len(),
+            DataValue::Uint64Array(v) => v.len(),
+            DataValue::StringArray(v) => v.len(),
+
+            DataValue::NvListArray(v) => v.len(),
+
+            DataValue::BooleanArray(v) => v.len(),
+            DataValue::Int8Array(v) => v.len(),
+            DataValue::Uint8Array(v) => v.len(),
+
+            _ => 1,
+        }
+    }
+
+    /** Gets the in memory size of the value, as used by the pair decoded size.
+     *
+     * Nested lists count only the in memory list structure, and not their
+     * pairs.
+     */
+    fn native_size(&self) -> usize {
+        match self {
+            DataValue::Boolean() => 0,
+
+            DataValue::Byte(_) => 1,
+            DataValue::Int16(_) => 2,
+            DataValue::Uint16(_) => 2,
+            DataValue::Int32(_) => 4,
+            DataValue::Uint32(_) => 4,
+            DataValue::Int64(_) => 8,
+            DataValue::Uint64(_) => 8,
+            DataValue::String(v) => v.len() + 1,
+
+            DataValue::ByteArray(v) => v.len(),
+            DataValue::Int16Array(v) => v.len() * 2,
+            DataValue::Uint16Array(v) => v.len() * 2,
+            DataValue::Int32Array(v) => v.len() * 4,
+            DataValue::Uint32Array(v) => v.len() * 4,
+            DataValue::Int64Array(v) => v.len() * 8,
+            DataValue::Uint64Array(v) => v.len() * 8,
+            DataValue::StringArray(v) => v.iter().map(|s| 8 + s.len() + 1).sum(),
+
+            DataValue::HrTime(_) => 8,
+
+            DataValue::NvList(_) => NATIVE_LIST_SIZE,
+            DataValue::NvListArray(v) => v.len() * (8 + NATIVE_LIST_SIZE),
+
+            DataValue::BooleanValue(_) => 4,
+
+            DataValue::Int8(_) => 1,
+            DataValue::Uint8(_) => 1,
+
+            DataValue::BooleanArray(v) => v.len() * 4,
+            DataValue::Int8Array(v) => v.len(),
+            DataValue::Uint8Array(v) => v.len(),
+
+            DataValue::Double(_) => 8,
+        }
+    }
+}
+
+/// In memory size of a pair header, without the name.
+const NATIVE_PAIR_SIZE: usize = 16;
+
+/// In memory size of a list structure.
+const NATIVE_LIST_SIZE: usize = 24;
+
+/** Computes the in memory size of a pair, as used by the pair decoded size.
+ *
+ * - `name` - Name of pair.
+ * - `value_size` - In memory size of the value.
+ */
+fn native_pair_size(name: &str, value_size: usize) -> usize {
+    let align = |x: usize| (x + 7) & !7;
+    align(NATIVE_PAIR_SIZE + name.len() + 1) + align(value_size)
+}
+
+/** An name value pair list encoder.
+ *
+ * - Pairs are encoded with [`Encoder::put`].
+ * - Nested lists can be encoded from a [`List`] with [`Encoder::put`], or
+ *   pair by pair, between [`Encoder::put_nv_list_start`] and
+ *   [`Encoder::put_nv_list_end`].
+ * - [`Encoder::finish`] ends the list.
+ *
+ * [`Encoding::Native`] is the in memory layout of the pairs, as packed by
+ * `nvs_native` in the C reference:
+ * - A list ends with a single [`u32`] zero, instead of two.
+ * - A pair is its in memory structure, of the decoded size: the encoded
+ *   size, name size, count, and data type, then the name and the value, each
+ *   padded to 8 bytes. Pointers, such as those of a string array, are zero.
+ * - A nested list pair has the in memory list structure as its value, and
+ *   the nested list follows the pair.
+ */
+#[derive(Debug)]
+pub struct Encoder<'a> {
+    encoder: EncoderData<'a>,
+    encoding: Encoding,
+    endian: Endian,
+}
+
+/// Encoder of the [`Encoding`] of an [`Encoder`].
+#[derive(Debug)]
+enum EncoderData<'a> {
+    Native(endian::Encoder<'a>),
+    Xdr(xdr::Encoder<'a>),
+}
+
+/** A nested list, or list array, that is being encoded.
+ *
+ * Returned by [`Encoder::put_nv_list_start`] and
+ * [`Encoder::put_nv_list_array_start`], and passed to
+ * [`Encoder::put_nv_list_end`] to fill in the encoded size of the pair.
+ */
+#[derive(Debug)]
+#[must_use]
+pub struct NestedEncoder {
+    data_type: DataType,
+
+    /// Offset of the pair, or of the first in memory list structure of a
+    /// [`Encoding::Native`] list array.
+    offset: usize,
+
+    count: usize,
+    index: usize,
+}
+
+impl Encoder<'_> {
+    /** Instantiates a NV list [`Encoder`] for a mutable slice of bytes, and
+     * encodes the NV list header.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{DataValue, Decoder, Encoder, Encoding, Unique};
+     *
+     * // Encode.
+     * let data = &mut [0; 256];
+     * let mut encoder = Encoder::to_bytes(data, Encoding::Xdr, Endian::Big, Unique::Name).unwrap();
+     *
+     * encoder.put("name", &DataValue::String("tank")).unwrap();
+     *
+     * let nested = encoder.put_nv_list_start("vdev_tree", Unique::Name).unwrap();
+     * encoder.put("id", &DataValue::Uint64(7)).unwrap();
+     * encoder.put_nv_list_end(nested).unwrap();
+     *
+     * let length = encoder.finish().unwrap();
+     *
+     * // Decode.
+     * let decoder = Decoder::from_bytes_strict(&data[0..length]).unwrap();
+     *
+     * let pair = decoder.next_pair().unwrap().unwrap();
+     * assert_eq!(pair.name, "name");
+     * assert_eq!(*pair.value.as_string().unwrap(), "tank");
+     *
+     * let pair = decoder.next_pair().unwrap().unwrap();
+     * let nested = pair.value.as_nv_list().unwrap();
+     * let id = decoder.find_nested(nested, "id").unwrap().unwrap();
+     * assert_eq!(*id.value.as_uint64().unwrap(), 7);
+     *
+     * assert!(decoder.next_pair().unwrap().is_none());
+     * ```
+     *
+     * Native encoding:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{DataValue, Encoder, Encoding, Unique};
+     *
+     * let data = &mut [0; 64];
+     * let mut encoder =
+     *     Encoder::to_bytes(data, Encoding::Native, Endian::Little, Unique::Name).unwrap();
+     * encoder.put("a", &DataValue::Uint64(7)).unwrap();
+     * let length = encoder.finish().unwrap();
+     *
+     * assert_eq!(
+     *     &data[0..length],
+     *     &[
+     *         0, 1, 0, 0, // Header.
+     *         0, 0, 0, 0, 1, 0, 0, 0, // Version and flags.
+     *         32, 0, 0, 0, 2, 0, 0, 0, // Size, name size, and reserved.
+     *         1, 0, 0, 0, 8, 0, 0, 0, // Count and data type.
+     *         b'a', 0, 0, 0, 0, 0, 0, 0, // Name.
+     *         7, 0, 0, 0, 0, 0, 0, 0, // Value.
+     *         0, 0, 0, 0, // End of list.
+     *     ]
+     * );
+     * ```
+     */
+    pub fn to_bytes(
+        data: &mut [u8],
+        encoding: Encoding,
+        endian: Endian,
+        unique: Unique,
+    ) -> Result<Encoder<'_>, EncodeError> {
+        // Header.
+        let endian_byte = match endian {
+            Endian::Big => 0,
+            Endian::Little => 1,
+        };
+        let header = [encoding as u8, endian_byte, 0, 0];
+
+        let encoder = match encoding {
+            Encoding::Native => {
+                let mut encoder = endian::Encoder::to_bytes(data, endian);
+                encoder.put_bytes(&header)?;
+                EncoderData::Native(encoder)
+            }
+            Encoding::Xdr => {
+                let mut encoder = xdr::Encoder::to_bytes(data);
+                encoder.put_n_bytes(&header)?;
+                EncoderData::Xdr(encoder)
+            }
+        };
+
+        let mut encoder = Encoder {
+            encoder: encoder,
+            encoding: encoding,
+            endian: endian,
+        };
+
+        // List version and flags.
+        encoder.put_list_start(unique)?;
+
+        Ok(encoder)
+    }
+
+    /// Returns the [`Encoding`] of the encoder.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the [`Endian`] of the encoder.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Returns the length of the encoded data.
+    pub fn len(&self) -> usize {
+        match &self.encoder {
+            EncoderData::Native(encoder) => encoder.len(),
+            EncoderData::Xdr(encoder) => encoder.len(),
+        }
+    }
+
+    /// Returns true if nothing has been encoded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /** Encodes a [`u32`].
+     */
+    fn put_u32(&mut self, value: u32) -> Result<(), EncodeError> {
+        match &mut self.encoder {
+            EncoderData::Native(encoder) => encoder.put_u32(value)?,
+            EncoderData::Xdr(encoder) => encoder.put_u32(value)?,
+        }
+        Ok(())
+    }
+
+    /** Encodes the version and flags that start a list.
+     */
+    fn put_list_start(&mut self, unique: Unique) -> Result<(), EncodeError> {
+        self.put_u32(0)?;
+        self.put_u32(unique as u32)?;
+        Ok(())
+    }
+
+    /** Encodes the zero sizes that end a list.
+     *
+     * - [`Encoding::Native`] has only the encoded size.
+     */
+    fn put_list_end(&mut self) -> Result<(), EncodeError> {
+        self.put_u32(0)?;
+        if let EncoderData::Xdr(_) = self.encoder {
+            self.put_u32(0)?;
+        }
+        Ok(())
+    }
+
+    /** Encodes a pair header.
+     *
+     * - [`Encoding::Xdr`] has an encoded size of zero, that is filled in by
+     *   [`Encoder::put_pair_end`].
+     *
+     * Returns the offset of the pair.
+     */
+    fn put_pair_start(
+        &mut self,
+        name: &str,
+        data_type: DataType,
+        count: usize,
+        value_size: usize,
+    ) -> Result<usize, EncodeError> {
+        let offset = self.len();
+        let size = native_pair_size(name, value_size);
+
+        match &mut self.encoder {
+            EncoderData::Native(encoder) => {
+                encoder.put_u32(size as u32)?;
+                encoder.put_u16((name.len() + 1) as u16)?;
+                encoder.put_u16(0)?;
+                encoder.put_u32(count as u32)?;
+                encoder.put_u32(data_type as u32)?;
+                encoder.put_bytes(name.as_bytes())?;
+                encoder.put_u8(0)?;
+                put_native_padding(encoder, offset)?;
+            }
+            EncoderData::Xdr(encoder) => {
+                encoder.put_u32(0)?;
+                encoder.put_usize(size)?;
+                encoder.put_str(name)?;
+                encoder.put_u32(data_type as u32)?;
+                encoder.put_usize(count)?;
+            }
+        }
+
+        Ok(offset)
+    }
+
+    /** Ends the pair starting at `offset`.
+     *
+     * - [`Encoding::Native`] pads the value to 8 bytes.
+     * - [`Encoding::Xdr`] fills in the encoded size.
+     */
+    fn put_pair_end(&mut self, offset: usize) -> Result<(), EncodeError> {
+        match &mut self.encoder {
+            EncoderData::Native(encoder) => put_native_padding(encoder, offset)?,
+            EncoderData::Xdr(encoder) => {
+                let end = encoder.len();
+
+                encoder.seek(offset)?;
+                encoder.put_usize(end - offset)?;
+                encoder.seek(end)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /** Encodes a name value pair.
+     *
+     * - [`DataValue::NvList`] and [`DataValue::NvListArray`] are encoded with
+     *   the [`Encoding`] and [`Endian`] of this encoder.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     */
+    pub fn put(&mut self, name: &str, value: &DataValue) -> Result<(), EncodeError> {
+        ////////////////////////////////
+        // Nested lists.
+        match value {
+            DataValue::NvList(list) => {
+                let nested = self.put_nv_list_start(name, list.unique)?;
+                for pair in list.pairs {
+                    self.put(pair.name, &pair.value)?;
+                }
+                return self.put_nv_list_end(nested);
+            }
+            DataValue::NvListArray(lists) => {
+                let mut nested = self.put_nv_list_array_start(name, lists.len())?;
+                for list in lists.iter() {
+                    self.put_nv_list_array_element_start(&mut nested, list.unique)?;
+                    for pair in list.pairs {
+                        self.put(pair.name, &pair.value)?;
+                    }
+                    self.put_nv_list_array_element_end(&nested)?;
+                }
+                return self.put_nv_list_end(nested);
+            }
+            _ => (),
+        }
+
+        ////////////////////////////////
+        // Pair header.
+        let offset =
+            self.put_pair_start(name, value.data_type(), value.count(), value.native_size())?;
+
+        ////////////////////////////////
+        // Value.
+        match &mut self.encoder {
+            EncoderData::Native(encoder) => put_native_value(encoder, value)?,
+            EncoderData::Xdr(encoder) => put_xdr_value(encoder, value)?,
+        }
+
+        ////////////////////////////////
+        // End of pair.
+        self.put_pair_end(offset)
+    }
+
+    /** Starts encoding a nested list pair.
+     *
+     * - Pairs encoded with [`Encoder::put`] are added to the nested list,
+     *   until [`Encoder::put_nv_list_end`] is called.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     */
+    pub fn put_nv_list_start(
+        &mut self,
+        name: &str,
+        unique: Unique,
+    ) -> Result<NestedEncoder, EncodeError> {
+        let offset = self.put_pair_start(name, DataType::NvList, 1, NATIVE_LIST_SIZE)?;
+
+        // The native pair has the in memory list, and is followed by the list.
+        if let EncoderData::Native(encoder) = &mut self.encoder {
+            put_native_list(encoder, unique)?;
+            put_native_padding(encoder, offset)?;
+        }
+
+        self.put_list_start(unique)?;
+
+        Ok(NestedEncoder {
+            data_type: DataType::NvList,
+            offset: offset,
+            count: 1,
+            index: 1,
+        })
+    }
+
+    /** Starts encoding a nested list array pair, of `count` lists.
+     *
+     * - Each list is started with [`Encoder::put_nv_list_array_element_start`]
+     *   and ended with [`Encoder::put_nv_list_array_element_end`].
+     * - The array is ended with [`Encoder::put_nv_list_end`].
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     */
+    pub fn put_nv_list_array_start(
+        &mut self,
+        name: &str,
+        count: usize,
+    ) -> Result<NestedEncoder, EncodeError> {
+        let mut offset = self.put_pair_start(
+            name,
+            DataType::NvListArray,
+            count,
+            count * (8 + NATIVE_LIST_SIZE),
+        )?;
+
+        // The native pair has the pointers and the in memory lists, whose flags
+        // are filled in as each list is started, and is followed by the lists.
+        if let EncoderData::Native(encoder) = &mut self.encoder {
+            encoder.put_zero_padding(count * 8)?;
+            offset = encoder.len();
+            encoder.put_zero_padding(count * NATIVE_LIST_SIZE)?;
+        }
+
+        Ok(NestedEncoder {
+            data_type: DataType::NvListArray,
+            offset: offset,
+            count: count,
+            index: 0,
+        })
+    }
+
+    /** Starts encoding the next list of a nested list array.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError::InvalidCount`] if all the lists of the array were
+     * already started.
+     */
+    pub fn put_nv_list_array_element_start(
+        &mut self,
+        nested: &mut NestedEncoder,
+        unique: Unique,
+    ) -> Result<(), EncodeError> {
+        match nested.data_type {
+            DataType::NvListArray => (),
+            _ => return Err(EncodeError::NestedEncoderMismatch {}),
+        }
+
+        if nested.index >= nested.count {
+            return Err(EncodeError::InvalidCount {
+                data_type: nested.data_type,
+                count: nested.index + 1,
+            });
+        }
+
+        // Fill in the flags of the in memory list.
+        if let EncoderData::Native(encoder) = &mut self.encoder {
+            let end = encoder.len();
+
+            encoder.seek(nested.offset + nested.index * NATIVE_LIST_SIZE)?;
+            put_native_list(encoder, unique)?;
+            encoder.seek(end)?;
+        }
+
+        nested.index += 1;
+
+        self.put_list_start(unique)
+    }
+
+    /** Ends encoding the current list of a nested list array.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     */
+    pub fn put_nv_list_array_element_end(
+        &mut self,
+        nested: &NestedEncoder,
+    ) -> Result<(), EncodeError> {
+        match nested.data_type {
+            DataType::NvListArray => (),
+            _ => return Err(EncodeError::NestedEncoderMismatch {}),
+        }
+
+        self.put_list_end()
+    }
+
+    /** Ends encoding a nested list, or nested list array pair.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError::InvalidCount`] if fewer lists than the count of
+     * the array were encoded.
+     */
+    pub fn put_nv_list_end(&mut self, nested: NestedEncoder) -> Result<(), EncodeError> {
+        match nested.data_type {
+            DataType::NvList => self.put_list_end()?,
+            _ => {
+                if nested.index != nested.count {
+                    return Err(EncodeError::InvalidCount {
+                        data_type: nested.data_type,
+                        count: nested.index,
+                    });
+                }
+            }
+        }
+
+        // The native pair ended before the nested lists.
+        match self.encoder {
+            EncoderData::Native(_) => Ok(()),
+            EncoderData::Xdr(_) => self.put_pair_end(nested.offset),
+        }
+    }
+
+    /** Ends the list, and returns the length of the encoded data.
+     *
+     * # Errors.
+     *
+     * Returns [`EncodeError`] on error.
+     */
+    pub fn finish(mut self) -> Result<usize, EncodeError> {
+        self.put_list_end()?;
+        Ok(self.len())
+    }
+}
+
+/** Encodes the value of a pair, except nested lists, with [`Encoding::Xdr`].
+ */
+fn put_xdr_value(encoder: &mut xdr::Encoder, value: &DataValue) -> Result<(), EncodeError> {
+    // Use macros for cleaner code.
+    macro_rules! put_array {
+        ($array:expr) => {{
+            for v in $array.iter() {
+                encoder.put(*v)?;
+            }
+        }};
+    }
+
+    match value {
+        DataValue::Boolean() => (),
+
+        DataValue::Byte(v) => encoder.put_u8(*v)?,
+        DataValue::Int16(v) => encoder.put_i16(*v)?,
+        DataValue::Uint16(v) => encoder.put_u16(*v)?,
+        DataValue::Int32(v) => encoder.put_i32(*v)?,
+        DataValue::Uint32(v) => encoder.put_u32(*v)?,
+        DataValue::Int64(v) => encoder.put_i64(*v)?,
+        DataValue::Uint64(v) => encoder.put_u64(*v)?,
+        DataValue::String(v) => encoder.put_str(v)?,
+
+        DataValue::ByteArray(v) => encoder.put_bytes(v)?,
+        DataValue::Int16Array(v) => put_array!(v),
+        DataValue::Uint16Array(v) => put_array!(v),
+        DataValue::Int32Array(v) => put_array!(v),
+        DataValue::Uint32Array(v) => put_array!(v),
+        DataValue::Int64Array(v) => put_array!(v),
+        DataValue::Uint64Array(v) => put_array!(v),
+        DataValue::StringArray(v) => {
+            for s in v.iter() {
+                encoder.put_str(s)?;
+            }
+        }
+
+        DataValue::HrTime(v) => encoder.put_i64(*v)?,
+
+        DataValue::NvList(_) | DataValue::NvListArray(_) => unreachable!(),
+
+        DataValue::BooleanValue(v) => encoder.put_bool(*v)?,
+
+        DataValue::Int8(v) => encoder.put_i8(*v)?,
+        DataValue::Uint8(v) => encoder.put_u8(*v)?,
+
+        DataValue::BooleanArray(v) => put_array!(v),
+        DataValue::Int8Array(v) => put_array!(v),
+        DataValue::Uint8Array(v) => put_array!(v),
+
+        DataValue::Double(v) => encoder.put_f64(*v)?,
+    }
+
+    Ok(())
+}
+
+/** Encodes the value of a pair, except nested lists, with
+ * [`Encoding::Native`].
+ *
+ * - Booleans are 4 bytes, and strings end with a zero byte.
+ * - A string array has a zero pointer for each string, then the strings.
+ */
+fn put_native_value(encoder: &mut endian::Encoder, value: &DataValue) -> Result<(), EncodeError> {
+    // Use macros for cleaner code.
+    macro_rules! put_array {
+        ($array:expr, $put:ident) => {{
+            for v in $array.iter() {
+                encoder.$put(*v)?;
+            }
+        }};
+    }
+
+    match value {
+        DataValue::Boolean() => (),
+
+        DataValue::Byte(v) => encoder.put_u8(*v)?,
+        DataValue::Int16(v) => encoder.put_i16(*v)?,
+        DataValue::Uint16(v) => encoder.put_u16(*v)?,
+        DataValue::Int32(v) => encoder.put_i32(*v)?,
+        DataValue::Uint32(v) => encoder.put_u32(*v)?,
+        DataValue::Int64(v) => encoder.put_i64(*v)?,
+        DataValue::Uint64(v) => encoder.put_u64(*v)?,
+        DataValue::String(v) => {
+            encoder.put_bytes(v.as_bytes())?;
+            encoder.put_u8(0)?;
+        }
+
+        DataValue::ByteArray(v) => encoder.put_bytes(v)?,
+        DataValue::Int16Array(v) => put_array!(v, put_i16),
+        DataValue::Uint16Array(v) => put_array!(v, put_u16),
+        DataValue::Int32Array(v) => put_array!(v, put_i32),
+        DataValue::Uint32Array(v) => put_array!(v, put_u32),
+        DataValue::Int64Array(v) => put_array!(v, put_i64),
+        DataValue::Uint64Array(v) => put_array!(v, put_u64),
+        DataValue::StringArray(v) => {
+            encoder.put_zero_padding(v.len() * 8)?;
+            for s in v.iter() {
+                encoder.put_bytes(s.as_bytes())?;
+                encoder.put_u8(0)?;
+            }
+        }
+
+        DataValue::HrTime(v) => encoder.put_i64(*v)?,
+
+        DataValue::NvList(_) | DataValue::NvListArray(_) => unreachable!(),
+
+        DataValue::BooleanValue(v) => encoder.put_u32(*v as u32)?,
+
+        DataValue::Int8(v) => encoder.put_i8(*v)?,
+        DataValue::Uint8(v) => encoder.put_u8(*v)?,
+
+        DataValue::BooleanArray(v) => {
+            for b in v.iter() {
+                encoder.put_u32(*b as u32)?;
+            }
+        }
+        DataValue::Int8Array(v) => put_array!(v, put_i8),
+        DataValue::Uint8Array(v) => encoder.put_bytes(v)?,
+
+        DataValue::Double(v) => encoder.put_u64(v.to_bits())?,
+    }
+
+    Ok(())
+}
+
+/** Encodes an in memory list structure, with [`Encoding::Native`].
+ *
+ * - C reference: `nvlist_t`
+ * - The version and flags, and a zero private pointer, flags, and padding.
+ */
+fn put_native_list(encoder: &mut endian::Encoder, unique: Unique) -> Result<(), EncodeError> {
+    encoder.put_u32(0)?;
+    encoder.put_u32(unique as u32)?;
+    encoder.put_u64(0)?;
+    encoder.put_u32(0)?;
+    encoder.put_u32(0)?;
+    Ok(())
+}
+
+/** Pads with zeroes to a multiple of 8 bytes from the pair starting at
+ * `offset`, with [`Encoding::Native`].
+ */
+fn put_native_padding(encoder: &mut endian::Encoder, offset: usize) -> Result<(), EncodeError> {
+    let length = encoder.len() - offset;
+    encoder.put_zero_padding(((length + 7) & !7) - length)?;
+    Ok(())
+}
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -1111,6 +2230,13 @@ pub enum DecodeError {
     /** Nested decoder mismatch. */
     NestedDecoderMismatch {},
 
+    /** Non-zero bytes after the end of the list (strict mode only).
+     *
+     * - `offset` - Byte offset of data after the end of the list.
+     * - `length` - Length of data after the end of the list.
+     */
+    TrailingGarbage { offset: usize, length: usize },
+
     /** XDR decoding error.
      *
      * - `err` - Error.
@@ -1171,6 +2297,12 @@ impl fmt::Display for DecodeError {
             DecodeError::NestedDecoderMismatch {} => {
                 write!(f, "NV List nested decoder mismatch")
             }
+            DecodeError::TrailingGarbage { offset, length } => {
+                write!(
+                    f,
+                    "NV List trailing garbage of length {length} at offset {offset}"
+                )
+            }
             DecodeError::Xdr { err } => {
                 write!(f, "NV List XDR decoding error: {err}")
             }
@@ -1187,3 +2319,124 @@ impl error::Error for DecodeError {
         }
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum EncodeError {
+    /** Data type has an invalid count.
+     *
+     * - `data_type` - Data type.
+     * - `count`     - Count.
+     */
+    InvalidCount { data_type: DataType, count: usize },
+
+    /** Nested encoder mismatch. */
+    NestedEncoderMismatch {},
+
+    /** Endian encoding error, of [`Encoding::Native`].
+     *
+     * - `err` - Error.
+     */
+    Endian { err: endian::EncodeError },
+
+    /** XDR encoding error.
+     *
+     * - `err` - Error.
+     */
+    Xdr { err: xdr::EncodeError },
+}
+
+impl From<endian::EncodeError> for EncodeError {
+    fn from(value: endian::EncodeError) -> Self {
+        EncodeError::Endian { err: value }
+    }
+}
+
+impl From<xdr::EncodeError> for EncodeError {
+    fn from(value: xdr::EncodeError) -> Self {
+        EncodeError::Xdr { err: value }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::InvalidCount { data_type, count } => {
+                write!(f, "NV Pair invalid count {count} for data type {data_type}")
+            }
+            EncodeError::NestedEncoderMismatch {} => {
+                write!(f, "NV List nested encoder mismatch")
+            }
+            EncodeError::Endian { err } => {
+                write!(f, "NV List native encoding error: {err}")
+            }
+            EncodeError::Xdr { err } => {
+                write!(f, "NV List XDR encoding error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            EncodeError::Endian { err } => Some(err),
+            EncodeError::Xdr { err } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum DumpError {
+    /** NV decoding error.
+     *
+     * - `err` - Error.
+     */
+    DecodeError { err: DecodeError },
+
+    /** Formatting error.
+     *
+     * - `err` - Error.
+     */
+    FormatError { err: fmt::Error },
+}
+
+impl From<DecodeError> for DumpError {
+    fn from(value: DecodeError) -> Self {
+        DumpError::DecodeError { err: value }
+    }
+}
+
+impl From<fmt::Error> for DumpError {
+    fn from(value: fmt::Error) -> Self {
+        DumpError::FormatError { err: value }
+    }
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DumpError::DecodeError { err } => {
+                write!(f, "NV List dump decode error: {err}")
+            }
+            DumpError::FormatError { err } => {
+                write!(f, "NV List dump format error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DumpError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DumpError::DecodeError { err } => Some(err),
+            DumpError::FormatError { err } => Some(err),
+        }
+    }
+}
@@ -0,0 +1,746 @@
+/*! A JSON to Name Value list encoder, for building test fixtures.
+ *
+ * The JSON text is an object, whose keys are the names of the pairs, in
+ * order. Each value is an object with a single key, which is the type of the
+ * pair, and whose value is the value of the pair:
+ *
+ * ```json
+ * {
+ *     "version": { "uint64": 5000 },
+ *     "name": { "string": "tank" },
+ *     "features_for_read": {
+ *         "nvlist": {
+ *             "com.delphix:hole_birth": { "boolean": null }
+ *         }
+ *     },
+ *     "children": {
+ *         "nvlist_array": [
+ *             { "id": { "uint64": 0 } },
+ *             { "id": { "uint64": 1 } }
+ *         ]
+ *     }
+ * }
+ * ```
+ *
+ * Types
+ * =====
+ * - `boolean` has a `null` value.
+ * - `byte`, `int8`, `uint8`, `int16`, `uint16`, `int32`, `uint32`, `int64`,
+ *   `uint64`, and `hrtime` have an integer value.
+ * - `boolean_value` has a `true` or `false` value.
+ * - `double` has a number value.
+ * - `string` has a string value.
+ * - `nvlist` has an object value, in the same format as the top level list.
+ * - `byte_array`, `int8_array`, `uint8_array`, `int16_array`, `uint16_array`,
+ *   `int32_array`, `uint32_array`, `int64_array`, `uint64_array`,
+ *   `boolean_array`, `string_array`, and `nvlist_array` have an array value
+ *   of the corresponding element values.
+ *
+ * Integers are parsed exactly, so 64 bit values such as GUIDs do not lose
+ * precision. Nested lists use the [`Unique`] flags of the top level list.
+ */
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+use core::str::FromStr;
+
+use std::error;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::endian;
+use crate::endian::Endian;
+use crate::nv;
+use crate::nv::{DataValue, Encoding, Unique};
+use crate::xdr;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A parsed JSON value.
+ *
+ * - Numbers are kept as text, so that they can be parsed exactly for the type
+ *   of the pair.
+ * - Object keys are kept in order.
+ */
+#[derive(Debug)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/** A JSON parser.
+ */
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    /** Returns the next character, without consuming it.
+     */
+    fn peek(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    /** Returns the next character.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError::EndOfInput`] at the end of the text.
+     */
+    fn next(&mut self) -> Result<char, ParseError> {
+        match self.peek() {
+            Some(c) => {
+                self.offset += c.len_utf8();
+                Ok(c)
+            }
+            None => Err(ParseError::EndOfInput {
+                offset: self.offset,
+            }),
+        }
+    }
+
+    /** Skips whitespace.
+     */
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.offset += 1;
+        }
+    }
+
+    /** Consumes the expected character, after any whitespace.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] if the next character is not `expected`.
+     */
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        let offset = self.offset;
+        let c = self.next()?;
+        if c != expected {
+            return Err(ParseError::InvalidCharacter {
+                offset: offset,
+                character: c,
+            });
+        }
+
+        Ok(())
+    }
+
+    /** Consumes the expected keyword.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] if the text does not match `keyword`.
+     */
+    fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        for expected in keyword.chars() {
+            let offset = self.offset;
+            let c = self.next()?;
+            if c != expected {
+                return Err(ParseError::InvalidCharacter {
+                    offset: offset,
+                    character: c,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /** Parses a value.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] on malformed JSON.
+     */
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+
+        let offset = self.offset;
+        match self.peek() {
+            Some('n') => {
+                self.keyword("null")?;
+                Ok(Value::Null)
+            }
+            Some('t') => {
+                self.keyword("true")?;
+                Ok(Value::Bool(true))
+            }
+            Some('f') => {
+                self.keyword("false")?;
+                Ok(Value::Bool(false))
+            }
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                while let Some(c) = self.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.') {
+                        break;
+                    }
+                    self.offset += 1;
+                }
+                Ok(Value::Number(String::from(&self.text[offset..self.offset])))
+            }
+            Some(c) => Err(ParseError::InvalidCharacter {
+                offset: offset,
+                character: c,
+            }),
+            None => Err(ParseError::EndOfInput { offset: offset }),
+        }
+    }
+
+    /** Parses a string.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] on malformed JSON.
+     */
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+
+        let mut value = String::new();
+
+        loop {
+            let offset = self.offset;
+            match self.next()? {
+                '"' => return Ok(value),
+                '\\' => match self.next()? {
+                    '"' => value.push('"'),
+                    '\\' => value.push('\\'),
+                    '/' => value.push('/'),
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{c}'),
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'u' => {
+                        let start = self.offset;
+                        for _ in 0..4 {
+                            self.next()?;
+                        }
+                        let c = match u32::from_str_radix(&self.text[start..self.offset], 16) {
+                            Ok(v) => char::from_u32(v),
+                            Err(_) => None,
+                        };
+                        match c {
+                            Some(c) => value.push(c),
+                            None => return Err(ParseError::InvalidEscape { offset: offset }),
+                        }
+                    }
+                    _ => return Err(ParseError::InvalidEscape { offset: offset }),
+                },
+                c => value.push(c),
+            }
+        }
+    }
+
+    /** Parses an array.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] on malformed JSON.
+     */
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect('[')?;
+
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.offset += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            let offset = self.offset;
+            match self.next()? {
+                ',' => continue,
+                ']' => return Ok(Value::Array(values)),
+                c => {
+                    return Err(ParseError::InvalidCharacter {
+                        offset: offset,
+                        character: c,
+                    })
+                }
+            }
+        }
+    }
+
+    /** Parses an object.
+     *
+     * # Errors
+     *
+     * Returns [`ParseError`] on malformed JSON.
+     */
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.offset += 1;
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.expect(':')?;
+            members.push((name, self.value()?));
+
+            self.skip_whitespace();
+            let offset = self.offset;
+            match self.next()? {
+                ',' => continue,
+                '}' => return Ok(Value::Object(members)),
+                c => {
+                    return Err(ParseError::InvalidCharacter {
+                        offset: offset,
+                        character: c,
+                    })
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Parses a number value of a pair.
+ *
+ * # Errors
+ *
+ * Returns [`ParseError::InvalidValue`] if the value is not a number, or is out
+ * of range.
+ */
+fn number<T: FromStr>(name: &str, value: &Value) -> Result<T, ParseError> {
+    match value {
+        Value::Number(v) => match v.parse() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(ParseError::InvalidValue {
+                name: String::from(name),
+                detail: "number is malformed or out of range",
+            }),
+        },
+        _ => Err(ParseError::InvalidValue {
+            name: String::from(name),
+            detail: "expected a number",
+        }),
+    }
+}
+
+/** Returns the elements of an array value of a pair.
+ *
+ * # Errors
+ *
+ * Returns [`ParseError::InvalidValue`] if the value is not an array.
+ */
+fn array<'a>(name: &str, value: &'a Value) -> Result<&'a [Value], ParseError> {
+    match value {
+        Value::Array(v) => Ok(v),
+        _ => Err(ParseError::InvalidValue {
+            name: String::from(name),
+            detail: "expected an array",
+        }),
+    }
+}
+
+/** Returns the members of an object value of a pair.
+ *
+ * # Errors
+ *
+ * Returns [`ParseError::InvalidValue`] if the value is not an object.
+ */
+fn object<'a>(name: &str, value: &'a Value) -> Result<&'a [(String, Value)], ParseError> {
+    match value {
+        Value::Object(v) => Ok(v),
+        _ => Err(ParseError::InvalidValue {
+            name: String::from(name),
+            detail: "expected an object",
+        }),
+    }
+}
+
+/** Encodes the members of an object as the pairs of a list.
+ *
+ * # Errors
+ *
+ * Returns [`ParseError`] on invalid pairs, or encoding errors.
+ */
+fn put_pairs(
+    encoder: &mut nv::Encoder,
+    members: &[(String, Value)],
+    unique: Unique,
+) -> Result<(), ParseError> {
+    for (name, typed_value) in members {
+        let name = name.as_str();
+
+        // Each pair is an object with one key, the type.
+        let (data_type, value) = match object(name, typed_value)? {
+            [(data_type, value)] => (data_type.as_str(), value),
+            _ => {
+                return Err(ParseError::InvalidValue {
+                    name: String::from(name),
+                    detail: "expected an object with one type",
+                })
+            }
+        };
+
+        ////////////////////////////////
+        // Use macros for cleaner code.
+        macro_rules! put_number {
+            ($data_value_type:tt) => {
+                encoder.put(name, &DataValue::$data_value_type(number(name, value)?))?
+            };
+        }
+
+        macro_rules! put_number_array {
+            ($data_value_type:tt) => {{
+                let mut values = Vec::new();
+                for v in array(name, value)? {
+                    values.push(number(name, v)?);
+                }
+                encoder.put(name, &DataValue::$data_value_type(&values))?
+            }};
+        }
+
+        ////////////////////////////////
+        // Encode pair.
+        match data_type {
+            "boolean" => match value {
+                Value::Null => encoder.put(name, &DataValue::Boolean())?,
+                _ => {
+                    return Err(ParseError::InvalidValue {
+                        name: String::from(name),
+                        detail: "expected null",
+                    })
+                }
+            },
+            "boolean_value" => match value {
+                Value::Bool(v) => encoder.put(name, &DataValue::BooleanValue(*v))?,
+                _ => {
+                    return Err(ParseError::InvalidValue {
+                        name: String::from(name),
+                        detail: "expected true or false",
+                    })
+                }
+            },
+
+            "byte" => put_number!(Byte),
+            "int8" => put_number!(Int8),
+            "uint8" => put_number!(Uint8),
+            "int16" => put_number!(Int16),
+            "uint16" => put_number!(Uint16),
+            "int32" => put_number!(Int32),
+            "uint32" => put_number!(Uint32),
+            "int64" => put_number!(Int64),
+            "uint64" => put_number!(Uint64),
+            "hrtime" => put_number!(HrTime),
+            "double" => put_number!(Double),
+
+            "string" => match value {
+                Value::String(v) => encoder.put(name, &DataValue::String(v))?,
+                _ => {
+                    return Err(ParseError::InvalidValue {
+                        name: String::from(name),
+                        detail: "expected a string",
+                    })
+                }
+            },
+
+            "byte_array" => put_number_array!(ByteArray),
+            "int8_array" => put_number_array!(Int8Array),
+            "uint8_array" => put_number_array!(Uint8Array),
+            "int16_array" => put_number_array!(Int16Array),
+            "uint16_array" => put_number_array!(Uint16Array),
+            "int32_array" => put_number_array!(Int32Array),
+            "uint32_array" => put_number_array!(Uint32Array),
+            "int64_array" => put_number_array!(Int64Array),
+            "uint64_array" => put_number_array!(Uint64Array),
+
+            "boolean_array" => {
+                let mut values = Vec::new();
+                for v in array(name, value)? {
+                    match v {
+                        Value::Bool(v) => values.push(*v),
+                        _ => {
+                            return Err(ParseError::InvalidValue {
+                                name: String::from(name),
+                                detail: "expected an array of true or false",
+                            })
+                        }
+                    }
+                }
+                encoder.put(name, &DataValue::BooleanArray(&values))?
+            }
+            "string_array" => {
+                let mut values = Vec::new();
+                for v in array(name, value)? {
+                    match v {
+                        Value::String(v) => values.push(v.as_str()),
+                        _ => {
+                            return Err(ParseError::InvalidValue {
+                                name: String::from(name),
+                                detail: "expected an array of strings",
+                            })
+                        }
+                    }
+                }
+                encoder.put(name, &DataValue::StringArray(&values))?
+            }
+
+            "nvlist" => {
+                let nested = encoder.put_nv_list_start(name, unique)?;
+                put_pairs(encoder, object(name, value)?, unique)?;
+                encoder.put_nv_list_end(nested)?;
+            }
+            "nvlist_array" => {
+                let lists = array(name, value)?;
+                let mut nested = encoder.put_nv_list_array_start(name, lists.len())?;
+                for list in lists {
+                    encoder.put_nv_list_array_element_start(&mut nested, unique)?;
+                    put_pairs(encoder, object(name, list)?, unique)?;
+                    encoder.put_nv_list_array_element_end(&nested)?;
+                }
+                encoder.put_nv_list_end(nested)?;
+            }
+
+            _ => {
+                return Err(ParseError::UnknownType {
+                    name: String::from(name),
+                    data_type: String::from(data_type),
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/** Parses the JSON description of a NV list, and encodes it to bytes.
+ *
+ * See the module documentation for the format.
+ *
+ * # Errors
+ *
+ * Returns [`ParseError`] on malformed JSON, invalid pairs, or encoding errors.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Vdev, VdevTree};
+ *
+ * let text = r#"{
+ *     "vdev_tree": {
+ *         "nvlist": {
+ *             "type": { "string": "disk" },
+ *             "id": { "uint64": 0 },
+ *             "guid": { "uint64": 18446744073709551615 },
+ *             "path": { "string": "/dev/sda1" },
+ *             "whole_disk": { "uint64": 1 }
+ *         }
+ *     }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Big, Unique::Name).unwrap();
+ *
+ * let decoder = Decoder::from_bytes_strict(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * assert_eq!(vdev_tree.guid, 0xffffffffffffffff);
+ * match vdev_tree.vdev {
+ *     Vdev::Disk(disk) => assert_eq!(disk.path, "/dev/sda1"),
+ *     _ => panic!("expected a disk"),
+ * }
+ * ```
+ *
+ * Malformed pair:
+ *
+ * ```
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Encoding, Unique};
+ * use zfs::nv_json;
+ *
+ * // Out of range.
+ * let text = r#"{ "a": { "uint8": 256 } }"#;
+ * assert!(nv_json::to_bytes(text, Encoding::Xdr, Endian::Big, Unique::Name).is_err());
+ *
+ * // Unknown type.
+ * let text = r#"{ "a": { "uint128": 0 } }"#;
+ * assert!(nv_json::to_bytes(text, Encoding::Xdr, Endian::Big, Unique::Name).is_err());
+ * ```
+ */
+pub fn to_bytes(
+    text: &str,
+    encoding: Encoding,
+    endian: Endian,
+    unique: Unique,
+) -> Result<Vec<u8>, ParseError> {
+    ////////////////////////////////
+    // Parse text.
+    let mut parser = Parser {
+        text: text,
+        offset: 0,
+    };
+
+    let value = parser.value()?;
+
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        return Err(ParseError::InvalidCharacter {
+            offset: parser.offset,
+            character: c,
+        });
+    }
+
+    let members = match &value {
+        Value::Object(v) => v,
+        _ => {
+            return Err(ParseError::InvalidValue {
+                name: String::new(),
+                detail: "expected an object at the top level",
+            })
+        }
+    };
+
+    ////////////////////////////////
+    // Encode, and grow the buffer until the list fits.
+    let mut data = vec![0; 1024];
+
+    loop {
+        let result = match nv::Encoder::to_bytes(&mut data, encoding, endian, unique) {
+            Ok(mut encoder) => match put_pairs(&mut encoder, members, unique) {
+                Ok(()) => encoder.finish().map_err(ParseError::from),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(ParseError::from(e)),
+        };
+
+        match result {
+            Ok(length) => {
+                data.truncate(length);
+                return Ok(data);
+            }
+            Err(ParseError::EncodeError {
+                err:
+                    nv::EncodeError::Endian {
+                        err: endian::EncodeError::EndOfOutput { .. },
+                    }
+                    | nv::EncodeError::Xdr {
+                        err: xdr::EncodeError::EndOfOutput { .. },
+                    },
+            }) => {
+                let length = data.len() * 2;
+                data.resize(length, 0);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum ParseError {
+    /** End of input text.
+     *
+     * - `offset` - Byte offset of text.
+     */
+    EndOfInput { offset: usize },
+
+    /** NV encoding error.
+     *
+     * - `err` - Error.
+     */
+    EncodeError { err: nv::EncodeError },
+
+    /** Invalid character.
+     *
+     * - `offset`    - Byte offset of text.
+     * - `character` - Character.
+     */
+    InvalidCharacter { offset: usize, character: char },
+
+    /** Invalid string escape.
+     *
+     * - `offset` - Byte offset of text.
+     */
+    InvalidEscape { offset: usize },
+
+    /** Invalid value for a pair.
+     *
+     * - `name`   - Name of pair.
+     * - `detail` - Detail of the expected value.
+     */
+    InvalidValue { name: String, detail: &'static str },
+
+    /** Unknown type for a pair.
+     *
+     * - `name`      - Name of pair.
+     * - `data_type` - Unknown type.
+     */
+    UnknownType { name: String, data_type: String },
+}
+
+impl From<nv::EncodeError> for ParseError {
+    fn from(value: nv::EncodeError) -> Self {
+        ParseError::EncodeError { err: value }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EndOfInput { offset } => {
+                write!(f, "NV JSON end of input at offset {offset}")
+            }
+            ParseError::EncodeError { err } => {
+                write!(f, "NV JSON encode error: {err}")
+            }
+            ParseError::InvalidCharacter { offset, character } => {
+                write!(
+                    f,
+                    "NV JSON invalid character '{character}' at offset {offset}"
+                )
+            }
+            ParseError::InvalidEscape { offset } => {
+                write!(f, "NV JSON invalid string escape at offset {offset}")
+            }
+            ParseError::InvalidValue { name, detail } => {
+                write!(f, "NV JSON invalid value for '{name}': {detail}")
+            }
+            ParseError::UnknownType { name, data_type } => {
+                write!(f, "NV JSON unknown type '{data_type}' for '{name}'")
+            }
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseError::EncodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
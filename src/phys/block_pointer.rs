@@ -8,7 +8,8 @@ use std::error;
 extern crate num;
 extern crate strum;
 
-use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, Endian};
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, Endian, ErrorLocation};
+use crate::phys::sector;
 use crate::phys::{
     ChecksumType, ChecksumTypeError, ChecksumValue, CompressionType, CompressionTypeError, DmuType,
     DmuTypeError, Dva, DvaDecodeError, DvaEncodeError,
@@ -56,11 +57,26 @@ impl BlockPointer {
      * or block pointer is malformed.
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<BlockPointer, BlockPointerDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
         ////////////////////////////////
         // Decode flags (and rewind position).
-        decoder.skip(3 * Dva::LENGTH)?;
-        let flags = decoder.get_u64()?;
-        decoder.rewind((3 * Dva::LENGTH) + 8)?;
+        decode!(decoder.skip(3 * Dva::LENGTH));
+        let flags = decode!(decoder.get_u64());
+        decode!(decoder.rewind((3 * Dva::LENGTH) + 8));
 
         ////////////////////////////////
         // Decode encrypted and embedded.
@@ -212,28 +228,43 @@ impl BlockPointerEmbedded {
     pub fn from_decoder(
         decoder: &Decoder,
     ) -> Result<BlockPointerEmbedded, BlockPointerDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
         let mut payload = [0; BlockPointerEmbedded::MAX_PAYLOAD_LENGTH];
 
         ////////////////////////////////
         // Decode embedded payload (part 1).
-        (&mut payload[0..48]).copy_from_slice(decoder.get_bytes(48)?);
+        (&mut payload[0..48]).copy_from_slice(decode!(decoder.get_bytes(48)));
 
         ////////////////////////////////
         // Decode flags.
-        let flags = decoder.get_u64()?;
+        let flags = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode embedded payload (part 2).
-        let payload_2 = decoder.get_bytes(24)?;
+        let payload_2 = decode!(decoder.get_bytes(24));
         (&mut payload[48..72]).copy_from_slice(payload_2);
 
         ////////////////////////////////
         // Decode logical birth transaction group.
-        let logical_birth_txg = decoder.get_u64()?;
+        let logical_birth_txg = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode embedded payload (part 3).
-        let payload_3 = decoder.get_bytes(40)?;
+        let payload_3 = decode!(decoder.get_bytes(40));
         (&mut payload[72..112]).copy_from_slice(payload_3);
 
         ////////////////////////////////
@@ -487,18 +518,50 @@ impl BlockPointerEncrypted {
     pub fn from_decoder(
         decoder: &Decoder,
     ) -> Result<BlockPointerEncrypted, BlockPointerDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
+        macro_rules! dva {
+            ($index:expr) => {{
+                decoder.push_index("dva", $index);
+                let dva = match Dva::from_decoder(decoder) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::DvaDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                };
+                decoder.pop_field();
+                dva
+            }};
+        }
+
         ////////////////////////////////
         // Decode DVAs.
-        let dvas = [Dva::from_decoder(decoder)?, Dva::from_decoder(decoder)?];
+        let dvas = [dva!(0), dva!(1)];
 
         ////////////////////////////////
         // Decode salt and iv (part 1).
-        let salt = decoder.get_u64()?;
-        let iv_1 = decoder.get_u64()?;
+        let salt = decode!(decoder.get_u64());
+        let iv_1 = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode flags.
-        let flags = decoder.get_u64()?;
+        let flags = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode encrypted and embedded.
@@ -549,26 +612,26 @@ impl BlockPointerEncrypted {
 
         ////////////////////////////////
         // Decode padding.
-        decoder.skip_zero_padding(16)?;
+        decode!(decoder.skip_zero_padding(16));
 
         ////////////////////////////////
         // Decode TXGs.
-        let physical_birth_txg = decoder.get_u64()?;
-        let logical_birth_txg = decoder.get_u64()?;
+        let physical_birth_txg = decode!(decoder.get_u64());
+        let logical_birth_txg = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode iv2 / fill count.
-        let iv_fill = decoder.get_u64()?;
+        let iv_fill = decode!(decoder.get_u64());
         let iv_2 = (iv_fill >> 32) as u32;
         let fill_count = (iv_fill & 0xffffffff) as u32;
 
         ////////////////////////////////
         // Decode checksum value.
-        let checksum_value = [decoder.get_u64()?, decoder.get_u64()?];
+        let checksum_value = decode!(decoder.get_u64_array());
 
         ////////////////////////////////
         // Decode MAC.
-        let mac = [decoder.get_u64()?, decoder.get_u64()?];
+        let mac = decode!(decoder.get_u64_array());
 
         ////////////////////////////////
         // Success.
@@ -652,15 +715,11 @@ impl BlockPointerEncrypted {
 
         ////////////////////////////////
         // Encode checksum value.
-        for checksum in self.checksum_value {
-            encoder.put_u64(checksum)?;
-        }
+        encoder.put_u64_slice(&self.checksum_value)?;
 
         ////////////////////////////////
         // Encode mac.
-        for mac in self.mac {
-            encoder.put_u64(mac)?;
-        }
+        encoder.put_u64_slice(&self.mac)?;
 
         ////////////////////////////////
         // Success.
@@ -731,7 +790,7 @@ impl BlockPointerEncrypted {
  *  logical size: in 512 byte (sectors - 1)
  * ```
  */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BlockPointerRegular {
     pub checksum_type: ChecksumType,
     pub checksum_value: ChecksumValue,
@@ -749,6 +808,37 @@ pub struct BlockPointerRegular {
 }
 
 impl BlockPointerRegular {
+    /// Returns the logical size in bytes.
+    pub fn logical_size_bytes(&self) -> u64 {
+        (self.logical_size as u64 + 1) << sector::shift!()
+    }
+
+    /// Returns the physical size in bytes.
+    pub fn physical_size_bytes(&self) -> u64 {
+        (self.physical_size as u64 + 1) << sector::shift!()
+    }
+
+    /** Returns the birth transaction group.
+     *
+     * - C reference: `BP_GET_BIRTH`
+     * - The physical birth transaction group is zero, unless it differs
+     *   from the logical birth transaction group.
+     */
+    pub fn birth_txg(&self) -> u64 {
+        match self.physical_birth_txg {
+            0 => self.logical_birth_txg,
+            v => v,
+        }
+    }
+
+    /** Returns true if the block pointer is a hole, which has no data.
+     *
+     * - C reference: `BP_IS_HOLE`
+     */
+    pub fn is_hole(&self) -> bool {
+        self.dvas[0].asize == 0
+    }
+
     /** Decodes a [`BlockPointerRegular`].
      *
      * # Errors
@@ -757,17 +847,45 @@ impl BlockPointerRegular {
      * or padding is non-zero.
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<BlockPointerRegular, BlockPointerDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
+        macro_rules! dva {
+            ($index:expr) => {{
+                decoder.push_index("dva", $index);
+                let dva = match Dva::from_decoder(decoder) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(BlockPointerDecodeError::DvaDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                };
+                decoder.pop_field();
+                dva
+            }};
+        }
+
         ////////////////////////////////
         // Decode DVAs.
-        let dvas = [
-            Dva::from_decoder(decoder)?,
-            Dva::from_decoder(decoder)?,
-            Dva::from_decoder(decoder)?,
-        ];
+        let dvas = [dva!(0), dva!(1), dva!(2)];
 
         ////////////////////////////////
         // Decode flags.
-        let flags = decoder.get_u64()?;
+        let flags = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode encrypted and embedded.
@@ -818,20 +936,22 @@ impl BlockPointerRegular {
 
         ////////////////////////////////
         // Decode padding.
-        decoder.skip_zero_padding(16)?;
+        decode!(decoder.skip_zero_padding(16));
 
         ////////////////////////////////
         // Decode TXGs.
-        let physical_birth_txg = decoder.get_u64()?;
-        let logical_birth_txg = decoder.get_u64()?;
+        let physical_birth_txg = decode!(decoder.get_u64());
+        let logical_birth_txg = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode fill count.
-        let fill_count = decoder.get_u64()?;
+        let fill_count = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decocde checksum value.
-        let checksum_value = ChecksumValue::from_decoder(decoder)?;
+        decoder.push_field("checksum_value");
+        let checksum_value = decode!(ChecksumValue::from_decoder(decoder));
+        decoder.pop_field();
 
         ////////////////////////////////
         // Success.
@@ -937,15 +1057,23 @@ pub enum BlockPointerDecodeError {
 
     /** DVA decode error.
      *
-     * - `err` - [`DvaDecodeError`]
+     * - `err`      - [`DvaDecodeError`]
+     * - `location` - Location of the last value decoded in the [`Dva`].
      */
-    DvaDecodeError { err: DvaDecodeError },
+    DvaDecodeError {
+        err: DvaDecodeError,
+        location: ErrorLocation,
+    },
 
     /** Endian decode error.
      *
-     * - `err` - [`DecodeError`]
+     * - `err`      - [`DecodeError`]
+     * - `location` - Location of the value.
      */
-    EndianDecodeError { err: DecodeError },
+    EndianDecodeError {
+        err: DecodeError,
+        location: ErrorLocation,
+    },
 
     /** Invalid [`BlockPointer`] type.
      *
@@ -991,18 +1119,6 @@ impl From<DmuTypeError> for BlockPointerDecodeError {
     }
 }
 
-impl From<DvaDecodeError> for BlockPointerDecodeError {
-    fn from(value: DvaDecodeError) -> Self {
-        BlockPointerDecodeError::DvaDecodeError { err: value }
-    }
-}
-
-impl From<DecodeError> for BlockPointerDecodeError {
-    fn from(value: DecodeError) -> Self {
-        BlockPointerDecodeError::EndianDecodeError { err: value }
-    }
-}
-
 impl fmt::Display for BlockPointerDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -1015,11 +1131,11 @@ impl fmt::Display for BlockPointerDecodeError {
             BlockPointerDecodeError::DmuTypeError { err } => {
                 write!(f, "BlockPointer DMU type decode error: {err}")
             }
-            BlockPointerDecodeError::DvaDecodeError { err } => {
-                write!(f, "Block Pointer DVA decode error: {err}")
+            BlockPointerDecodeError::DvaDecodeError { err, location } => {
+                write!(f, "Block Pointer DVA decode error at {location}: {err}")
             }
-            BlockPointerDecodeError::EndianDecodeError { err } => {
-                write!(f, "Block Pointer Endian decode error: {err}")
+            BlockPointerDecodeError::EndianDecodeError { err, location } => {
+                write!(f, "Block Pointer Endian decode error at {location}: {err}")
             }
             BlockPointerDecodeError::InvalidBlockPointerType {
                 embedded,
@@ -1056,7 +1172,8 @@ impl error::Error for BlockPointerDecodeError {
             BlockPointerDecodeError::ChecksumTypeError { err } => Some(err),
             BlockPointerDecodeError::CompressionTypeError { err } => Some(err),
             BlockPointerDecodeError::DmuTypeError { err } => Some(err),
-            BlockPointerDecodeError::EndianDecodeError { err } => Some(err),
+            BlockPointerDecodeError::DvaDecodeError { err, location: _ } => Some(err),
+            BlockPointerDecodeError::EndianDecodeError { err, location: _ } => Some(err),
             _ => None,
         }
     }
@@ -19,7 +19,7 @@ use crate::endian::{DecodeError, Decoder, EncodeError, Encoder};
  * +---------------+
  * ```
  */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ChecksumValue {
     pub words: [u64; 4],
 }
@@ -36,12 +36,7 @@ impl ChecksumValue {
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<ChecksumValue, DecodeError> {
         Ok(ChecksumValue {
-            words: [
-                decoder.get_u64()?,
-                decoder.get_u64()?,
-                decoder.get_u64()?,
-                decoder.get_u64()?,
-            ],
+            words: decoder.get_u64_array()?,
         })
     }
 
@@ -52,11 +47,6 @@ impl ChecksumValue {
      * Returns [`EncodeError`] if there is not enough space.
      */
     pub fn to_encoder(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
-        encoder.put_u64(self.words[0])?;
-        encoder.put_u64(self.words[1])?;
-        encoder.put_u64(self.words[2])?;
-        encoder.put_u64(self.words[3])?;
-
-        Ok(())
+        encoder.put_u64_slice(&self.words)
     }
 }
@@ -5,7 +5,7 @@ use core::result::Result::{Err, Ok};
 #[cfg(feature = "std")]
 use std::error;
 
-use crate::endian::{DecodeError, Decoder, EncodeError, Encoder};
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, ErrorLocation};
 use crate::phys::{
     BlockPointer, BlockPointerDecodeError, BlockPointerEncodeError, ChecksumType,
     ChecksumTypeError, CompressionType, CompressionTypeError, DmuType, DmuTypeError,
@@ -212,37 +212,66 @@ impl Dnode {
      * Returns [`DecodeError`] if there are not enough bytes, or magic is invalid.
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<Dnode, DnodeDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(DnodeDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
+        macro_rules! decode_block_pointer {
+            () => {
+                match BlockPointer::from_decoder(decoder) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(DnodeDecodeError::BlockPointerDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
         ////////////////////////////////
         // Decode DMU type.
-        let dmu = DmuType::try_from(decoder.get_u8()?)?;
+        let dmu = DmuType::try_from(decode!(decoder.get_u8()))?;
 
         ////////////////////////////////
         // Decode indirect block shift.
-        let indirect_block_shift = decoder.get_u8()?;
+        let indirect_block_shift = decode!(decoder.get_u8());
 
         ////////////////////////////////
         // Decode levels.
-        let levels = decoder.get_u8()?;
+        let levels = decode!(decoder.get_u8());
 
         ////////////////////////////////
         // Decode number of block pointers.
-        let block_pointers_n = decoder.get_u8()?;
+        let block_pointers_n = decode!(decoder.get_u8());
 
         ////////////////////////////////
         // Decode bonus type.
-        let bonus_type = DmuType::try_from(decoder.get_u8()?)?;
+        let bonus_type = DmuType::try_from(decode!(decoder.get_u8()))?;
 
         ////////////////////////////////
         // Decode checksum.
-        let checksum = ChecksumType::try_from(decoder.get_u8()?)?;
+        let checksum = ChecksumType::try_from(decode!(decoder.get_u8()))?;
 
         ////////////////////////////////
         // Decode compression.
-        let compression = CompressionType::try_from(decoder.get_u8()?)?;
+        let compression = CompressionType::try_from(decode!(decoder.get_u8()))?;
 
         ////////////////////////////////
         // Decode flags.
-        let flags = decoder.get_u8()?;
+        let flags = decode!(decoder.get_u8());
         if (flags & DNODE_FLAG_ALL) != flags {
             return Err(DnodeDecodeError::InvalidFlags { flags: flags });
         }
@@ -258,40 +287,49 @@ impl Dnode {
 
         ////////////////////////////////
         // Decode block size sectors.
-        let data_block_size_sectors = decoder.get_u16()?;
+        let data_block_size_sectors = decode!(decoder.get_u16());
 
         ////////////////////////////////
         // Decode bonus length.
-        let bonus_len = decoder.get_u16()?;
+        let bonus_len = decode!(decoder.get_u16());
 
         ////////////////////////////////
         // Decode extra slots.
-        let extra_slots = decoder.get_u8()?;
+        let extra_slots = decode!(decoder.get_u8());
 
         ////////////////////////////////
         // Decode padding.
-        decoder.skip_zero_padding(3)?;
+        decode!(decoder.skip_zero_padding(3));
 
         ////////////////////////////////
         // Decode max block id.
-        let max_block_id = decoder.get_u64()?;
+        let max_block_id = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode used.
-        let used = decoder.get_u64()?;
+        let used = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode padding.
-        decoder.skip_zero_padding(32)?;
+        decode!(decoder.skip_zero_padding(32));
 
         ////////////////////////////////
         // Decode tail.
+        macro_rules! ptr {
+            ($index:expr) => {{
+                decoder.push_index("ptr", $index);
+                let ptr = decode_block_pointer!();
+                decoder.pop_field();
+                ptr
+            }};
+        }
+
         let max_bonus_len: usize;
         let tail = match block_pointers_n {
             0 => {
                 let tail = DnodeTailZero {
                     ptrs: [],
-                    bonus: decoder.get_bytes(448)?.try_into().unwrap(),
+                    bonus: decode!(decoder.get_bytes(448)).try_into().unwrap(),
                 };
                 max_bonus_len = tail.bonus.len();
                 DnodeTail::Zero(tail)
@@ -299,16 +337,21 @@ impl Dnode {
             1 => {
                 if is_spill {
                     let tail = DnodeTailSpill {
-                        ptrs: [BlockPointer::from_decoder(decoder)?],
-                        bonus: decoder.get_bytes(192)?.try_into().unwrap(),
-                        spill: BlockPointer::from_decoder(decoder)?,
+                        ptrs: [ptr!(0)],
+                        bonus: decode!(decoder.get_bytes(192)).try_into().unwrap(),
+                        spill: {
+                            decoder.push_field("spill");
+                            let spill = decode_block_pointer!();
+                            decoder.pop_field();
+                            spill
+                        },
                     };
                     max_bonus_len = tail.bonus.len();
                     DnodeTail::Spill(tail)
                 } else {
                     let tail = DnodeTailOne {
-                        ptrs: [BlockPointer::from_decoder(decoder)?],
-                        bonus: decoder.get_bytes(320)?.try_into().unwrap(),
+                        ptrs: [ptr!(0)],
+                        bonus: decode!(decoder.get_bytes(320)).try_into().unwrap(),
                     };
                     max_bonus_len = tail.bonus.len();
                     DnodeTail::One(tail)
@@ -316,23 +359,16 @@ impl Dnode {
             }
             2 => {
                 let tail = DnodeTailTwo {
-                    ptrs: [
-                        BlockPointer::from_decoder(decoder)?,
-                        BlockPointer::from_decoder(decoder)?,
-                    ],
-                    bonus: decoder.get_bytes(192)?.try_into().unwrap(),
+                    ptrs: [ptr!(0), ptr!(1)],
+                    bonus: decode!(decoder.get_bytes(192)).try_into().unwrap(),
                 };
                 max_bonus_len = tail.bonus.len();
                 DnodeTail::Two(tail)
             }
             3 => {
                 let tail = DnodeTailThree {
-                    ptrs: [
-                        BlockPointer::from_decoder(decoder)?,
-                        BlockPointer::from_decoder(decoder)?,
-                        BlockPointer::from_decoder(decoder)?,
-                    ],
-                    bonus: decoder.get_bytes(64)?.try_into().unwrap(),
+                    ptrs: [ptr!(0), ptr!(1), ptr!(2)],
+                    bonus: decode!(decoder.get_bytes(64)).try_into().unwrap(),
                 };
                 max_bonus_len = tail.bonus.len();
                 DnodeTail::Three(tail)
@@ -545,9 +581,13 @@ impl Dnode {
 pub enum DnodeDecodeError {
     /** [`BlockPointer`] decode error.
      *
-     * - `err` - [`BlockPointerDecodeError`]
+     * - `err`      - [`BlockPointerDecodeError`]
+     * - `location` - Location of the last value decoded in the [`BlockPointer`].
      */
-    BlockPointerDecodeError { err: BlockPointerDecodeError },
+    BlockPointerDecodeError {
+        err: BlockPointerDecodeError,
+        location: ErrorLocation,
+    },
 
     /** Invalid checksum type.
      *
@@ -569,9 +609,13 @@ pub enum DnodeDecodeError {
 
     /** Endian decode error.
      *
-     * - `err` - [`DecodeError`]
+     * - `err`      - [`DecodeError`]
+     * - `location` - Location of the value.
      */
-    EndianDecodeError { err: DecodeError },
+    EndianDecodeError {
+        err: DecodeError,
+        location: ErrorLocation,
+    },
 
     /** Invalid block pointer count.
      *
@@ -598,12 +642,6 @@ pub enum DnodeDecodeError {
     InvalidSpillBlockPointerCount { count: u8 },
 }
 
-impl From<BlockPointerDecodeError> for DnodeDecodeError {
-    fn from(value: BlockPointerDecodeError) -> Self {
-        DnodeDecodeError::BlockPointerDecodeError { err: value }
-    }
-}
-
 impl From<ChecksumTypeError> for DnodeDecodeError {
     fn from(value: ChecksumTypeError) -> Self {
         DnodeDecodeError::ChecksumTypeError { err: value }
@@ -622,17 +660,11 @@ impl From<DmuTypeError> for DnodeDecodeError {
     }
 }
 
-impl From<DecodeError> for DnodeDecodeError {
-    fn from(value: DecodeError) -> Self {
-        DnodeDecodeError::EndianDecodeError { err: value }
-    }
-}
-
 impl fmt::Display for DnodeDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            DnodeDecodeError::BlockPointerDecodeError { err } => {
-                write!(f, "Dnode Block Pointer decode error: {err}")
+            DnodeDecodeError::BlockPointerDecodeError { err, location } => {
+                write!(f, "Dnode Block Pointer decode error at {location}: {err}")
             }
             DnodeDecodeError::ChecksumTypeError { err } => {
                 write!(f, "Dnode checksum type decode error: {err}")
@@ -643,8 +675,8 @@ impl fmt::Display for DnodeDecodeError {
             DnodeDecodeError::DmuTypeError { err } => {
                 write!(f, "Dnode DMU type decode error: {err}")
             }
-            DnodeDecodeError::EndianDecodeError { err } => {
-                write!(f, "Dnode Endian decode error: {err}")
+            DnodeDecodeError::EndianDecodeError { err, location } => {
+                write!(f, "Dnode Endian decode error at {location}: {err}")
             }
             DnodeDecodeError::InvalidBlockPointerCount { count } => {
                 write!(f, "Dnode decode error: invalid block pointer count {count}")
@@ -669,11 +701,11 @@ impl fmt::Display for DnodeDecodeError {
 impl error::Error for DnodeDecodeError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            DnodeDecodeError::BlockPointerDecodeError { err } => Some(err),
+            DnodeDecodeError::BlockPointerDecodeError { err, location: _ } => Some(err),
             DnodeDecodeError::ChecksumTypeError { err } => Some(err),
             DnodeDecodeError::CompressionTypeError { err } => Some(err),
             DnodeDecodeError::DmuTypeError { err } => Some(err),
-            DnodeDecodeError::EndianDecodeError { err } => Some(err),
+            DnodeDecodeError::EndianDecodeError { err, location: _ } => Some(err),
             _ => None,
         }
     }
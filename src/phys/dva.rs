@@ -71,7 +71,7 @@ const BASE_OFFSET_SECTORS: u64 = BASE_OFFSET_BYTES >> sector::shift!();
  * g: Gang
  * ```
  */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Dva {
     pub vdev: u32,
     pub grid: u8,
@@ -84,6 +84,33 @@ impl Dva {
     /// Byte length of an encoded [`Dva`].
     pub const LENGTH: usize = 16;
 
+    /** Byte offset of the allocatable space of a leaf vdev.
+     *
+     * - C reference: `VDEV_LABEL_START_SIZE`
+     * - The first two labels and the boot block are before it.
+     */
+    pub const LEAF_OFFSET: u64 = BASE_OFFSET_BYTES;
+
+    /// Returns the byte offset in the top level vdev.
+    pub fn offset_bytes(&self) -> u64 {
+        self.offset << sector::shift!()
+    }
+
+    /// Returns the allocated byte size in the top level vdev.
+    pub fn asize_bytes(&self) -> u64 {
+        (self.asize as u64) << sector::shift!()
+    }
+
+    /** Returns the byte offset on a leaf vdev of a top level vdev with a
+     * single copy of the data, such as a disk, file, or mirror.
+     *
+     * - For RAID-Z and dRAID, the data is spread over the children, so use
+     *   [`crate::read::DvaMap`].
+     */
+    pub fn leaf_offset_bytes(&self) -> u64 {
+        (self.offset + BASE_OFFSET_SECTORS) << sector::shift!()
+    }
+
     /** Decodes a [`Dva`].
      *
      * # Errors
@@ -0,0 +1,546 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::nv;
+use crate::phys::{MicroZap, MicroZapDecodeError, PoolUnknownNames};
+
+////////////////////////////////////////////////////////////////////////////////
+
+// Use macros for cleaner code.
+macro_rules! features {
+    ($($feature:ident => ($guid:expr, $read_only_compatible:expr, $is_supported:expr),)+) => {
+        /** Pool feature.
+         *
+         * - C reference: `enum spa_feature spa_feature_t`
+         */
+        #[derive(Clone, Copy, Debug, Eq, PartialEq, strum::Display)]
+        pub enum Feature {
+            $($feature,)+
+        }
+
+        impl Feature {
+            /// Number of known features.
+            pub const COUNT: usize = [$($guid,)+].len();
+
+            /// All known features.
+            pub const ALL: [Feature; Feature::COUNT] = [$(Feature::$feature,)+];
+
+            /** Returns the GUID of the feature, such as `com.delphix:async_destroy`.
+             */
+            pub fn guid(&self) -> &'static str {
+                match self {
+                    $(Feature::$feature => $guid,)+
+                }
+            }
+
+            /** Returns true if the feature is read only compatible.
+             *
+             * - Read only compatible features are counted in `features_for_write`,
+             *   and a pool can be read without supporting them.
+             * - Other features are counted in `features_for_read`.
+             */
+            pub fn is_read_only_compatible(&self) -> bool {
+                match self {
+                    $(Feature::$feature => $read_only_compatible,)+
+                }
+            }
+
+            /** Returns true if the feature is supported by this crate.
+             *
+             * - A pool with an active feature that is not supported, such as
+             *   a checksum or compression that is not implemented, cannot be
+             *   read, see [`PoolFeatures::can_read`].
+             */
+            pub fn is_supported(&self) -> bool {
+                match self {
+                    $(Feature::$feature => $is_supported,)+
+                }
+            }
+        }
+    };
+}
+
+features! {
+    AllocationClasses => ("org.zfsonlinux:allocation_classes", true, true),
+    AsyncDestroy => ("com.delphix:async_destroy", true, true),
+    Blake3 => ("org.openzfs:blake3", false, false),
+    BlockCloning => ("com.fudosecurity:block_cloning", true, true),
+    BookmarkV2 => ("com.datto:bookmark_v2", false, true),
+    BookmarkWritten => ("com.delphix:bookmark_written", false, true),
+    Bookmarks => ("com.delphix:bookmarks", true, true),
+    DeviceRebuild => ("org.openzfs:device_rebuild", true, true),
+    DeviceRemoval => ("com.delphix:device_removal", false, true),
+    Draid => ("org.openzfs:draid", false, true),
+    Edonr => ("org.illumos:edonr", false, false),
+    EmbeddedData => ("com.delphix:embedded_data", false, true),
+    EmptyBlockPointerObject => ("com.delphix:empty_bpobj", true, true),
+    EnabledTxg => ("com.delphix:enabled_txg", true, true),
+    Encryption => ("com.datto:encryption", false, false),
+    ExtensibleDataset => ("com.delphix:extensible_dataset", false, true),
+    FastDedup => ("com.klarasystems:fast_dedup", true, true),
+    FilesystemLimits => ("com.joyent:filesystem_limits", true, true),
+    HeadErrorLog => ("com.delphix:head_errlog", false, true),
+    HoleBirth => ("com.delphix:hole_birth", false, true),
+    LargeBlocks => ("org.open-zfs:large_blocks", false, true),
+    LargeDnode => ("org.zfsonlinux:large_dnode", false, true),
+    LargeMicroZap => ("com.klarasystems:large_microzap", false, true),
+    LiveList => ("com.delphix:livelist", true, true),
+    LogSpaceMap => ("com.delphix:log_spacemap", true, true),
+    LongName => ("org.zfsonlinux:longname", false, true),
+    Lz4Compress => ("org.illumos:lz4_compress", false, true),
+    MultiVdevCrashDump => ("com.joyent:multi_vdev_crash_dump", true, true),
+    ObsoleteCounts => ("com.delphix:obsolete_counts", true, true),
+    ProjectQuota => ("org.zfsonlinux:project_quota", true, true),
+    RaidZExpansion => ("org.openzfs:raidz_expansion", false, false),
+    RedactedDatasets => ("com.delphix:redacted_datasets", false, true),
+    RedactionBookmarks => ("com.delphix:redaction_bookmarks", false, true),
+    RedactionListSpill => ("com.delphix:redaction_list_spill", false, true),
+    ResilverDefer => ("com.datto:resilver_defer", true, true),
+    Sha512 => ("org.illumos:sha512", false, true),
+    Skein => ("org.illumos:skein", false, false),
+    SpaceMapHistogram => ("com.delphix:spacemap_histogram", true, true),
+    SpaceMapV2 => ("com.delphix:spacemap_v2", true, true),
+    UserObjectAccounting => ("org.zfsonlinux:userobj_accounting", true, true),
+    VdevZapsV2 => ("com.klarasystems:vdev_zaps_v2", false, true),
+    ZilSaXattr => ("org.openzfs:zilsaxattr", true, true),
+    ZpoolCheckpoint => ("com.delphix:zpool_checkpoint", true, true),
+    ZstdCompress => ("org.freebsd:zstd_compress", false, true),
+}
+
+impl Feature {
+    /** Returns the [`Feature`] for the GUID, or [`None`] if it is unknown.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::phys::Feature;
+     *
+     * assert_eq!(Feature::from_guid("org.illumos:lz4_compress"), Some(Feature::Lz4Compress));
+     * assert_eq!(
+     *     Feature::from_guid("com.klarasystems:fast_dedup"),
+     *     Some(Feature::FastDedup),
+     * );
+     * assert_eq!(Feature::from_guid("com.example:unknown"), None);
+     * ```
+     */
+    pub fn from_guid(guid: &str) -> Option<Feature> {
+        Feature::ALL.iter().find(|f| f.guid() == guid).copied()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A set of [`Feature`].
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FeatureSet {
+    bits: u64,
+}
+
+impl FeatureSet {
+    /// Returns true if the set contains the feature.
+    pub fn contains(&self, feature: Feature) -> bool {
+        (self.bits & (1 << (feature as u64))) != 0
+    }
+
+    /// Adds the feature to the set.
+    pub fn insert(&mut self, feature: Feature) {
+        self.bits |= 1 << (feature as u64);
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns the number of features in the set.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns an iterator over the features in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Feature> + '_ {
+        Feature::ALL.iter().copied().filter(|f| self.contains(*f))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+const POOL_CONFIG_FEATURES_FOR_READ: &str = "features_for_read";
+
+/** Pool features.
+ *
+ * - `for_read` and `for_write` are the active features, with a non-zero
+ *   reference count.
+ * - `enabled` are the features that are enabled, which includes the active
+ *   features.
+ * - Active features that are not known are collected in `unknown_for_read`
+ *   and `unknown_for_write`, as GUID strings.
+ */
+#[derive(Debug)]
+pub struct PoolFeatures<'a> {
+    pub enabled: FeatureSet,
+    pub for_read: FeatureSet,
+    pub for_write: FeatureSet,
+    pub unknown_for_read: PoolUnknownNames<'a>,
+    pub unknown_for_write: PoolUnknownNames<'a>,
+    enabled_txgs: [u64; Feature::COUNT],
+}
+
+impl Default for PoolFeatures<'_> {
+    fn default() -> Self {
+        PoolFeatures {
+            enabled: FeatureSet::default(),
+            for_read: FeatureSet::default(),
+            for_write: FeatureSet::default(),
+            unknown_for_read: PoolUnknownNames::default(),
+            unknown_for_write: PoolUnknownNames::default(),
+            enabled_txgs: [0; Feature::COUNT],
+        }
+    }
+}
+
+impl<'a> PoolFeatures<'a> {
+    /** Decodes the `features_for_read` NV list of a label configuration.
+     *
+     * - The label only lists the active features that are needed to read the
+     *   pool, so `for_write` and the enabled transaction groups are empty.
+     *
+     * # Errors
+     *
+     * Returns [`FeatureDecodeError`] in case of an error.
+     */
+    pub fn from_label_decoder(
+        decoder: &nv::Decoder<'a>,
+    ) -> Result<Option<PoolFeatures<'a>>, FeatureDecodeError> {
+        ////////////////////////////////
+        // Find features_for_read.
+        let feature_decoder = match nv::find_option!(
+            decoder,
+            POOL_CONFIG_FEATURES_FOR_READ,
+            NvList,
+            FeatureDecodeError
+        )? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let feature_decoder = feature_decoder.get_decoder();
+        feature_decoder.reset();
+
+        ////////////////////////////////
+        // Add each feature.
+        let mut features = PoolFeatures::default();
+
+        loop {
+            // Get next pair.
+            let pair = feature_decoder.next_pair()?;
+
+            // Check if its the end of the list.
+            let pair = match pair {
+                Some(v) => v,
+                None => break,
+            };
+
+            // Check the value type.
+            match pair.value {
+                nv::DecodedDataValue::Boolean() => (),
+                _ => {
+                    return Err(FeatureDecodeError::ValueTypeMismatch {
+                        name: POOL_CONFIG_FEATURES_FOR_READ,
+                        data_type: pair.data_type(),
+                    })
+                }
+            }
+
+            features.add_for_read(pair.name, 1);
+        }
+
+        Ok(Some(features))
+    }
+
+    /** Creates [`PoolFeatures`] from the feature objects of the MOS.
+     *
+     * - `for_read` and `for_write` are the `(guid, reference count)` entries
+     *   of the `features_for_read` and `features_for_write` ZAP objects.
+     * - `enabled_txg` are the `(guid, txg)` entries of the
+     *   `feature_enabled_txg` ZAP object.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::phys::{Feature, PoolFeatures};
+     *
+     * let features = PoolFeatures::from_mos(
+     *     [("org.illumos:lz4_compress", 1), ("com.delphix:hole_birth", 0)],
+     *     [("com.delphix:async_destroy", 0), ("com.example:new_write", 2)],
+     *     [("org.illumos:lz4_compress", 4), ("com.delphix:async_destroy", 5)],
+     * );
+     *
+     * assert!(features.for_read.contains(Feature::Lz4Compress));
+     * assert!(!features.for_read.contains(Feature::HoleBirth));
+     * assert!(features.enabled.contains(Feature::HoleBirth));
+     * assert_eq!(features.enabled_txg(Feature::AsyncDestroy), Some(5));
+     *
+     * // Unknown read only compatible feature is active.
+     * assert_eq!(features.unknown_for_write.names(), ["com.example:new_write"]);
+     * assert!(features.can_read());
+     * assert!(!features.can_write());
+     *
+     * // Known feature is active, but not supported.
+     * let features = PoolFeatures::from_mos([("com.datto:encryption", 1)], [], []);
+     * assert!(!Feature::Encryption.is_supported());
+     * assert!(!features.can_read());
+     * ```
+     */
+    pub fn from_mos<R, W, T>(for_read: R, for_write: W, enabled_txg: T) -> PoolFeatures<'a>
+    where
+        R: IntoIterator<Item = (&'a str, u64)>,
+        W: IntoIterator<Item = (&'a str, u64)>,
+        T: IntoIterator<Item = (&'a str, u64)>,
+    {
+        let mut features = PoolFeatures::default();
+
+        for (guid, ref_count) in for_read {
+            features.add_for_read(guid, ref_count);
+        }
+
+        for (guid, ref_count) in for_write {
+            features.add_for_write(guid, ref_count);
+        }
+
+        for (guid, txg) in enabled_txg {
+            if let Some(feature) = Feature::from_guid(guid) {
+                features.enabled.insert(feature);
+                features.enabled_txgs[feature as usize] = txg;
+            }
+        }
+
+        features
+    }
+
+    /** Decodes [`PoolFeatures`] from the blocks of the feature objects of the
+     * MOS.
+     *
+     * - `for_read`, `for_write`, and `enabled_txg` are the data of the
+     *   `features_for_read`, `features_for_write`, and `feature_enabled_txg`
+     *   ZAP objects, which are [`MicroZap`] objects.
+     * - `enabled_txg` is [`None`] if the pool does not have the
+     *   [`Feature::EnabledTxg`] feature.
+     *
+     * # Errors
+     *
+     * Returns [`FeatureDecodeError`] if a ZAP object is invalid.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Encoder, Endian};
+     * use zfs::phys::{Feature, MicroZap, PoolFeatures};
+     *
+     * // Micro ZAP blocks of features.
+     * let zap = |entries: &[(&str, u64)]| {
+     *     let mut bytes = vec![0u8; 4 * MicroZap::ENTRY_LENGTH];
+     *     let mut encoder = Encoder::to_bytes(&mut bytes, Endian::Little);
+     *     encoder.put_u64(MicroZap::BLOCK_TYPE).unwrap();
+     *     encoder.put_zero_padding(56).unwrap();
+     *     for (name, value) in entries {
+     *         encoder.put_u64(*value).unwrap();
+     *         encoder.put_zero_padding(6).unwrap();
+     *         encoder.put_bytes(name.as_bytes()).unwrap();
+     *         encoder.put_zero_padding(MicroZap::NAME_LENGTH - name.len()).unwrap();
+     *     }
+     *     bytes
+     * };
+     *
+     * let for_read = zap(&[("org.illumos:lz4_compress", 1)]);
+     * let for_write = zap(&[("com.delphix:async_destroy", 0), ("com.example:new_write", 1)]);
+     * let enabled_txg = zap(&[("com.delphix:async_destroy", 5)]);
+     *
+     * let features = PoolFeatures::from_mos_bytes(&for_read, &for_write, Some(&enabled_txg))
+     *     .unwrap();
+     * assert!(features.for_read.contains(Feature::Lz4Compress));
+     * assert!(features.enabled.contains(Feature::AsyncDestroy));
+     * assert_eq!(features.enabled_txg(Feature::AsyncDestroy), Some(5));
+     * assert!(!features.can_write());
+     * ```
+     */
+    pub fn from_mos_bytes(
+        for_read: &'a [u8],
+        for_write: &'a [u8],
+        enabled_txg: Option<&'a [u8]>,
+    ) -> Result<PoolFeatures<'a>, FeatureDecodeError> {
+        let for_read = MicroZap::from_bytes(for_read)?;
+        let for_write = MicroZap::from_bytes(for_write)?;
+        let enabled_txg = match enabled_txg {
+            Some(v) => Some(MicroZap::from_bytes(v)?),
+            None => None,
+        };
+
+        Ok(PoolFeatures::from_mos(
+            for_read.iter().map(|e| (e.name, e.value)),
+            for_write.iter().map(|e| (e.name, e.value)),
+            enabled_txg
+                .iter()
+                .flat_map(|z| z.iter())
+                .map(|e| (e.name, e.value)),
+        ))
+    }
+
+    /// Adds a `features_for_read` entry.
+    fn add_for_read(&mut self, guid: &'a str, ref_count: u64) {
+        match Feature::from_guid(guid) {
+            Some(feature) => {
+                self.enabled.insert(feature);
+                if ref_count > 0 {
+                    self.for_read.insert(feature);
+                }
+            }
+            None => {
+                if ref_count > 0 {
+                    self.unknown_for_read.push(guid);
+                }
+            }
+        }
+    }
+
+    /// Adds a `features_for_write` entry.
+    fn add_for_write(&mut self, guid: &'a str, ref_count: u64) {
+        match Feature::from_guid(guid) {
+            Some(feature) => {
+                self.enabled.insert(feature);
+                if ref_count > 0 {
+                    self.for_write.insert(feature);
+                }
+            }
+            None => {
+                if ref_count > 0 {
+                    self.unknown_for_write.push(guid);
+                }
+            }
+        }
+    }
+
+    /// Returns true if the feature is active.
+    pub fn is_active(&self, feature: Feature) -> bool {
+        self.for_read.contains(feature) || self.for_write.contains(feature)
+    }
+
+    /// Returns the transaction group in which the feature was enabled.
+    pub fn enabled_txg(&self, feature: Feature) -> Option<u64> {
+        match self.enabled_txgs[feature as usize] {
+            0 => None,
+            txg => Some(txg),
+        }
+    }
+
+    /** Returns true if the pool can be read.
+     *
+     * - All the active features that are needed to read the pool are known,
+     *   and supported, see [`Feature::is_supported`].
+     */
+    pub fn can_read(&self) -> bool {
+        self.unknown_for_read.is_empty() && self.for_read.iter().all(|f| f.is_supported())
+    }
+
+    /** Returns true if the pool can be written.
+     *
+     * - All the active features are known, and supported.
+     */
+    pub fn can_write(&self) -> bool {
+        self.can_read()
+            && self.unknown_for_write.is_empty()
+            && self.for_write.iter().all(|f| f.is_supported())
+    }
+}
+
+/// Former name of [`PoolFeatures`], when it only had the features of the label.
+#[deprecated(note = "renamed to PoolFeatures")]
+pub type PoolFeaturesForRead<'a> = PoolFeatures<'a>;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum FeatureDecodeError {
+    /** NV decoding error.
+     *
+     * - `err` - Error.
+     */
+    NvDecodeError { err: nv::DecodeError },
+
+    /** Micro ZAP decoding error.
+     *
+     * - `err` - Error.
+     */
+    MicroZapDecodeError { err: MicroZapDecodeError },
+
+    /** Missing NV pair.
+     *
+     * - `name` - Key.
+     */
+    MissingValue { name: &'static str },
+
+    /** Value type mismatch.
+     *
+     * - `name`      - Of NV pair.
+     * - `data_type` - Of NV pair.
+     */
+    ValueTypeMismatch {
+        name: &'static str,
+        data_type: nv::DataType,
+    },
+}
+
+impl From<nv::DecodeError> for FeatureDecodeError {
+    fn from(value: nv::DecodeError) -> Self {
+        FeatureDecodeError::NvDecodeError { err: value }
+    }
+}
+
+impl From<MicroZapDecodeError> for FeatureDecodeError {
+    fn from(value: MicroZapDecodeError) -> Self {
+        FeatureDecodeError::MicroZapDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for FeatureDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureDecodeError::NvDecodeError { err } => {
+                write!(f, "Feature NV decode error: {err}")
+            }
+            FeatureDecodeError::MicroZapDecodeError { err } => {
+                write!(f, "Feature Micro ZAP decode error: {err}")
+            }
+            FeatureDecodeError::MissingValue { name } => {
+                write!(f, "Feature decode error: missing '{name}'")
+            }
+            FeatureDecodeError::ValueTypeMismatch { name, data_type } => {
+                write!(
+                    f,
+                    "Feature decode value type mismatch for '{name}' got {data_type}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for FeatureDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FeatureDecodeError::NvDecodeError { err } => Some(err),
+            FeatureDecodeError::MicroZapDecodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::Ok;
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::checksum::{gang_checksum, gang_verify, GangChecksumError, GangVerifyError};
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, Endian};
+use crate::phys::{
+    BlockPointer, BlockPointerDecodeError, BlockPointerEncodeError, ChecksumTail, ChecksumValue,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Gang header.
+ *
+ * - Bytes: 512
+ * - C reference: `typedef struct zio_gbh_phys zio_gbh_phys_t`
+ * - A block that could not be allocated in one piece is split into up to
+ *   three gang members. The DVA of the block is marked as a gang DVA, and
+ *   points to the gang header, which has the block pointers of the members.
+ * - The data of the block is the physical data of the members, in order.
+ *   Unused block pointers are holes.
+ * - The checksum is embedded in the tail (see [`crate::checksum::gang`]).
+ *
+ * ```text
+ * +----------------+-----+
+ * | block pointers | 384 |
+ * +----------------+-----+
+ * |        padding |  88 |
+ * +----------------+-----+
+ * |  checksum tail |  40 |
+ * +----------------+-----+
+ * ```
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::checksum::gang_verifier;
+ * use zfs::endian::{Decoder, Endian};
+ * use zfs::phys::{BlockPointer, Dva, GangHeader};
+ *
+ * // Gang header with three holes.
+ * let holes = [0u8; 3 * BlockPointer::LENGTH];
+ * let decoder = Decoder::from_bytes(&holes, Endian::Little);
+ * let header = GangHeader {
+ *     block_pointers: [
+ *         BlockPointer::from_decoder(&decoder).unwrap(),
+ *         BlockPointer::from_decoder(&decoder).unwrap(),
+ *         BlockPointer::from_decoder(&decoder).unwrap(),
+ *     ],
+ * };
+ *
+ * // The verifier is from the block pointer to the gang header.
+ * let dva = Dva { vdev: 0, grid: 0, asize: 1, offset: 8, is_gang: true };
+ * let verifier = gang_verifier(&dva, 42);
+ *
+ * let mut bytes = [0u8; GangHeader::LENGTH];
+ * header.to_bytes(&mut bytes, &verifier, Endian::Little).unwrap();
+ * assert!(GangHeader::from_bytes(&bytes, &verifier).is_ok());
+ *
+ * // A different verifier does not match.
+ * let verifier = gang_verifier(&dva, 43);
+ * assert!(GangHeader::from_bytes(&bytes, &verifier).is_err());
+ * ```
+ */
+#[derive(Debug)]
+pub struct GangHeader {
+    pub block_pointers: [BlockPointer; GangHeader::BLOCK_POINTERS],
+}
+
+impl GangHeader {
+    /// Byte length of an encoded [`GangHeader`] (`SPA_GANGBLOCKSIZE`).
+    pub const LENGTH: usize = 512;
+
+    /// Number of block pointers (`SPA_GBH_NBLKPTRS`).
+    pub const BLOCK_POINTERS: usize = 3;
+
+    /// Byte length of the padding between the block pointers and the tail.
+    const PADDING_LENGTH: usize = GangHeader::LENGTH
+        - GangHeader::BLOCK_POINTERS * BlockPointer::LENGTH
+        - ChecksumTail::LENGTH;
+
+    /** Decodes a [`GangHeader`].
+     *
+     * - `verifier` is the verifier of the block pointer to the gang header
+     *   (see [`crate::checksum::gang_verifier`]).
+     *
+     * # Errors
+     *
+     * Returns [`GangHeaderDecodeError`] if the checksum does not match, or a
+     * block pointer is invalid.
+     */
+    pub fn from_bytes(
+        bytes: &[u8; GangHeader::LENGTH],
+        verifier: &ChecksumValue,
+    ) -> Result<GangHeader, GangHeaderDecodeError> {
+        ////////////////////////////////
+        // Verify checksum.
+        gang_verify(bytes, verifier)?;
+
+        ////////////////////////////////
+        // Get endian from the tail.
+        let tail = &bytes[GangHeader::LENGTH - ChecksumTail::LENGTH..];
+        let tail = ChecksumTail::from_bytes(tail.try_into().unwrap())?;
+
+        ////////////////////////////////
+        // Decode block pointers.
+        let decoder = Decoder::from_bytes(bytes, tail.endian);
+        let block_pointers = [
+            BlockPointer::from_decoder(&decoder)?,
+            BlockPointer::from_decoder(&decoder)?,
+            BlockPointer::from_decoder(&decoder)?,
+        ];
+
+        ////////////////////////////////
+        // Decode padding.
+        decoder.skip_zero_padding(GangHeader::PADDING_LENGTH)?;
+
+        Ok(GangHeader {
+            block_pointers: block_pointers,
+        })
+    }
+
+    /** Encodes a [`GangHeader`].
+     *
+     * - `verifier` is the verifier of the block pointer to the gang header
+     *   (see [`crate::checksum::gang_verifier`]).
+     *
+     * # Errors
+     *
+     * Returns [`GangHeaderEncodeError`] if a block pointer is invalid.
+     */
+    pub fn to_bytes(
+        &self,
+        bytes: &mut [u8; GangHeader::LENGTH],
+        verifier: &ChecksumValue,
+        endian: Endian,
+    ) -> Result<(), GangHeaderEncodeError> {
+        ////////////////////////////////
+        // Encode block pointers.
+        let mut encoder = Encoder::to_bytes(bytes, endian);
+        for block_pointer in &self.block_pointers {
+            block_pointer.to_encoder(&mut encoder)?;
+        }
+
+        ////////////////////////////////
+        // Encode padding.
+        encoder.put_zero_padding(GangHeader::PADDING_LENGTH)?;
+
+        ////////////////////////////////
+        // Encode checksum.
+        gang_checksum(bytes, verifier, endian)?;
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum GangHeaderDecodeError {
+    /** Block pointer decode error.
+     *
+     * - `err` - [`BlockPointerDecodeError`]
+     */
+    BlockPointerDecodeError { err: BlockPointerDecodeError },
+
+    /** Endian decode error.
+     *
+     * - `err` - [`DecodeError`]
+     */
+    EndianDecodeError { err: DecodeError },
+
+    /** Gang verify error.
+     *
+     * - `err` - [`GangVerifyError`]
+     */
+    GangVerifyError { err: GangVerifyError },
+}
+
+impl From<BlockPointerDecodeError> for GangHeaderDecodeError {
+    fn from(value: BlockPointerDecodeError) -> Self {
+        GangHeaderDecodeError::BlockPointerDecodeError { err: value }
+    }
+}
+
+impl From<DecodeError> for GangHeaderDecodeError {
+    fn from(value: DecodeError) -> Self {
+        GangHeaderDecodeError::EndianDecodeError { err: value }
+    }
+}
+
+impl From<GangVerifyError> for GangHeaderDecodeError {
+    fn from(value: GangVerifyError) -> Self {
+        GangHeaderDecodeError::GangVerifyError { err: value }
+    }
+}
+
+impl fmt::Display for GangHeaderDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GangHeaderDecodeError::BlockPointerDecodeError { err } => {
+                write!(f, "Gang Header Block Pointer decode error: {err}")
+            }
+            GangHeaderDecodeError::EndianDecodeError { err } => {
+                write!(f, "Gang Header Endian decode error: {err}")
+            }
+            GangHeaderDecodeError::GangVerifyError { err } => {
+                write!(f, "Gang Header verify error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for GangHeaderDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GangHeaderDecodeError::BlockPointerDecodeError { err } => Some(err),
+            GangHeaderDecodeError::EndianDecodeError { err } => Some(err),
+            GangHeaderDecodeError::GangVerifyError { err } => Some(err),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum GangHeaderEncodeError {
+    /** Block pointer encode error.
+     *
+     * - `err` - [`BlockPointerEncodeError`]
+     */
+    BlockPointerEncodeError { err: BlockPointerEncodeError },
+
+    /** Endian encode error.
+     *
+     * - `err` - [`EncodeError`]
+     */
+    EndianEncodeError { err: EncodeError },
+
+    /** Gang checksum error.
+     *
+     * - `err` - [`GangChecksumError`]
+     */
+    GangChecksumError { err: GangChecksumError },
+}
+
+impl From<BlockPointerEncodeError> for GangHeaderEncodeError {
+    fn from(value: BlockPointerEncodeError) -> Self {
+        GangHeaderEncodeError::BlockPointerEncodeError { err: value }
+    }
+}
+
+impl From<EncodeError> for GangHeaderEncodeError {
+    fn from(value: EncodeError) -> Self {
+        GangHeaderEncodeError::EndianEncodeError { err: value }
+    }
+}
+
+impl From<GangChecksumError> for GangHeaderEncodeError {
+    fn from(value: GangChecksumError) -> Self {
+        GangHeaderEncodeError::GangChecksumError { err: value }
+    }
+}
+
+impl fmt::Display for GangHeaderEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GangHeaderEncodeError::BlockPointerEncodeError { err } => {
+                write!(f, "Gang Header Block Pointer encode error: {err}")
+            }
+            GangHeaderEncodeError::EndianEncodeError { err } => {
+                write!(f, "Gang Header Endian encode error: {err}")
+            }
+            GangHeaderEncodeError::GangChecksumError { err } => {
+                write!(f, "Gang Header checksum error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for GangHeaderEncodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GangHeaderEncodeError::BlockPointerEncodeError { err } => Some(err),
+            GangHeaderEncodeError::EndianEncodeError { err } => Some(err),
+            GangHeaderEncodeError::GangChecksumError { err } => Some(err),
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder};
+use crate::phys::sector;
+use crate::phys::{Dva, DvaDecodeError, DvaEncodeError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Mask for mark bit of the source offset.
+const MARK_MASK: u64 = 0x8000000000000000;
+
+/// Mask for source offset field.
+const SRC_OFFSET_MASK: u64 = 0x7fffffffffffffff;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Indirect mapping header.
+ *
+ * - Bytes: 24 or 32
+ * - C reference: `typedef struct vdev_indirect_mapping_phys vdev_indirect_mapping_phys_t`
+ * - The bonus buffer of the `indirect_object` of an indirect vdev.
+ * - `counts_object` was added with the `com.delphix:obsolete_counts`
+ *   feature, and is not present in older pools.
+ *
+ * ```text
+ * +---------------+---+
+ * |    max_offset | 8 |
+ * +---------------+---+
+ * |  bytes_mapped | 8 |
+ * +---------------+---+
+ * |   num_entries | 8 |
+ * +---------------+---+
+ * | counts_object | 8 | obsolete_counts
+ * +---------------+---+
+ * ```
+ */
+#[derive(Debug)]
+pub struct IndirectMappingHeader {
+    /// Byte offset in the removed vdev after the last mapped entry.
+    pub max_offset: u64,
+
+    /// Bytes mapped by all the entries.
+    pub bytes_mapped: u64,
+
+    /// Number of [`IndirectMappingEntry`] in the object.
+    pub num_entries: u64,
+
+    /// Object of the obsolete counts of each entry.
+    pub counts_object: Option<u64>,
+}
+
+impl IndirectMappingHeader {
+    /// Byte length of an encoded [`IndirectMappingHeader`] without `counts_object`.
+    pub const LENGTH_V0: usize = 24;
+
+    /// Byte length of an encoded [`IndirectMappingHeader`] with `counts_object`.
+    pub const LENGTH: usize = 32;
+
+    /** Decodes an [`IndirectMappingHeader`].
+     *
+     * - `counts_object` is decoded if there are enough bytes remaining.
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingDecodeError`] if there are not enough bytes.
+     */
+    pub fn from_decoder(
+        decoder: &Decoder,
+    ) -> Result<IndirectMappingHeader, IndirectMappingDecodeError> {
+        Ok(IndirectMappingHeader {
+            max_offset: decoder.get_u64()?,
+            bytes_mapped: decoder.get_u64()?,
+            num_entries: decoder.get_u64()?,
+            counts_object: match decoder.len() {
+                0 => None,
+                _ => Some(decoder.get_u64()?),
+            },
+        })
+    }
+
+    /** Encodes an [`IndirectMappingHeader`].
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingEncodeError`] if there is not enough space.
+     */
+    pub fn to_encoder(&self, encoder: &mut Encoder) -> Result<(), IndirectMappingEncodeError> {
+        encoder.put_u64(self.max_offset)?;
+        encoder.put_u64(self.bytes_mapped)?;
+        encoder.put_u64(self.num_entries)?;
+        if let Some(counts_object) = self.counts_object {
+            encoder.put_u64(counts_object)?;
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Indirect mapping entry.
+ *
+ * - Bytes: 24
+ * - C reference: `typedef struct vdev_indirect_mapping_entry_phys vdev_indirect_mapping_entry_phys_t`
+ * - Maps a range of a removed vdev to its new location. The range starts at
+ *   `src_offset`, and its length is the allocated size of `dst`.
+ * - The entries of an `indirect_object` are sorted by `src_offset`, and do not
+ *   overlap.
+ *
+ * ```text
+ * +-----+----+
+ * | src |  8 |
+ * +-----+----+
+ * | dst | 16 |
+ * +-----+----+
+ *
+ *        6                   5                   4                   3                   2                   1                   0
+ *  3 2 1 0 9 8 7 6 5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0 9 8 7 6 5 4 3 2 1 0
+ * +-------------------------------------------------------------------------------------------------------------------------------+
+ * |m|                                                       src_offset (63)                                                       |
+ * +-------------------------------------------------------------------------------------------------------------------------------+
+ *
+ * m: Mark
+ * ```
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::{Decoder, Encoder, Endian};
+ * use zfs::phys::{Dva, IndirectMappingEntry};
+ *
+ * // Sectors 16 to 23 of the removed vdev are at sector 1024 of vdev 2.
+ * let entry = IndirectMappingEntry {
+ *     src_offset: 16,
+ *     mark: false,
+ *     dst: Dva { vdev: 2, grid: 0, asize: 8, offset: 1024, is_gang: false },
+ * };
+ *
+ * let mut bytes = [0u8; IndirectMappingEntry::LENGTH];
+ * let mut encoder = Encoder::to_bytes(&mut bytes, Endian::Little);
+ * entry.to_encoder(&mut encoder).unwrap();
+ *
+ * let decoder = Decoder::from_bytes(&bytes, Endian::Little);
+ * let entry = IndirectMappingEntry::from_decoder(&decoder).unwrap();
+ * assert_eq!(entry.src_offset_bytes(), 16 * 512);
+ * assert_eq!(entry.dst.vdev, 2);
+ * ```
+ */
+#[derive(Debug)]
+pub struct IndirectMappingEntry {
+    /// Offset in the removed vdev, in sectors.
+    pub src_offset: u64,
+
+    /// Mark used by `zdb` to find leaked entries.
+    pub mark: bool,
+
+    /// New location of the range.
+    pub dst: Dva,
+}
+
+impl IndirectMappingEntry {
+    /// Byte length of an encoded [`IndirectMappingEntry`].
+    pub const LENGTH: usize = 8 + Dva::LENGTH;
+
+    /// Returns the byte offset in the removed vdev.
+    pub fn src_offset_bytes(&self) -> u64 {
+        self.src_offset << sector::shift!()
+    }
+
+    /** Decodes an [`IndirectMappingEntry`].
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingDecodeError`] if there are not enough bytes,
+     * or the DVA is invalid.
+     */
+    pub fn from_decoder(
+        decoder: &Decoder,
+    ) -> Result<IndirectMappingEntry, IndirectMappingDecodeError> {
+        let src = decoder.get_u64()?;
+
+        Ok(IndirectMappingEntry {
+            src_offset: src & SRC_OFFSET_MASK,
+            mark: (src & MARK_MASK) != 0,
+            dst: Dva::from_decoder(decoder)?,
+        })
+    }
+
+    /** Encodes an [`IndirectMappingEntry`].
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingEncodeError`] if there is not enough space, or
+     * input is invalid.
+     */
+    pub fn to_encoder(&self, encoder: &mut Encoder) -> Result<(), IndirectMappingEncodeError> {
+        if self.src_offset > SRC_OFFSET_MASK {
+            return Err(IndirectMappingEncodeError::InvalidSrcOffset {
+                src_offset: self.src_offset,
+            });
+        }
+
+        encoder.put_u64(if self.mark { MARK_MASK } else { 0 } | self.src_offset)?;
+        self.dst.to_encoder(encoder)?;
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Indirect birth entry.
+ *
+ * - Bytes: 16
+ * - C reference: `typedef struct vdev_indirect_birth_entry_phys vdev_indirect_birth_entry_phys_t`
+ * - The entries of an `indirect_births` object record the transaction group
+ *   in which each part of a removed vdev was copied. The part ends at
+ *   `max_offset`, and starts at the `max_offset` of the previous entry.
+ *
+ * ```text
+ * +--------------------+---+
+ * |         max_offset | 8 |
+ * +--------------------+---+
+ * | physical_birth_txg | 8 |
+ * +--------------------+---+
+ * ```
+ */
+#[derive(Debug)]
+pub struct IndirectBirthEntry {
+    /// Byte offset in the removed vdev after the part.
+    pub max_offset: u64,
+
+    /// Transaction group in which the part was copied.
+    pub physical_birth_txg: u64,
+}
+
+impl IndirectBirthEntry {
+    /// Byte length of an encoded [`IndirectBirthEntry`].
+    pub const LENGTH: usize = 16;
+
+    /** Decodes an [`IndirectBirthEntry`].
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingDecodeError`] if there are not enough bytes.
+     */
+    pub fn from_decoder(
+        decoder: &Decoder,
+    ) -> Result<IndirectBirthEntry, IndirectMappingDecodeError> {
+        Ok(IndirectBirthEntry {
+            max_offset: decoder.get_u64()?,
+            physical_birth_txg: decoder.get_u64()?,
+        })
+    }
+
+    /** Encodes an [`IndirectBirthEntry`].
+     *
+     * # Errors
+     *
+     * Returns [`IndirectMappingEncodeError`] if there is not enough space.
+     */
+    pub fn to_encoder(&self, encoder: &mut Encoder) -> Result<(), IndirectMappingEncodeError> {
+        encoder.put_u64(self.max_offset)?;
+        encoder.put_u64(self.physical_birth_txg)?;
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum IndirectMappingDecodeError {
+    /** DVA decode error.
+     *
+     * - `err` - [`DvaDecodeError`]
+     */
+    DvaDecodeError { err: DvaDecodeError },
+
+    /** Endian decode error.
+     *
+     * - `err` - [`DecodeError`]
+     */
+    EndianDecodeError { err: DecodeError },
+}
+
+impl From<DvaDecodeError> for IndirectMappingDecodeError {
+    fn from(value: DvaDecodeError) -> Self {
+        IndirectMappingDecodeError::DvaDecodeError { err: value }
+    }
+}
+
+impl From<DecodeError> for IndirectMappingDecodeError {
+    fn from(value: DecodeError) -> Self {
+        IndirectMappingDecodeError::EndianDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for IndirectMappingDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndirectMappingDecodeError::DvaDecodeError { err } => {
+                write!(f, "Indirect Mapping DVA decode error: {err}")
+            }
+            IndirectMappingDecodeError::EndianDecodeError { err } => {
+                write!(f, "Indirect Mapping Endian decode error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for IndirectMappingDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            IndirectMappingDecodeError::DvaDecodeError { err } => Some(err),
+            IndirectMappingDecodeError::EndianDecodeError { err } => Some(err),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum IndirectMappingEncodeError {
+    /** DVA encode error.
+     *
+     * - `err` - [`DvaEncodeError`]
+     */
+    DvaEncodeError { err: DvaEncodeError },
+
+    /** Endian encode error.
+     *
+     * - `err` - [`EncodeError`]
+     */
+    EndianEncodeError { err: EncodeError },
+
+    /** Invalid source offset.
+     *
+     * - `src_offset` - Source offset in sectors.
+     */
+    InvalidSrcOffset { src_offset: u64 },
+}
+
+impl From<DvaEncodeError> for IndirectMappingEncodeError {
+    fn from(value: DvaEncodeError) -> Self {
+        IndirectMappingEncodeError::DvaEncodeError { err: value }
+    }
+}
+
+impl From<EncodeError> for IndirectMappingEncodeError {
+    fn from(value: EncodeError) -> Self {
+        IndirectMappingEncodeError::EndianEncodeError { err: value }
+    }
+}
+
+impl fmt::Display for IndirectMappingEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndirectMappingEncodeError::DvaEncodeError { err } => {
+                write!(f, "Indirect Mapping DVA encode error: {err}")
+            }
+            IndirectMappingEncodeError::EndianEncodeError { err } => {
+                write!(f, "Indirect Mapping Endian encode error: {err}")
+            }
+            IndirectMappingEncodeError::InvalidSrcOffset { src_offset } => {
+                write!(
+                    f,
+                    "Indirect Mapping error: invalid source offset {src_offset}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for IndirectMappingEncodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            IndirectMappingEncodeError::DvaEncodeError { err } => Some(err),
+            IndirectMappingEncodeError::EndianEncodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -5,8 +5,12 @@ use core::result::Result::{Err, Ok};
 #[cfg(feature = "std")]
 use std::error;
 
-use crate::checksum::{label_verify, LabelVerifyError};
-use crate::phys::{ChecksumTail, UberBlock, UberBlockDecodeError};
+use crate::checksum::{label_checksum, label_verify, LabelChecksumError, LabelVerifyError};
+use crate::endian::{Endian, FieldPath};
+use crate::nv;
+use crate::phys::{
+    ChecksumTail, UberBlock, UberBlockDecodeError, UberBlockEncodeError, VdevAlignmentMetaSlab,
+};
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -46,6 +50,16 @@ impl Blank {
             payload: bytes[0..Blank::PAYLOAD_LENGTH].try_into().unwrap(),
         })
     }
+
+    /** Encodes a [`Blank`].
+     *
+     * - The blank is not checksummed, so the tail is zero.
+     */
+    pub fn to_bytes(&self, bytes: &mut [u8; Blank::LENGTH]) {
+        let (payload, tail) = bytes.split_at_mut(Blank::PAYLOAD_LENGTH);
+        payload.copy_from_slice(&self.payload);
+        tail.fill(0);
+    }
 }
 
 #[derive(Debug)]
@@ -111,6 +125,24 @@ impl BootHeader {
             payload: bytes[0..BootHeader::PAYLOAD_LENGTH].try_into().unwrap(),
         })
     }
+
+    /** Encodes a [`BootHeader`].
+     *
+     * - `offset` into virtual device of the [`BootHeader`].
+     *
+     * # Errors.
+     *
+     * Returns [`LabelChecksumError`] on error.
+     */
+    pub fn to_bytes(
+        &self,
+        bytes: &mut [u8; BootHeader::LENGTH],
+        offset: u64,
+        endian: Endian,
+    ) -> Result<(), LabelChecksumError> {
+        bytes[0..BootHeader::PAYLOAD_LENGTH].copy_from_slice(&self.payload);
+        label_checksum(bytes, offset, endian)
+    }
 }
 
 #[derive(Debug)]
@@ -236,6 +268,50 @@ impl NvPairs {
             payload: bytes[0..NvPairs::PAYLOAD_LENGTH].try_into().unwrap(),
         })
     }
+
+    /** Creates a [`NvPairs`] from an encoded pool configuration.
+     *
+     * - `config` is an encoded NV list, such as from [`crate::nv::Encoder`],
+     *   [`crate::nv_json`], or the payload of the [`NvPairs`] of another
+     *   label. The rest of the payload is zero.
+     * - A decoded [`crate::phys::Pool`] cannot be encoded back, since it only
+     *   keeps the names of the pairs it does not know, so the configuration
+     *   is always built or copied as an NV list.
+     *
+     * # Errors.
+     *
+     * Returns [`LabelEncodeError`] if the configuration is too long.
+     */
+    pub fn from_config(config: &[u8]) -> Result<NvPairs, LabelEncodeError> {
+        if config.len() > NvPairs::PAYLOAD_LENGTH {
+            return Err(LabelEncodeError::ConfigTooLong {
+                length: config.len(),
+            });
+        }
+
+        let mut payload = [0; NvPairs::PAYLOAD_LENGTH];
+        payload[0..config.len()].copy_from_slice(config);
+
+        Ok(NvPairs { payload: payload })
+    }
+
+    /** Encodes a [`NvPairs`].
+     *
+     * - `offset` into virtual device of the [`NvPairs`].
+     *
+     * # Errors.
+     *
+     * Returns [`LabelChecksumError`] on error.
+     */
+    pub fn to_bytes(
+        &self,
+        bytes: &mut [u8; NvPairs::LENGTH],
+        offset: u64,
+        endian: Endian,
+    ) -> Result<(), LabelChecksumError> {
+        bytes[0..NvPairs::PAYLOAD_LENGTH].copy_from_slice(&self.payload);
+        label_checksum(bytes, offset, endian)
+    }
 }
 
 #[derive(Debug)]
@@ -268,6 +344,136 @@ impl error::Error for NvPairsDecodeError {
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/**
+ * Uber block ring of a label.
+ *
+ * - Bytes: 131072 (128 KiB)
+ * - C reference: `VDEV_UBERBLOCK_SHIFT`, `VDEV_UBERBLOCK_COUNT`
+ * - The slot length is `1 << shift`, where the shift is the `ashift` of the
+ *   top level vdev, limited to 10 (1 KiB) to 13 (8 KiB).
+ * - When multihost is enabled, the last [`UberBlockRing::MMP_COUNT`] slots are
+ *   reserved for MMP writes.
+ *
+ * ```text
+ * +---------+-----------------+
+ * | ashift  |  slots          |
+ * +---------+-----------------+
+ * |   <= 10 | 128 x 1 KiB     |
+ * +---------+-----------------+
+ * |      11 |  64 x 2 KiB     |
+ * +---------+-----------------+
+ * |      12 |  32 x 4 KiB     |
+ * +---------+-----------------+
+ * |   >= 13 |  16 x 8 KiB     |
+ * +---------+-----------------+
+ * ```
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UberBlockRing {
+    pub shift: u32,
+}
+
+impl UberBlockRing {
+    /// Byte length of the uber block ring (128 KiB).
+    pub const LENGTH: usize = 128 * 1024;
+
+    /// Byte offset into a [`Label`].
+    pub const OFFSET: usize = NvPairs::OFFSET + NvPairs::LENGTH;
+
+    /// Minimum shift of a slot (1 KiB).
+    pub const MIN_SHIFT: u32 = 10;
+
+    /// Maximum shift of a slot (8 KiB).
+    pub const MAX_SHIFT: u32 = 13;
+
+    /// Number of slots at the end of the ring reserved for MMP writes, when
+    /// multihost is enabled.
+    pub const MMP_COUNT: usize = 1;
+
+    /** Gets the ring layout for the `ashift` of a top level vdev.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::phys::UberBlockRing;
+     *
+     * let ring = UberBlockRing::from_ashift(12);
+     * assert_eq!((ring.count(), ring.slot_length()), (32, 4096));
+     *
+     * // Transaction group 100 is written to slot 100 % 32.
+     * assert_eq!(ring.slot(100, false), 4);
+     * assert_eq!(ring.slot_offset(4), 128 * 1024 + 4 * 4096);
+     *
+     * // With multihost, the last slot is reserved, so it is slot 100 % 31.
+     * assert_eq!(ring.slot(100, true), 7);
+     * ```
+     */
+    pub fn from_ashift(ashift: u64) -> UberBlockRing {
+        UberBlockRing {
+            shift: ashift.clamp(
+                UberBlockRing::MIN_SHIFT as u64,
+                UberBlockRing::MAX_SHIFT as u64,
+            ) as u32,
+        }
+    }
+
+    /** Gets the ring layout from the `ashift` of the top level vdev of a label
+     * configuration.
+     *
+     * Returns [`None`] if the configuration cannot be decoded, or has no
+     * `ashift`.
+     */
+    pub fn from_nv_pairs(nv_pairs: &NvPairs) -> Option<UberBlockRing> {
+        let decoder = nv::Decoder::from_bytes(&nv_pairs.payload).ok()?;
+        let alignment = VdevAlignmentMetaSlab::from_config(&decoder).ok()??;
+
+        Some(UberBlockRing::from_ashift(alignment.a_shift))
+    }
+
+    /// Returns the byte length of a slot.
+    pub fn slot_length(&self) -> usize {
+        1 << self.shift
+    }
+
+    /// Returns the number of slots.
+    pub fn count(&self) -> usize {
+        UberBlockRing::LENGTH >> self.shift
+    }
+
+    /** Returns the slot of the uber block for a transaction group.
+     *
+     * - C reference: `vdev_uberblock_sync`
+     * - `multihost` is the `multihost` property of the pool. When it is
+     *   enabled, the [`UberBlockRing::MMP_COUNT`] slots reserved for MMP
+     *   writes are not used.
+     */
+    pub fn slot(&self, txg: u64, multihost: bool) -> usize {
+        let count = match multihost {
+            true => self.count() - UberBlockRing::MMP_COUNT,
+            false => self.count(),
+        };
+
+        (txg % count as u64) as usize
+    }
+
+    /// Returns the byte offset of a slot into a [`Label`].
+    pub fn slot_offset(&self, slot: usize) -> usize {
+        UberBlockRing::OFFSET + slot * self.slot_length()
+    }
+}
+
+impl Default for UberBlockRing {
+    fn default() -> Self {
+        UberBlockRing {
+            shift: UberBlockRing::MIN_SHIFT,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 /**
  * Label of a block device.
  *
@@ -305,7 +511,7 @@ impl Label {
     /// Count of [`Label`] in a vdev.
     pub const COUNT: usize = 4;
 
-    /// Count of [`UberBlock`]
+    /// Maximum count of [`UberBlock`], for 1 KiB slots (see [`UberBlockRing`]).
     pub const UBER_COUNT: usize = 128;
 
     /// Byte length of an encoded [`Label`] (256 KiB).
@@ -314,7 +520,24 @@ impl Label {
         + NvPairs::LENGTH
         + (Label::UBER_COUNT * UberBlock::LENGTH);
 
-    /** Get label offsets for a virtual device size. */
+    /** Get label offsets for a virtual device size.
+     *
+     * - C reference: `vdev_label_offset`
+     * - The size is first aligned down to the [`Label::LENGTH`], so the last
+     *   two labels are at the end of the aligned size.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::phys::Label;
+     *
+     * let l = Label::LENGTH as u64;
+     * let offsets = Label::offsets(8 * l + 512).unwrap();
+     * assert_eq!(offsets, [0, l, 6 * l, 7 * l]);
+     * ```
+     */
     pub fn offsets(vdev_size: u64) -> Result<[u64; 4], LabelOffsetError> {
         let label_length = Label::LENGTH as u64;
 
@@ -323,22 +546,170 @@ impl Label {
             return Err(LabelOffsetError::InvalidSize { size: vdev_size });
         }
 
+        // Align size to label length.
+        let vdev_size = vdev_size - (vdev_size % label_length);
+
         Ok([
+            0,
             label_length,
-            2 * label_length,
             vdev_size - 2 * label_length,
             vdev_size - label_length,
         ])
     }
+
+    /** Encodes a [`Label`], with an empty uber block ring.
+     *
+     * - `offset` into virtual device from [`Label::offsets`] function.
+     * - The blank and boot header are zero.
+     *
+     * # Errors
+     *
+     * Returns [`LabelEncodeError`] in case of an error.
+     */
+    pub fn to_bytes(
+        bytes: &mut [u8; Label::LENGTH],
+        offset: u64,
+        nv_pairs: &NvPairs,
+        endian: Endian,
+    ) -> Result<(), LabelEncodeError> {
+        // Split data.
+        let (blank, rest) = bytes.split_at_mut(Blank::LENGTH);
+        let (boot_header, rest) = rest.split_at_mut(BootHeader::LENGTH);
+        let (nv_pairs_bytes, uber_blocks) = rest.split_at_mut(NvPairs::LENGTH);
+
+        // Encode all components.
+        let blank_value = Blank {
+            payload: [0; Blank::PAYLOAD_LENGTH],
+        };
+        blank_value.to_bytes(blank.try_into().unwrap());
+
+        let boot_header_value = BootHeader {
+            payload: [0; BootHeader::PAYLOAD_LENGTH],
+        };
+        boot_header_value.to_bytes(
+            boot_header.try_into().unwrap(),
+            offset + BootHeader::OFFSET as u64,
+            endian,
+        )?;
+
+        nv_pairs.to_bytes(
+            nv_pairs_bytes.try_into().unwrap(),
+            offset + NvPairs::OFFSET as u64,
+            endian,
+        )?;
+
+        uber_blocks.fill(0);
+
+        Ok(())
+    }
+
+    /** Encodes all four labels of a virtual device, with empty uber block
+     * rings.
+     *
+     * - `device` is the bytes of the whole virtual device.
+     * - `nv_pairs` is the pool configuration, from an encoded NV list (see
+     *   [`NvPairs::from_config`]).
+     *
+     * # Errors
+     *
+     * Returns [`LabelEncodeError`] in case of an error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Label, LabelDecode, NvPairs, UberBlock, UberBlockRing};
+     *
+     * let text = r#"{ "name": { "string": "tank" }, "txg": { "uint64": 4 } }"#;
+     * let config = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let nv_pairs = NvPairs::from_config(&config).unwrap();
+     *
+     * let mut device = vec![0u8; 4 * Label::LENGTH];
+     * Label::to_device_bytes(&mut device, &nv_pairs, Endian::Little).unwrap();
+     *
+     * // Write an uber block for transaction group 130.
+     * let mut bytes = [0u8; UberBlock::LENGTH];
+     * bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     * zfs::checksum::label_checksum(&mut bytes, 0, Endian::Little).unwrap();
+     * let mut uber_block = UberBlock::from_bytes(&bytes, 0).unwrap();
+     * uber_block.txg = 130;
+     *
+     * let ring = UberBlockRing::default();
+     * Label::write_uber_block(&mut device, ring, &uber_block, false).unwrap();
+     *
+     * // All four labels decode, with the uber block in slot 130 % 128.
+     * for offset in Label::offsets(device.len() as u64).unwrap() {
+     *     let start = offset as usize;
+     *     let data = device[start..start + Label::LENGTH].try_into().unwrap();
+     *     let label = LabelDecode::from_bytes(data, offset);
+     *
+     *     let nv_pairs = label.nv_pairs.unwrap();
+     *     let decoder = Decoder::from_bytes(&nv_pairs.payload).unwrap();
+     *     assert_eq!(decoder.find("name").unwrap().unwrap().value.as_string(), Some(&"tank"));
+     *
+     *     assert_eq!(label.uber_blocks[2].as_ref().unwrap().txg, 130);
+     *     assert!(label.uber_blocks[3].is_err());
+     * }
+     * ```
+     */
+    pub fn to_device_bytes(
+        device: &mut [u8],
+        nv_pairs: &NvPairs,
+        endian: Endian,
+    ) -> Result<(), LabelEncodeError> {
+        for offset in Label::offsets(device.len() as u64)? {
+            let start = offset as usize;
+            let bytes = &mut device[start..start + Label::LENGTH];
+            Label::to_bytes(bytes.try_into().unwrap(), offset, nv_pairs, endian)?;
+        }
+
+        Ok(())
+    }
+
+    /** Writes an uber block to its slot in all four labels of a virtual
+     * device.
+     *
+     * - The slot is [`UberBlockRing::slot`] of the transaction group, and
+     *   `multihost` is the `multihost` property of the pool.
+     *
+     * # Errors
+     *
+     * Returns [`LabelEncodeError`] in case of an error.
+     */
+    pub fn write_uber_block(
+        device: &mut [u8],
+        ring: UberBlockRing,
+        uber_block: &UberBlock,
+        multihost: bool,
+    ) -> Result<(), LabelEncodeError> {
+        let slot_offset = ring.slot_offset(ring.slot(uber_block.txg, multihost));
+
+        for offset in Label::offsets(device.len() as u64)? {
+            let start = offset as usize + slot_offset;
+            let bytes = &mut device[start..start + ring.slot_length()];
+            uber_block.to_slot_bytes(bytes, offset + slot_offset as u64)?;
+        }
+
+        Ok(())
+    }
 }
 
 /**
  * Decoded values of a label.
+ *
+ * - `uber_blocks` has [`Label::UBER_COUNT`] entries, but only the first
+ *   [`UberBlockRing::count`] slots of `ring` are used. The rest are
+ *   [`UberBlockDecodeError::OutsideRing`].
  */
 pub struct LabelDecode {
     pub blank: Result<Blank, BlankDecodeError>,
     pub boot_header: Result<BootHeader, BootHeaderDecodeError>,
     pub nv_pairs: Result<NvPairs, NvPairsDecodeError>,
+    pub ring: UberBlockRing,
     pub uber_blocks: [Result<UberBlock, UberBlockDecodeError>; Label::UBER_COUNT],
 }
 
@@ -348,15 +719,115 @@ impl LabelDecode {
      * - `offset` into virtual device from [`Label::offsets`] function.
      */
     pub fn from_bytes(data: &[u8; Label::LENGTH], offset: u64) -> LabelDecode {
+        LabelDecode::from_bytes_at(data, offset, &FieldPath::new())
+    }
+
+    /** Decodes a [`LabelDecode`], with a [`FieldPath`] prefix for errors.
+     *
+     * - `offset` into virtual device from [`Label::offsets`] function.
+     * - `path` of the label, such as `label[2]`. Uber block errors are
+     *   reported with paths such as `label[2].uber_block[17].ptr.dva[1]`.
+     * - The uber block ring layout is from the `ashift` of the label
+     *   configuration, see [`UberBlockRing::from_nv_pairs`]. If it is not
+     *   known, then the default layout of 1 KiB slots is used.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Label, LabelDecode, NvPairs, UberBlock, UberBlockRing};
+     *
+     * // Configuration of a 4K native disk.
+     * let text = r#"{
+     *     "txg": { "uint64": 4 },
+     *     "vdev_tree": { "nvlist": {
+     *         "ashift": { "uint64": 12 },
+     *         "asize": { "uint64": 1048576 },
+     *         "metaslab_array": { "uint64": 64 },
+     *         "metaslab_shift": { "uint64": 24 }
+     *     } }
+     * }"#;
+     * let config = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let nv_pairs = NvPairs::from_config(&config).unwrap();
+     *
+     * let mut device = vec![0u8; 4 * Label::LENGTH];
+     * Label::to_device_bytes(&mut device, &nv_pairs, Endian::Little).unwrap();
+     *
+     * // Write an uber block in a 4 KiB slot.
+     * let mut bytes = [0u8; UberBlock::LENGTH];
+     * bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     * zfs::checksum::label_checksum(&mut bytes, 0, Endian::Little).unwrap();
+     * let mut uber_block = UberBlock::from_bytes(&bytes, 0).unwrap();
+     * uber_block.txg = 40;
+     *
+     * let ring = UberBlockRing::from_ashift(12);
+     * Label::write_uber_block(&mut device, ring, &uber_block, false).unwrap();
+     *
+     * let label = LabelDecode::from_bytes(device[0..Label::LENGTH].try_into().unwrap(), 0);
+     * assert_eq!(label.ring, ring);
+     * assert_eq!(label.uber_blocks[40 % 32].as_ref().unwrap().txg, 40);
+     * assert_eq!(label.best_uber_block(None).unwrap().txg, 40);
+     * ```
+     */
+    pub fn from_bytes_at(data: &[u8; Label::LENGTH], offset: u64, path: &FieldPath) -> LabelDecode {
+        let nv_pairs = &data[NvPairs::OFFSET..NvPairs::OFFSET + NvPairs::LENGTH];
+        let nv_pairs = NvPairs::from_bytes(
+            nv_pairs.try_into().unwrap(),
+            offset + NvPairs::OFFSET as u64,
+        );
+
+        // Get ring layout from configuration.
+        let ring = match &nv_pairs {
+            Ok(v) => UberBlockRing::from_nv_pairs(v).unwrap_or_default(),
+            Err(_) => UberBlockRing::default(),
+        };
+
+        LabelDecode::from_parts(data, offset, nv_pairs, ring, path)
+    }
+
+    /** Decodes a [`LabelDecode`], with a known uber block ring layout.
+     *
+     * - `offset` into virtual device from [`Label::offsets`] function.
+     * - `ring` layout, such as from the `ashift` of the top level vdev.
+     * - `path` of the label, such as `label[2]`.
+     */
+    pub fn from_bytes_ring(
+        data: &[u8; Label::LENGTH],
+        offset: u64,
+        ring: UberBlockRing,
+        path: &FieldPath,
+    ) -> LabelDecode {
+        let nv_pairs = &data[NvPairs::OFFSET..NvPairs::OFFSET + NvPairs::LENGTH];
+        let nv_pairs = NvPairs::from_bytes(
+            nv_pairs.try_into().unwrap(),
+            offset + NvPairs::OFFSET as u64,
+        );
+
+        LabelDecode::from_parts(data, offset, nv_pairs, ring, path)
+    }
+
+    /// Decodes the parts of a [`LabelDecode`] other than the [`NvPairs`],
+    /// which are decoded and verified by the caller.
+    fn from_parts(
+        data: &[u8; Label::LENGTH],
+        offset: u64,
+        nv_pairs: Result<NvPairs, NvPairsDecodeError>,
+        ring: UberBlockRing,
+        path: &FieldPath,
+    ) -> LabelDecode {
         // Split data.
         let (blank, rest) = data.split_at(Blank::LENGTH);
         let (boot_header, rest) = rest.split_at(BootHeader::LENGTH);
-        let (nv_pairs, uber_blocks) = rest.split_at(NvPairs::LENGTH);
+        let uber_blocks = &rest[NvPairs::LENGTH..];
 
         // Calculate offsets.
         let boot_header_offset = offset + BootHeader::OFFSET as u64;
-        let nv_pairs_offset = offset + NvPairs::OFFSET as u64;
-        let uber_offset = offset + (Blank::LENGTH + BootHeader::LENGTH + NvPairs::LENGTH) as u64;
+        let uber_offset = offset + UberBlockRing::OFFSET as u64;
+        let slot_length = ring.slot_length();
 
         // Decode all components.
         LabelDecode {
@@ -365,782 +836,35 @@ impl LabelDecode {
                 boot_header.try_into().unwrap(),
                 boot_header_offset,
             ),
-            nv_pairs: NvPairs::from_bytes(nv_pairs.try_into().unwrap(), nv_pairs_offset),
-            // TODO(cybojanek): Clean this up...is it possible to use a macro?
-            //                  Avoid partial initialization.
-            //                  Avoid copy/clone.
-            uber_blocks: [
-                UberBlock::from_bytes(
-                    uber_blocks[0 * UberBlock::LENGTH..1 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (0 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[1 * UberBlock::LENGTH..2 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (1 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[2 * UberBlock::LENGTH..3 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (2 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[3 * UberBlock::LENGTH..4 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (3 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[4 * UberBlock::LENGTH..5 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (4 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[5 * UberBlock::LENGTH..6 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (5 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[6 * UberBlock::LENGTH..7 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (6 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[7 * UberBlock::LENGTH..8 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (7 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[8 * UberBlock::LENGTH..9 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (8 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[9 * UberBlock::LENGTH..10 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (9 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[10 * UberBlock::LENGTH..11 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (10 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[11 * UberBlock::LENGTH..12 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (11 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[12 * UberBlock::LENGTH..13 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (12 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[13 * UberBlock::LENGTH..14 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (13 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[14 * UberBlock::LENGTH..15 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (14 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[15 * UberBlock::LENGTH..16 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (15 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[16 * UberBlock::LENGTH..17 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (16 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[17 * UberBlock::LENGTH..18 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (17 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[18 * UberBlock::LENGTH..19 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (18 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[19 * UberBlock::LENGTH..20 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (19 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[20 * UberBlock::LENGTH..21 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (20 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[21 * UberBlock::LENGTH..22 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (21 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[22 * UberBlock::LENGTH..23 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (22 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[23 * UberBlock::LENGTH..24 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (23 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[24 * UberBlock::LENGTH..25 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (24 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[25 * UberBlock::LENGTH..26 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (25 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[26 * UberBlock::LENGTH..27 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (26 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[27 * UberBlock::LENGTH..28 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (27 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[28 * UberBlock::LENGTH..29 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (28 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[29 * UberBlock::LENGTH..30 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (29 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[30 * UberBlock::LENGTH..31 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (30 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[31 * UberBlock::LENGTH..32 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (31 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[32 * UberBlock::LENGTH..33 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (32 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[33 * UberBlock::LENGTH..34 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (33 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[34 * UberBlock::LENGTH..35 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (34 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[35 * UberBlock::LENGTH..36 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (35 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[36 * UberBlock::LENGTH..37 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (36 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[37 * UberBlock::LENGTH..38 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (37 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[38 * UberBlock::LENGTH..39 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (38 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[39 * UberBlock::LENGTH..40 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (39 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[40 * UberBlock::LENGTH..41 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (40 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[41 * UberBlock::LENGTH..42 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (41 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[42 * UberBlock::LENGTH..43 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (42 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[43 * UberBlock::LENGTH..44 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (43 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[44 * UberBlock::LENGTH..45 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (44 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[45 * UberBlock::LENGTH..46 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (45 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[46 * UberBlock::LENGTH..47 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (46 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[47 * UberBlock::LENGTH..48 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (47 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[48 * UberBlock::LENGTH..49 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (48 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[49 * UberBlock::LENGTH..50 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (49 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[50 * UberBlock::LENGTH..51 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (50 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[51 * UberBlock::LENGTH..52 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (51 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[52 * UberBlock::LENGTH..53 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (52 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[53 * UberBlock::LENGTH..54 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (53 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[54 * UberBlock::LENGTH..55 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (54 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[55 * UberBlock::LENGTH..56 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (55 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[56 * UberBlock::LENGTH..57 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (56 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[57 * UberBlock::LENGTH..58 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (57 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[58 * UberBlock::LENGTH..59 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (58 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[59 * UberBlock::LENGTH..60 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (59 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[60 * UberBlock::LENGTH..61 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (60 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[61 * UberBlock::LENGTH..62 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (61 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[62 * UberBlock::LENGTH..63 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (62 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[63 * UberBlock::LENGTH..64 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (63 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[64 * UberBlock::LENGTH..65 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (64 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[65 * UberBlock::LENGTH..66 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (65 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[66 * UberBlock::LENGTH..67 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (66 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[67 * UberBlock::LENGTH..68 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (67 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[68 * UberBlock::LENGTH..69 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (68 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[69 * UberBlock::LENGTH..70 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (69 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[70 * UberBlock::LENGTH..71 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (70 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[71 * UberBlock::LENGTH..72 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (71 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[72 * UberBlock::LENGTH..73 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (72 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[73 * UberBlock::LENGTH..74 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (73 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[74 * UberBlock::LENGTH..75 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (74 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[75 * UberBlock::LENGTH..76 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (75 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[76 * UberBlock::LENGTH..77 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (76 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[77 * UberBlock::LENGTH..78 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (77 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[78 * UberBlock::LENGTH..79 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (78 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[79 * UberBlock::LENGTH..80 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (79 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[80 * UberBlock::LENGTH..81 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (80 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[81 * UberBlock::LENGTH..82 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (81 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[82 * UberBlock::LENGTH..83 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (82 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[83 * UberBlock::LENGTH..84 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (83 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[84 * UberBlock::LENGTH..85 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (84 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[85 * UberBlock::LENGTH..86 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (85 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[86 * UberBlock::LENGTH..87 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (86 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[87 * UberBlock::LENGTH..88 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (87 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[88 * UberBlock::LENGTH..89 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (88 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[89 * UberBlock::LENGTH..90 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (89 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[90 * UberBlock::LENGTH..91 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (90 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[91 * UberBlock::LENGTH..92 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (91 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[92 * UberBlock::LENGTH..93 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (92 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[93 * UberBlock::LENGTH..94 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (93 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[94 * UberBlock::LENGTH..95 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (94 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[95 * UberBlock::LENGTH..96 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (95 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[96 * UberBlock::LENGTH..97 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (96 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[97 * UberBlock::LENGTH..98 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (97 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[98 * UberBlock::LENGTH..99 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (98 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[99 * UberBlock::LENGTH..100 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (99 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[100 * UberBlock::LENGTH..101 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (100 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[101 * UberBlock::LENGTH..102 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (101 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[102 * UberBlock::LENGTH..103 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (102 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[103 * UberBlock::LENGTH..104 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (103 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[104 * UberBlock::LENGTH..105 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (104 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[105 * UberBlock::LENGTH..106 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (105 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[106 * UberBlock::LENGTH..107 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (106 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[107 * UberBlock::LENGTH..108 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (107 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[108 * UberBlock::LENGTH..109 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (108 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[109 * UberBlock::LENGTH..110 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (109 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[110 * UberBlock::LENGTH..111 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (110 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[111 * UberBlock::LENGTH..112 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (111 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[112 * UberBlock::LENGTH..113 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (112 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[113 * UberBlock::LENGTH..114 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (113 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[114 * UberBlock::LENGTH..115 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (114 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[115 * UberBlock::LENGTH..116 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (115 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[116 * UberBlock::LENGTH..117 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (116 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[117 * UberBlock::LENGTH..118 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (117 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[118 * UberBlock::LENGTH..119 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (118 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[119 * UberBlock::LENGTH..120 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (119 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[120 * UberBlock::LENGTH..121 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (120 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[121 * UberBlock::LENGTH..122 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (121 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[122 * UberBlock::LENGTH..123 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (122 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[123 * UberBlock::LENGTH..124 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (123 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[124 * UberBlock::LENGTH..125 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (124 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[125 * UberBlock::LENGTH..126 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (125 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[126 * UberBlock::LENGTH..127 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (126 * UberBlock::LENGTH) as u64,
-                ),
-                UberBlock::from_bytes(
-                    uber_blocks[127 * UberBlock::LENGTH..128 * UberBlock::LENGTH]
-                        .try_into()
-                        .unwrap(),
-                    uber_offset + (127 * UberBlock::LENGTH) as u64,
-                ),
-            ],
+            nv_pairs: nv_pairs,
+            ring: ring,
+            uber_blocks: core::array::from_fn(|idx| {
+                if idx >= ring.count() {
+                    return Err(UberBlockDecodeError::OutsideRing {
+                        slot: idx,
+                        count: ring.count(),
+                    });
+                }
+
+                let mut uber_path = *path;
+                uber_path.push_index("uber_block", idx);
+                UberBlock::from_slot_bytes_at(
+                    &uber_blocks[idx * slot_length..(idx + 1) * slot_length],
+                    uber_offset + (idx * slot_length) as u64,
+                    &uber_path,
+                )
+            }),
         }
     }
+
+    /** Returns the most recent valid uber block of the label.
+     *
+     * - `max_txg` limits the transaction group, see [`UberBlock::select`].
+     */
+    pub fn best_uber_block(&self, max_txg: Option<u64>) -> Option<&UberBlock> {
+        let index = UberBlock::select(self.uber_blocks.iter().map(|v| v.as_ref().ok()), max_txg)?;
+        self.uber_blocks[index].as_ref().ok()
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -1170,3 +894,81 @@ impl error::Error for LabelOffsetError {
         None
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum LabelEncodeError {
+    /** Configuration is too long for [`NvPairs`].
+     *
+     * - `length` - Length of configuration.
+     */
+    ConfigTooLong { length: usize },
+
+    /** Label checksum error.
+     *
+     * - `err` - [`LabelChecksumError`]
+     */
+    LabelChecksumError { err: LabelChecksumError },
+
+    /** Label offset error.
+     *
+     * - `err` - [`LabelOffsetError`]
+     */
+    LabelOffsetError { err: LabelOffsetError },
+
+    /** Uber block encode error.
+     *
+     * - `err` - [`UberBlockEncodeError`]
+     */
+    UberBlockEncodeError { err: UberBlockEncodeError },
+}
+
+impl From<LabelChecksumError> for LabelEncodeError {
+    fn from(value: LabelChecksumError) -> Self {
+        LabelEncodeError::LabelChecksumError { err: value }
+    }
+}
+
+impl From<LabelOffsetError> for LabelEncodeError {
+    fn from(value: LabelOffsetError) -> Self {
+        LabelEncodeError::LabelOffsetError { err: value }
+    }
+}
+
+impl From<UberBlockEncodeError> for LabelEncodeError {
+    fn from(value: UberBlockEncodeError) -> Self {
+        LabelEncodeError::UberBlockEncodeError { err: value }
+    }
+}
+
+impl fmt::Display for LabelEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelEncodeError::ConfigTooLong { length } => {
+                write!(f, "Label encode error: configuration too long {length}")
+            }
+            LabelEncodeError::LabelChecksumError { err } => {
+                write!(f, "Label encode checksum error: {err}")
+            }
+            LabelEncodeError::LabelOffsetError { err } => {
+                write!(f, "Label encode offset error: {err}")
+            }
+            LabelEncodeError::UberBlockEncodeError { err } => {
+                write!(f, "Label Uber Block encode error: {err}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for LabelEncodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LabelEncodeError::LabelChecksumError { err } => Some(err),
+            LabelEncodeError::LabelOffsetError { err } => Some(err),
+            LabelEncodeError::UberBlockEncodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
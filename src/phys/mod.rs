@@ -6,15 +6,22 @@ mod compression_type;
 mod dmu_type;
 mod dnode;
 mod dva;
+mod feature;
+mod gang_header;
+mod indirect_mapping;
 mod label;
 mod object_set;
 mod pool;
 mod sector;
 mod uber_block;
 mod vdev;
+mod zap;
 mod zil_header;
 
-pub use block_pointer::{BlockPointer, BlockPointerDecodeError, BlockPointerEncodeError};
+pub use block_pointer::{
+    BlockPointer, BlockPointerDecodeError, BlockPointerEmbedded, BlockPointerEmbeddedType,
+    BlockPointerEncodeError, BlockPointerEncrypted, BlockPointerRegular,
+};
 pub use checksum_tail::ChecksumTail;
 pub use checksum_type::{ChecksumType, ChecksumTypeError};
 pub use checksum_value::ChecksumValue;
@@ -22,14 +29,30 @@ pub use compression_type::{CompressionType, CompressionTypeError};
 pub use dmu_type::{DmuType, DmuTypeError};
 pub use dnode::{Dnode, DnodeDecodeError, DnodeEncodeError};
 pub use dva::{Dva, DvaDecodeError, DvaEncodeError};
+#[allow(deprecated)]
+pub use feature::PoolFeaturesForRead;
+pub use feature::{Feature, FeatureDecodeError, FeatureSet, PoolFeatures};
+pub use gang_header::{GangHeader, GangHeaderDecodeError, GangHeaderEncodeError};
+pub use indirect_mapping::{
+    IndirectBirthEntry, IndirectMappingDecodeError, IndirectMappingEncodeError,
+    IndirectMappingEntry, IndirectMappingHeader,
+};
 pub use label::{
     Blank, BlankDecodeError, BootBlock, BootBlockDecodeError, BootHeader, BootHeaderDecodeError,
-    Label, LabelDecode, LabelOffsetError,
+    Label, LabelDecode, LabelEncodeError, LabelOffsetError, NvPairs, NvPairsDecodeError,
+    UberBlockRing,
 };
 pub use object_set::{ObjectSet, ObjectSetDecodeError, ObjectSetEncodeError, ObjectSetType};
 pub use pool::{
-    Pool, PoolDecodeError, PoolFeaturesForRead, PoolHealth, PoolHost, PoolState, PoolVersion,
+    Pool, PoolDecodeError, PoolHealth, PoolHoles, PoolHolesIter, PoolHost, PoolState,
+    PoolUnknownNames, PoolVersion,
+};
+pub use uber_block::{UberBlock, UberBlockDecodeError, UberBlockEncodeError, UberBlockMmp};
+pub use vdev::{
+    Vdev, VdevAlignmentMetaSlab, VdevChildren, VdevChildrenIter, VdevDecodeError, VdevDisk,
+    VdevDraid, VdevDraidSpare, VdevFile, VdevHealth, VdevIndirect, VdevL2Cache, VdevMirror,
+    VdevRaidZ, VdevReplacing, VdevRoot, VdevSpare, VdevState, VdevStats, VdevTree,
+    VdevUnknownNames,
 };
-pub use uber_block::{UberBlock, UberBlockDecodeError, UberBlockEncodeError};
-pub use vdev::{VdevDecodeError, VdevTree};
+pub use zap::{MicroZap, MicroZapDecodeError, MicroZapEntry};
 pub use zil_header::{ZilHeader, ZilHeaderDecodeError, ZilHeaderEncodeError};
@@ -5,7 +5,7 @@ use core::result::Result::{Err, Ok};
 #[cfg(feature = "std")]
 use std::error;
 
-use crate::endian::{DecodeError, Decoder, EncodeError, Encoder};
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, ErrorLocation};
 use crate::phys::{
     Dnode, DnodeDecodeError, DnodeEncodeError, ZilHeader, ZilHeaderDecodeError,
     ZilHeaderEncodeError,
@@ -132,17 +132,36 @@ impl ObjectSet {
      * Returns [`DecodeError`] if there are not enough bytes, or magic is invalid.
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<ObjectSet, ObjectSetDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($variant:ident, $value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(ObjectSetDecodeError::$variant {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
         ////////////////////////////////
         // Decode object set dnode.
-        let os_meta = Dnode::from_decoder(decoder)?;
+        decoder.push_field("os_meta");
+        let os_meta = decode!(DnodeDecodeError, Dnode::from_decoder(decoder));
+        decoder.pop_field();
 
         ////////////////////////////////
         // Decode ZIL header.
-        let zil_header = ZilHeader::from_decoder(decoder)?;
+        decoder.push_field("zil_header");
+        let zil_header = decode!(ZilHeaderDecodeError, ZilHeader::from_decoder(decoder));
+        decoder.pop_field();
 
         ////////////////////////////////
         // Decode object set type.
-        let os_type = decoder.get_u64()?;
+        let os_type = decode!(EndianDecodeError, decoder.get_u64());
         let os_type = match num::FromPrimitive::from_u64(os_type) {
             Some(os_type) => os_type,
             None => return Err(ObjectSetDecodeError::InvalidObjectSetType { os_type: os_type }),
@@ -150,19 +169,23 @@ impl ObjectSet {
 
         ////////////////////////////////
         // Decode flags.
-        let flags = decoder.get_u64()?;
+        let flags = decode!(EndianDecodeError, decoder.get_u64());
         if (flags & FLAG_ALL) != flags {
             return Err(ObjectSetDecodeError::InvalidFlags { flags: flags });
         }
 
         ////////////////////////////////
         // Decode MACs.
-        let portable_mac = decoder.get_bytes(ObjectSet::MAC_LEN)?.try_into().unwrap();
-        let local_mac = decoder.get_bytes(ObjectSet::MAC_LEN)?.try_into().unwrap();
+        let portable_mac = decode!(EndianDecodeError, decoder.get_bytes(ObjectSet::MAC_LEN))
+            .try_into()
+            .unwrap();
+        let local_mac = decode!(EndianDecodeError, decoder.get_bytes(ObjectSet::MAC_LEN))
+            .try_into()
+            .unwrap();
 
         ////////////////////////////////
         // Decode padding up to LENGTH_V1.
-        decoder.skip_zero_padding(240)?;
+        decode!(EndianDecodeError, decoder.skip_zero_padding(240));
 
         ////////////////////////////////
         // Check for extensions based on length.
@@ -171,8 +194,12 @@ impl ObjectSet {
         if decoder.len() > 0 {
             ////////////////////////////
             // Decode user used and group used.
-            let user_used = Dnode::from_decoder(decoder)?;
-            let group_used = Dnode::from_decoder(decoder)?;
+            decoder.push_field("user_used");
+            let user_used = decode!(DnodeDecodeError, Dnode::from_decoder(decoder));
+            decoder.pop_field();
+            decoder.push_field("group_used");
+            let group_used = decode!(DnodeDecodeError, Dnode::from_decoder(decoder));
+            decoder.pop_field();
 
             if decoder.is_empty() {
                 extension = ObjectSetExtension::Two {
@@ -183,7 +210,9 @@ impl ObjectSet {
             } else {
                 ////////////////////////
                 // Decode project used.
-                let project_used = Dnode::from_decoder(decoder)?;
+                decoder.push_field("project_used");
+                let project_used = decode!(DnodeDecodeError, Dnode::from_decoder(decoder));
+                decoder.pop_field();
 
                 extension = ObjectSetExtension::Three {
                     user_used: user_used,
@@ -192,7 +221,7 @@ impl ObjectSet {
                 };
 
                 // Decode padding up to LENGTH_V3.
-                decoder.skip_zero_padding(1536)?;
+                decode!(EndianDecodeError, decoder.skip_zero_padding(1536));
             }
         }
 
@@ -296,15 +325,23 @@ impl ObjectSet {
 pub enum ObjectSetDecodeError {
     /** [`Dnode`] decode error.
      *
-     * - `err` - [`DnodeDecodeError`]
+     * - `err`      - [`DnodeDecodeError`]
+     * - `location` - Location of the last value decoded in the [`Dnode`].
      */
-    DnodeDecodeError { err: DnodeDecodeError },
+    DnodeDecodeError {
+        err: DnodeDecodeError,
+        location: ErrorLocation,
+    },
 
     /** Endian decode error.
      *
-     * - `err` - [`DecodeError`]
+     * - `err`      - [`DecodeError`]
+     * - `location` - Location of the value.
      */
-    EndianDecodeError { err: DecodeError },
+    EndianDecodeError {
+        err: DecodeError,
+        location: ErrorLocation,
+    },
 
     /** Invalid flags.
      *
@@ -320,37 +357,23 @@ pub enum ObjectSetDecodeError {
 
     /** [`ZilHeader`] decode error.
      *
-     * - `err` - [`ZilHeaderDecodeError`]
+     * - `err`      - [`ZilHeaderDecodeError`]
+     * - `location` - Location of the last value decoded in the [`ZilHeader`].
      */
-    ZilHeaderDecodeError { err: ZilHeaderDecodeError },
-}
-
-impl From<DnodeDecodeError> for ObjectSetDecodeError {
-    fn from(value: DnodeDecodeError) -> Self {
-        ObjectSetDecodeError::DnodeDecodeError { err: value }
-    }
-}
-
-impl From<DecodeError> for ObjectSetDecodeError {
-    fn from(value: DecodeError) -> Self {
-        ObjectSetDecodeError::EndianDecodeError { err: value }
-    }
-}
-
-impl From<ZilHeaderDecodeError> for ObjectSetDecodeError {
-    fn from(value: ZilHeaderDecodeError) -> Self {
-        ObjectSetDecodeError::ZilHeaderDecodeError { err: value }
-    }
+    ZilHeaderDecodeError {
+        err: ZilHeaderDecodeError,
+        location: ErrorLocation,
+    },
 }
 
 impl fmt::Display for ObjectSetDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ObjectSetDecodeError::DnodeDecodeError { err } => {
-                write!(f, "ObjectSet Dnode decode error: {err}")
+            ObjectSetDecodeError::DnodeDecodeError { err, location } => {
+                write!(f, "ObjectSet Dnode decode error at {location}: {err}")
             }
-            ObjectSetDecodeError::EndianDecodeError { err } => {
-                write!(f, "ObjectSet Endian decode error: {err}")
+            ObjectSetDecodeError::EndianDecodeError { err, location } => {
+                write!(f, "ObjectSet Endian decode error at {location}: {err}")
             }
             ObjectSetDecodeError::InvalidFlags { flags } => {
                 write!(f, "ObjectSet invalid flags: {flags}")
@@ -358,8 +381,11 @@ impl fmt::Display for ObjectSetDecodeError {
             ObjectSetDecodeError::InvalidObjectSetType { os_type } => {
                 write!(f, "ObjectSet invalid type: {os_type}")
             }
-            ObjectSetDecodeError::ZilHeaderDecodeError { err } => {
-                write!(f, "ObjectSet Block Zil Header decode error: {err}")
+            ObjectSetDecodeError::ZilHeaderDecodeError { err, location } => {
+                write!(
+                    f,
+                    "ObjectSet Block Zil Header decode error at {location}: {err}"
+                )
             }
         }
     }
@@ -369,9 +395,9 @@ impl fmt::Display for ObjectSetDecodeError {
 impl error::Error for ObjectSetDecodeError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            ObjectSetDecodeError::DnodeDecodeError { err } => Some(err),
-            ObjectSetDecodeError::EndianDecodeError { err } => Some(err),
-            ObjectSetDecodeError::ZilHeaderDecodeError { err } => Some(err),
+            ObjectSetDecodeError::DnodeDecodeError { err, location: _ } => Some(err),
+            ObjectSetDecodeError::EndianDecodeError { err, location: _ } => Some(err),
+            ObjectSetDecodeError::ZilHeaderDecodeError { err, location: _ } => Some(err),
             _ => None,
         }
     }
@@ -9,10 +9,10 @@ extern crate fixedstr;
 extern crate num;
 extern crate strum;
 
-use fixedstr::{str16, str32};
+use fixedstr::str16;
 
 use crate::nv;
-use crate::phys::{VdevDecodeError, VdevTree};
+use crate::phys::{FeatureDecodeError, PoolFeatures, VdevChildren, VdevDecodeError, VdevTree};
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -20,7 +20,7 @@ use crate::phys::{VdevDecodeError, VdevTree};
  *
  * - C reference: `SPA_VERSION`
  * - Historically, it was incremented when the format of data on disk changed.
- * - Since V5000, changes are indicated using [`PoolFeaturesForRead`].
+ * - Since V5000, changes are indicated using [`PoolFeatures`].
  */
 #[derive(Clone, Copy, Debug, FromPrimitive, strum::Display)]
 pub enum PoolVersion {
@@ -107,227 +107,111 @@ fn pool_health_from_str(string: &str) -> Result<PoolHealth, PoolDecodeError> {
 
 ////////////////////////////////////////////////////////////////////////////////
 
-const POOL_FEATURE_FOR_READ_ALLOCATION_CLASSES: &str = "org.zfsonlinux:allocation_classes";
-const POOL_FEATURE_FOR_READ_ASYNC_DESTROY: &str = "com.delphix:async_destroy";
-const POOL_FEATURE_FOR_READ_BLAKE_3: &str = "org.openzfs:blake3";
-const POOL_FEATURE_FOR_READ_BLOCK_CLONING: &str = "com.fudosecurity:block_cloning";
-const POOL_FEATURE_FOR_READ_BOOKMARK_V2: &str = "com.datto:bookmark_v2";
-const POOL_FEATURE_FOR_READ_BOOKMARK_WRITTEN: &str = "com.delphix:bookmark_written";
-const POOL_FEATURE_FOR_READ_BOOKMARKS: &str = "com.delphix:bookmarks";
-const POOL_FEATURE_FOR_READ_DEVICE_REBUILD: &str = "org.openzfs:device_rebuild";
-const POOL_FEATURE_FOR_READ_DEVICE_REMOVAL: &str = "com.delphix:device_removal";
-const POOL_FEATURE_FOR_READ_DRAID: &str = "org.openzfs:draid";
-const POOL_FEATURE_FOR_READ_EDONR: &str = "org.illumos:edonr";
-const POOL_FEATURE_FOR_READ_EMBEDDED_DATA: &str = "com.delphix:embedded_data";
-const POOL_FEATURE_FOR_READ_EMPTY_BLOCK_POINTER_OBJECT: &str = "com.delphix:empty_bpobj";
-const POOL_FEATURE_FOR_READ_ENABLED_TXG: &str = "com.delphix:enabled_txg";
-const POOL_FEATURE_FOR_READ_ENCRYPTION: &str = "com.datto:encryption";
-const POOL_FEATURE_FOR_READ_EXTENSIBLE_DATASET: &str = "com.delphix:extensible_dataset";
-const POOL_FEATURE_FOR_READ_FILESYSTEM_LIMITS: &str = "com.joyent:filesystem_limits";
-const POOL_FEATURE_FOR_READ_HEAD_ERROR_LOG: &str = "com.delphix:head_errlog";
-const POOL_FEATURE_FOR_READ_HOLE_BIRTH: &str = "com.delphix:hole_birth";
-const POOL_FEATURE_FOR_READ_LARGE_BLOCKS: &str = "org.open-zfs:large_blocks";
-const POOL_FEATURE_FOR_READ_LARGE_DNODE: &str = "org.zfsonlinux:large_dnode";
-const POOL_FEATURE_FOR_READ_LIVE_LIST: &str = "com.delphix:livelist";
-const POOL_FEATURE_FOR_READ_LOG_SPACE_MAP: &str = "com.delphix:log_spacemap";
-const POOL_FEATURE_FOR_READ_LZ4_COMPRESS: &str = "org.illumos:lz4_compress";
-const POOL_FEATURE_FOR_READ_MULTI_VDEV_CRASH_DUMP: &str = "com.joyent:multi_vdev_crash_dump";
-const POOL_FEATURE_FOR_READ_OBSOLETE_COUNTS: &str = "com.delphix:obsolete_counts";
-const POOL_FEATURE_FOR_READ_PROJECT_QUOTA: &str = "org.zfsonlinux:project_quota";
-const POOL_FEATURE_FOR_READ_REDACTED_DATASETS: &str = "com.delphix:redacted_datasets";
-const POOL_FEATURE_FOR_READ_REDACTION_BOOKMARKS: &str = "com.delphix:redaction_bookmarks";
-const POOL_FEATURE_FOR_READ_RESILVER_DEFER: &str = "com.datto:resilver_defer";
-const POOL_FEATURE_FOR_READ_SHA_512: &str = "org.illumos:sha512";
-const POOL_FEATURE_FOR_READ_SKEIN: &str = "org.illumos:skein";
-const POOL_FEATURE_FOR_READ_SPACEMAP_HISTORGRAM: &str = "com.delphix:spacemap_histogram";
-const POOL_FEATURE_FOR_READ_SPACEMAP_V2: &str = "com.delphix:spacemap_v2";
-const POOL_FEATURE_FOR_READ_USER_OBJECT_ACCOUNTING: &str = "org.zfsonlinux:userobj_accounting";
-const POOL_FEATURE_FOR_READ_ZIL_SA_XATTR: &str = "org.openzfs:zilsaxattr";
-const POOL_FEATURE_FOR_READ_ZPOOL_CHECKPOINT: &str = "com.delphix:zpool_checkpoint";
-const POOL_FEATURE_FOR_READ_ZSTD_COMPRESS: &str = "org.freebsd:zstd_compress";
-
-/** Pool features.
+/** Names of pairs in a pool configuration that are not known.
+ *
+ * - Unknown names are not an error, so that configurations written by newer
+ *   software can be decoded.
+ * - Stores up to [`PoolUnknownNames::CAPACITY`] names, and counts the rest.
+ */
+#[derive(Debug, Default)]
+pub struct PoolUnknownNames<'a> {
+    names: [&'a str; PoolUnknownNames::CAPACITY],
+    count: usize,
+}
+
+impl<'a> PoolUnknownNames<'a> {
+    /// Maximum number of names stored.
+    pub const CAPACITY: usize = 16;
+
+    /// Adds an unknown name.
+    pub(crate) fn push(&mut self, name: &'a str) {
+        if self.count < PoolUnknownNames::CAPACITY {
+            self.names[self.count] = name;
+        }
+        self.count += 1;
+    }
+
+    /// Returns the number of unknown names, including those not stored.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if there are no unknown names.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the stored unknown names, in the order of the NV pair list.
+    pub fn names(&self) -> &[&'a str] {
+        &self.names[0..self.count.min(PoolUnknownNames::CAPACITY)]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Top level vdev IDs of the holes in a pool configuration.
+ *
+ * - C reference: `ZPOOL_CONFIG_HOLE_ARRAY`
+ * - A hole is a placeholder for a removed log vdev, so that the IDs of the
+ *   other top level vdevs do not change. A hole has no devices, and its GUID
+ *   is zero.
  */
 #[derive(Debug)]
-pub struct PoolFeaturesForRead {
-    pub allocation_classes: bool,
-    pub async_destroy: bool,
-    pub blake_3: bool,
-    pub block_cloning: bool,
-    pub bookmark_v2: bool,
-    pub bookmark_written: bool,
-    pub bookmarks: bool,
-    pub device_rebuild: bool,
-    pub device_removal: bool,
-    pub draid: bool,
-    pub edonr: bool,
-    pub embedded_data: bool,
-    pub empty_block_pointer_object: bool,
-    pub enabled_txg: bool,
-    pub encryption: bool,
-    pub extensible_dataset: bool,
-    pub filesystem_limits: bool,
-    pub head_error_log: bool,
-    pub hole_birth: bool,
-    pub large_blocks: bool,
-    pub large_dnode: bool,
-    pub live_list: bool,
-    pub log_space_map: bool,
-    pub lz4_compress: bool,
-    pub multi_vdev_crash_dump: bool,
-    pub obsolete_counts: bool,
-    pub project_quota: bool,
-    pub redacted_datasets: bool,
-    pub redaction_bookmarks: bool,
-    pub resilver_defer: bool,
-    pub sha_512: bool,
-    pub skein: bool,
-    pub spacemap_historgram: bool,
-    pub spacemap_v2: bool,
-    pub user_object_accounting: bool,
-    pub zil_sa_xattr: bool,
-    pub zpool_checkpoint: bool,
-    pub zstd_compress: bool,
+pub struct PoolHoles<'a> {
+    array: nv::ArrayDecoder<'a, u64>,
 }
 
-impl PoolFeaturesForRead {
-    /** Decodes a [`PoolFeaturesForRead`] from the decoder.
+impl<'a> PoolHoles<'a> {
+    /// Returns the number of holes.
+    pub fn len(&self) -> usize {
+        self.array.capacity()
+    }
+
+    /// Returns true if there are no holes.
+    pub fn is_empty(&self) -> bool {
+        self.array.capacity() == 0
+    }
+
+    /// Returns an iterator over the top level vdev IDs of the holes.
+    pub fn iter(&self) -> PoolHolesIter<'a> {
+        let array = self.array.clone();
+        array.reset();
+
+        PoolHolesIter { array: array }
+    }
+
+    /** Returns true if the top level vdev ID is a hole.
      *
      * # Errors
      *
-     * Returns [`PoolDecodeError`] in case of an error.
+     * Returns [`PoolDecodeError`] if the array cannot be decoded.
      */
-    pub fn from_decoder(
-        decoder: &nv::Decoder,
-    ) -> Result<Option<PoolFeaturesForRead>, PoolDecodeError> {
-        ////////////////////////////////
-        // Find features_for_read.
-        let feature_decoder = match nv::find_option!(
-            decoder,
-            POOL_CONFIG_FEATURES_FOR_READ,
-            NvList,
-            PoolDecodeError
-        )? {
-            Some(v) => v,
-            None => return Ok(None),
-        };
-        let feature_decoder = feature_decoder.get_decoder();
-
-        ////////////////////////////////
-        // Use macro for cleaner code.
-        macro_rules! find {
-            ($name:expr) => {
-                nv::find_option_bool!(feature_decoder, $name, PoolDecodeError)?
-            };
+    pub fn contains(&self, id: u64) -> Result<bool, PoolDecodeError> {
+        for hole in self.iter() {
+            if hole? == id {
+                return Ok(true);
+            }
         }
 
-        ////////////////////////////////
-        // Loop through all the pairs, and check for unknown names.
-        let known = [
-            POOL_FEATURE_FOR_READ_ALLOCATION_CLASSES,
-            POOL_FEATURE_FOR_READ_ASYNC_DESTROY,
-            POOL_FEATURE_FOR_READ_BLAKE_3,
-            POOL_FEATURE_FOR_READ_BLOCK_CLONING,
-            POOL_FEATURE_FOR_READ_BOOKMARK_V2,
-            POOL_FEATURE_FOR_READ_BOOKMARK_WRITTEN,
-            POOL_FEATURE_FOR_READ_BOOKMARKS,
-            POOL_FEATURE_FOR_READ_DEVICE_REBUILD,
-            POOL_FEATURE_FOR_READ_DEVICE_REMOVAL,
-            POOL_FEATURE_FOR_READ_DRAID,
-            POOL_FEATURE_FOR_READ_EDONR,
-            POOL_FEATURE_FOR_READ_EMBEDDED_DATA,
-            POOL_FEATURE_FOR_READ_EMPTY_BLOCK_POINTER_OBJECT,
-            POOL_FEATURE_FOR_READ_ENABLED_TXG,
-            POOL_FEATURE_FOR_READ_ENCRYPTION,
-            POOL_FEATURE_FOR_READ_EXTENSIBLE_DATASET,
-            POOL_FEATURE_FOR_READ_FILESYSTEM_LIMITS,
-            POOL_FEATURE_FOR_READ_HEAD_ERROR_LOG,
-            POOL_FEATURE_FOR_READ_HOLE_BIRTH,
-            POOL_FEATURE_FOR_READ_LARGE_BLOCKS,
-            POOL_FEATURE_FOR_READ_LARGE_DNODE,
-            POOL_FEATURE_FOR_READ_LIVE_LIST,
-            POOL_FEATURE_FOR_READ_LOG_SPACE_MAP,
-            POOL_FEATURE_FOR_READ_LZ4_COMPRESS,
-            POOL_FEATURE_FOR_READ_MULTI_VDEV_CRASH_DUMP,
-            POOL_FEATURE_FOR_READ_OBSOLETE_COUNTS,
-            POOL_FEATURE_FOR_READ_PROJECT_QUOTA,
-            POOL_FEATURE_FOR_READ_REDACTED_DATASETS,
-            POOL_FEATURE_FOR_READ_REDACTION_BOOKMARKS,
-            POOL_FEATURE_FOR_READ_RESILVER_DEFER,
-            POOL_FEATURE_FOR_READ_SHA_512,
-            POOL_FEATURE_FOR_READ_SKEIN,
-            POOL_FEATURE_FOR_READ_SPACEMAP_HISTORGRAM,
-            POOL_FEATURE_FOR_READ_SPACEMAP_V2,
-            POOL_FEATURE_FOR_READ_USER_OBJECT_ACCOUNTING,
-            POOL_FEATURE_FOR_READ_ZIL_SA_XATTR,
-            POOL_FEATURE_FOR_READ_ZPOOL_CHECKPOINT,
-            POOL_FEATURE_FOR_READ_ZSTD_COMPRESS,
-        ];
-
-        loop {
-            // Get next pair.
-            let pair = feature_decoder.next_pair()?;
+        Ok(false)
+    }
+}
 
-            // Check if its the end of the list.
-            let pair = match pair {
-                Some(v) => v,
-                None => break,
-            };
+/// Iterator over the top level vdev IDs of [`PoolHoles`].
+#[derive(Debug)]
+pub struct PoolHolesIter<'a> {
+    array: nv::ArrayDecoder<'a, u64>,
+}
 
-            // Check if name is known.
-            let feature = &pair.name;
+impl Iterator for PoolHolesIter<'_> {
+    type Item = Result<u64, PoolDecodeError>;
 
-            if !known.contains(feature) {
-                // Feature is unknown.
-                return Err(PoolDecodeError::UnknownFeature {
-                    feature: feature.into(),
-                    full_length: feature.len(),
-                });
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.array.is_empty() {
+            return None;
         }
 
-        ////////////////////////////////
-        // Fill in all known features for read.
-        Ok(Some(PoolFeaturesForRead {
-            allocation_classes: find!(POOL_FEATURE_FOR_READ_ALLOCATION_CLASSES),
-            async_destroy: find!(POOL_FEATURE_FOR_READ_ASYNC_DESTROY),
-            blake_3: find!(POOL_FEATURE_FOR_READ_BLAKE_3),
-            block_cloning: find!(POOL_FEATURE_FOR_READ_BLOCK_CLONING),
-            bookmark_v2: find!(POOL_FEATURE_FOR_READ_BOOKMARK_V2),
-            bookmark_written: find!(POOL_FEATURE_FOR_READ_BOOKMARK_WRITTEN),
-            bookmarks: find!(POOL_FEATURE_FOR_READ_BOOKMARKS),
-            device_rebuild: find!(POOL_FEATURE_FOR_READ_DEVICE_REBUILD),
-            device_removal: find!(POOL_FEATURE_FOR_READ_DEVICE_REMOVAL),
-            draid: find!(POOL_FEATURE_FOR_READ_DRAID),
-            edonr: find!(POOL_FEATURE_FOR_READ_EDONR),
-            embedded_data: find!(POOL_FEATURE_FOR_READ_EMBEDDED_DATA),
-            empty_block_pointer_object: find!(POOL_FEATURE_FOR_READ_EMPTY_BLOCK_POINTER_OBJECT),
-            enabled_txg: find!(POOL_FEATURE_FOR_READ_ENABLED_TXG),
-            encryption: find!(POOL_FEATURE_FOR_READ_ENCRYPTION),
-            extensible_dataset: find!(POOL_FEATURE_FOR_READ_EXTENSIBLE_DATASET),
-            filesystem_limits: find!(POOL_FEATURE_FOR_READ_FILESYSTEM_LIMITS),
-            head_error_log: find!(POOL_FEATURE_FOR_READ_HEAD_ERROR_LOG),
-            hole_birth: find!(POOL_FEATURE_FOR_READ_HOLE_BIRTH),
-            large_blocks: find!(POOL_FEATURE_FOR_READ_LARGE_BLOCKS),
-            large_dnode: find!(POOL_FEATURE_FOR_READ_LARGE_DNODE),
-            live_list: find!(POOL_FEATURE_FOR_READ_LIVE_LIST),
-            log_space_map: find!(POOL_FEATURE_FOR_READ_LOG_SPACE_MAP),
-            lz4_compress: find!(POOL_FEATURE_FOR_READ_LZ4_COMPRESS),
-            multi_vdev_crash_dump: find!(POOL_FEATURE_FOR_READ_MULTI_VDEV_CRASH_DUMP),
-            obsolete_counts: find!(POOL_FEATURE_FOR_READ_OBSOLETE_COUNTS),
-            project_quota: find!(POOL_FEATURE_FOR_READ_PROJECT_QUOTA),
-            redacted_datasets: find!(POOL_FEATURE_FOR_READ_REDACTED_DATASETS),
-            redaction_bookmarks: find!(POOL_FEATURE_FOR_READ_REDACTION_BOOKMARKS),
-            resilver_defer: find!(POOL_FEATURE_FOR_READ_RESILVER_DEFER),
-            sha_512: find!(POOL_FEATURE_FOR_READ_SHA_512),
-            skein: find!(POOL_FEATURE_FOR_READ_SKEIN),
-            spacemap_historgram: find!(POOL_FEATURE_FOR_READ_SPACEMAP_HISTORGRAM),
-            spacemap_v2: find!(POOL_FEATURE_FOR_READ_SPACEMAP_V2),
-            user_object_accounting: find!(POOL_FEATURE_FOR_READ_USER_OBJECT_ACCOUNTING),
-            zil_sa_xattr: find!(POOL_FEATURE_FOR_READ_ZIL_SA_XATTR),
-            zpool_checkpoint: find!(POOL_FEATURE_FOR_READ_ZPOOL_CHECKPOINT),
-            zstd_compress: find!(POOL_FEATURE_FOR_READ_ZSTD_COMPRESS),
-        }))
+        Some(self.array.get().map_err(|err| err.into()))
     }
 }
 
@@ -348,7 +232,7 @@ impl PoolHost<'_> {
      * Returns [`PoolDecodeError`] in case of an error.
      */
     pub fn from_decoder<'a>(
-        decoder: &'a nv::Decoder<'a>,
+        decoder: &nv::Decoder<'a>,
     ) -> Result<Option<PoolHost<'a>>, PoolDecodeError> {
         // Find hostid.
         match nv::find_option!(decoder, POOL_CONFIG_HOST_ID, Uint64, PoolDecodeError)? {
@@ -390,15 +274,28 @@ const POOL_CONFIG_VERSION: &str = "version";
 // V1: Deprecated in V3.
 const POOL_CONFIG_POOL_HEALTH: &str = "pool_health";
 
+// V3
+const POOL_CONFIG_SPARES: &str = "spares";
+
 // V6
 const POOL_CONFIG_HOST_ID: &str = "hostid";
 const POOL_CONFIG_HOST_NAME: &str = "hostname";
 
+// V10
+const POOL_CONFIG_L2_CACHE: &str = "l2cache";
+
 // V19
+const POOL_CONFIG_HOLE_ARRAY: &str = "hole_array";
 const POOL_CONFIG_VDEV_CHILDREN: &str = "vdev_children";
 
+// Set by 'zpool split'.
+const POOL_CONFIG_SPLIT_GUID: &str = "split_guid";
+
 // V5000
+const POOL_CONFIG_COMMENT: &str = "comment";
+const POOL_CONFIG_COMPATIBILITY: &str = "compatibility";
 const POOL_CONFIG_ERRATA: &str = "errata";
+const POOL_CONFIG_HAS_PER_VDEV_ZAPS: &str = "com.delphix:has_per_vdev_zaps";
 const POOL_CONFIG_FEATURES_FOR_READ: &str = "features_for_read";
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -421,25 +318,100 @@ pub struct Pool<'a> {
     // V1: Deprecated in V3.
     pub pool_health: Option<PoolHealth>,
 
+    // V3
+    pub spares: Option<VdevChildren<'a>>,
+
     // V6
     pub host: Option<PoolHost<'a>>,
 
+    // V10
+    pub l2_cache: Option<VdevChildren<'a>>,
+
     // V19
+    pub holes: Option<PoolHoles<'a>>,
     pub vdev_children: Option<u64>,
 
+    // Set by 'zpool split'.
+    pub split_guid: Option<u64>,
+
     // V5000
+    pub comment: Option<&'a str>,
+    pub compatibility: Option<&'a str>,
     pub errata: Option<u64>,
-    pub features_for_read: Option<PoolFeaturesForRead>,
+    pub features_for_read: Option<PoolFeatures<'a>>,
+    pub has_per_vdev_zaps: bool,
+
+    // Names that are not decoded.
+    pub unknown_names: PoolUnknownNames<'a>,
 }
 
 impl Pool<'_> {
     /** Decodes a [`Pool`] NV pair list.
+     *
+     * - Names that are not known are collected in [`Pool::unknown_names`].
      *
      * # Errors
      *
      * Returns [`PoolDecodeError`] in case of an error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Feature, Pool};
+     *
+     * let text = r#"{
+     *     "version": { "uint64": 5000 },
+     *     "name": { "string": "tank" },
+     *     "state": { "uint64": 0 },
+     *     "txg": { "uint64": 4 },
+     *     "pool_guid": { "uint64": 1 },
+     *     "errata": { "uint64": 0 },
+     *     "comment": { "string": "backups" },
+     *     "compatibility": { "string": "openzfs-2.1-linux" },
+     *     "com.example:unknown": { "uint64": 7 },
+     *     "top_guid": { "uint64": 2 },
+     *     "guid": { "uint64": 2 },
+     *     "vdev_children": { "uint64": 2 },
+     *     "hole_array": { "uint64_array": [1] },
+     *     "vdev_tree": { "nvlist": {
+     *         "type": { "string": "file" },
+     *         "id": { "uint64": 0 },
+     *         "guid": { "uint64": 2 },
+     *         "path": { "string": "/tmp/a" }
+     *     } },
+     *     "features_for_read": { "nvlist": {
+     *         "com.delphix:hole_birth": { "boolean": null },
+     *         "com.example:new_feature": { "boolean": null }
+     *     } },
+     *     "com.delphix:has_per_vdev_zaps": { "boolean": null }
+     * }"#;
+     *
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let pool = Pool::from_decoder(&decoder).unwrap();
+     *
+     * assert_eq!(pool.name, "tank");
+     * assert_eq!(pool.comment, Some("backups"));
+     * assert!(pool.has_per_vdev_zaps);
+     * assert_eq!(pool.unknown_names.names(), ["com.example:unknown"]);
+     *
+     * // Top level vdev 1 is a hole.
+     * let holes = pool.holes.unwrap();
+     * assert!(holes.contains(1).unwrap());
+     * assert!(!holes.contains(0).unwrap());
+     *
+     * let features = pool.features_for_read.unwrap();
+     * assert!(features.for_read.contains(Feature::HoleBirth));
+     * assert_eq!(features.unknown_for_read.names(), ["com.example:new_feature"]);
+     * assert!(!features.can_read());
+     * ```
      */
-    pub fn from_decoder<'a>(decoder: &'a nv::Decoder<'a>) -> Result<Pool<'a>, PoolDecodeError> {
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<Pool<'a>, PoolDecodeError> {
         decoder.reset();
 
         ////////////////////////////////
@@ -452,20 +424,32 @@ impl Pool<'_> {
             POOL_CONFIG_STATE,
             POOL_CONFIG_TOP_GUID,
             POOL_CONFIG_TXG,
-            POOL_CONFIG_VDEV_TREE, // TODO(cybojanek): Implement.
+            POOL_CONFIG_VDEV_TREE,
             POOL_CONFIG_VERSION,
             // V1: Deprecated in V3.
             POOL_CONFIG_POOL_HEALTH,
+            // V3
+            POOL_CONFIG_SPARES,
             // V6
             POOL_CONFIG_HOST_ID,
             POOL_CONFIG_HOST_NAME,
+            // V10
+            POOL_CONFIG_L2_CACHE,
             // V19
+            POOL_CONFIG_HOLE_ARRAY,
             POOL_CONFIG_VDEV_CHILDREN,
+            // Set by 'zpool split'.
+            POOL_CONFIG_SPLIT_GUID,
             // V5000
+            POOL_CONFIG_COMMENT,
+            POOL_CONFIG_COMPATIBILITY,
             POOL_CONFIG_ERRATA,
             POOL_CONFIG_FEATURES_FOR_READ,
+            POOL_CONFIG_HAS_PER_VDEV_ZAPS,
         ];
 
+        let mut unknown_names = PoolUnknownNames::default();
+
         loop {
             // Get next pair.
             let pair = decoder.next_pair()?;
@@ -477,11 +461,8 @@ impl Pool<'_> {
             };
 
             if !known.contains(&pair.name) {
-                // Name is unknown.
-                return Err(PoolDecodeError::UnknownName {
-                    name: pair.name.into(),
-                    full_length: pair.name.len(),
-                });
+                // Name is unknown, so keep track of it, and continue.
+                unknown_names.push(pair.name);
             }
         }
 
@@ -511,6 +492,15 @@ impl Pool<'_> {
             };
         }
 
+        macro_rules! find_option_vdevs {
+            ($name:expr) => {
+                match nv::find_option!(decoder, $name, NvListArray, PoolDecodeError)? {
+                    Some(array) => Some(VdevChildren::from_array(array)?),
+                    None => None,
+                }
+            };
+        }
+
         ////////////////////////////////
         // Success!
         Ok(Pool {
@@ -545,15 +535,41 @@ impl Pool<'_> {
                 }
             },
 
+            // V3
+            spares: find_option_vdevs!(POOL_CONFIG_SPARES),
+
             // V6
             host: PoolHost::from_decoder(decoder)?,
 
+            // V10
+            l2_cache: find_option_vdevs!(POOL_CONFIG_L2_CACHE),
+
             // V19
+            holes: nv::find_option!(
+                decoder,
+                POOL_CONFIG_HOLE_ARRAY,
+                Uint64Array,
+                PoolDecodeError
+            )?
+            .map(|array| PoolHoles { array: array }),
             vdev_children: find_option_u64!(POOL_CONFIG_VDEV_CHILDREN),
 
+            // Set by 'zpool split'.
+            split_guid: find_option_u64!(POOL_CONFIG_SPLIT_GUID),
+
             // V5000
+            comment: find_option_string!(POOL_CONFIG_COMMENT),
+            compatibility: find_option_string!(POOL_CONFIG_COMPATIBILITY),
             errata: find_option_u64!(POOL_CONFIG_ERRATA),
-            features_for_read: PoolFeaturesForRead::from_decoder(decoder)?,
+            features_for_read: PoolFeatures::from_label_decoder(decoder)?,
+            has_per_vdev_zaps: nv::find_option_bool!(
+                decoder,
+                POOL_CONFIG_HAS_PER_VDEV_ZAPS,
+                PoolDecodeError
+            )?,
+
+            // Names that are not decoded.
+            unknown_names: unknown_names,
         })
     }
 }
@@ -562,6 +578,12 @@ impl Pool<'_> {
 
 #[derive(Debug)]
 pub enum PoolDecodeError {
+    /** Feature decode error.
+     *
+     * - `err` - Error.
+     */
+    FeatureDecodeError { err: FeatureDecodeError },
+
     /** Invalid configuration.
      *
      * - `reason` - Reason for invalid configuration.
@@ -596,20 +618,6 @@ pub enum PoolDecodeError {
      */
     NvDecodeError { err: nv::DecodeError },
 
-    /** Unknown feature error.
-     *
-     * - `feature`     - Truncated string of unknown feature.
-     * - `full_length` - The full length of the unknown feature.
-     */
-    UnknownFeature { feature: str32, full_length: usize },
-
-    /** Unknown name error.
-     *
-     * - `name`        - Truncated string of unknown name.
-     * - `full_length` - The full length of the unknown name.
-     */
-    UnknownName { name: str16, full_length: usize },
-
     /** Invalid version field.
      *
      * - `version` - Version.
@@ -639,6 +647,12 @@ impl From<nv::DecodeError> for PoolDecodeError {
     }
 }
 
+impl From<FeatureDecodeError> for PoolDecodeError {
+    fn from(value: FeatureDecodeError) -> Self {
+        PoolDecodeError::FeatureDecodeError { err: value }
+    }
+}
+
 impl From<VdevDecodeError> for PoolDecodeError {
     fn from(value: VdevDecodeError) -> Self {
         PoolDecodeError::VdevDecodeError { err: value }
@@ -648,6 +662,9 @@ impl From<VdevDecodeError> for PoolDecodeError {
 impl fmt::Display for PoolDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            PoolDecodeError::FeatureDecodeError { err } => {
+                write!(f, "Pool feature decode error: {err}")
+            }
             PoolDecodeError::InvalidConfiguration { reason } => {
                 write!(f, "Pool decode error: invalid configuration: {reason}")
             }
@@ -673,29 +690,6 @@ impl fmt::Display for PoolDecodeError {
             PoolDecodeError::NvDecodeError { err } => {
                 write!(f, "Pool NV decode error: {err}")
             }
-            PoolDecodeError::UnknownFeature {
-                feature,
-                full_length,
-            } => {
-                if *full_length > feature.len() {
-                    write!(
-                        f,
-                        "Pool decode error: unknown feature of length {full_length}: '{feature}...'"
-                    )
-                } else {
-                    write!(f, "Pool decode error: unknown feature: '{feature}'")
-                }
-            }
-            PoolDecodeError::UnknownName { name, full_length } => {
-                if *full_length > name.len() {
-                    write!(
-                        f,
-                        "Pool decode error: unknown name of length {full_length}: '{name}...'"
-                    )
-                } else {
-                    write!(f, "Pool decode error: unknown name: '{name}'")
-                }
-            }
             PoolDecodeError::UnsupportedVersion { version } => {
                 write!(f, "Pool decode error: invalid 'version' {version}")
             }
@@ -716,6 +710,7 @@ impl fmt::Display for PoolDecodeError {
 impl error::Error for PoolDecodeError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
+            PoolDecodeError::FeatureDecodeError { err } => Some(err),
             PoolDecodeError::NvDecodeError { err } => Some(err),
             PoolDecodeError::VdevDecodeError { err } => Some(err),
             _ => None,
@@ -1,3 +1,4 @@
+use core::cmp::Ordering;
 use core::fmt;
 use core::result::Result;
 use core::result::Result::{Err, Ok};
@@ -6,18 +7,74 @@ use core::result::Result::{Err, Ok};
 use std::error;
 
 use crate::checksum::{label_checksum, label_verify, LabelChecksumError, LabelVerifyError};
-use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, Endian};
+use crate::endian::{
+    DecodeError, Decoder, EncodeError, Encoder, Endian, ErrorLocation, FieldPath, Location,
+};
 use crate::phys::{BlockPointer, BlockPointerDecodeError, BlockPointerEncodeError};
 
 ////////////////////////////////////////////////////////////////////////////////
 
-/// UberBlock MMP configuration.
+/** UberBlock MMP configuration.
+ *
+ * - C reference: `MMP_INTERVAL`, `MMP_SEQ`, `MMP_FAIL_INT`
+ *
+ * ```text
+ * +----------------+--------+
+ * |     valid bits |   0..8 |
+ * +----------------+--------+
+ * |    interval ms |  8..32 |
+ * +----------------+--------+
+ * |       sequence | 32..48 |
+ * +----------------+--------+
+ * | fail intervals | 48..64 |
+ * +----------------+--------+
+ * ```
+ */
 #[derive(Debug)]
 pub struct UberBlockMmp {
     pub delay: u64,
     pub config: u64,
 }
 
+impl UberBlockMmp {
+    /// Write interval is valid.
+    const INTERVAL_VALID: u64 = 0x01;
+
+    /// Sequence is valid.
+    const SEQUENCE_VALID: u64 = 0x02;
+
+    /// Fail intervals is valid.
+    const FAIL_INTERVALS_VALID: u64 = 0x04;
+
+    /// Returns the MMP write interval in milliseconds, if valid.
+    pub fn interval(&self) -> Option<u64> {
+        match self.config & UberBlockMmp::INTERVAL_VALID {
+            0 => None,
+            _ => Some((self.config & 0x0000_0000_ffff_ff00) >> 8),
+        }
+    }
+
+    /** Returns the MMP sequence number, if valid.
+     *
+     * - The sequence number is incremented each time an uber block is
+     *   written by MMP in the same transaction group.
+     */
+    pub fn sequence(&self) -> Option<u64> {
+        match self.config & UberBlockMmp::SEQUENCE_VALID {
+            0 => None,
+            _ => Some((self.config & 0x0000_ffff_0000_0000) >> 32),
+        }
+    }
+
+    /// Returns the number of MMP write intervals before suspending, if valid.
+    pub fn fail_intervals(&self) -> Option<u64> {
+        match self.config & UberBlockMmp::FAIL_INTERVALS_VALID {
+            0 => None,
+            _ => Some((self.config & 0xffff_0000_0000_0000) >> 48),
+        }
+    }
+}
+
 /** Checksum tail.
  *
  * - Bytes: 1024
@@ -89,34 +146,144 @@ impl UberBlock {
         bytes: &[u8; UberBlock::LENGTH],
         offset: u64,
     ) -> Result<UberBlock, UberBlockDecodeError> {
+        UberBlock::from_bytes_at(bytes, offset, &FieldPath::new())
+    }
+
+    /** Decodes an [`UberBlock`], with a [`FieldPath`] prefix for errors.
+     *
+     * - `offset` into virtual device of the [`UberBlock`].
+     * - `path` of the [`UberBlock`], such as `label[2].uber_block[17]`.
+     *
+     * Decode errors report the absolute offset, and the field path.
+     *
+     * # Errors
+     *
+     * Returns [`UberBlockDecodeError`] if there are not enough bytes, or magic is invalid.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::checksum::label_checksum;
+     * use zfs::endian::{Endian, FieldPath};
+     * use zfs::phys::{UberBlock, UberBlockDecodeError};
+     *
+     * // Uber block with non-zero padding in the second DVA of the block pointer.
+     * let mut bytes = [0u8; UberBlock::LENGTH];
+     * bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     * bytes[63] = 0xff;
+     * bytes[64] = 0x01;
+     *
+     * let offset = 128 * 1024 + 17 * 1024;
+     * label_checksum(&mut bytes, offset, Endian::Little).unwrap();
+     *
+     * let mut path = FieldPath::new();
+     * path.push_index("label", 0);
+     * path.push_index("uber_block", 17);
+     *
+     * match UberBlock::from_bytes_at(&bytes, offset, &path) {
+     *     Err(UberBlockDecodeError::BlockPointerDecodeError { err: _, location }) => {
+     *         assert_eq!(location.offset, offset + 64);
+     *         assert_eq!(format!("{}", location.path), "label[0].uber_block[17].ptr.dva[1]");
+     *     }
+     *     _ => panic!("expected block pointer decode error"),
+     * }
+     * ```
+     */
+    pub fn from_bytes_at(
+        bytes: &[u8; UberBlock::LENGTH],
+        offset: u64,
+        path: &FieldPath,
+    ) -> Result<UberBlock, UberBlockDecodeError> {
+        UberBlock::from_slot_bytes_at(bytes, offset, path)
+    }
+
+    /** Decodes an [`UberBlock`] from a slot of an uber block ring.
+     *
+     * - The slot is at least [`UberBlock::LENGTH`] bytes, see
+     *   [`crate::phys::UberBlockRing`]. The uber block is padded with zeroes,
+     *   and the checksum tail is at the end of the slot.
+     *
+     * # Errors
+     *
+     * Returns [`UberBlockDecodeError`] if the slot is too short, or the
+     * uber block is invalid.
+     */
+    pub fn from_slot_bytes_at(
+        bytes: &[u8],
+        offset: u64,
+        path: &FieldPath,
+    ) -> Result<UberBlock, UberBlockDecodeError> {
+        ////////////////////////////////
+        // Check slot length.
+        let length = bytes.len();
+        if length < UberBlock::LENGTH {
+            return Err(UberBlockDecodeError::InvalidLength { length: length });
+        }
+
         ////////////////////////////////
         // Verify checksum.
         label_verify(bytes, offset)?;
 
         ////////////////////////////////
         // Create decoder.
-        let decoder = Decoder::from_u64_magic(bytes, UberBlock::MAGIC)?;
+        let location = Location::new(offset, *path);
+        let decoder = match Decoder::from_u64_magic_at(bytes, UberBlock::MAGIC, location) {
+            Ok(v) => v,
+            Err(err) => {
+                return Err(UberBlockDecodeError::EndianDecodeError {
+                    err: err,
+                    location: location.into(),
+                })
+            }
+        };
+
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(UberBlockDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
 
         ////////////////////////////////
         // Decode fields.
-        let version = decoder.get_u64()?;
-        let txg = decoder.get_u64()?;
-        let guid_sum = decoder.get_u64()?;
-        let timestamp = decoder.get_u64()?;
+        let version = decode!(decoder.get_u64());
+        let txg = decode!(decoder.get_u64());
+        let guid_sum = decode!(decoder.get_u64());
+        let timestamp = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode block pointer.
-        let block_ptr = BlockPointer::from_decoder(&decoder)?;
+        decoder.push_field("ptr");
+        let block_ptr = match BlockPointer::from_decoder(&decoder) {
+            Ok(v) => v,
+            Err(err) => {
+                return Err(UberBlockDecodeError::BlockPointerDecodeError {
+                    err: err,
+                    location: decoder.location().into(),
+                })
+            }
+        };
+        decoder.pop_field();
 
         ////////////////////////////////
         // Decode software version.
-        let software_version = decoder.get_u64()?;
+        let software_version = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Decode MMP.
-        let mmp_magic = decoder.get_u64()?;
-        let mmp_delay = decoder.get_u64()?;
-        let mmp_config = decoder.get_u64()?;
+        let mmp_magic = decode!(decoder.get_u64());
+        let mmp_delay = decode!(decoder.get_u64());
+        let mmp_config = decode!(decoder.get_u64());
 
         // Check MMP magic.
         let mmp = match mmp_magic {
@@ -138,12 +305,12 @@ impl UberBlock {
 
         ////////////////////////////////
         // Decode checkpoint transaction group.
-        let checkpoint_txg = decoder.get_u64()?;
+        let checkpoint_txg = decode!(decoder.get_u64());
 
         ////////////////////////////////
         // Check that the rest of the uber block (up to the checksum at the
         // tail) is all zeroes.
-        decoder.skip_zero_padding(UberBlock::PADDING_SIZE)?;
+        decode!(decoder.skip_zero_padding(length - UberBlock::LENGTH + UberBlock::PADDING_SIZE));
 
         ////////////////////////////////
         // Success.
@@ -160,6 +327,90 @@ impl UberBlock {
         })
     }
 
+    /** Compares two uber blocks, to find the most recent one.
+     *
+     * - C reference: `vdev_uberblock_compare`
+     * - Ordered by transaction group, then timestamp, then MMP sequence
+     *   number. A missing or invalid MMP sequence number is 0.
+     */
+    pub fn compare(&self, other: &UberBlock) -> Ordering {
+        let sequence = |uber_block: &UberBlock| match &uber_block.mmp {
+            Some(mmp) => mmp.sequence().unwrap_or(0),
+            None => 0,
+        };
+
+        self.txg
+            .cmp(&other.txg)
+            .then(self.timestamp.cmp(&other.timestamp))
+            .then(sequence(self).cmp(&sequence(other)))
+    }
+
+    /** Selects the most recent uber block, and returns its index.
+     *
+     * - C reference: `vdev_uberblock_load`
+     * - `max_txg` limits the transaction group of the selected uber block,
+     *   to rewind to an earlier state of the pool, like `zpool import -T`.
+     * - Entries that are [`None`], such as uber blocks that fail to decode,
+     *   are skipped, so the index is the position in `uber_blocks`.
+     * - Of equal uber blocks, the first is selected.
+     *
+     * Returns [`None`] if there are no uber blocks, or all are after `max_txg`.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::checksum::label_checksum;
+     * use zfs::endian::Endian;
+     * use zfs::phys::UberBlock;
+     *
+     * // Uber block with a transaction group, timestamp and MMP sequence.
+     * let uber_block = |txg: u64, timestamp: u64, sequence: u64| {
+     *     let mut bytes = [0u8; UberBlock::LENGTH];
+     *     bytes[0..8].copy_from_slice(&UberBlock::MAGIC.to_le_bytes());
+     *     bytes[16..24].copy_from_slice(&txg.to_le_bytes());
+     *     bytes[32..40].copy_from_slice(&timestamp.to_le_bytes());
+     *     bytes[176..184].copy_from_slice(&UberBlock::MMP_MAGIC.to_le_bytes());
+     *     bytes[192..200].copy_from_slice(&((sequence << 32) | 0x02).to_le_bytes());
+     *     label_checksum(&mut bytes, 0, Endian::Little).unwrap();
+     *     UberBlock::from_bytes(&bytes, 0).unwrap()
+     * };
+     *
+     * let uber_blocks = [
+     *     Some(uber_block(10, 100, 0)),
+     *     Some(uber_block(12, 120, 0)),
+     *     None,
+     *     Some(uber_block(12, 120, 3)),
+     *     Some(uber_block(11, 130, 0)),
+     *     Some(uber_block(12, 120, 3)),
+     * ];
+     * let select = |max_txg| UberBlock::select(uber_blocks.iter().map(Option::as_ref), max_txg);
+     *
+     * // Highest transaction group, with highest MMP sequence.
+     * assert_eq!(select(None), Some(3));
+     *
+     * // Rewind to transaction group 11.
+     * assert_eq!(select(Some(11)), Some(4));
+     *
+     * assert_eq!(select(Some(9)), None);
+     * ```
+     */
+    pub fn select<'a, I>(uber_blocks: I, max_txg: Option<u64>) -> Option<usize>
+    where
+        I: IntoIterator<Item = Option<&'a UberBlock>>,
+    {
+        uber_blocks
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, uber_block)| uber_block.map(|v| (index, v)))
+            // Skip uber blocks after the maximum transaction group.
+            .filter(|(_, uber_block)| max_txg.is_none_or(|max_txg| uber_block.txg <= max_txg))
+            // Prefer the lower index of equal uber blocks.
+            .max_by(|(a_index, a), (b_index, b)| a.compare(b).then(b_index.cmp(a_index)))
+            .map(|(index, _)| index)
+    }
+
     /** Encodes an [`UberBlock`].
      *
      * # Errors
@@ -172,6 +423,27 @@ impl UberBlock {
         bytes: &mut [u8; UberBlock::LENGTH],
         offset: u64,
     ) -> Result<(), UberBlockEncodeError> {
+        self.to_slot_bytes(bytes, offset)
+    }
+
+    /** Encodes an [`UberBlock`] into a slot of an uber block ring.
+     *
+     * - The slot is at least [`UberBlock::LENGTH`] bytes. The uber block is
+     *   padded with zeroes, and the checksum tail is at the end of the slot.
+     *
+     * # Errors
+     *
+     * Returns [`UberBlockEncodeError`] if the slot is too short or
+     * uberblock is invalid.
+     */
+    pub fn to_slot_bytes(&self, bytes: &mut [u8], offset: u64) -> Result<(), UberBlockEncodeError> {
+        ////////////////////////////////
+        // Check slot length.
+        let length = bytes.len();
+        if length < UberBlock::LENGTH {
+            return Err(UberBlockEncodeError::InvalidLength { length: length });
+        }
+
         ////////////////////////////////
         // Create encoder.
         let mut encoder = Encoder::to_bytes(bytes, self.endian);
@@ -211,7 +483,7 @@ impl UberBlock {
 
         ////////////////////////////////
         // Encode padding.
-        encoder.put_zero_padding(UberBlock::PADDING_SIZE)?;
+        encoder.put_zero_padding(length - UberBlock::LENGTH + UberBlock::PADDING_SIZE)?;
 
         ////////////////////////////////
         // Compute checksum.
@@ -229,15 +501,29 @@ impl UberBlock {
 pub enum UberBlockDecodeError {
     /** [`BlockPointer`] decode error.
      *
-     * - `err` - [`BlockPointerDecodeError`]
+     * - `err`      - [`BlockPointerDecodeError`]
+     * - `location` - Location of the last value decoded in the [`BlockPointer`].
      */
-    BlockPointerDecodeError { err: BlockPointerDecodeError },
+    BlockPointerDecodeError {
+        err: BlockPointerDecodeError,
+        location: ErrorLocation,
+    },
 
     /** Endian decode error.
      *
-     * - `err` - [`DecodeError`]
+     * - `err`      - [`DecodeError`]
+     * - `location` - Location of the value.
+     */
+    EndianDecodeError {
+        err: DecodeError,
+        location: ErrorLocation,
+    },
+
+    /** Invalid uber block slot length.
+     *
+     * - `length` - Length of slot.
      */
-    EndianDecodeError { err: DecodeError },
+    InvalidLength { length: usize },
 
     /** Invalid MMP magic.
      *
@@ -257,18 +543,14 @@ pub enum UberBlockDecodeError {
      * - `config` - MMP config.
      */
     NonZeroMmpValues { delay: u64, config: u64 },
-}
 
-impl From<BlockPointerDecodeError> for UberBlockDecodeError {
-    fn from(value: BlockPointerDecodeError) -> Self {
-        UberBlockDecodeError::BlockPointerDecodeError { err: value }
-    }
-}
-
-impl From<DecodeError> for UberBlockDecodeError {
-    fn from(value: DecodeError) -> Self {
-        UberBlockDecodeError::EndianDecodeError { err: value }
-    }
+    /** Slot is past the end of the uber block ring, for the ring layout of
+     * the vdev.
+     *
+     * - `slot`  - Slot.
+     * - `count` - Number of slots in the ring.
+     */
+    OutsideRing { slot: usize, count: usize },
 }
 
 impl From<LabelVerifyError> for UberBlockDecodeError {
@@ -280,11 +562,17 @@ impl From<LabelVerifyError> for UberBlockDecodeError {
 impl fmt::Display for UberBlockDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            UberBlockDecodeError::BlockPointerDecodeError { err } => {
-                write!(f, "Uber Block Block Pointer decode error: {err}")
+            UberBlockDecodeError::BlockPointerDecodeError { err, location } => {
+                write!(
+                    f,
+                    "Uber Block Block Pointer decode error at {location}: {err}"
+                )
             }
-            UberBlockDecodeError::EndianDecodeError { err } => {
-                write!(f, "Uber Block Endian decode error: {err}")
+            UberBlockDecodeError::EndianDecodeError { err, location } => {
+                write!(f, "Uber Block Endian decode error at {location}: {err}")
+            }
+            UberBlockDecodeError::InvalidLength { length } => {
+                write!(f, "Uber Block decode error: invalid slot length {length}")
             }
             UberBlockDecodeError::InvalidMmpMagic { magic } => {
                 write!(
@@ -301,6 +589,12 @@ impl fmt::Display for UberBlockDecodeError {
                     "Uber Block decode error: non-zero MMP values delay 0x{delay:016x} config 0x{config:016x} for MMP magic 0"
                 )
             }
+            UberBlockDecodeError::OutsideRing { slot, count } => {
+                write!(
+                    f,
+                    "Uber Block decode error: slot {slot} outside ring of {count} slots"
+                )
+            }
         }
     }
 }
@@ -309,8 +603,8 @@ impl fmt::Display for UberBlockDecodeError {
 impl error::Error for UberBlockDecodeError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            UberBlockDecodeError::BlockPointerDecodeError { err } => Some(err),
-            UberBlockDecodeError::EndianDecodeError { err } => Some(err),
+            UberBlockDecodeError::BlockPointerDecodeError { err, location: _ } => Some(err),
+            UberBlockDecodeError::EndianDecodeError { err, location: _ } => Some(err),
             UberBlockDecodeError::LabelVerifyError { err } => Some(err),
             _ => None,
         }
@@ -333,6 +627,12 @@ pub enum UberBlockEncodeError {
      */
     EndianEncodeError { err: EncodeError },
 
+    /** Invalid uber block slot length.
+     *
+     * - `length` - Length of slot.
+     */
+    InvalidLength { length: usize },
+
     /** [`LabelChecksumError`] checksum error.
      *
      * - `err` - [`LabelChecksumError`]
@@ -367,6 +667,9 @@ impl fmt::Display for UberBlockEncodeError {
             UberBlockEncodeError::EndianEncodeError { err } => {
                 write!(f, "Uber Block Endian encode error: {err}")
             }
+            UberBlockEncodeError::InvalidLength { length } => {
+                write!(f, "Uber Block encode error: invalid slot length {length}")
+            }
             UberBlockEncodeError::LabelChecksumError { err } => {
                 write!(f, "Uber Block encode checksum error: {err}")
             }
@@ -381,6 +684,7 @@ impl error::Error for UberBlockEncodeError {
             UberBlockEncodeError::BlockPointerEncodeError { err } => Some(err),
             UberBlockEncodeError::EndianEncodeError { err } => Some(err),
             UberBlockEncodeError::LabelChecksumError { err } => Some(err),
+            _ => None,
         }
     }
 }
@@ -16,12 +16,18 @@ use crate::nv;
 ////////////////////////////////////////////////////////////////////////////////
 
 const VDEV_TYPE_DISK: &str = "disk";
+const VDEV_TYPE_DRAID: &str = "draid";
+const VDEV_TYPE_DRAID_SPARE: &str = "dspare";
 const VDEV_TYPE_FILE: &str = "file";
+const VDEV_TYPE_HOLE: &str = "hole";
+const VDEV_TYPE_INDIRECT: &str = "indirect";
+const VDEV_TYPE_L2_CACHE: &str = "l2cache";
 const VDEV_TYPE_MIRROR: &str = "mirror";
 const VDEV_TYPE_MISSING: &str = "missing";
 const VDEV_TYPE_RAIDZ: &str = "raidz";
 const VDEV_TYPE_REPLACING: &str = "replacing";
 const VDEV_TYPE_ROOT: &str = "root";
+const VDEV_TYPE_SPARE: &str = "spare";
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -29,230 +35,1043 @@ const POOL_CONFIG_KEY_VDEV_TREE: &str = "vdev_tree";
 
 // V1
 const VDEV_CONFIG_A_SHIFT: &str = "ashift";
+const VDEV_CONFIG_AUX_STATE: &str = "aux_state";
 const VDEV_CONFIG_A_SIZE: &str = "asize";
+const VDEV_CONFIG_CHILDREN: &str = "children";
 const VDEV_CONFIG_CREATE_TXG: &str = "create_txg";
 const VDEV_CONFIG_DTL: &str = "DTL";
+const VDEV_CONFIG_DEGRADED: &str = "degraded";
 const VDEV_CONFIG_DEV_ID: &str = "devid";
+const VDEV_CONFIG_FAULTED: &str = "faulted";
 const VDEV_CONFIG_GUID: &str = "guid";
 const VDEV_CONFIG_ID: &str = "id";
 const VDEV_CONFIG_META_SLAB_ARRAY: &str = "metaslab_array";
 const VDEV_CONFIG_META_SLAB_SHIFT: &str = "metaslab_shift";
+const VDEV_CONFIG_NOT_PRESENT: &str = "not_present";
+const VDEV_CONFIG_OFFLINE: &str = "offline";
 const VDEV_CONFIG_PATH: &str = "path";
+const VDEV_CONFIG_REMOVED: &str = "removed";
+const VDEV_CONFIG_RESILVER_TXG: &str = "resilver_txg";
 const VDEV_CONFIG_TYPE: &str = "type";
+const VDEV_CONFIG_VDEV_STATS: &str = "vdev_stats";
 const VDEV_CONFIG_WHOLE_DISK: &str = "whole_disk";
 
+// V3
+const VDEV_CONFIG_N_PARITY: &str = "nparity";
+
 // V6
 const VDEV_CONFIG_PHYS_PATH: &str = "phys_path";
 
 // V7
 const VDEV_CONFIG_IS_LOG: &str = "is_log";
 
-////////////////////////////////////////////////////////////////////////////////
+// V19
+const VDEV_CONFIG_IS_HOLE: &str = "is_hole";
+
+// V5000
+const VDEV_CONFIG_DRAID_N_DATA: &str = "draid_ndata";
+const VDEV_CONFIG_DRAID_N_GROUPS: &str = "draid_ngroups";
+const VDEV_CONFIG_DRAID_N_SPARES: &str = "draid_nspares";
+const VDEV_CONFIG_INDIRECT_BIRTHS: &str = "com.delphix:indirect_births";
+const VDEV_CONFIG_INDIRECT_OBJECT: &str = "com.delphix:indirect_object";
+const VDEV_CONFIG_PREV_INDIRECT_VDEV: &str = "com.delphix:prev_indirect_vdev";
+
+////////////////////////////////////////////////////////////////////////////////
+
+// Use macros for cleaner code.
+macro_rules! find_string {
+    ($decoder:expr, $name:expr) => {
+        nv::find!($decoder, $name, String, VdevDecodeError)?
+    };
+}
+
+macro_rules! find_u64 {
+    ($decoder:expr, $name:expr) => {
+        nv::find!($decoder, $name, Uint64, VdevDecodeError)?
+    };
+}
+
+macro_rules! find_u64_bool {
+    ($decoder:expr, $name:expr) => {
+        match find_u64!($decoder, $name) {
+            0 => false,
+            1 => true,
+            n => {
+                return Err(VdevDecodeError::InvalidU64Bool {
+                    name: $name,
+                    value: n,
+                })
+            }
+        }
+    };
+}
+
+macro_rules! find_option_string {
+    ($decoder:expr, $name:expr) => {
+        nv::find_option!($decoder, $name, String, VdevDecodeError)?
+    };
+}
+
+macro_rules! find_option_u64 {
+    ($decoder:expr, $name:expr) => {
+        nv::find_option!($decoder, $name, Uint64, VdevDecodeError)?
+    };
+}
+
+macro_rules! find_option_u64_bool {
+    ($decoder:expr, $name:expr) => {
+        match find_option_u64!($decoder, $name) {
+            None => None,
+            Some(n) => match n {
+                0 => Some(false),
+                1 => Some(true),
+                _ => {
+                    return Err(VdevDecodeError::InvalidU64Bool {
+                        name: $name,
+                        value: n,
+                    })
+                }
+            },
+        }
+    };
+}
+
+/** Names of pairs in a vdev NV list that are not known.
+ *
+ * - Unknown names are not an error, so that configurations written by newer
+ *   software, such as the `com.delphix:vdev_zap_leaf` of OpenZFS, can be
+ *   decoded.
+ * - Stores up to [`VdevUnknownNames::CAPACITY`] names, and counts the rest.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Vdev, VdevTree};
+ *
+ * // The vdev tree of a label written by OpenZFS 2.1.
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "mirror" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 200 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 },
+ *         "ashift": { "uint64": 12 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "is_log": { "uint64": 0 },
+ *         "create_txg": { "uint64": 4 },
+ *         "com.delphix:vdev_zap_top": { "uint64": 129 },
+ *         "children": { "nvlist_array": [
+ *             {
+ *                 "type": { "string": "disk" },
+ *                 "id": { "uint64": 0 },
+ *                 "guid": { "uint64": 201 },
+ *                 "path": { "string": "/dev/sda1" },
+ *                 "devid": { "string": "ata-DISK_A-part1" },
+ *                 "phys_path": { "string": "pci-0000:00:1f.2-ata-1" },
+ *                 "vdev_enc_sysfs_path": { "string": "/sys/class/enclosure/0:0:0:0/0" },
+ *                 "whole_disk": { "uint64": 1 },
+ *                 "DTL": { "uint64": 640 },
+ *                 "create_txg": { "uint64": 4 },
+ *                 "com.delphix:vdev_zap_leaf": { "uint64": 130 }
+ *             },
+ *             {
+ *                 "type": { "string": "disk" },
+ *                 "id": { "uint64": 1 },
+ *                 "guid": { "uint64": 202 },
+ *                 "path": { "string": "/dev/sdb1" },
+ *                 "whole_disk": { "uint64": 1 },
+ *                 "create_txg": { "uint64": 4 },
+ *                 "orig_guid": { "uint64": 302 },
+ *                 "com.delphix:vdev_zap_leaf": { "uint64": 131 }
+ *             }
+ *         ] }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * assert_eq!(vdev_tree.unknown_names.names(), ["com.delphix:vdev_zap_top"]);
+ *
+ * let children = vdev_tree.vdev.children().unwrap();
+ * let disk = children.get(0).unwrap();
+ * assert_eq!(
+ *     disk.unknown_names.names(),
+ *     ["vdev_enc_sysfs_path", "com.delphix:vdev_zap_leaf"],
+ * );
+ * match disk.vdev {
+ *     Vdev::Disk(disk) => assert_eq!(disk.path, "/dev/sda1"),
+ *     _ => panic!("expected a disk"),
+ * }
+ *
+ * let disk = children.get(1).unwrap();
+ * assert_eq!(disk.unknown_names.names(), ["orig_guid", "com.delphix:vdev_zap_leaf"]);
+ * ```
+ */
+#[derive(Debug, Default)]
+pub struct VdevUnknownNames<'a> {
+    names: [&'a str; VdevUnknownNames::CAPACITY],
+    count: usize,
+}
+
+impl<'a> VdevUnknownNames<'a> {
+    /// Maximum number of names stored.
+    pub const CAPACITY: usize = 16;
+
+    /// Names of the pairs in all vdev NV lists, and the [`VdevHealth`] names.
+    pub const COMMON_NAMES: &'static [&'static str] = &[
+        // Common.
+        VDEV_CONFIG_ID,
+        VDEV_CONFIG_TYPE,
+        VDEV_CONFIG_GUID,
+        // Health.
+        VDEV_CONFIG_AUX_STATE,
+        VDEV_CONFIG_DEGRADED,
+        VDEV_CONFIG_DTL,
+        VDEV_CONFIG_FAULTED,
+        VDEV_CONFIG_NOT_PRESENT,
+        VDEV_CONFIG_OFFLINE,
+        VDEV_CONFIG_REMOVED,
+        VDEV_CONFIG_RESILVER_TXG,
+        VDEV_CONFIG_VDEV_STATS,
+    ];
+
+    /** Collects the names of the pairs in the vdev NV list that are not known.
+     *
+     * - `known` are the names specific to the vdev type, such as
+     *   [`VdevDisk::NAMES`]. The [`VdevUnknownNames::COMMON_NAMES`] are always
+     *   known.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] if the NV list cannot be decoded.
+     */
+    pub fn from_decoder(
+        decoder: &nv::Decoder<'a>,
+        known: &[&str],
+    ) -> Result<VdevUnknownNames<'a>, VdevDecodeError> {
+        let mut unknown_names = VdevUnknownNames::default();
+
+        decoder.reset();
+
+        loop {
+            // Get next pair.
+            let pair = decoder.next_pair()?;
+
+            // Check if its the end of the list.
+            let pair = match pair {
+                Some(v) => v,
+                None => break,
+            };
+
+            if !VdevUnknownNames::COMMON_NAMES.contains(&pair.name) && !known.contains(&pair.name) {
+                unknown_names.push(pair.name);
+            }
+        }
+
+        Ok(unknown_names)
+    }
+
+    /// Adds an unknown name.
+    fn push(&mut self, name: &'a str) {
+        if self.count < VdevUnknownNames::CAPACITY {
+            self.names[self.count] = name;
+        }
+        self.count += 1;
+    }
+
+    /// Returns the number of unknown names, including those not stored.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if there are no unknown names.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the stored unknown names, in the order of the NV pair list.
+    pub fn names(&self) -> &[&'a str] {
+        &self.names[0..self.count.min(VdevUnknownNames::CAPACITY)]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevAlignmentMetaSlab {
+    pub a_shift: u64,
+    pub a_size: u64,
+    pub meta_slab_array: u64,
+    pub meta_slab_shift: u64,
+}
+
+impl VdevAlignmentMetaSlab {
+    /** Decodes the [`VdevAlignmentMetaSlab`] of the top level vdev of a pool
+     * configuration NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_config(
+        decoder: &nv::Decoder<'_>,
+    ) -> Result<Option<VdevAlignmentMetaSlab>, VdevDecodeError> {
+        let nested_decoder =
+            nv::find!(decoder, POOL_CONFIG_KEY_VDEV_TREE, NvList, VdevDecodeError)?;
+
+        VdevAlignmentMetaSlab::from_decoder(nested_decoder.get_decoder())
+    }
+
+    /** Decodes a [`VdevAlignmentMetaSlab`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder(
+        decoder: &nv::Decoder<'_>,
+    ) -> Result<Option<VdevAlignmentMetaSlab>, VdevDecodeError> {
+        ////////////////////////////////
+        // If one is set, then all should be set.
+        let a_shift = find_option_u64!(decoder, VDEV_CONFIG_A_SHIFT);
+        match a_shift {
+            Some(a_shift) => Ok(Some(VdevAlignmentMetaSlab {
+                a_shift: a_shift,
+                a_size: find_u64!(decoder, VDEV_CONFIG_A_SIZE),
+                meta_slab_array: find_u64!(decoder, VDEV_CONFIG_META_SLAB_ARRAY),
+                meta_slab_shift: find_u64!(decoder, VDEV_CONFIG_META_SLAB_SHIFT),
+            })),
+            None => {
+                let a_size = find_option_u64!(decoder, VDEV_CONFIG_A_SIZE);
+                let meta_slab_array = find_option_u64!(decoder, VDEV_CONFIG_META_SLAB_ARRAY);
+                let meta_slab_shift = find_option_u64!(decoder, VDEV_CONFIG_META_SLAB_SHIFT);
+                if !a_size.is_none() || !meta_slab_array.is_none() || !meta_slab_shift.is_none() {
+                    return Err(VdevDecodeError::InvalidConfiguration{
+                        reason: "'ashift' is set, but 'asize', 'metaslab_array', or 'metaslab_shift' is missing"
+                    });
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Children of an interior vdev, such as a mirror or raidz.
+ *
+ * - Children are decoded from the `children` NV list array on demand, since
+ *   the tree is not allocated.
+ * - All children are decoded once when the parent is decoded, so
+ *   [`VdevChildren::get`] only fails for an index out of bounds.
+ * - Also used for the `spares` and `l2cache` NV list arrays of a pool
+ *   configuration.
+ * - [`VdevChildren::get`] and each iterator decode from their own copy of the
+ *   array position, so iterations can be nested or interleaved.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::VdevTree;
+ *
+ * let text = r#"{
+ *     "type": { "string": "mirror" },
+ *     "id": { "uint64": 0 },
+ *     "guid": { "uint64": 200 },
+ *     "children": { "nvlist_array": [
+ *         { "type": { "string": "file" }, "id": { "uint64": 0 },
+ *           "guid": { "uint64": 201 }, "path": { "string": "/a" } },
+ *         { "type": { "string": "file" }, "id": { "uint64": 1 },
+ *           "guid": { "uint64": 202 }, "path": { "string": "/b" } }
+ *     ] }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let mirror = VdevTree::from_list(&decoder).unwrap();
+ * let children = mirror.vdev.children().unwrap();
+ *
+ * // Pairs of children, with nested iterators and lookups.
+ * let mut pairs = Vec::new();
+ * for a in children.iter() {
+ *     for b in children.iter() {
+ *         let last = children.get(1).unwrap();
+ *         pairs.push((a.as_ref().unwrap().guid, b.unwrap().guid, last.guid));
+ *     }
+ * }
+ * assert_eq!(
+ *     pairs,
+ *     [(201, 201, 202), (201, 202, 202), (202, 201, 202), (202, 202, 202)],
+ * );
+ * ```
+ */
+#[derive(Debug)]
+pub struct VdevChildren<'a> {
+    children: nv::ArrayDecoder<'a, nv::Decoder<'a>>,
+}
+
+impl<'a> VdevChildren<'a> {
+    /** Decodes and checks the children of a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    fn from_decoder(decoder: &nv::Decoder<'a>) -> Result<VdevChildren<'a>, VdevDecodeError> {
+        let children = VdevChildren::from_array(nv::find!(
+            decoder,
+            VDEV_CONFIG_CHILDREN,
+            NvListArray,
+            VdevDecodeError
+        )?)?;
+
+        if children.is_empty() {
+            return Err(VdevDecodeError::InvalidConfiguration {
+                reason: "'children' is empty",
+            });
+        }
+
+        Ok(children)
+    }
+
+    /** Decodes and checks the vdevs of an NV list array.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_array(
+        array: nv::ArrayDecoder<'a, nv::Decoder<'a>>,
+    ) -> Result<VdevChildren<'a>, VdevDecodeError> {
+        let children = VdevChildren { children: array };
+
+        // Decode each child, to check the entire tree.
+        for child in children.iter() {
+            child?;
+        }
+
+        Ok(children)
+    }
+
+    /// Returns the number of children.
+    pub fn len(&self) -> usize {
+        self.children.capacity()
+    }
+
+    /// Returns true if there are no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.capacity() == 0
+    }
+
+    /** Decodes the child at `index`.
+     *
+     * - The children before it are decoded to find it, so use
+     *   [`VdevChildren::iter`] to decode many of them.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] if `index` is out of bounds.
+     */
+    pub fn get(&self, index: usize) -> Result<VdevTree<'a>, VdevDecodeError> {
+        if index >= self.len() {
+            return Err(VdevDecodeError::InvalidChildIndex {
+                index: index,
+                count: self.len(),
+            });
+        }
+
+        // Skip to the child.
+        let children = self.children.clone();
+        children.reset();
+        for _ in 0..index {
+            children.get()?;
+        }
+
+        VdevTree::from_list(&children.get()?)
+    }
+
+    /// Returns an iterator over the decoded children.
+    pub fn iter(&self) -> VdevChildrenIter<'a> {
+        let children = self.children.clone();
+        children.reset();
+
+        VdevChildrenIter { children: children }
+    }
+}
+
+/// Iterator over [`VdevChildren`].
+#[derive(Debug)]
+pub struct VdevChildrenIter<'a> {
+    children: nv::ArrayDecoder<'a, nv::Decoder<'a>>,
+}
+
+impl<'a> Iterator for VdevChildrenIter<'a> {
+    type Item = Result<VdevTree<'a>, VdevDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.children.is_empty() {
+            return None;
+        }
+
+        Some(match self.children.get() {
+            Ok(decoder) => VdevTree::from_list(&decoder),
+            Err(err) => Err(err.into()),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevDisk<'a> {
+    pub path: &'a str,
+    pub whole_disk: bool,
+
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+    pub dev_id: Option<&'a str>,
+    pub phys_path: Option<&'a str>,
+}
+
+impl VdevDisk<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_DEV_ID,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+        VDEV_CONFIG_PATH,
+        VDEV_CONFIG_PHYS_PATH,
+        VDEV_CONFIG_WHOLE_DISK,
+    ];
+
+    /** Decodes a [`VdevDisk`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevDisk<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevDisk {
+            path: find_string!(decoder, VDEV_CONFIG_PATH),
+            whole_disk: find_u64_bool!(decoder, VDEV_CONFIG_WHOLE_DISK),
+
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            dev_id: find_option_string!(decoder, VDEV_CONFIG_DEV_ID),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+            phys_path: find_option_string!(decoder, VDEV_CONFIG_PHYS_PATH),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevDraid<'a> {
+    pub children: VdevChildren<'a>,
+    pub n_data: u64,
+    pub n_groups: u64,
+    pub n_parity: u64,
+    pub n_spares: u64,
+
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+}
+
+impl VdevDraid<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CHILDREN,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_DRAID_N_DATA,
+        VDEV_CONFIG_DRAID_N_GROUPS,
+        VDEV_CONFIG_DRAID_N_SPARES,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+        VDEV_CONFIG_N_PARITY,
+    ];
+
+    /// Maximum number of parity columns.
+    pub const MAX_PARITY: u64 = 3;
+
+    /** Decodes a [`VdevDraid`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevDraid<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode layout.
+        let n_parity = find_u64!(decoder, VDEV_CONFIG_N_PARITY);
+        let n_data = find_u64!(decoder, VDEV_CONFIG_DRAID_N_DATA);
+        let n_spares = find_u64!(decoder, VDEV_CONFIG_DRAID_N_SPARES);
+        let n_groups = find_u64!(decoder, VDEV_CONFIG_DRAID_N_GROUPS);
+
+        if n_parity == 0 || n_parity > VdevDraid::MAX_PARITY {
+            return Err(VdevDecodeError::InvalidParity { n_parity: n_parity });
+        }
+
+        if n_data == 0 || n_groups == 0 {
+            return Err(VdevDecodeError::InvalidConfiguration {
+                reason: "'draid_ndata' or 'draid_ngroups' is zero",
+            });
+        }
+
+        ////////////////////////////////
+        // Decode children, and check that the groups fill the children that
+        // are not distributed spares.
+        let children = VdevChildren::from_decoder(decoder)?;
+        let group_width = n_data + n_parity;
+        let n_children = children.len() as u64;
+
+        if n_children < group_width + n_spares {
+            return Err(VdevDecodeError::InvalidConfiguration {
+                reason: "'children' count is less than 'draid_ndata' + 'nparity' + 'draid_nspares'",
+            });
+        }
+
+        if (group_width * n_groups) % (n_children - n_spares) != 0 {
+            return Err(VdevDecodeError::InvalidConfiguration {
+                reason: "'draid_ngroups' do not fill the children that are not spares",
+            });
+        }
+
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevDraid {
+            children: children,
+            n_data: n_data,
+            n_groups: n_groups,
+            n_parity: n_parity,
+            n_spares: n_spares,
+
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A distributed spare of a dRAID vdev.
+ *
+ * - The path is `draid<n_parity>-<top_id>-<spare_id>`.
+ * - The spare has no device. Its data is on the other children of the dRAID
+ *   vdev `top_id`, in the spare columns of each permutation.
+ */
+#[derive(Debug)]
+pub struct VdevDraidSpare<'a> {
+    pub path: &'a str,
+    pub n_parity: u64,
+    pub top_id: u64,
+    pub spare_id: u64,
+
+    pub create_txg: Option<u64>,
+}
+
+impl VdevDraidSpare<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[VDEV_CONFIG_CREATE_TXG, VDEV_CONFIG_PATH];
+
+    /** Decodes a [`VdevDraidSpare`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(
+        decoder: &nv::Decoder<'a>,
+    ) -> Result<VdevDraidSpare<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode path.
+        let path = find_string!(decoder, VDEV_CONFIG_PATH);
+
+        let mut ids = path
+            .strip_prefix(VDEV_TYPE_DRAID)
+            .unwrap_or("")
+            .split('-')
+            .map(|id| id.parse::<u64>());
+
+        let (n_parity, top_id, spare_id) = match (ids.next(), ids.next(), ids.next(), ids.next()) {
+            (Some(Ok(n_parity)), Some(Ok(top_id)), Some(Ok(spare_id)), None) => {
+                (n_parity, top_id, spare_id)
+            }
+            _ => {
+                return Err(VdevDecodeError::InvalidConfiguration {
+                    reason: "'path' of 'dspare' is not 'draid<nparity>-<top>-<spare>'",
+                })
+            }
+        };
+
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevDraidSpare {
+            path: path,
+            n_parity: n_parity,
+            top_id: top_id,
+            spare_id: spare_id,
+
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevFile<'a> {
+    pub path: &'a str,
+
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+}
+
+impl VdevFile<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+        VDEV_CONFIG_PATH,
+    ];
+
+    /** Decodes a [`VdevFile`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevFile<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevFile {
+            path: find_string!(decoder, VDEV_CONFIG_PATH),
+
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevIndirect {
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+
+    pub indirect_births: Option<u64>,
+    pub indirect_object: Option<u64>,
+    pub prev_indirect_vdev: Option<u64>,
+}
+
+impl VdevIndirect {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_INDIRECT_BIRTHS,
+        VDEV_CONFIG_INDIRECT_OBJECT,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+        VDEV_CONFIG_PREV_INDIRECT_VDEV,
+    ];
+
+    /** Decodes a [`VdevIndirect`] from a vdev NV pair list.
+     *
+     * - An indirect vdev is a top level vdev that was removed. Its blocks are
+     *   remapped to other vdevs using the `indirect_object` mapping.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder(decoder: &nv::Decoder<'_>) -> Result<VdevIndirect, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevIndirect {
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+
+            indirect_births: find_option_u64!(decoder, VDEV_CONFIG_INDIRECT_BIRTHS),
+            indirect_object: find_option_u64!(decoder, VDEV_CONFIG_INDIRECT_OBJECT),
+            prev_indirect_vdev: find_option_u64!(decoder, VDEV_CONFIG_PREV_INDIRECT_VDEV),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevL2Cache<'a> {
+    pub children: VdevChildren<'a>,
+}
+
+impl VdevL2Cache<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[VDEV_CONFIG_CHILDREN];
+
+    /** Decodes a [`VdevL2Cache`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevL2Cache<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevL2Cache {
+            children: VdevChildren::from_decoder(decoder)?,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevMirror<'a> {
+    pub children: VdevChildren<'a>,
+
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+}
+
+impl VdevMirror<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CHILDREN,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+    ];
+
+    /** Decodes a [`VdevMirror`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevMirror<'a>, VdevDecodeError> {
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevMirror {
+            children: VdevChildren::from_decoder(decoder)?,
+
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct VdevRaidZ<'a> {
+    pub children: VdevChildren<'a>,
+    pub n_parity: u64,
+
+    pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
+    pub create_txg: Option<u64>,
+    pub is_log: Option<bool>,
+}
+
+impl VdevRaidZ<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CHILDREN,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_IS_LOG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+        VDEV_CONFIG_N_PARITY,
+    ];
 
-#[derive(Debug)]
-pub struct VdevAlignmentMetaSlab {
-    pub a_shift: u64,
-    pub a_size: u64,
-    pub meta_slab_array: u64,
-    pub meta_slab_shift: u64,
-}
+    /// Maximum number of parity columns.
+    pub const MAX_PARITY: u64 = 3;
 
-impl VdevAlignmentMetaSlab {
-    /** Decodes a [`VdevTree`] NV pair list.
+    /** Decodes a [`VdevRaidZ`] from a vdev NV pair list.
      *
      * # Errors
      *
      * Returns [`VdevDecodeError`] in case of an error.
      */
-    pub fn from_decoder<'a, 'b>(
-        decoder: &'a nv::Decoder<'a>,
-        nested_decoder: &'b nv::NestedDecoder<'b>,
-    ) -> Result<Option<VdevAlignmentMetaSlab>, VdevDecodeError> {
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevRaidZ<'a>, VdevDecodeError> {
         ////////////////////////////////
-        // Use macros for cleaner code.
-        macro_rules! find_u64 {
-            ($name:expr) => {
-                nv::find_nested!(decoder, nested_decoder, $name, Uint64, VdevDecodeError)?
-            };
-        }
+        // Decode parity. Pools older than V3 only have single parity, and
+        // do not store it.
+        let n_parity = find_option_u64!(decoder, VDEV_CONFIG_N_PARITY).unwrap_or(1);
 
-        macro_rules! find_option_u64 {
-            ($name:expr) => {
-                nv::find_option_nested!(decoder, nested_decoder, $name, Uint64, VdevDecodeError)?
-            };
+        if n_parity == 0 || n_parity > VdevRaidZ::MAX_PARITY {
+            return Err(VdevDecodeError::InvalidParity { n_parity: n_parity });
         }
 
         ////////////////////////////////
-        // If one is set, then all should be set.
-        let a_shift = find_option_u64!(VDEV_CONFIG_A_SHIFT);
-        match a_shift {
-            Some(a_shift) => Ok(Some(VdevAlignmentMetaSlab {
-                a_shift: a_shift,
-                a_size: find_u64!(VDEV_CONFIG_A_SIZE),
-                meta_slab_array: find_u64!(VDEV_CONFIG_META_SLAB_ARRAY),
-                meta_slab_shift: find_u64!(VDEV_CONFIG_META_SLAB_SHIFT),
-            })),
-            None => {
-                let a_size = find_option_u64!(VDEV_CONFIG_A_SIZE);
-                let meta_slab_array = find_option_u64!(VDEV_CONFIG_META_SLAB_ARRAY);
-                let meta_slab_shift = find_option_u64!(VDEV_CONFIG_META_SLAB_SHIFT);
-                if !a_size.is_none() || !meta_slab_array.is_none() || !meta_slab_shift.is_none() {
-                    return Err(VdevDecodeError::InvalidConfiguration{
-                        reason: "'ashift' is set, but 'asize', 'metaslab_array', or 'metaslab_shift' is missing"
-                    });
-                }
-                Ok(None)
-            }
+        // Decode children, and check that there are more than parity.
+        let children = VdevChildren::from_decoder(decoder)?;
+        if (children.len() as u64) <= n_parity {
+            return Err(VdevDecodeError::InvalidConfiguration {
+                reason: "'children' count is not greater than 'nparity'",
+            });
         }
+
+        ////////////////////////////////
+        // Decode vdev.
+        Ok(VdevRaidZ {
+            children: children,
+            n_parity: n_parity,
+
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+            is_log: find_option_u64_bool!(decoder, VDEV_CONFIG_IS_LOG),
+        })
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug)]
-pub struct VdevDisk<'a> {
-    pub path: &'a str,
-    pub whole_disk: bool,
+pub struct VdevReplacing<'a> {
+    pub children: VdevChildren<'a>,
 
     pub a_meta_slab: Option<VdevAlignmentMetaSlab>,
     pub create_txg: Option<u64>,
-    pub is_log: Option<bool>,
-    pub dev_id: Option<&'a str>,
-    pub phys_path: Option<&'a str>,
 }
 
-impl VdevDisk<'_> {
-    /** Decodes a [`VdevTree`] NV pair list.
+impl VdevReplacing<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[
+        VDEV_CONFIG_A_SHIFT,
+        VDEV_CONFIG_A_SIZE,
+        VDEV_CONFIG_CHILDREN,
+        VDEV_CONFIG_CREATE_TXG,
+        VDEV_CONFIG_META_SLAB_ARRAY,
+        VDEV_CONFIG_META_SLAB_SHIFT,
+    ];
+
+    /** Decodes a [`VdevReplacing`] from a vdev NV pair list.
+     *
+     * - The first child is the old vdev, and the second child is the new vdev.
      *
      * # Errors
      *
      * Returns [`VdevDecodeError`] in case of an error.
      */
-    pub fn from_decoder<'a, 'b>(
-        decoder: &'a nv::Decoder<'a>,
-        nested_decoder: &'b nv::NestedDecoder<'b>,
-    ) -> Result<VdevDisk<'a>, VdevDecodeError> {
+    pub fn from_decoder<'a>(
+        decoder: &nv::Decoder<'a>,
+    ) -> Result<VdevReplacing<'a>, VdevDecodeError> {
         ////////////////////////////////
-        // Loop through all the pairs, and check for unknown names.
-        let known = [
-            // Common.
-            VDEV_CONFIG_ID,
-            VDEV_CONFIG_TYPE,
-            VDEV_CONFIG_GUID,
-            // Disk.
-            VDEV_CONFIG_A_SHIFT,
-            VDEV_CONFIG_A_SIZE,
-            VDEV_CONFIG_CREATE_TXG,
-            VDEV_CONFIG_DEV_ID,
-            VDEV_CONFIG_IS_LOG,
-            VDEV_CONFIG_META_SLAB_ARRAY,
-            VDEV_CONFIG_META_SLAB_SHIFT,
-            VDEV_CONFIG_PATH,
-            VDEV_CONFIG_PHYS_PATH,
-            VDEV_CONFIG_WHOLE_DISK,
-        ];
-
-        let nd = nested_decoder.get_decoder();
-        nd.reset();
+        // Decode vdev.
+        Ok(VdevReplacing {
+            children: VdevChildren::from_decoder(decoder)?,
 
-        loop {
-            // Get next pair.
-            let pair = nd.next_pair()?;
+            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder)?,
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+        })
+    }
+}
 
-            // Check if its the end of the list.
-            let pair = match pair {
-                Some(v) => v,
-                None => break,
-            };
+////////////////////////////////////////////////////////////////////////////////
 
-            if !known.contains(&pair.name) {
-                // Name is unknown.
-                return Err(VdevDecodeError::UnknownName {
-                    name: pair.name.into(),
-                    full_length: pair.name.len(),
-                });
-            }
-        }
+#[derive(Debug)]
+pub struct VdevRoot<'a> {
+    pub children: VdevChildren<'a>,
+
+    pub create_txg: Option<u64>,
+}
+
+impl VdevRoot<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[VDEV_CONFIG_CHILDREN, VDEV_CONFIG_CREATE_TXG];
 
+    /** Decodes a [`VdevRoot`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevRoot<'a>, VdevDecodeError> {
         ////////////////////////////////
-        // Use macros for cleaner code.
-        macro_rules! find_string {
-            ($name:expr) => {
-                nv::find_nested!(decoder, nested_decoder, $name, String, VdevDecodeError)?
-            };
-        }
+        // Decode vdev.
+        Ok(VdevRoot {
+            children: VdevChildren::from_decoder(decoder)?,
 
-        macro_rules! find_u64 {
-            ($name:expr) => {
-                nv::find_nested!(decoder, nested_decoder, $name, Uint64, VdevDecodeError)?
-            };
-        }
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+        })
+    }
+}
 
-        macro_rules! find_u64_bool {
-            ($name:expr) => {
-                match find_u64!($name) {
-                    0 => false,
-                    1 => true,
-                    n => {
-                        return Err(VdevDecodeError::InvalidU64Bool {
-                            name: $name,
-                            value: n,
-                        })
-                    }
-                }
-            };
-        }
+////////////////////////////////////////////////////////////////////////////////
 
-        macro_rules! find_option_string {
-            ($name:expr) => {
-                nv::find_option_nested!(decoder, nested_decoder, $name, String, VdevDecodeError)?
-            };
-        }
+#[derive(Debug)]
+pub struct VdevSpare<'a> {
+    pub children: VdevChildren<'a>,
 
-        macro_rules! find_option_u64 {
-            ($name:expr) => {
-                nv::find_option_nested!(decoder, nested_decoder, $name, Uint64, VdevDecodeError)?
-            };
-        }
+    pub create_txg: Option<u64>,
+}
 
-        macro_rules! find_option_u64_bool {
-            ($name:expr) => {
-                match nv::find_option_nested!(
-                    decoder,
-                    nested_decoder,
-                    $name,
-                    Uint64,
-                    VdevDecodeError
-                )? {
-                    None => None,
-                    Some(n) => match n {
-                        0 => Some(false),
-                        1 => Some(true),
-                        _ => {
-                            return Err(VdevDecodeError::InvalidU64Bool {
-                                name: $name,
-                                value: n,
-                            })
-                        }
-                    },
-                }
-            };
-        }
+impl VdevSpare<'_> {
+    /// Names of the pairs in the vdev NV list, other than the common names.
+    pub const NAMES: &'static [&'static str] = &[VDEV_CONFIG_CHILDREN, VDEV_CONFIG_CREATE_TXG];
 
+    /** Decodes a [`VdevSpare`] from a vdev NV pair list.
+     *
+     * - The first child is the original vdev, and the other children are the
+     *   hot spares in use.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevSpare<'a>, VdevDecodeError> {
         ////////////////////////////////
         // Decode vdev.
-        let disk = VdevDisk {
-            path: find_string!(VDEV_CONFIG_PATH),
-            whole_disk: find_u64_bool!(VDEV_CONFIG_WHOLE_DISK),
-
-            a_meta_slab: VdevAlignmentMetaSlab::from_decoder(decoder, nested_decoder)?,
-            create_txg: find_option_u64!(VDEV_CONFIG_CREATE_TXG),
-            dev_id: find_option_string!(VDEV_CONFIG_DEV_ID),
-            is_log: find_option_u64_bool!(VDEV_CONFIG_IS_LOG),
-            phys_path: find_option_string!(VDEV_CONFIG_PHYS_PATH),
-        };
+        Ok(VdevSpare {
+            children: VdevChildren::from_decoder(decoder)?,
 
-        Ok(disk)
+            create_txg: find_option_u64!(decoder, VDEV_CONFIG_CREATE_TXG),
+        })
     }
 }
 
@@ -261,12 +1080,296 @@ impl VdevDisk<'_> {
 #[derive(Debug)]
 pub enum Vdev<'a> {
     Disk(VdevDisk<'a>),
-    File(),
-    Mirror(),
+    Draid(VdevDraid<'a>),
+    DraidSpare(VdevDraidSpare<'a>),
+    File(VdevFile<'a>),
+    Hole(),
+    Indirect(VdevIndirect),
+    L2Cache(VdevL2Cache<'a>),
+    Mirror(VdevMirror<'a>),
     Missing(),
-    RaidZ(),
-    Replacing(),
-    Root(),
+    RaidZ(VdevRaidZ<'a>),
+    Replacing(VdevReplacing<'a>),
+    Root(VdevRoot<'a>),
+    Spare(VdevSpare<'a>),
+}
+
+impl<'a> Vdev<'a> {
+    /// Returns the children of an interior vdev, or [`None`] for a leaf vdev.
+    pub fn children(&self) -> Option<&VdevChildren<'a>> {
+        match self {
+            Vdev::Draid(v) => Some(&v.children),
+            Vdev::L2Cache(v) => Some(&v.children),
+            Vdev::Mirror(v) => Some(&v.children),
+            Vdev::RaidZ(v) => Some(&v.children),
+            Vdev::Replacing(v) => Some(&v.children),
+            Vdev::Root(v) => Some(&v.children),
+            Vdev::Spare(v) => Some(&v.children),
+            Vdev::Disk(_)
+            | Vdev::DraidSpare(_)
+            | Vdev::File(_)
+            | Vdev::Hole()
+            | Vdev::Indirect(_)
+            | Vdev::Missing() => None,
+        }
+    }
+
+    /** Returns the alignment and metaslab values of a top level vdev.
+     *
+     * - Returns [`None`] if the vdev is not a top level vdev, or does not have
+     *   them.
+     */
+    pub fn a_meta_slab(&self) -> Option<&VdevAlignmentMetaSlab> {
+        match self {
+            Vdev::Disk(v) => v.a_meta_slab.as_ref(),
+            Vdev::Draid(v) => v.a_meta_slab.as_ref(),
+            Vdev::File(v) => v.a_meta_slab.as_ref(),
+            Vdev::Indirect(v) => v.a_meta_slab.as_ref(),
+            Vdev::Mirror(v) => v.a_meta_slab.as_ref(),
+            Vdev::RaidZ(v) => v.a_meta_slab.as_ref(),
+            Vdev::Replacing(v) => v.a_meta_slab.as_ref(),
+            Vdev::DraidSpare(_)
+            | Vdev::Hole()
+            | Vdev::L2Cache(_)
+            | Vdev::Missing()
+            | Vdev::Root(_)
+            | Vdev::Spare(_) => None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Vdev state.
+ *
+ * - C reference: `enum vdev_state vdev_state_t`
+ */
+#[derive(Clone, Copy, Debug, FromPrimitive, PartialEq, strum::Display)]
+pub enum VdevState {
+    Unknown = 0,
+    Closed = 1,
+    Offline = 2,
+    Removed = 3,
+    CantOpen = 4,
+    Faulted = 5,
+    Degraded = 6,
+    Healthy = 7,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Vdev statistics, from the `vdev_stats` array.
+ *
+ * - C reference: `struct vdev_stat vdev_stat_t`
+ * - Only the leading values, which are the same in all versions, are decoded.
+ */
+#[derive(Debug)]
+pub struct VdevStats {
+    pub timestamp: u64,
+    pub state: VdevState,
+    pub aux: u64,
+    pub alloc: u64,
+    pub space: u64,
+    pub dspace: u64,
+    pub rsize: u64,
+    pub esize: u64,
+    pub ops: [u64; VdevStats::ZIO_TYPES],
+    pub bytes: [u64; VdevStats::ZIO_TYPES],
+    pub read_errors: u64,
+    pub write_errors: u64,
+    pub checksum_errors: u64,
+}
+
+impl VdevStats {
+    /// Number of I/O types in [`VdevStats::ops`] and [`VdevStats::bytes`].
+    pub const ZIO_TYPES: usize = 6;
+
+    /// Minimum number of values in the `vdev_stats` array.
+    pub const MIN_LENGTH: usize = 11 + 2 * VdevStats::ZIO_TYPES;
+
+    /** Decodes [`VdevStats`] from a `vdev_stats` array.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_array(array: &nv::ArrayDecoder<'_, u64>) -> Result<VdevStats, VdevDecodeError> {
+        // Check length.
+        let length = array.capacity();
+        if length < VdevStats::MIN_LENGTH {
+            return Err(VdevDecodeError::InvalidStatsLength { length: length });
+        }
+
+        array.reset();
+
+        let timestamp = array.get()?;
+        let state = array.get()?;
+        let state = match num::FromPrimitive::from_u64(state) {
+            Some(v) => v,
+            None => return Err(VdevDecodeError::InvalidState { state: state }),
+        };
+
+        Ok(VdevStats {
+            timestamp: timestamp,
+            state: state,
+            aux: array.get()?,
+            alloc: array.get()?,
+            space: array.get()?,
+            dspace: array.get()?,
+            rsize: array.get()?,
+            esize: array.get()?,
+            ops: [
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+            ],
+            bytes: [
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+                array.get()?,
+            ],
+            read_errors: array.get()?,
+            write_errors: array.get()?,
+            checksum_errors: array.get()?,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Vdev health, from the state names of a vdev NV pair list.
+ *
+ * - Boolean names are only present when set, so are `false` when missing.
+ */
+#[derive(Debug)]
+pub struct VdevHealth<'a> {
+    pub aux_state: Option<&'a str>,
+    pub degraded: bool,
+    pub dtl: Option<u64>,
+    pub faulted: bool,
+    pub not_present: bool,
+    pub offline: bool,
+    pub removed: bool,
+    pub resilver_txg: Option<u64>,
+    pub stats: Option<VdevStats>,
+}
+
+impl VdevHealth<'_> {
+    /** Decodes a [`VdevHealth`] from a vdev NV pair list.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     */
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevHealth<'a>, VdevDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! find_flag {
+            ($name:expr) => {
+                find_option_u64_bool!(decoder, $name).unwrap_or(false)
+            };
+        }
+
+        Ok(VdevHealth {
+            aux_state: find_option_string!(decoder, VDEV_CONFIG_AUX_STATE),
+            degraded: find_flag!(VDEV_CONFIG_DEGRADED),
+            dtl: find_option_u64!(decoder, VDEV_CONFIG_DTL),
+            faulted: find_flag!(VDEV_CONFIG_FAULTED),
+            not_present: find_flag!(VDEV_CONFIG_NOT_PRESENT),
+            offline: find_flag!(VDEV_CONFIG_OFFLINE),
+            removed: find_flag!(VDEV_CONFIG_REMOVED),
+            resilver_txg: find_option_u64!(decoder, VDEV_CONFIG_RESILVER_TXG),
+            stats: match nv::find_option!(
+                decoder,
+                VDEV_CONFIG_VDEV_STATS,
+                Uint64Array,
+                VdevDecodeError
+            )? {
+                Some(array) => Some(VdevStats::from_array(&array)?),
+                None => None,
+            },
+        })
+    }
+
+    /** Returns the [`VdevState`] of the vdev.
+     *
+     * - The state in [`VdevHealth::stats`] is used if present.
+     * - Otherwise, the state is derived from the flags, in the same order of
+     *   precedence as when a pool is imported.
+     * - Returns [`None`] if there is no state, which is the case for a vdev
+     *   that has not had any errors.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{VdevState, VdevTree};
+     *
+     * let text = r#"{
+     *     "type": { "string": "mirror" },
+     *     "id": { "uint64": 0 },
+     *     "guid": { "uint64": 1 },
+     *     "DTL": { "uint64": 130 },
+     *     "resilver_txg": { "uint64": 1200 },
+     *     "children": { "nvlist_array": [
+     *         { "type": { "string": "file" }, "id": { "uint64": 0 },
+     *           "guid": { "uint64": 2 }, "path": { "string": "/a" },
+     *           "faulted": { "uint64": 1 }, "aux_state": { "string": "external" } },
+     *         { "type": { "string": "file" }, "id": { "uint64": 1 },
+     *           "guid": { "uint64": 3 }, "path": { "string": "/b" },
+     *           "vdev_stats": { "uint64_array": [
+     *               0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4
+     *           ] } }
+     *     ] }
+     * }"#;
+     *
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let mirror = VdevTree::from_list(&decoder).unwrap();
+     *
+     * assert_eq!(mirror.health.dtl, Some(130));
+     * assert_eq!(mirror.health.resilver_txg, Some(1200));
+     * assert_eq!(mirror.health.state(), None);
+     *
+     * let children = mirror.vdev.children().unwrap();
+     *
+     * let faulted = children.get(0).unwrap();
+     * assert_eq!(faulted.health.state(), Some(VdevState::Faulted));
+     * assert_eq!(faulted.health.aux_state, Some("external"));
+     *
+     * let healthy = children.get(1).unwrap();
+     * assert_eq!(healthy.health.state(), Some(VdevState::Healthy));
+     * assert_eq!(healthy.health.stats.unwrap().checksum_errors, 4);
+     * ```
+     */
+    pub fn state(&self) -> Option<VdevState> {
+        if let Some(stats) = &self.stats {
+            return Some(stats.state);
+        }
+
+        if self.offline {
+            Some(VdevState::Offline)
+        } else if self.removed {
+            Some(VdevState::Removed)
+        } else if self.faulted {
+            Some(VdevState::Faulted)
+        } else if self.not_present {
+            Some(VdevState::CantOpen)
+        } else if self.degraded {
+            Some(VdevState::Degraded)
+        } else {
+            None
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -275,41 +1378,275 @@ pub enum Vdev<'a> {
 pub struct VdevTree<'a> {
     pub id: u64,
     pub guid: u64,
+    pub health: VdevHealth<'a>,
     pub vdev: Vdev<'a>,
+
+    /// Names in the vdev NV list that are not known, which are ignored.
+    pub unknown_names: VdevUnknownNames<'a>,
 }
 
 impl VdevTree<'_> {
-    /** Decodes a [`VdevTree`] NV pair list.
+    /** Decodes the [`VdevTree`] of a pool configuration NV pair list.
+     *
+     * - Decodes the `vdev_tree` NV list, and all of its children.
      *
      * # Errors
      *
      * Returns [`VdevDecodeError`] in case of an error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Vdev, VdevTree};
+     *
+     * let text = r#"{
+     *     "vdev_tree": { "nvlist": {
+     *         "type": { "string": "root" },
+     *         "id": { "uint64": 0 },
+     *         "guid": { "uint64": 100 },
+     *         "children": { "nvlist_array": [
+     *             {
+     *                 "type": { "string": "mirror" },
+     *                 "id": { "uint64": 0 },
+     *                 "guid": { "uint64": 200 },
+     *                 "ashift": { "uint64": 12 },
+     *                 "asize": { "uint64": 1073741824 },
+     *                 "metaslab_array": { "uint64": 256 },
+     *                 "metaslab_shift": { "uint64": 29 },
+     *                 "is_log": { "uint64": 0 },
+     *                 "children": { "nvlist_array": [
+     *                     {
+     *                         "type": { "string": "disk" },
+     *                         "id": { "uint64": 0 },
+     *                         "guid": { "uint64": 201 },
+     *                         "path": { "string": "/dev/sda1" },
+     *                         "whole_disk": { "uint64": 1 }
+     *                     },
+     *                     {
+     *                         "type": { "string": "disk" },
+     *                         "id": { "uint64": 1 },
+     *                         "guid": { "uint64": 202 },
+     *                         "path": { "string": "/dev/sdb1" },
+     *                         "whole_disk": { "uint64": 1 }
+     *                     }
+     *                 ] }
+     *             },
+     *             {
+     *                 "type": { "string": "raidz" },
+     *                 "id": { "uint64": 1 },
+     *                 "guid": { "uint64": 300 },
+     *                 "nparity": { "uint64": 1 },
+     *                 "children": { "nvlist_array": [
+     *                     { "type": { "string": "file" }, "id": { "uint64": 0 },
+     *                       "guid": { "uint64": 301 }, "path": { "string": "/a" } },
+     *                     { "type": { "string": "missing" }, "id": { "uint64": 1 },
+     *                       "guid": { "uint64": 302 } },
+     *                     { "type": { "string": "file" }, "id": { "uint64": 2 },
+     *                       "guid": { "uint64": 303 }, "path": { "string": "/c" } }
+     *                 ] }
+     *             }
+     *         ] }
+     *     } }
+     * }"#;
+     *
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Big, Unique::Name).unwrap();
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+     *
+     * let root = vdev_tree.vdev.children().unwrap();
+     * assert_eq!(root.len(), 2);
+     *
+     * let mirror = root.get(0).unwrap();
+     * assert_eq!(mirror.guid, 200);
+     * match &mirror.vdev {
+     *     Vdev::Mirror(m) => {
+     *         assert_eq!(m.a_meta_slab.as_ref().unwrap().a_shift, 12);
+     *         let paths: Vec<&str> = m.children.iter().map(|c| match c.unwrap().vdev {
+     *             Vdev::Disk(d) => d.path,
+     *             _ => panic!("expected a disk"),
+     *         }).collect();
+     *         assert_eq!(paths, ["/dev/sda1", "/dev/sdb1"]);
+     *     }
+     *     _ => panic!("expected a mirror"),
+     * }
+     *
+     * match root.get(1).unwrap().vdev {
+     *     Vdev::RaidZ(r) => {
+     *         assert_eq!(r.n_parity, 1);
+     *         assert!(matches!(r.children.get(1).unwrap().vdev, Vdev::Missing()));
+     *     }
+     *     _ => panic!("expected a raidz"),
+     * }
+     * ```
      */
-    pub fn from_decoder<'a>(decoder: &'a nv::Decoder<'a>) -> Result<VdevTree<'a>, VdevDecodeError> {
-        // TODO(cybojanek): Can find_nested be deprecated while fixing lifetimes?
-
+    pub fn from_decoder<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevTree<'a>, VdevDecodeError> {
         // Find vdev tree.
         let nested_decoder =
-            &nv::find!(decoder, POOL_CONFIG_KEY_VDEV_TREE, NvList, VdevDecodeError)?;
+            nv::find!(decoder, POOL_CONFIG_KEY_VDEV_TREE, NvList, VdevDecodeError)?;
 
+        // Decode vdev tree.
+        VdevTree::from_list(nested_decoder.get_decoder())
+    }
+
+    /** Decodes a [`VdevTree`] from a vdev NV pair list.
+     *
+     * - For example, the `vdev_tree` NV list of a pool configuration, or an
+     *   element of a `children` NV list array.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] in case of an error.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::Endian;
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Vdev, VdevTree};
+     *
+     * let text = r#"{
+     *     "type": { "string": "root" },
+     *     "id": { "uint64": 0 },
+     *     "guid": { "uint64": 100 },
+     *     "children": { "nvlist_array": [
+     *         {
+     *             "type": { "string": "draid" },
+     *             "id": { "uint64": 0 },
+     *             "guid": { "uint64": 200 },
+     *             "nparity": { "uint64": 1 },
+     *             "draid_ndata": { "uint64": 2 },
+     *             "draid_nspares": { "uint64": 1 },
+     *             "draid_ngroups": { "uint64": 1 },
+     *             "children": { "nvlist_array": [
+     *                 {
+     *                     "type": { "string": "spare" },
+     *                     "id": { "uint64": 0 },
+     *                     "guid": { "uint64": 210 },
+     *                     "children": { "nvlist_array": [
+     *                         { "type": { "string": "file" }, "id": { "uint64": 0 },
+     *                           "guid": { "uint64": 211 }, "path": { "string": "/a" } },
+     *                         { "type": { "string": "file" }, "id": { "uint64": 1 },
+     *                           "guid": { "uint64": 212 }, "path": { "string": "/s" } }
+     *                     ] }
+     *                 },
+     *                 { "type": { "string": "file" }, "id": { "uint64": 1 },
+     *                   "guid": { "uint64": 220 }, "path": { "string": "/b" } },
+     *                 { "type": { "string": "file" }, "id": { "uint64": 2 },
+     *                   "guid": { "uint64": 230 }, "path": { "string": "/c" } },
+     *                 { "type": { "string": "file" }, "id": { "uint64": 3 },
+     *                   "guid": { "uint64": 240 }, "path": { "string": "/d" } }
+     *             ] }
+     *         },
+     *         {
+     *             "type": { "string": "indirect" },
+     *             "id": { "uint64": 1 },
+     *             "guid": { "uint64": 300 },
+     *             "com.delphix:indirect_object": { "uint64": 70 },
+     *             "com.delphix:indirect_births": { "uint64": 71 }
+     *         },
+     *         {
+     *             "type": { "string": "hole" },
+     *             "id": { "uint64": 2 },
+     *             "guid": { "uint64": 0 },
+     *             "is_hole": { "uint64": 1 }
+     *         }
+     *     ] }
+     * }"#;
+     *
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let root = VdevTree::from_list(&decoder).unwrap();
+     * let children = root.vdev.children().unwrap();
+     *
+     * match children.get(0).unwrap().vdev {
+     *     Vdev::Draid(draid) => {
+     *         assert_eq!((draid.n_data, draid.n_parity, draid.n_spares), (2, 1, 1));
+     *         assert!(matches!(draid.children.get(0).unwrap().vdev, Vdev::Spare(_)));
+     *     }
+     *     _ => panic!("expected a draid"),
+     * }
+     *
+     * match children.get(1).unwrap().vdev {
+     *     Vdev::Indirect(indirect) => assert_eq!(indirect.indirect_object, Some(70)),
+     *     _ => panic!("expected an indirect"),
+     * }
+     *
+     * assert!(matches!(children.get(2).unwrap().vdev, Vdev::Hole()));
+     * ```
+     */
+    pub fn from_list<'a>(decoder: &nv::Decoder<'a>) -> Result<VdevTree<'a>, VdevDecodeError> {
         // Find vdev type.
-        let vdev_type = nv::find_nested!(
-            decoder,
-            nested_decoder,
-            VDEV_CONFIG_TYPE,
-            String,
-            VdevDecodeError
-        )?;
+        let vdev_type = find_string!(decoder, VDEV_CONFIG_TYPE);
 
-        // Decode vdev.
-        let vdev = match vdev_type {
-            VDEV_TYPE_DISK => Vdev::Disk(VdevDisk::from_decoder(decoder, nested_decoder)?),
-            VDEV_TYPE_FILE => todo!("Implement"),
-            VDEV_TYPE_MIRROR => todo!("Implement"),
-            VDEV_TYPE_MISSING => todo!("Implement"),
-            VDEV_TYPE_RAIDZ => todo!("Implement"),
-            VDEV_TYPE_REPLACING => todo!("Implement"),
-            VDEV_TYPE_ROOT => todo!("Implement"),
+        // Decode vdev, and the names known for its type.
+        let (vdev, known): (Vdev<'a>, &[&str]) = match vdev_type {
+            VDEV_TYPE_DISK => (
+                Vdev::Disk(VdevDisk::from_decoder(decoder)?),
+                VdevDisk::NAMES,
+            ),
+            VDEV_TYPE_DRAID => (
+                Vdev::Draid(VdevDraid::from_decoder(decoder)?),
+                VdevDraid::NAMES,
+            ),
+            VDEV_TYPE_DRAID_SPARE => (
+                Vdev::DraidSpare(VdevDraidSpare::from_decoder(decoder)?),
+                VdevDraidSpare::NAMES,
+            ),
+            VDEV_TYPE_FILE => (
+                Vdev::File(VdevFile::from_decoder(decoder)?),
+                VdevFile::NAMES,
+            ),
+            VDEV_TYPE_HOLE => {
+                // A hole is a placeholder for a removed log vdev, and keeps
+                // the size and metaslab values of the removed vdev.
+                let names: &[&str] = &[
+                    VDEV_CONFIG_A_SHIFT,
+                    VDEV_CONFIG_A_SIZE,
+                    VDEV_CONFIG_CREATE_TXG,
+                    VDEV_CONFIG_IS_HOLE,
+                    VDEV_CONFIG_IS_LOG,
+                    VDEV_CONFIG_META_SLAB_ARRAY,
+                    VDEV_CONFIG_META_SLAB_SHIFT,
+                ];
+                (Vdev::Hole(), names)
+            }
+            VDEV_TYPE_INDIRECT => (
+                Vdev::Indirect(VdevIndirect::from_decoder(decoder)?),
+                VdevIndirect::NAMES,
+            ),
+            VDEV_TYPE_L2_CACHE => (
+                Vdev::L2Cache(VdevL2Cache::from_decoder(decoder)?),
+                VdevL2Cache::NAMES,
+            ),
+            VDEV_TYPE_MIRROR => (
+                Vdev::Mirror(VdevMirror::from_decoder(decoder)?),
+                VdevMirror::NAMES,
+            ),
+            VDEV_TYPE_MISSING => (Vdev::Missing(), &[]),
+            VDEV_TYPE_RAIDZ => (
+                Vdev::RaidZ(VdevRaidZ::from_decoder(decoder)?),
+                VdevRaidZ::NAMES,
+            ),
+            VDEV_TYPE_REPLACING => (
+                Vdev::Replacing(VdevReplacing::from_decoder(decoder)?),
+                VdevReplacing::NAMES,
+            ),
+            VDEV_TYPE_ROOT => (
+                Vdev::Root(VdevRoot::from_decoder(decoder)?),
+                VdevRoot::NAMES,
+            ),
+            VDEV_TYPE_SPARE => (
+                Vdev::Spare(VdevSpare::from_decoder(decoder)?),
+                VdevSpare::NAMES,
+            ),
             _ => {
                 return Err(VdevDecodeError::UnknownType {
                     vdev_type: vdev_type.into(),
@@ -318,32 +1655,72 @@ impl VdevTree<'_> {
             }
         };
 
-        ////////////////////////////////
-        // Use macros for cleaner code.
-        macro_rules! find_u64 {
-            ($name:expr) => {
-                nv::find_nested!(decoder, nested_decoder, $name, Uint64, VdevDecodeError)?
-            };
-        }
-
         Ok(VdevTree {
-            id: find_u64!(VDEV_CONFIG_ID),
-            guid: find_u64!(VDEV_CONFIG_GUID),
+            id: find_u64!(decoder, VDEV_CONFIG_ID),
+            guid: find_u64!(decoder, VDEV_CONFIG_GUID),
+            health: VdevHealth::from_decoder(decoder)?,
+            unknown_names: VdevUnknownNames::from_decoder(decoder, known)?,
             vdev: vdev,
         })
     }
+
+    /** Returns the sum of the GUIDs of this vdev and all of its children.
+     *
+     * - C reference: `vdev_guid_sum`
+     * - Spares and level 2 caches are not part of the tree, and are not summed.
+     *
+     * # Errors
+     *
+     * Returns [`VdevDecodeError`] if a child cannot be decoded.
+     */
+    pub fn guid_sum(&self) -> Result<u64, VdevDecodeError> {
+        let mut sum = self.guid;
+
+        if let Some(children) = self.vdev.children() {
+            for child in children.iter() {
+                sum = sum.wrapping_add(child?.guid_sum()?);
+            }
+        }
+
+        Ok(sum)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug)]
 pub enum VdevDecodeError {
+    /** Invalid child index.
+     *
+     * - `index` - Index of child.
+     * - `count` - Number of children.
+     */
+    InvalidChildIndex { index: usize, count: usize },
+
     /** Invalid configuration.
      *
      * - `reason` - Reason for invalid configuration.
      */
     InvalidConfiguration { reason: &'static str },
 
+    /** Invalid raidz or draid parity.
+     *
+     * - `n_parity` - Number of parity columns.
+     */
+    InvalidParity { n_parity: u64 },
+
+    /** Invalid vdev state in `vdev_stats`.
+     *
+     * - `state` - State.
+     */
+    InvalidState { state: u64 },
+
+    /** Invalid `vdev_stats` array length.
+     *
+     * - `length` - Length of array.
+     */
+    InvalidStatsLength { length: usize },
+
     /** Invalid 64 bit unsigned integer boolean value.
      *
      * - `name`  - Of NV pair.
@@ -363,13 +1740,6 @@ pub enum VdevDecodeError {
      */
     NvDecodeError { err: nv::DecodeError },
 
-    /** Unknown name error.
-     *
-     * - `name`        - Truncated string of unknown name.
-     * - `full_length` - The full length of the unknown name.
-     */
-    UnknownName { name: str16, full_length: usize },
-
     /** Unknown type error.
      *
      * - `vdev_type`   - Truncated string of unknown vdev_type.
@@ -400,9 +1770,28 @@ impl From<nv::DecodeError> for VdevDecodeError {
 impl fmt::Display for VdevDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            VdevDecodeError::InvalidChildIndex { index, count } => {
+                write!(
+                    f,
+                    "Vdev decode error: invalid child index {index}, count is {count}"
+                )
+            }
             VdevDecodeError::InvalidConfiguration { reason } => {
                 write!(f, "Vdev decode error: invalid configuration: {reason}")
             }
+            VdevDecodeError::InvalidParity { n_parity } => {
+                write!(f, "Vdev decode error: invalid parity {n_parity}")
+            }
+            VdevDecodeError::InvalidState { state } => {
+                write!(f, "Vdev decode error: invalid state {state}")
+            }
+            VdevDecodeError::InvalidStatsLength { length } => {
+                write!(
+                    f,
+                    "Vdev decode error: invalid vdev_stats length {length}, minimum is {}",
+                    VdevStats::MIN_LENGTH
+                )
+            }
             VdevDecodeError::InvalidU64Bool { name, value } => {
                 write!(
                     f,
@@ -415,16 +1804,6 @@ impl fmt::Display for VdevDecodeError {
             VdevDecodeError::NvDecodeError { err } => {
                 write!(f, "Vdev Nv decode error: {err}")
             }
-            VdevDecodeError::UnknownName { name, full_length } => {
-                if *full_length > name.len() {
-                    write!(
-                        f,
-                        "Vdev decode error: unknown name of length {full_length}: '{name}...'"
-                    )
-                } else {
-                    write!(f, "Vdev decode error: unknown name: '{name}'")
-                }
-            }
             VdevDecodeError::UnknownType {
                 vdev_type,
                 full_length,
@@ -0,0 +1,288 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::endian::{DecodeError, Decoder, Endian};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Micro ZAP.
+ *
+ * - Bytes: a block, a multiple of 64.
+ * - C reference: `typedef struct mzap_phys mzap_phys_t`
+ * - A ZAP object of one block, whose entries each have a [`u64`] value, and
+ *   a name shorter than [`MicroZap::NAME_LENGTH`], such as the feature
+ *   objects of the MOS.
+ * - Larger ZAP objects are fat ZAPs, which are not supported.
+ *
+ * ```text
+ * +------------+----+
+ * | block type |  8 |
+ * +------------+----+
+ * |       salt |  8 |
+ * +------------+----+
+ * | norm flags |  8 |
+ * +------------+----+
+ * |    padding | 40 |
+ * +------------+----+
+ * |    entries | 64 | (repeated)
+ * +------------+----+
+ * ```
+ *
+ * Each entry has:
+ *
+ * ```text
+ * +-----------+----+
+ * |     value |  8 |
+ * +-----------+----+
+ * |        cd |  4 |
+ * +-----------+----+
+ * |   padding |  2 |
+ * +-----------+----+
+ * |      name | 50 |
+ * +-----------+----+
+ * ```
+ *
+ * Unused entries have an empty name.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::{Encoder, Endian};
+ * use zfs::phys::MicroZap;
+ *
+ * // A micro ZAP with one entry, and one unused entry.
+ * let mut bytes = [0u8; 3 * MicroZap::ENTRY_LENGTH];
+ * let mut encoder = Encoder::to_bytes(&mut bytes, Endian::Big);
+ * encoder.put_u64(MicroZap::BLOCK_TYPE).unwrap();
+ * encoder.put_u64(0x1234).unwrap();
+ * encoder.put_zero_padding(48).unwrap();
+ * encoder.put_u64(7).unwrap();
+ * encoder.put_u32(0).unwrap();
+ * encoder.put_u16(0).unwrap();
+ * encoder.put_bytes(b"org.illumos:lz4_compress").unwrap();
+ *
+ * let zap = MicroZap::from_bytes(&bytes).unwrap();
+ * assert!(matches!(zap.endian, Endian::Big));
+ * assert_eq!(zap.salt, 0x1234);
+ *
+ * let entries: Vec<(&str, u64)> = zap.iter().map(|e| (e.name, e.value)).collect();
+ * assert_eq!(entries, [("org.illumos:lz4_compress", 7)]);
+ * ```
+ */
+#[derive(Debug)]
+pub struct MicroZap<'a> {
+    /// Endian of the block.
+    pub endian: Endian,
+
+    /// Salt of the hash of the names.
+    pub salt: u64,
+
+    /// Normalization flags of the names.
+    pub norm_flags: u64,
+
+    /// Bytes of the entries.
+    entries: &'a [u8],
+}
+
+/** An entry of a [`MicroZap`].
+ *
+ * - C reference: `typedef struct mzap_ent_phys mzap_ent_phys_t`
+ */
+#[derive(Debug)]
+pub struct MicroZapEntry<'a> {
+    /// Value.
+    pub value: u64,
+
+    /// Collision differentiator, of entries with the same hash.
+    pub cd: u32,
+
+    /// Name.
+    pub name: &'a str,
+}
+
+impl<'a> MicroZap<'a> {
+    /// Block type of a [`MicroZap`] (`ZBT_MICRO`).
+    pub const BLOCK_TYPE: u64 = (1 << 63) + 3;
+
+    /// Block type of a fat ZAP header (`ZBT_HEADER`).
+    pub const BLOCK_TYPE_FAT: u64 = (1 << 63) + 1;
+
+    /// Byte length of an entry, and of the header.
+    pub const ENTRY_LENGTH: usize = 64;
+
+    /// Maximum byte length of a name, including the terminating zero
+    /// (`MZAP_NAME_LEN`).
+    pub const NAME_LENGTH: usize = 50;
+
+    /// Byte length of the padding of the header.
+    const HEADER_PADDING_LENGTH: usize = 40;
+
+    /** Decodes a [`MicroZap`], and checks its entries.
+     *
+     * - The endian is the endian of the block type.
+     *
+     * # Errors
+     *
+     * Returns [`MicroZapDecodeError`] if the block is a fat ZAP, its length
+     * is not a multiple of [`MicroZap::ENTRY_LENGTH`], or an entry name is
+     * invalid.
+     */
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<MicroZap<'a>, MicroZapDecodeError> {
+        ////////////////////////////////
+        // Check length.
+        if bytes.is_empty() || !bytes.len().is_multiple_of(MicroZap::ENTRY_LENGTH) {
+            return Err(MicroZapDecodeError::InvalidLength {
+                length: bytes.len(),
+            });
+        }
+
+        ////////////////////////////////
+        // Decode block type.
+        let decoder = match Decoder::from_u64_magic(bytes, MicroZap::BLOCK_TYPE) {
+            Ok(v) => v,
+            Err(err) => {
+                let block_type = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+                if block_type == MicroZap::BLOCK_TYPE_FAT
+                    || block_type.swap_bytes() == MicroZap::BLOCK_TYPE_FAT
+                {
+                    return Err(MicroZapDecodeError::FatZap {});
+                }
+                return Err(err.into());
+            }
+        };
+
+        ////////////////////////////////
+        // Decode header.
+        let salt = decoder.get_u64()?;
+        let norm_flags = decoder.get_u64()?;
+        decoder.skip(MicroZap::HEADER_PADDING_LENGTH)?;
+
+        let zap = MicroZap {
+            endian: decoder.endian(),
+            salt: salt,
+            norm_flags: norm_flags,
+            entries: &bytes[MicroZap::ENTRY_LENGTH..],
+        };
+
+        ////////////////////////////////
+        // Check entries.
+        for index in 0..zap.capacity() {
+            zap.entry(index)?;
+        }
+
+        Ok(zap)
+    }
+
+    /// Returns the number of entries, used or unused.
+    pub fn capacity(&self) -> usize {
+        self.entries.len() / MicroZap::ENTRY_LENGTH
+    }
+
+    /** Decodes the entry at `index`, or returns [`None`] if it is unused.
+     */
+    fn entry(&self, index: usize) -> Result<Option<MicroZapEntry<'a>>, MicroZapDecodeError> {
+        let start = index * MicroZap::ENTRY_LENGTH;
+        let bytes: &'a [u8] = &self.entries[start..start + MicroZap::ENTRY_LENGTH];
+        let decoder = Decoder::from_bytes(bytes, self.endian);
+
+        let value = decoder.get_u64()?;
+        let cd = decoder.get_u32()?;
+        decoder.skip(2)?;
+        let name = &bytes[MicroZap::ENTRY_LENGTH - MicroZap::NAME_LENGTH..];
+
+        // Unused entry.
+        if name[0] == 0 {
+            return Ok(None);
+        }
+
+        // The name is terminated by a zero.
+        let name = match name.iter().position(|b| *b == 0) {
+            Some(length) => &name[0..length],
+            None => return Err(MicroZapDecodeError::InvalidName { index: index }),
+        };
+        let name = match core::str::from_utf8(name) {
+            Ok(v) => v,
+            Err(_) => return Err(MicroZapDecodeError::InvalidName { index: index }),
+        };
+
+        Ok(Some(MicroZapEntry {
+            value: value,
+            cd: cd,
+            name: name,
+        }))
+    }
+
+    /** Returns an iterator over the used entries.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = MicroZapEntry<'a>> + '_ {
+        // The entries were checked by [`MicroZap::from_bytes`].
+        (0..self.capacity()).filter_map(|index| self.entry(index).ok().flatten())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum MicroZapDecodeError {
+    /** Endian decode error.
+     *
+     * - `err` - [`DecodeError`]
+     */
+    EndianDecodeError { err: DecodeError },
+
+    /// Block is a fat ZAP, which is not supported.
+    FatZap {},
+
+    /** Invalid name of an entry, without a terminating zero, or not UTF-8.
+     *
+     * - `index` - Index of entry.
+     */
+    InvalidName { index: usize },
+
+    /** Invalid length of block.
+     *
+     * - `length` - Length.
+     */
+    InvalidLength { length: usize },
+}
+
+impl From<DecodeError> for MicroZapDecodeError {
+    fn from(value: DecodeError) -> Self {
+        MicroZapDecodeError::EndianDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for MicroZapDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MicroZapDecodeError::EndianDecodeError { err } => {
+                write!(f, "Micro ZAP Endian decode error: {err}")
+            }
+            MicroZapDecodeError::FatZap {} => {
+                write!(f, "Micro ZAP decode error: fat ZAP is not supported")
+            }
+            MicroZapDecodeError::InvalidName { index } => {
+                write!(f, "Micro ZAP decode error: invalid name of entry {index}")
+            }
+            MicroZapDecodeError::InvalidLength { length } => {
+                write!(f, "Micro ZAP decode error: invalid length {length}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for MicroZapDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MicroZapDecodeError::EndianDecodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
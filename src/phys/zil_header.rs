@@ -1,11 +1,11 @@
 use core::fmt;
 use core::result::Result;
-use core::result::Result::Ok;
+use core::result::Result::{Err, Ok};
 
 #[cfg(feature = "std")]
 use std::error;
 
-use crate::endian::{DecodeError, Decoder, EncodeError, Encoder};
+use crate::endian::{DecodeError, Decoder, EncodeError, Encoder, ErrorLocation};
 use crate::phys::{BlockPointer, BlockPointerDecodeError, BlockPointerEncodeError};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -54,16 +54,44 @@ impl ZilHeader {
      * Returns [`ZilHeaderDecodeError`] on error.
      */
     pub fn from_decoder(decoder: &Decoder) -> Result<ZilHeader, ZilHeaderDecodeError> {
+        // Use macros for cleaner code.
+        macro_rules! decode {
+            ($value:expr) => {
+                match $value {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(ZilHeaderDecodeError::EndianDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                }
+            };
+        }
+
         let zil_header = ZilHeader {
-            claim_txg: decoder.get_u64()?,
-            replay_seq: decoder.get_u64()?,
-            log: BlockPointer::from_decoder(decoder)?,
-            claim_blk_seq: decoder.get_u64()?,
-            flags: decoder.get_u64()?,
-            claim_lr_seq: decoder.get_u64()?,
+            claim_txg: decode!(decoder.get_u64()),
+            replay_seq: decode!(decoder.get_u64()),
+            log: {
+                decoder.push_field("log");
+                let log = match BlockPointer::from_decoder(decoder) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return Err(ZilHeaderDecodeError::BlockPointerDecodeError {
+                            err: err,
+                            location: decoder.location().into(),
+                        })
+                    }
+                };
+                decoder.pop_field();
+                log
+            },
+            claim_blk_seq: decode!(decoder.get_u64()),
+            flags: decode!(decoder.get_u64()),
+            claim_lr_seq: decode!(decoder.get_u64()),
         };
 
-        decoder.skip_zero_padding(24)?;
+        decode!(decoder.skip_zero_padding(24));
 
         Ok(zil_header)
     }
@@ -93,37 +121,36 @@ impl ZilHeader {
 pub enum ZilHeaderDecodeError {
     /** [`BlockPointer`] decode error.
      *
-     * - `err` - [`BlockPointerDecodeError`]
+     * - `err`      - [`BlockPointerDecodeError`]
+     * - `location` - Location of the last value decoded in the [`BlockPointer`].
      */
-    BlockPointerDecodeError { err: BlockPointerDecodeError },
+    BlockPointerDecodeError {
+        err: BlockPointerDecodeError,
+        location: ErrorLocation,
+    },
 
     /** Endian decode error.
      *
-     * - `err` - [`DecodeError`]
+     * - `err`      - [`DecodeError`]
+     * - `location` - Location of the value.
      */
-    EndianDecodeError { err: DecodeError },
-}
-
-impl From<BlockPointerDecodeError> for ZilHeaderDecodeError {
-    fn from(value: BlockPointerDecodeError) -> Self {
-        ZilHeaderDecodeError::BlockPointerDecodeError { err: value }
-    }
-}
-
-impl From<DecodeError> for ZilHeaderDecodeError {
-    fn from(value: DecodeError) -> Self {
-        ZilHeaderDecodeError::EndianDecodeError { err: value }
-    }
+    EndianDecodeError {
+        err: DecodeError,
+        location: ErrorLocation,
+    },
 }
 
 impl fmt::Display for ZilHeaderDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ZilHeaderDecodeError::BlockPointerDecodeError { err } => {
-                write!(f, "Zil Header Block Pointer decode error: {err}")
+            ZilHeaderDecodeError::BlockPointerDecodeError { err, location } => {
+                write!(
+                    f,
+                    "Zil Header Block Pointer decode error at {location}: {err}"
+                )
             }
-            ZilHeaderDecodeError::EndianDecodeError { err } => {
-                write!(f, "Zil Header Endian decode error: {err}")
+            ZilHeaderDecodeError::EndianDecodeError { err, location } => {
+                write!(f, "Zil Header Endian decode error at {location}: {err}")
             }
         }
     }
@@ -133,8 +160,8 @@ impl fmt::Display for ZilHeaderDecodeError {
 impl error::Error for ZilHeaderDecodeError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            ZilHeaderDecodeError::BlockPointerDecodeError { err } => Some(err),
-            ZilHeaderDecodeError::EndianDecodeError { err } => Some(err),
+            ZilHeaderDecodeError::BlockPointerDecodeError { err, location: _ } => Some(err),
+            ZilHeaderDecodeError::EndianDecodeError { err, location: _ } => Some(err),
         }
     }
 }
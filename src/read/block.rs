@@ -0,0 +1,347 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::checksum::block_verify;
+use crate::compression::block_decompress;
+use crate::endian::{Decoder, Endian};
+use crate::phys::{
+    BlockPointer, BlockPointerDecodeError, BlockPointerEmbedded, BlockPointerEmbeddedType,
+    BlockPointerRegular, ChecksumType, Dva, VdevTree,
+};
+use crate::read::{
+    read_draid, read_gang, read_indirect, read_mirror, read_raidz, DvaMap, LeafDevices, ReadError,
+    VdevMaps,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Reads the logical data of a block.
+ *
+ * - C reference: `zio_read`, `zio_decompress_data`
+ * - A hole reads as zeroes, and the data of an embedded block pointer is in
+ *   the block pointer.
+ * - Each DVA of a regular block pointer is tried in order, until one is read,
+ *   matches the checksum, and decompresses. Gang DVAs are read with
+ *   [`read_gang`].
+ * - `maps` are the maps of dRAID and indirect vdevs, as in
+ *   [`DvaMap::from_dva_maps`].
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if the block pointer is not supported, or all DVAs
+ * fail. [`ReadError::DvaErrors`] has the error of each DVA.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::checksum::block_checksum;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{
+ *     BlockPointer, BlockPointerRegular, ChecksumType, CompressionType, DmuType, Dva, VdevTree,
+ * };
+ * use zfs::read::{read_block, LeafDevices, LeafRead, ReadError, VdevMaps};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "file" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 201 },
+ *         "path": { "string": "/a" },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * let mut devices = LeafDevices::new();
+ * devices.insert(201, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ *
+ * // A block of 1024 zeroes, compressed with ZLE to five runs of 192 zeroes
+ * // and one of 64, in one sector, with copies at sectors 4, 8, and 12.
+ * let mut compressed = [0u8; 512];
+ * compressed[0..6].copy_from_slice(&[255, 255, 255, 255, 255, 127]);
+ * for sector in [4, 8, 12] {
+ *     let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + sector * 512, length: 512 };
+ *     devices.write(&read, &compressed).unwrap();
+ * }
+ *
+ * // Use closures for cleaner code.
+ * let dva = |offset: u64| Dva { vdev: 0, grid: 0, asize: 1, offset: offset, is_gang: false };
+ * let ptr = BlockPointer::Regular(BlockPointerRegular {
+ *     checksum_type: ChecksumType::Fletcher4,
+ *     checksum_value: block_checksum(&compressed, Endian::Little, ChecksumType::Fletcher4).unwrap(),
+ *     compression: CompressionType::Zle,
+ *     dedup: false,
+ *     dmu: DmuType::PlainFileContents,
+ *     dvas: [dva(4), dva(8), dva(12)],
+ *     endian: Endian::Little,
+ *     fill_count: 1,
+ *     level: 0,
+ *     logical_birth_txg: 42,
+ *     logical_size: 1,
+ *     physical_birth_txg: 0,
+ *     physical_size: 0,
+ * });
+ *
+ * // The first two copies are damaged.
+ * for sector in [4, 8] {
+ *     let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + sector * 512, length: 512 };
+ *     devices.write(&read, &[1u8; 512]).unwrap();
+ * }
+ * assert_eq!(read_block(&vdev_tree, &ptr, &devices, &VdevMaps::default()).unwrap(), [0u8; 1024]);
+ *
+ * // All copies are damaged.
+ * let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 12 * 512, length: 512 };
+ * devices.write(&read, &[1u8; 512]).unwrap();
+ * match read_block(&vdev_tree, &ptr, &devices, &VdevMaps::default()) {
+ *     Err(ReadError::DvaErrors { errors }) => {
+ *         assert_eq!(errors.iter().map(|(dva, _)| *dva).collect::<Vec<usize>>(), [0, 1, 2]);
+ *     }
+ *     _ => panic!("expected DVA errors"),
+ * }
+ * ```
+ */
+pub fn read_block<D: BlockDevice>(
+    vdev_tree: &VdevTree<'_>,
+    ptr: &BlockPointer,
+    devices: &LeafDevices<D>,
+    maps: &VdevMaps,
+) -> Result<Vec<u8>, ReadError> {
+    let ptr = match ptr {
+        BlockPointer::Embedded(ptr) => return read_embedded(ptr),
+        BlockPointer::Encrypted(_) => {
+            return Err(ReadError::UnsupportedBlockPointer { kind: "encrypted" })
+        }
+        BlockPointer::Regular(ptr) => ptr,
+    };
+
+    ////////////////////////////////
+    // A hole has no data.
+    if ptr.is_hole() {
+        return Ok(vec![0u8; ptr.logical_size_bytes() as usize]);
+    }
+
+    check_checksum_type(ptr)?;
+
+    ////////////////////////////////
+    // Try each DVA.
+    let mut errors = Vec::new();
+    for (index, dva) in ptr.dvas.iter().enumerate() {
+        if dva.asize == 0 {
+            continue;
+        }
+
+        match read_dva(vdev_tree, ptr, dva, devices, maps) {
+            Ok(v) => return Ok(v),
+            Err(err) => errors.push((index, err)),
+        }
+    }
+
+    Err(ReadError::DvaErrors { errors: errors })
+}
+
+/// Reads the logical data of a block from one DVA.
+fn read_dva<D: BlockDevice>(
+    vdev_tree: &VdevTree<'_>,
+    ptr: &BlockPointerRegular,
+    dva: &Dva,
+    devices: &LeafDevices<D>,
+    maps: &VdevMaps,
+) -> Result<Vec<u8>, ReadError> {
+    match dva.is_gang {
+        true => {
+            let physical = read_gang(vdev_tree, ptr, dva, devices, maps)?;
+            decompress(ptr, &physical)
+        }
+        false => {
+            let map = DvaMap::from_dva_maps(vdev_tree, dva, ptr.physical_size_bytes(), maps)?;
+            read_dva_map(ptr, &map, devices)
+        }
+    }
+}
+
+/** Reads the logical data of a block from the [`DvaMap`] of one of its DVAs,
+ * which is not a gang DVA.
+ *
+ * - The checksum type must be checked with [`check_checksum_type`].
+ */
+pub(crate) fn read_dva_map<D: BlockDevice>(
+    ptr: &BlockPointerRegular,
+    map: &DvaMap,
+    devices: &LeafDevices<D>,
+) -> Result<Vec<u8>, ReadError> {
+    ////////////////////////////////
+    // Read and verify the physical data.
+    let verify = |data: &[u8]| checksum_matches(ptr, data);
+    let mut physical = read_map(map, devices, &verify)?;
+    physical.truncate(ptr.physical_size_bytes() as usize);
+
+    decompress(ptr, &physical)
+}
+
+/// Decompresses the physical data of a block to its logical data.
+fn decompress(ptr: &BlockPointerRegular, physical: &[u8]) -> Result<Vec<u8>, ReadError> {
+    let mut data = vec![0u8; ptr.logical_size_bytes() as usize];
+    block_decompress(physical, &mut data, ptr.compression)?;
+
+    Ok(data)
+}
+
+/** Reads the logical data of an embedded block pointer.
+ *
+ * - C reference: `decode_embedded_bp_compressed`
+ * - The payload is stored in 64 bit words of the endian of the block
+ *   pointer, and the bytes are in order from the lowest byte of each word.
+ */
+fn read_embedded(ptr: &BlockPointerEmbedded) -> Result<Vec<u8>, ReadError> {
+    match ptr.embedded_type {
+        BlockPointerEmbeddedType::Data => (),
+        BlockPointerEmbeddedType::Redacted => {
+            return Err(ReadError::UnsupportedBlockPointer { kind: "redacted" })
+        }
+        BlockPointerEmbeddedType::Reserved => {
+            return Err(ReadError::UnsupportedBlockPointer { kind: "reserved" })
+        }
+    }
+
+    ////////////////////////////////
+    // Get the payload bytes.
+    let mut payload = ptr.payload;
+    if let Endian::Big = ptr.endian {
+        for word in payload.chunks_exact_mut(8) {
+            word.reverse();
+        }
+    }
+
+    let physical_size = ptr.physical_size as usize + 1;
+    if physical_size > payload.len() {
+        return Err(ReadError::InvalidLength {
+            length: physical_size,
+            expected: payload.len() as u64,
+        });
+    }
+
+    ////////////////////////////////
+    // Decompress.
+    let mut data = vec![0u8; ptr.logical_size as usize + 1];
+    block_decompress(&payload[..physical_size], &mut data, ptr.compression)?;
+
+    Ok(data)
+}
+
+/** Reads a block from a [`DvaMap`].
+ *
+ * - Uses the reader for the layout of the map, such as [`read_mirror`],
+ *   [`read_raidz`], or [`read_indirect`].
+ * - `verify` checks the data of the block, which is rounded up to the sector
+ *   size.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if the block cannot be read, or does not verify.
+ */
+pub fn read_map<D: BlockDevice>(
+    map: &DvaMap,
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<Vec<u8>, ReadError> {
+    match map {
+        DvaMap::Leaf(read) => {
+            let data = devices.read(read)?;
+            if !verify(&data) {
+                return Err(ReadError::ChecksumMismatch {});
+            }
+            Ok(data)
+        }
+        DvaMap::Missing { guid } => Err(ReadError::MissingDevice { guid: *guid }),
+        DvaMap::Mirror(copies) => Ok(read_mirror(copies, devices, verify)?.data),
+        DvaMap::RaidZ(raidz) => Ok(read_raidz(raidz, devices, verify)?.data),
+        DvaMap::Draid(rows) => Ok(read_draid(rows, devices, verify)?.data),
+        DvaMap::Indirect(segments) => read_indirect(segments, devices, verify),
+    }
+}
+
+/** Checks that the checksum type of a block pointer can be verified.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if the checksum type is not supported.
+ */
+pub(crate) fn check_checksum_type(ptr: &BlockPointerRegular) -> Result<(), ReadError> {
+    match ptr.checksum_type {
+        ChecksumType::Off
+        | ChecksumType::NoParity
+        | ChecksumType::Fletcher2
+        | ChecksumType::Fletcher4
+        | ChecksumType::Sha256
+        | ChecksumType::Sha512_256 => Ok(()),
+        checksum_type => Err(ReadError::UnsupportedChecksum {
+            checksum_type: checksum_type,
+        }),
+    }
+}
+
+/** Returns true if the physical data of a block matches the checksum of its
+ * block pointer.
+ *
+ * - `data` may be rounded up to the sector size, and is truncated to the
+ *   physical size.
+ * - The checksum type must be checked with [`check_checksum_type`].
+ */
+pub(crate) fn checksum_matches(ptr: &BlockPointerRegular, data: &[u8]) -> bool {
+    let length = ptr.physical_size_bytes() as usize;
+    if data.len() < length {
+        return false;
+    }
+
+    match ptr.checksum_type {
+        ChecksumType::Off | ChecksumType::NoParity => true,
+        checksum_type => block_verify(
+            &data[..length],
+            ptr.endian,
+            checksum_type,
+            &ptr.checksum_value,
+        )
+        .is_ok(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Decodes the block pointers of an indirect block.
+ *
+ * - C reference: `dbuf_read`, `dbuf_prefetch`
+ * - `data` is the logical data of a block pointer with a level greater than
+ *   zero, and `endian` is the endian of that block pointer.
+ *
+ * # Errors
+ *
+ * Returns [`BlockPointerDecodeError`] if a block pointer is invalid.
+ */
+pub fn indirect_block_pointers(
+    data: &[u8],
+    endian: Endian,
+) -> Result<Vec<BlockPointer>, BlockPointerDecodeError> {
+    let decoder = Decoder::from_bytes(data, endian);
+
+    let mut ptrs = Vec::with_capacity(data.len() / BlockPointer::LENGTH);
+    while decoder.len() >= BlockPointer::LENGTH {
+        ptrs.push(BlockPointer::from_decoder(&decoder)?);
+    }
+
+    Ok(ptrs)
+}
@@ -0,0 +1,372 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::block_device::BlockDevice;
+use crate::phys::{BlockPointer, VdevTree};
+use crate::read::{read_block, LeafDevices, ReadError, VdevMaps};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Key of a block in a [`BlockCache`].
+ *
+ * - C reference: `buf_hash`
+ * - A block is identified by its first DVA and its birth transaction group,
+ *   like the ARC. Block pointers with the same key, such as the copies of a
+ *   deduplicated block, have the same data.
+ */
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BlockKey {
+    /// Top level vdev ID of the first DVA.
+    pub vdev: u32,
+
+    /// Offset of the first DVA, in sectors.
+    pub offset: u64,
+
+    /// Physical birth transaction group, or the logical one if it is zero.
+    pub birth_txg: u64,
+}
+
+impl BlockKey {
+    /** Returns the key of a block pointer.
+     *
+     * - Returns [`None`] for holes, embedded block pointers, which are not
+     *   read from a vdev, and encrypted block pointers, which are not
+     *   supported by [`read_block`]. They are not cached.
+     */
+    pub fn from_ptr(ptr: &BlockPointer) -> Option<BlockKey> {
+        let ptr = match ptr {
+            BlockPointer::Regular(ptr) if !ptr.is_hole() => ptr,
+            _ => return None,
+        };
+
+        Some(BlockKey {
+            vdev: ptr.dvas[0].vdev,
+            offset: ptr.dvas[0].offset,
+            birth_txg: ptr.birth_txg(),
+        })
+    }
+}
+
+/** Statistics of a [`BlockCache`].
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockCacheStats {
+    /// Lookups that found the block.
+    pub hits: u64,
+
+    /// Lookups that did not find the block.
+    pub misses: u64,
+
+    /// Blocks inserted.
+    pub insertions: u64,
+
+    /// Blocks evicted to stay under the budget.
+    pub evictions: u64,
+
+    /// Blocks in the cache.
+    pub entries: usize,
+
+    /// Bytes of data in the cache.
+    pub bytes: usize,
+
+    /// Maximum bytes of data in the cache.
+    pub budget: usize,
+}
+
+/// A block in a [`BlockCache`].
+#[derive(Debug)]
+struct CacheEntry {
+    data: Arc<[u8]>,
+    tick: u64,
+}
+
+/// State of a [`BlockCache`], behind its lock.
+#[derive(Debug)]
+struct CacheState {
+    /// Blocks by key.
+    entries: BTreeMap<BlockKey, CacheEntry>,
+
+    /// Keys by the tick of their last use, least recently used first.
+    order: BTreeMap<u64, BlockKey>,
+
+    /// Tick of the next use.
+    tick: u64,
+
+    stats: BlockCacheStats,
+}
+
+impl CacheState {
+    /// Evicts least recently used blocks until the data fits in the budget.
+    fn evict(&mut self) {
+        while self.stats.bytes > self.stats.budget {
+            let key = match self.order.pop_first() {
+                Some((_, key)) => key,
+                None => break,
+            };
+
+            if let Some(entry) = self.entries.remove(&key) {
+                self.stats.bytes -= entry.data.len();
+                self.stats.entries -= 1;
+                self.stats.evictions += 1;
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Cache of the logical data of blocks, with a budget of bytes.
+ *
+ * - C reference: `arc_read`
+ * - Blocks are verified and decompressed before they are cached, so a hit
+ *   does not read any device.
+ * - When the data is over the budget, the least recently used blocks are
+ *   evicted. A block larger than the budget is not cached.
+ * - The cache is locked internally, so it can be shared between threads.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::checksum::block_checksum;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{
+ *     BlockPointer, BlockPointerRegular, ChecksumType, CompressionType, DmuType, Dva, VdevTree,
+ * };
+ * use zfs::read::{BlockCache, LeafDevices, LeafRead, VdevMaps};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "file" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 201 },
+ *         "path": { "string": "/a" },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * let mut devices = LeafDevices::new();
+ * devices.insert(201, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ *
+ * // Use closures for cleaner code.
+ * let empty = || Dva { vdev: 0, grid: 0, asize: 0, offset: 0, is_gang: false };
+ * let ptr = |sector: u64, data: &[u8]| BlockPointer::Regular(BlockPointerRegular {
+ *     checksum_type: ChecksumType::Fletcher4,
+ *     checksum_value: block_checksum(data, Endian::Little, ChecksumType::Fletcher4).unwrap(),
+ *     compression: CompressionType::Off,
+ *     dedup: false,
+ *     dmu: DmuType::PlainFileContents,
+ *     dvas: [Dva { vdev: 0, grid: 0, asize: 2, offset: sector, is_gang: false }, empty(), empty()],
+ *     endian: Endian::Little,
+ *     fill_count: 1,
+ *     level: 0,
+ *     logical_birth_txg: 42,
+ *     logical_size: 1,
+ *     physical_birth_txg: 0,
+ *     physical_size: 1,
+ * });
+ *
+ * // Three blocks of 1024 bytes, at sectors 0, 2, and 4.
+ * let mut ptrs = Vec::new();
+ * for sector in [0, 2, 4] {
+ *     let block = [sector as u8; 1024];
+ *     let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + sector * 512, length: 1024 };
+ *     devices.write(&read, &block).unwrap();
+ *     ptrs.push(ptr(sector, &block));
+ * }
+ *
+ * // A budget for two blocks.
+ * let maps = VdevMaps::default();
+ * let cache = BlockCache::new(2048);
+ * assert_eq!(*cache.read_block(&vdev_tree, &ptrs[0], &devices, &maps).unwrap(), [0u8; 1024]);
+ * assert_eq!(*cache.read_block(&vdev_tree, &ptrs[0], &devices, &maps).unwrap(), [0u8; 1024]);
+ * cache.read_block(&vdev_tree, &ptrs[1], &devices, &maps).unwrap();
+ *
+ * // The third block evicts the least recently used, which is the first.
+ * cache.read_block(&vdev_tree, &ptrs[2], &devices, &maps).unwrap();
+ *
+ * let stats = cache.stats();
+ * assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 1));
+ * assert_eq!((stats.entries, stats.bytes), (2, 2048));
+ * assert!(cache.get(&ptrs[0]).is_none());
+ * assert!(cache.get(&ptrs[1]).is_some());
+ * ```
+ */
+#[derive(Debug)]
+pub struct BlockCache {
+    state: Mutex<CacheState>,
+}
+
+impl BlockCache {
+    /// Creates an empty [`BlockCache`] with a budget of `budget` bytes.
+    pub fn new(budget: usize) -> BlockCache {
+        BlockCache {
+            state: Mutex::new(CacheState {
+                entries: BTreeMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+                stats: BlockCacheStats {
+                    budget: budget,
+                    ..Default::default()
+                },
+            }),
+        }
+    }
+
+    /// Locks the state, ignoring a panic of another thread that held it.
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        match self.state.lock() {
+            Ok(v) => v,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /** Returns the cached data of a block pointer.
+     *
+     * - Counts a hit or a miss, and marks the block as recently used.
+     * - Returns [`None`] for block pointers that are not cached, such as
+     *   holes.
+     */
+    pub fn get(&self, ptr: &BlockPointer) -> Option<Arc<[u8]>> {
+        let key = BlockKey::from_ptr(ptr)?;
+        let mut state = self.lock();
+        let state = &mut *state;
+
+        let tick = state.tick;
+        match state.entries.get_mut(&key) {
+            Some(entry) => {
+                state.order.remove(&entry.tick);
+                state.order.insert(tick, key);
+                entry.tick = tick;
+                state.tick += 1;
+                state.stats.hits += 1;
+                Some(entry.data.clone())
+            }
+            None => {
+                state.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /** Returns true if the data of a block pointer is cached.
+     *
+     * - Does not count a hit or a miss, or mark the block as recently used.
+     */
+    pub fn contains(&self, ptr: &BlockPointer) -> bool {
+        match BlockKey::from_ptr(ptr) {
+            Some(key) => self.lock().entries.contains_key(&key),
+            None => false,
+        }
+    }
+
+    /** Inserts the logical data of a block pointer.
+     *
+     * - Replaces the data of the block, if it is cached.
+     * - Evicts the least recently used blocks to stay under the budget. The
+     *   data is not cached if it is larger than the budget, or the block
+     *   pointer is not cached, such as a hole.
+     */
+    pub fn insert(&self, ptr: &BlockPointer, data: Arc<[u8]>) {
+        let key = match BlockKey::from_ptr(ptr) {
+            Some(v) => v,
+            None => return,
+        };
+
+        let mut state = self.lock();
+        if data.len() > state.stats.budget {
+            return;
+        }
+
+        let tick = state.tick;
+        state.tick += 1;
+        state.stats.bytes += data.len();
+        state.stats.entries += 1;
+        state.stats.insertions += 1;
+        state.order.insert(tick, key);
+
+        let entry = CacheEntry {
+            data: data,
+            tick: tick,
+        };
+        if let Some(old) = state.entries.insert(key, entry) {
+            state.order.remove(&old.tick);
+            state.stats.bytes -= old.data.len();
+            state.stats.entries -= 1;
+        }
+
+        state.evict();
+    }
+
+    /** Reads the logical data of a block, from the cache or with
+     * [`read_block`].
+     *
+     * - A block that is read is inserted in the cache.
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if the block is not cached and cannot be read.
+     */
+    pub fn read_block<D: BlockDevice>(
+        &self,
+        vdev_tree: &VdevTree<'_>,
+        ptr: &BlockPointer,
+        devices: &LeafDevices<D>,
+        maps: &VdevMaps,
+    ) -> Result<Arc<[u8]>, ReadError> {
+        if let Some(data) = self.get(ptr) {
+            return Ok(data);
+        }
+
+        let data: Arc<[u8]> = read_block(vdev_tree, ptr, devices, maps)?.into();
+        self.insert(ptr, data.clone());
+
+        Ok(data)
+    }
+
+    /// Returns the statistics.
+    pub fn stats(&self) -> BlockCacheStats {
+        self.lock().stats
+    }
+
+    /// Resets the hit, miss, insertion, and eviction counts.
+    pub fn reset_stats(&self) {
+        let mut state = self.lock();
+        state.stats = BlockCacheStats {
+            entries: state.stats.entries,
+            bytes: state.stats.bytes,
+            budget: state.stats.budget,
+            ..Default::default()
+        };
+    }
+
+    /// Sets the budget in bytes, and evicts blocks to stay under it.
+    pub fn set_budget(&self, budget: usize) {
+        let mut state = self.lock();
+        state.stats.budget = budget;
+        state.evict();
+    }
+
+    /// Removes all blocks.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.order.clear();
+        state.stats.entries = 0;
+        state.stats.bytes = 0;
+    }
+}
@@ -0,0 +1,716 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::checksum::fletcher_4_le;
+use crate::phys::{Vdev, VdevChildren, VdevDraid, VdevTree};
+use crate::read::{
+    read_raidz, DvaMap, DvaMapError, LeafDevices, RaidZColumn, RaidZMap, RaidZRead, ReadError,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Seed of the permutations of all dRAID maps (`VDEV_DRAID_SEED`).
+const DRAID_SEED: u64 = 0xd7a1d5eed;
+
+/// Minimum number of children of a dRAID vdev (`VDEV_DRAID_MIN_CHILDREN`).
+const DRAID_MIN_CHILDREN: u64 = 2;
+
+/// Maximum number of children of a dRAID vdev (`VDEV_DRAID_MAX_CHILDREN`).
+const DRAID_MAX_CHILDREN: u64 = 255;
+
+/** dRAID maps, one for each number of children.
+ *
+ * - C reference: `draid_maps`
+ * - The seeds were chosen by a search in OpenZFS for well balanced
+ *   permutations, and cannot be derived. The entries are copied from
+ *   `draid_maps` in `module/zfs/vdev_draid.c`, and each is checked against
+ *   its checksum when it is used.
+ * - The entries have not been copied yet, so the table is empty, and
+ *   [`DraidPermutations::for_children`] returns
+ *   [`DvaMapError::MissingDraidPermutations`]. Until then, the permutations
+ *   of an entry are generated with [`DraidPermutations::from_map`], which
+ *   checks the checksum, and added to [`VdevMaps`](crate::read::VdevMaps).
+ */
+const DRAID_MAPS: &[DraidMap] = &[];
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A dRAID map, which gives the permutations for a number of children.
+ *
+ * - C reference: `draid_map_t`
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DraidMap {
+    /// Number of children.
+    pub children: u64,
+
+    /// Number of permutations.
+    pub n_perms: u64,
+
+    /// Seed of the permutations.
+    pub seed: u64,
+
+    /// First word of the Fletcher-4 checksum of the permutations.
+    pub checksum: u64,
+}
+
+impl DraidMap {
+    /** Returns the map of OpenZFS for a number of children.
+     *
+     * - C reference: `vdev_draid_lookup_map`
+     * - Returns [`None`] if there is no map for the number of children.
+     */
+    pub fn from_children(children: u64) -> Option<&'static DraidMap> {
+        DRAID_MAPS.iter().find(|map| map.children == children)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Geometry of a dRAID vdev.
+ *
+ * - C reference: `vdev_draid_config_t`
+ * - The children that are not distributed spares are the disks. Groups of
+ *   `group_width` columns are laid out one after the other over rows of the
+ *   disks, each [`DraidGeometry::ROW_HEIGHT`] bytes high.
+ * - A slice is `n_groups` groups, which fill whole rows. The children are
+ *   shuffled by a different permutation for each slice.
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub struct DraidGeometry {
+    pub n_data: u64,
+    pub n_parity: u64,
+    pub n_spares: u64,
+    pub n_children: u64,
+    pub n_groups: u64,
+
+    /// Data and parity columns of a group.
+    pub group_width: u64,
+
+    /// Children that are not distributed spares.
+    pub n_disks: u64,
+
+    /// Allocated bytes of a group.
+    pub group_size: u64,
+
+    /// Bytes of a slice on each child.
+    pub dev_slice_size: u64,
+}
+
+impl DraidGeometry {
+    /// Height of a row on each child in bytes (`VDEV_DRAID_ROWHEIGHT`).
+    pub const ROW_HEIGHT: u64 = 1 << 24;
+
+    /// Gets the [`DraidGeometry`] of a dRAID vdev.
+    pub fn from_vdev(draid: &VdevDraid<'_>) -> DraidGeometry {
+        let n_children = draid.children.len() as u64;
+        let group_width = draid.n_data + draid.n_parity;
+        let n_disks = n_children - draid.n_spares;
+        let group_size = group_width * DraidGeometry::ROW_HEIGHT;
+
+        DraidGeometry {
+            n_data: draid.n_data,
+            n_parity: draid.n_parity,
+            n_spares: draid.n_spares,
+            n_children: n_children,
+            n_groups: draid.n_groups,
+            group_width: group_width,
+            n_disks: n_disks,
+            group_size: group_size,
+            dev_slice_size: (group_size * draid.n_groups) / n_disks,
+        }
+    }
+
+    /** Returns the allocated size of a block.
+     *
+     * - C reference: `vdev_draid_asize`
+     * - Blocks are allocated in full stripes of `group_width` sectors.
+     */
+    pub fn asize(&self, psize: u64, a_shift: u64) -> u64 {
+        let rows = ((psize - 1) / (self.n_data << a_shift)) + 1;
+        (rows * self.group_width) << a_shift
+    }
+
+    /** Returns the physical size of the data of an allocated size.
+     *
+     * - C reference: `vdev_draid_asize_to_psize`
+     */
+    pub fn asize_to_psize(&self, asize: u64) -> u64 {
+        (asize / self.group_width) * self.n_data
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Permutations of the children of a dRAID vdev.
+ *
+ * - C reference: `draid_map_t`, `vdev_draid_generate_perms`
+ * - The permutations of a dRAID vdev are those of the [`DraidMap`] for its
+ *   number of children, see [`DraidPermutations::for_children`].
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::read::DraidPermutations;
+ *
+ * let perms = DraidPermutations::generate(5, 4, 0x1234, None).unwrap();
+ * assert_eq!(perms.children(), 5);
+ *
+ * // Each row is a permutation of the children.
+ * let mut row: Vec<usize> = (0..5).map(|column| perms.child(1, column)).collect();
+ * row.sort();
+ * assert_eq!(row, [0, 1, 2, 3, 4]);
+ *
+ * // The checksum of the permutations is checked.
+ * let checksum = perms.checksum();
+ * assert!(DraidPermutations::generate(5, 4, 0x1234, Some(checksum)).is_ok());
+ * assert!(DraidPermutations::generate(5, 4, 0x1234, Some(checksum + 1)).is_err());
+ * ```
+ */
+#[derive(Debug)]
+pub struct DraidPermutations {
+    children: u64,
+    n_perms: u64,
+    perms: Vec<u8>,
+}
+
+impl DraidPermutations {
+    /** Generates the permutations of a dRAID map.
+     *
+     * - Each row is a Fisher-Yates shuffle of the previous row, using the
+     *   `xorshift128+` generator seeded with `seed`.
+     * - `checksum` is the first word of the Fletcher-4 checksum of the
+     *   permutations, if known.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the map is invalid, or the checksum does not
+     * match.
+     */
+    pub fn generate(
+        children: u64,
+        n_perms: u64,
+        seed: u64,
+        checksum: Option<u64>,
+    ) -> Result<DraidPermutations, DvaMapError> {
+        if !(DRAID_MIN_CHILDREN..=DRAID_MAX_CHILDREN).contains(&children)
+            || n_perms == 0
+            || seed == 0
+        {
+            return Err(DvaMapError::InvalidDraidPermutations {
+                children: children,
+                n_perms: n_perms,
+            });
+        }
+
+        ////////////////////////////////
+        // Shuffle each row, starting from the previous row.
+        let row_length = children as usize;
+        let mut perms = Vec::with_capacity(row_length * n_perms as usize);
+        let mut row: Vec<u8> = (0..children).map(|child| child as u8).collect();
+        let mut state = [DRAID_SEED, seed];
+
+        for _ in 0..n_perms {
+            for j in (1..row_length).rev() {
+                let k = (draid_rand(&mut state) % (j as u64 + 1)) as usize;
+                row.swap(j, k);
+            }
+            perms.extend_from_slice(&row);
+        }
+
+        let perms = DraidPermutations {
+            children: children,
+            n_perms: n_perms,
+            perms: perms,
+        };
+
+        ////////////////////////////////
+        // Check the checksum.
+        if let Some(checksum) = checksum {
+            let computed = perms.checksum();
+            if computed != checksum {
+                return Err(DvaMapError::DraidChecksumMismatch {
+                    checksum: checksum,
+                    computed: computed,
+                });
+            }
+        }
+
+        Ok(perms)
+    }
+
+    /** Generates the permutations of a dRAID map, and checks its checksum.
+     *
+     * - C reference: `vdev_draid_generate_perms`, `vdev_draid_check_map`
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the map is invalid, or the checksum does not
+     * match.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::read::{DraidMap, DraidPermutations};
+     *
+     * let checksum = DraidPermutations::generate(5, 4, 0x1234, None).unwrap().checksum();
+     * let map = DraidMap { children: 5, n_perms: 4, seed: 0x1234, checksum: checksum };
+     * assert!(DraidPermutations::from_map(&map).is_ok());
+     *
+     * let map = DraidMap { checksum: checksum + 1, ..map };
+     * assert!(DraidPermutations::from_map(&map).is_err());
+     * ```
+     */
+    pub fn from_map(map: &DraidMap) -> Result<DraidPermutations, DvaMapError> {
+        DraidPermutations::generate(map.children, map.n_perms, map.seed, Some(map.checksum))
+    }
+
+    /** Generates the permutations for a number of children, from the map of
+     * OpenZFS.
+     *
+     * - C reference: `vdev_draid_lookup_map`, `vdev_draid_generate_perms`
+     * - This is how the permutations of a dRAID vdev are found from its
+     *   configuration.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError::MissingDraidPermutations`] if there is no map for
+     * the number of children, see [`DraidMap::from_children`], or
+     * [`DvaMapError`] if the checksum of the map does not match.
+     */
+    pub fn for_children(children: u64) -> Result<DraidPermutations, DvaMapError> {
+        match DraidMap::from_children(children) {
+            Some(map) => DraidPermutations::from_map(map),
+            None => Err(DvaMapError::MissingDraidPermutations { children: children }),
+        }
+    }
+
+    /// Returns the number of children.
+    pub fn children(&self) -> u64 {
+        self.children
+    }
+
+    /// Returns the first word of the Fletcher-4 checksum of the permutations.
+    pub fn checksum(&self) -> u64 {
+        fletcher_4_le(&self.perms)[0]
+    }
+
+    /** Returns the child of a column of a permutation.
+     *
+     * - C reference: `vdev_draid_get_perm`, `vdev_draid_permute_id`
+     * - The rows are used in turn, and each row is rotated by one for each
+     *   pass over the rows.
+     */
+    pub fn child(&self, perm: u64, column: u64) -> usize {
+        let offset = perm % (self.n_perms * self.children);
+        let row = (offset / self.children) as usize;
+        let rotation = offset % self.children;
+
+        let base = self.perms[row * self.children as usize + column as usize] as u64;
+        ((base + rotation) % self.children) as usize
+    }
+}
+
+/// Returns the next value of the `xorshift128+` generator (`vdev_draid_rand`).
+fn draid_rand(state: &mut [u64; 2]) -> u64 {
+    let mut s1 = state[0];
+    let s0 = state[1];
+
+    state[0] = s0;
+    s1 ^= s1 << 23;
+    state[1] = s1 ^ s0 ^ (s1 >> 18) ^ (s0 >> 5);
+
+    state[1].wrapping_add(s0)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Values to map the columns of a dRAID vdev to its children.
+struct DraidLayout<'a, 'b> {
+    /// Children of the vdev, decoded once for all columns.
+    children: Vec<VdevTree<'b>>,
+    geometry: DraidGeometry,
+    perms: &'a DraidPermutations,
+    a_shift: u64,
+    draid: &'a [DraidPermutations],
+}
+
+/** Maps a byte range of a dRAID vdev to its rows.
+ *
+ * - C reference: `vdev_draid_map_alloc`, `vdev_draid_map_alloc_row`
+ * - A block has one row in each group it is in, which is at most two.
+ * - `draid` are the permutations for each number of children, see
+ *   [`crate::read::VdevMaps::from_vdev_tree`].
+ */
+pub(crate) fn draid_map(
+    draid_vdev: &VdevDraid<'_>,
+    a_shift: u64,
+    offset: u64,
+    length: u64,
+    draid: &[DraidPermutations],
+) -> Result<Vec<RaidZMap>, DvaMapError> {
+    let geometry = DraidGeometry::from_vdev(draid_vdev);
+    let perms = match draid
+        .iter()
+        .find(|perms| perms.children == geometry.n_children)
+    {
+        Some(v) => v,
+        None => {
+            return Err(DvaMapError::MissingDraidPermutations {
+                children: geometry.n_children,
+            })
+        }
+    };
+
+    let layout = DraidLayout {
+        children: draid_vdev.children.iter().collect::<Result<_, _>>()?,
+        geometry: geometry,
+        perms: perms,
+        a_shift: a_shift,
+        draid: draid,
+    };
+
+    ////////////////////////////////
+    // Split the block at the end of a group.
+    let mut rows = Vec::new();
+    let mut row_offset = offset;
+    let mut remaining = length;
+
+    while remaining > 0 {
+        let group = row_offset / layout.geometry.group_size;
+        let next_group = (group + 1) * layout.geometry.group_size;
+
+        let mut row_length = remaining;
+        if row_offset + layout.geometry.asize(row_length, a_shift) > next_group {
+            row_length = layout.geometry.asize_to_psize(next_group - row_offset);
+        }
+
+        if row_length == 0 || rows.len() == 2 {
+            return Err(DvaMapError::InvalidDraidOffset { offset: row_offset });
+        }
+
+        rows.push(draid_row(&layout, row_offset, row_length)?);
+
+        remaining -= row_length;
+        row_offset = next_group;
+    }
+
+    Ok(rows)
+}
+
+/// Maps a row of a block in one group of a dRAID vdev.
+fn draid_row(layout: &DraidLayout, offset: u64, length: u64) -> Result<RaidZMap, DvaMapError> {
+    let geometry = &layout.geometry;
+    let a_shift = layout.a_shift;
+
+    ////////////////////////////////
+    // Blocks start at a stripe of the group.
+    let group = offset / geometry.group_size;
+    let sector = (offset - group * geometry.group_size) >> a_shift;
+    if !sector.is_multiple_of(geometry.group_width) {
+        return Err(DvaMapError::InvalidDraidOffset { offset: offset });
+    }
+
+    ////////////////////////////////
+    // Every column has `quotient` sectors, and the first `big_columns`
+    // columns have one more.
+    let sectors = length >> a_shift;
+    let quotient = sectors / geometry.n_data;
+    let remainder = sectors - quotient * geometry.n_data;
+    let big_columns = if remainder == 0 {
+        0
+    } else {
+        remainder + geometry.n_parity
+    };
+    let column_count = if quotient == 0 {
+        big_columns
+    } else {
+        geometry.group_width
+    };
+
+    ////////////////////////////////
+    // Find the disks of the group, from the permutation of the slice.
+    let perm = group / geometry.n_groups;
+    let group_start = (group * geometry.group_width) % geometry.n_disks;
+    let mut physical_offset = ((group * geometry.group_width) / geometry.n_disks)
+        * DraidGeometry::ROW_HEIGHT
+        + ((sector / geometry.group_width) << a_shift);
+
+    let mut columns = Vec::with_capacity(column_count as usize);
+    for column in 0..column_count {
+        let disk = (column + group_start) % geometry.n_disks;
+
+        // The group continues on the next row.
+        if column > 0 && disk == 0 {
+            physical_offset += DraidGeometry::ROW_HEIGHT;
+        }
+
+        let length = if column < big_columns {
+            (quotient + 1) << a_shift
+        } else {
+            quotient << a_shift
+        };
+
+        let child = layout.perms.child(perm, disk);
+        columns.push(RaidZColumn {
+            child: child,
+            offset: physical_offset,
+            length: length,
+            map: draid_child_map(layout, &layout.children[child], physical_offset, length, 0)?,
+        });
+    }
+
+    ////////////////////////////////
+    // Allocations are full stripes.
+    let total = sectors + geometry.n_parity * (quotient + if remainder == 0 { 0 } else { 1 });
+
+    Ok(RaidZMap {
+        n_parity: geometry.n_parity as usize,
+        columns: columns,
+        skip_sectors: total.div_ceil(geometry.group_width) * geometry.group_width - total,
+    })
+}
+
+/** Maps a column on a child of a dRAID vdev.
+ *
+ * - C reference: `vdev_draid_spare_get_child`
+ * - The data of a distributed spare is on the child in the spare column of
+ *   the permutation of the slice. That child may also be a spare.
+ */
+fn draid_child_map(
+    layout: &DraidLayout,
+    vdev_tree: &VdevTree<'_>,
+    offset: u64,
+    length: u64,
+    depth: u64,
+) -> Result<DvaMap, DvaMapError> {
+    let geometry = &layout.geometry;
+
+    match &vdev_tree.vdev {
+        Vdev::DraidSpare(spare) => {
+            if spare.spare_id >= geometry.n_spares || depth >= geometry.n_spares {
+                return Ok(DvaMap::Missing {
+                    guid: vdev_tree.guid,
+                });
+            }
+
+            let perm = offset / geometry.dev_slice_size;
+            let child = layout
+                .perms
+                .child(perm, geometry.n_children - 1 - spare.spare_id);
+
+            draid_child_map(layout, &layout.children[child], offset, length, depth + 1)
+        }
+        Vdev::Replacing(v) => draid_copies_map(layout, &v.children, offset, length, depth),
+        Vdev::Spare(v) => draid_copies_map(layout, &v.children, offset, length, depth),
+        _ => DvaMap::from_vdev(vdev_tree, layout.a_shift, offset, length, layout.draid),
+    }
+}
+
+/// Maps a column on the copies of a replacing or spare child of a dRAID vdev.
+fn draid_copies_map(
+    layout: &DraidLayout,
+    children: &VdevChildren<'_>,
+    offset: u64,
+    length: u64,
+    depth: u64,
+) -> Result<DvaMap, DvaMapError> {
+    let mut copies = Vec::with_capacity(children.len());
+    for child in children.iter() {
+        copies.push(draid_child_map(layout, &child?, offset, length, depth)?);
+    }
+
+    Ok(DvaMap::Mirror(copies))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A block read from a dRAID vdev.
+ */
+#[derive(Debug)]
+pub struct DraidRead {
+    /// Data of the block, which is the data of the rows in order.
+    pub data: Vec<u8>,
+
+    /// Row and column indices of the columns that were missing or damaged.
+    pub bad_columns: Vec<(usize, usize)>,
+}
+
+/** Reads a block from the rows of a dRAID vdev, and reconstructs damaged
+ * columns using parity.
+ *
+ * - The rows are the rows of a [`DvaMap::Draid`].
+ * - Missing columns are reconstructed in all rows. If the data does not
+ *   verify, then each row is reconstructed as in [`read_raidz`], with the
+ *   other rows as read.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if too many columns are missing, or no
+ * reconstruction verifies.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Dva, VdevTree};
+ * use zfs::read::{read_draid, DraidPermutations, DvaMap, LeafDevices, VdevMaps};
+ *
+ * // dRAID1 with 2 data columns, and a distributed spare, which replaces the
+ * // first child.
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "draid" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 100 },
+ *         "nparity": { "uint64": 1 },
+ *         "draid_ndata": { "uint64": 2 },
+ *         "draid_nspares": { "uint64": 1 },
+ *         "draid_ngroups": { "uint64": 1 },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 },
+ *         "children": { "nvlist_array": [
+ *             {
+ *                 "type": { "string": "spare" },
+ *                 "id": { "uint64": 0 },
+ *                 "guid": { "uint64": 110 },
+ *                 "children": { "nvlist_array": [
+ *                     { "type": { "string": "file" }, "id": { "uint64": 0 },
+ *                       "guid": { "uint64": 101 }, "path": { "string": "/a" } },
+ *                     { "type": { "string": "dspare" }, "id": { "uint64": 1 },
+ *                       "guid": { "uint64": 111 }, "path": { "string": "draid1-0-0" } }
+ *                 ] }
+ *             },
+ *             { "type": { "string": "file" }, "id": { "uint64": 1 },
+ *               "guid": { "uint64": 102 }, "path": { "string": "/b" } },
+ *             { "type": { "string": "file" }, "id": { "uint64": 2 },
+ *               "guid": { "uint64": 103 }, "path": { "string": "/c" } },
+ *             { "type": { "string": "file" }, "id": { "uint64": 3 },
+ *               "guid": { "uint64": 104 }, "path": { "string": "/d" } }
+ *         ] }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ * let maps = VdevMaps {
+ *     draid: vec![DraidPermutations::generate(4, 8, 0x5eed, None).unwrap()],
+ *     ..Default::default()
+ * };
+ *
+ * // Map a block of 1024 bytes.
+ * let dva = Dva { vdev: 0, grid: 0, asize: 3, offset: 0, is_gang: false };
+ * let map = DvaMap::from_dva_maps(&vdev_tree, &dva, 1024, &maps).unwrap();
+ * let rows = match &map {
+ *     DvaMap::Draid(v) => v,
+ *     _ => panic!("expected a draid"),
+ * };
+ * assert_eq!(rows.len(), 1);
+ * assert_eq!(rows[0].columns.len(), 3);
+ *
+ * // Write the parity and data columns, to all copies.
+ * let block: Vec<u8> = (0..1024).map(|i| (i * 3) as u8).collect();
+ * let mut columns = rows[0].parity(&block).unwrap();
+ * columns.push(block[..512].to_vec());
+ * columns.push(block[512..].to_vec());
+ *
+ * let mut devices = LeafDevices::new();
+ * for guid in 101..=104 {
+ *     devices.insert(guid, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ * }
+ * for (column, bytes) in rows[0].columns.iter().zip(columns.iter()) {
+ *     let reads = match &column.map {
+ *         DvaMap::Mirror(copies) => copies.iter().flat_map(|c| c.leaf_reads()).collect(),
+ *         map => map.leaf_reads(),
+ *     };
+ *     for read in reads {
+ *         devices.write(&read, bytes).unwrap();
+ *     }
+ * }
+ *
+ * // The first child failed, and its data is read from the spare.
+ * devices.remove(101);
+ *
+ * let verify = |data: &[u8]| data == &block[..];
+ * let read = read_draid(rows, &devices, &verify).unwrap();
+ * assert_eq!(read.data, block);
+ * assert!(read.bad_columns.is_empty());
+ * ```
+ */
+pub fn read_draid<D: BlockDevice>(
+    rows: &[RaidZMap],
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<DraidRead, ReadError> {
+    // Use macros for cleaner code.
+    macro_rules! concat {
+        ($reads:expr) => {{
+            let mut data = Vec::new();
+            for read in $reads {
+                data.extend_from_slice(&read.data);
+            }
+            data
+        }};
+    }
+
+    ////////////////////////////////
+    // Reconstruct the missing columns of all rows.
+    let accept = |_: &[u8]| true;
+    let mut reads: Vec<RaidZRead> = Vec::with_capacity(rows.len());
+    for row in rows {
+        reads.push(read_raidz(row, devices, &accept)?);
+    }
+
+    ////////////////////////////////
+    // Reconstruct each row, with the other rows as read.
+    if !verify(&concat!(&reads)) {
+        let mut found = false;
+
+        for index in 0..rows.len() {
+            let before = concat!(&reads[..index]);
+            let after = concat!(&reads[index + 1..]);
+            let verify_row = |data: &[u8]| {
+                let mut block = before.clone();
+                block.extend_from_slice(data);
+                block.extend_from_slice(&after);
+                verify(&block)
+            };
+
+            if let Ok(read) = read_raidz(&rows[index], devices, &verify_row) {
+                reads[index] = read;
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            return Err(ReadError::ChecksumMismatch {});
+        }
+    }
+
+    let bad_columns = reads
+        .iter()
+        .enumerate()
+        .flat_map(|(row, read)| read.bad_columns.iter().map(move |column| (row, *column)))
+        .collect();
+
+    Ok(DraidRead {
+        data: concat!(&reads),
+        bad_columns: bad_columns,
+    })
+}
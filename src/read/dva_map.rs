@@ -0,0 +1,935 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::phys::{Dva, IndirectMappingDecodeError, Vdev, VdevChildren, VdevDecodeError, VdevTree};
+use crate::read::draid::draid_map;
+use crate::read::{DraidPermutations, IndirectMapping};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Minimum `ashift` of a top level vdev (`ASHIFT_MIN`).
+const A_SHIFT_MIN: u64 = 9;
+
+/// Maximum `ashift` of a top level vdev (`ASHIFT_MAX`).
+const A_SHIFT_MAX: u64 = 16;
+
+/// RAID-Z1 swaps the parity and first data column for offsets with this bit.
+const RAIDZ_SWAP_MASK: u64 = 1 << 20;
+
+/// Maximum depth of remapping through indirect vdevs, to stop on loops in
+/// damaged pools.
+const INDIRECT_DEPTH_MAX: usize = 16;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A read of a leaf vdev.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LeafRead {
+    /// GUID of the leaf vdev.
+    pub guid: u64,
+
+    /// Byte offset on the device, including the labels and boot block in front.
+    pub offset: u64,
+
+    /// Byte length.
+    pub length: u64,
+}
+
+/** A column of a RAID-Z map.
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub struct RaidZColumn {
+    /// Index of the child vdev.
+    pub child: usize,
+
+    /// Byte offset in the child vdev, not including the labels and boot block.
+    pub offset: u64,
+
+    /// Byte length, a multiple of the sector size of the top level vdev.
+    pub length: u64,
+
+    /// Map of the column in the child vdev.
+    pub map: DvaMap,
+}
+
+/** Layout of a block on a RAID-Z vdev.
+ *
+ * - C reference: `vdev_raidz_map_alloc`
+ * - The first `n_parity` columns are the P, Q, and R parity columns, and the
+ *   other columns are the data, in order.
+ * - Columns that are not used by a small block are not included.
+ * - `skip_sectors` are allocated after the columns, but not written.
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub struct RaidZMap {
+    pub n_parity: usize,
+    pub columns: Vec<RaidZColumn>,
+    pub skip_sectors: u64,
+}
+
+impl RaidZMap {
+    /// Returns the data columns.
+    pub fn data_columns(&self) -> &[RaidZColumn] {
+        &self.columns[self.n_parity..]
+    }
+
+    /// Returns the parity columns.
+    pub fn parity_columns(&self) -> &[RaidZColumn] {
+        &self.columns[..self.n_parity]
+    }
+}
+
+/** Maps of a pool that are not in its vdev tree.
+ *
+ * - `draid` are the [`DraidPermutations`] for each number of children of
+ *   the dRAID vdevs in the pool, usually generated once by
+ *   [`VdevMaps::from_vdev_tree`].
+ * - `indirect` are the [`IndirectMapping`] of the indirect vdevs in the
+ *   pool, which are top level vdevs that were removed.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::read::{DraidMap, DraidPermutations, VdevMaps};
+ *
+ * // Permutations of a dRAID map that is not in the table of this crate,
+ * // such as an entry of `draid_maps` in OpenZFS, are checked and added.
+ * let map = DraidMap { children: 5, n_perms: 4, seed: 0x1234, checksum: 0xc0a0a08 };
+ *
+ * let mut maps = VdevMaps::default();
+ * maps.draid.push(DraidPermutations::from_map(&map).unwrap());
+ * assert_eq!(maps.draid[0].children(), 5);
+ *
+ * // A map with the wrong checksum is not added.
+ * let map = DraidMap { checksum: 0xc0a0a09, ..map };
+ * assert!(DraidPermutations::from_map(&map).is_err());
+ * ```
+ */
+#[derive(Debug, Default)]
+pub struct VdevMaps {
+    pub draid: Vec<DraidPermutations>,
+    pub indirect: Vec<IndirectMapping>,
+}
+
+impl VdevMaps {
+    /** Creates the [`VdevMaps`] of a pool, with the permutations of its dRAID
+     * vdevs.
+     *
+     * - `vdev_tree` is either the root vdev of a pool, or a top level vdev.
+     * - The permutations are generated once for each number of children of
+     *   the dRAID vdevs, with [`DraidPermutations::for_children`]. Numbers of
+     *   children without a dRAID map are skipped, so blocks on those vdevs
+     *   are not mapped, unless their permutations are added to `draid`.
+     * - `indirect` is empty, as the mappings are read from the pool.
+     */
+    pub fn from_vdev_tree(vdev_tree: &VdevTree<'_>) -> VdevMaps {
+        let mut maps = VdevMaps::default();
+
+        let mut add = |top: &VdevTree<'_>| {
+            let children = match &top.vdev {
+                Vdev::Draid(draid) => draid.children.len() as u64,
+                _ => return,
+            };
+
+            if maps.draid.iter().any(|perms| perms.children() == children) {
+                return;
+            }
+
+            if let Ok(perms) = DraidPermutations::for_children(children) {
+                maps.draid.push(perms);
+            }
+        };
+
+        match &vdev_tree.vdev {
+            Vdev::Root(root) => root.children.iter().flatten().for_each(|top| add(&top)),
+            _ => add(vdev_tree),
+        }
+
+        maps
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Map of a block to the reads of the leaf vdevs that store it.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Dva, VdevTree};
+ * use zfs::read::{DvaMap, LeafRead};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "root" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 100 },
+ *         "children": { "nvlist_array": [
+ *             {
+ *                 "type": { "string": "mirror" },
+ *                 "id": { "uint64": 0 },
+ *                 "guid": { "uint64": 200 },
+ *                 "ashift": { "uint64": 12 },
+ *                 "asize": { "uint64": 1073741824 },
+ *                 "metaslab_array": { "uint64": 256 },
+ *                 "metaslab_shift": { "uint64": 29 },
+ *                 "children": { "nvlist_array": [
+ *                     { "type": { "string": "file" }, "id": { "uint64": 0 },
+ *                       "guid": { "uint64": 201 }, "path": { "string": "/a" } },
+ *                     { "type": { "string": "missing" }, "id": { "uint64": 1 },
+ *                       "guid": { "uint64": 202 } }
+ *                 ] }
+ *             },
+ *             {
+ *                 "type": { "string": "raidz" },
+ *                 "id": { "uint64": 1 },
+ *                 "guid": { "uint64": 300 },
+ *                 "nparity": { "uint64": 1 },
+ *                 "ashift": { "uint64": 9 },
+ *                 "asize": { "uint64": 1073741824 },
+ *                 "metaslab_array": { "uint64": 257 },
+ *                 "metaslab_shift": { "uint64": 29 },
+ *                 "children": { "nvlist_array": [
+ *                     { "type": { "string": "file" }, "id": { "uint64": 0 },
+ *                       "guid": { "uint64": 301 }, "path": { "string": "/b" } },
+ *                     { "type": { "string": "file" }, "id": { "uint64": 1 },
+ *                       "guid": { "uint64": 302 }, "path": { "string": "/c" } },
+ *                     { "type": { "string": "file" }, "id": { "uint64": 2 },
+ *                       "guid": { "uint64": 303 }, "path": { "string": "/d" } }
+ *                 ] }
+ *             }
+ *         ] }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let root = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * // A block of 1000 bytes on the mirror, rounded up to the 4096 byte sectors.
+ * let dva = Dva { vdev: 0, grid: 0, asize: 8, offset: 16, is_gang: false };
+ * let map = DvaMap::from_dva(&root, &dva, 1000).unwrap();
+ * assert_eq!(
+ *     map.leaf_reads(),
+ *     [LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 8192, length: 4096 }],
+ * );
+ * match map {
+ *     DvaMap::Mirror(copies) => assert_eq!(copies[1], DvaMap::Missing { guid: 202 }),
+ *     _ => panic!("expected a mirror"),
+ * }
+ *
+ * // A block of one sector on the RAID-Z, at the second sector of the vdev,
+ * // has parity on the second child, and data on the third child.
+ * let dva = Dva { vdev: 1, grid: 0, asize: 2, offset: 1, is_gang: false };
+ * let map = DvaMap::from_dva(&root, &dva, 512).unwrap();
+ * assert_eq!(
+ *     map.leaf_reads(),
+ *     [LeafRead { guid: 303, offset: Dva::LEAF_OFFSET, length: 512 }],
+ * );
+ * match map {
+ *     DvaMap::RaidZ(raidz) => assert_eq!(raidz.parity_columns()[0].child, 1),
+ *     _ => panic!("expected a raidz"),
+ * }
+ *
+ * // At a byte offset with bit 20 set, RAID-Z1 swaps the parity and first
+ * // data column. A block of two sectors at sector 2048 starts on the third
+ * // child, so the parity is on the first child, and the first data column
+ * // is on the third child.
+ * let dva = Dva { vdev: 1, grid: 0, asize: 3, offset: 2048, is_gang: false };
+ * let map = DvaMap::from_dva(&root, &dva, 1024).unwrap();
+ * let raidz = match &map {
+ *     DvaMap::RaidZ(v) => v,
+ *     _ => panic!("expected a raidz"),
+ * };
+ * let columns: Vec<(usize, u64)> = raidz.columns.iter().map(|c| (c.child, c.offset)).collect();
+ * assert_eq!(columns, [(0, 682 * 512 + 512), (2, 682 * 512), (1, 682 * 512 + 512)]);
+ * assert_eq!(
+ *     map.leaf_reads(),
+ *     [
+ *         LeafRead { guid: 303, offset: Dva::LEAF_OFFSET + 682 * 512, length: 512 },
+ *         LeafRead { guid: 302, offset: Dva::LEAF_OFFSET + 683 * 512, length: 512 },
+ *     ],
+ * );
+ * ```
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub enum DvaMap {
+    /// Read of a disk or file.
+    Leaf(LeafRead),
+
+    /** A vdev that is missing from the configuration, such as a vdev of type
+     * `missing`, or a hole.
+     *
+     * - `guid` - GUID of the vdev.
+     */
+    Missing { guid: u64 },
+
+    /// Each child has a copy, such as a mirror, replacing, or spare vdev.
+    Mirror(Vec<DvaMap>),
+
+    /// The block is spread over the children, with parity.
+    RaidZ(RaidZMap),
+
+    /// The block is spread over the children of a dRAID vdev, with one row
+    /// for each group it is in.
+    Draid(Vec<RaidZMap>),
+
+    /// The block was on a removed vdev, and its data is in these segments on
+    /// other vdevs, in order.
+    Indirect(Vec<DvaMap>),
+}
+
+impl DvaMap {
+    /** Maps a [`Dva`] to the reads of the leaf vdevs.
+     *
+     * - `vdev_tree` is either the root vdev of a pool, or a top level vdev,
+     *   such as the `vdev_tree` of a label.
+     * - `size` is the physical size of the block in bytes. It is rounded up
+     *   to the sector size of the top level vdev.
+     * - The [`VdevMaps`] are created for each call, with
+     *   [`VdevMaps::from_vdev_tree`], so use [`DvaMap::from_dva_maps`] to map
+     *   many blocks of a pool with dRAID vdevs.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the top level vdev is not found, or the
+     * layout is not supported.
+     */
+    pub fn from_dva(vdev_tree: &VdevTree<'_>, dva: &Dva, size: u64) -> Result<DvaMap, DvaMapError> {
+        DvaMap::from_dva_maps(vdev_tree, dva, size, &VdevMaps::from_vdev_tree(vdev_tree))
+    }
+
+    /** Maps a [`Dva`] to the reads of the leaf vdevs, with the [`VdevMaps`]
+     * of the pool.
+     *
+     * - A [`Dva`] of an indirect vdev is remapped with its [`IndirectMapping`]
+     *   to the vdevs that have its data, which must be in `vdev_tree`. The
+     *   data may be split into the segments of a [`DvaMap::Indirect`].
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if a top level vdev is not found, the layout is
+     * not supported, or the maps of a dRAID or indirect vdev are missing.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::endian::{Encoder, Endian};
+     * use zfs::nv::{Decoder, Encoding, Unique};
+     * use zfs::nv_json;
+     * use zfs::phys::{Dva, IndirectMappingEntry, IndirectMappingHeader, VdevTree};
+     * use zfs::read::{DvaMap, IndirectMapping, LeafRead, VdevMaps};
+     *
+     * let text = r#"{
+     *     "vdev_tree": { "nvlist": {
+     *         "type": { "string": "root" },
+     *         "id": { "uint64": 0 },
+     *         "guid": { "uint64": 100 },
+     *         "children": { "nvlist_array": [
+     *             {
+     *                 "type": { "string": "file" },
+     *                 "id": { "uint64": 0 },
+     *                 "guid": { "uint64": 201 },
+     *                 "path": { "string": "/a" },
+     *                 "ashift": { "uint64": 9 },
+     *                 "asize": { "uint64": 1073741824 },
+     *                 "metaslab_array": { "uint64": 256 },
+     *                 "metaslab_shift": { "uint64": 29 }
+     *             },
+     *             {
+     *                 "type": { "string": "indirect" },
+     *                 "id": { "uint64": 1 },
+     *                 "guid": { "uint64": 202 },
+     *                 "ashift": { "uint64": 9 },
+     *                 "asize": { "uint64": 1073741824 },
+     *                 "metaslab_array": { "uint64": 0 },
+     *                 "metaslab_shift": { "uint64": 29 },
+     *                 "com.delphix:indirect_object": { "uint64": 70 },
+     *                 "com.delphix:indirect_births": { "uint64": 71 }
+     *             }
+     *         ] }
+     *     } }
+     * }"#;
+     *
+     * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+     * let decoder = Decoder::from_bytes(&data).unwrap();
+     * let root = VdevTree::from_decoder(&decoder).unwrap();
+     *
+     * // Sectors 0 to 7 of the removed vdev are at sector 100 of vdev 0, and
+     * // sectors 8 to 15 are at sector 20.
+     * let mut data = [0u8; 2 * IndirectMappingEntry::LENGTH];
+     * let mut encoder = Encoder::to_bytes(&mut data, Endian::Little);
+     * for (src_offset, offset) in [(0, 100), (8, 20)] {
+     *     let dst = Dva { vdev: 0, grid: 0, asize: 8, offset: offset, is_gang: false };
+     *     let entry = IndirectMappingEntry { src_offset: src_offset, mark: false, dst: dst };
+     *     entry.to_encoder(&mut encoder).unwrap();
+     * }
+     * let header = IndirectMappingHeader {
+     *     max_offset: 8192,
+     *     bytes_mapped: 8192,
+     *     num_entries: 2,
+     *     counts_object: Some(72),
+     * };
+     * let maps = VdevMaps {
+     *     indirect: vec![IndirectMapping::from_bytes(1, &header, &data, Endian::Little).unwrap()],
+     *     ..Default::default()
+     * };
+     *
+     * // A block of two sectors in the first entry is at one location.
+     * let dva = Dva { vdev: 1, grid: 0, asize: 2, offset: 2, is_gang: false };
+     * let map = DvaMap::from_dva_maps(&root, &dva, 1024, &maps).unwrap();
+     * assert_eq!(
+     *     map,
+     *     DvaMap::Leaf(LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 102 * 512, length: 1024 }),
+     * );
+     *
+     * // A block of two sectors over both entries is split.
+     * let dva = Dva { vdev: 1, grid: 0, asize: 2, offset: 7, is_gang: false };
+     * let map = DvaMap::from_dva_maps(&root, &dva, 1024, &maps).unwrap();
+     * assert_eq!(
+     *     map.leaf_reads(),
+     *     [
+     *         LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 107 * 512, length: 512 },
+     *         LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 20 * 512, length: 512 },
+     *     ],
+     * );
+     *
+     * // Without the mapping, the removed vdev cannot be mapped.
+     * assert!(DvaMap::from_dva(&root, &dva, 1024).is_err());
+     * ```
+     */
+    pub fn from_dva_maps(
+        vdev_tree: &VdevTree<'_>,
+        dva: &Dva,
+        size: u64,
+        maps: &VdevMaps,
+    ) -> Result<DvaMap, DvaMapError> {
+        map_vdev_id(vdev_tree, dva.vdev, dva.offset_bytes(), size, maps, 0)
+    }
+
+    /** Maps a byte range of a vdev to the reads of its leaf vdevs.
+     *
+     * - `a_shift` is the sector shift of the top level vdev.
+     * - `offset` is in the vdev, not including the labels and boot block.
+     * - `length` is a multiple of the sector size.
+     * - `draid` are the permutations of dRAID vdevs, as in [`VdevMaps`].
+     * - An indirect vdev is not supported, because its data is on other top
+     *   level vdevs (see [`DvaMap::from_dva_maps`]).
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the layout is not supported.
+     */
+    pub fn from_vdev(
+        vdev_tree: &VdevTree<'_>,
+        a_shift: u64,
+        offset: u64,
+        length: u64,
+        draid: &[DraidPermutations],
+    ) -> Result<DvaMap, DvaMapError> {
+        // Use macros for cleaner code.
+        macro_rules! mirror {
+            ($children:expr) => {{
+                let mut copies = Vec::with_capacity($children.len());
+                for child in $children.iter() {
+                    copies.push(DvaMap::from_vdev(&child?, a_shift, offset, length, draid)?);
+                }
+                Ok(DvaMap::Mirror(copies))
+            }};
+        }
+
+        match &vdev_tree.vdev {
+            Vdev::Disk(_) | Vdev::File(_) => Ok(DvaMap::Leaf(LeafRead {
+                guid: vdev_tree.guid,
+                offset: Dva::LEAF_OFFSET + offset,
+                length: length,
+            })),
+            Vdev::Hole() | Vdev::Missing() => Ok(DvaMap::Missing {
+                guid: vdev_tree.guid,
+            }),
+            Vdev::Mirror(v) => mirror!(v.children),
+            Vdev::Replacing(v) => mirror!(v.children),
+            Vdev::Spare(v) => mirror!(v.children),
+            Vdev::RaidZ(v) => Ok(DvaMap::RaidZ(raidz_map(
+                &v.children,
+                v.n_parity,
+                a_shift,
+                offset,
+                length,
+                draid,
+            )?)),
+            Vdev::Draid(v) => Ok(DvaMap::Draid(draid_map(v, a_shift, offset, length, draid)?)),
+            // A distributed spare is only mapped by its dRAID vdev.
+            Vdev::DraidSpare(_) => Ok(DvaMap::Missing {
+                guid: vdev_tree.guid,
+            }),
+            Vdev::Indirect(_) => Err(DvaMapError::Unsupported {
+                vdev_type: "indirect",
+            }),
+            Vdev::L2Cache(_) => Err(DvaMapError::Unsupported {
+                vdev_type: "l2cache",
+            }),
+            Vdev::Root(_) => Err(DvaMapError::Unsupported { vdev_type: "root" }),
+        }
+    }
+
+    /** Returns all the reads of leaf vdevs that are not missing.
+     *
+     * - For a mirror, each copy is read.
+     * - For RAID-Z and dRAID, the data columns are read, but not the parity
+     *   columns.
+     */
+    pub fn leaf_reads(&self) -> Vec<LeafRead> {
+        let mut reads = Vec::new();
+        self.push_leaf_reads(&mut reads);
+        reads
+    }
+
+    /// Pushes the leaf reads of [`DvaMap::leaf_reads`].
+    fn push_leaf_reads(&self, reads: &mut Vec<LeafRead>) {
+        match self {
+            DvaMap::Leaf(v) => reads.push(*v),
+            DvaMap::Missing { guid: _ } => (),
+            DvaMap::Mirror(copies) => {
+                for copy in copies {
+                    copy.push_leaf_reads(reads);
+                }
+            }
+            DvaMap::RaidZ(raidz) => {
+                for column in raidz.data_columns() {
+                    column.map.push_leaf_reads(reads);
+                }
+            }
+            DvaMap::Draid(rows) => {
+                for row in rows {
+                    for column in row.data_columns() {
+                        column.map.push_leaf_reads(reads);
+                    }
+                }
+            }
+            DvaMap::Indirect(segments) => {
+                for segment in segments {
+                    segment.push_leaf_reads(reads);
+                }
+            }
+        }
+    }
+}
+
+/** Maps a byte range of the top level vdev with ID `vdev`.
+ *
+ * - `depth` is the number of indirect vdevs the range was remapped through.
+ */
+fn map_vdev_id(
+    vdev_tree: &VdevTree<'_>,
+    vdev: u32,
+    offset: u64,
+    size: u64,
+    maps: &VdevMaps,
+    depth: usize,
+) -> Result<DvaMap, DvaMapError> {
+    match &vdev_tree.vdev {
+        Vdev::Root(root) => {
+            for child in root.children.iter() {
+                let child = child?;
+                if child.id == vdev as u64 {
+                    return map_top_level(vdev_tree, &child, offset, size, maps, depth);
+                }
+            }
+            Err(DvaMapError::VdevNotFound { vdev: vdev })
+        }
+        _ if vdev_tree.id == vdev as u64 => {
+            map_top_level(vdev_tree, vdev_tree, offset, size, maps, depth)
+        }
+        _ => Err(DvaMapError::VdevNotFound { vdev: vdev }),
+    }
+}
+
+/** Maps a byte range of a top level vdev.
+ *
+ * - `vdev_tree` is the root vdev, to find the vdevs that an indirect vdev is
+ *   remapped to.
+ */
+fn map_top_level(
+    vdev_tree: &VdevTree<'_>,
+    top: &VdevTree<'_>,
+    offset: u64,
+    size: u64,
+    maps: &VdevMaps,
+    depth: usize,
+) -> Result<DvaMap, DvaMapError> {
+    ////////////////////////////////
+    // A hole or missing top level vdev has no data.
+    if let Vdev::Hole() | Vdev::Missing() = top.vdev {
+        return Ok(DvaMap::Missing { guid: top.guid });
+    }
+
+    ////////////////////////////////
+    // Get sector size.
+    let a_shift = match top.vdev.a_meta_slab() {
+        Some(v) => v.a_shift,
+        None => {
+            return Err(DvaMapError::MissingAShift {
+                vdev: top.id as u32,
+            })
+        }
+    };
+
+    if !(A_SHIFT_MIN..=A_SHIFT_MAX).contains(&a_shift) {
+        return Err(DvaMapError::InvalidAShift { a_shift: a_shift });
+    }
+
+    ////////////////////////////////
+    // Round size up to sectors.
+    if size == 0 {
+        return Err(DvaMapError::InvalidSize { size: size });
+    }
+    let length = size.div_ceil(1 << a_shift) << a_shift;
+
+    match top.vdev {
+        Vdev::Indirect(_) => map_indirect(vdev_tree, top, offset, length, maps, depth),
+        _ => DvaMap::from_vdev(top, a_shift, offset, length, &maps.draid),
+    }
+}
+
+/** Maps a byte range of an indirect vdev to the vdevs it was remapped to.
+ *
+ * - C reference: `vdev_indirect_remap`
+ * - A range in one segment is mapped to that segment, and a range split over
+ *   several segments is a [`DvaMap::Indirect`].
+ */
+fn map_indirect(
+    vdev_tree: &VdevTree<'_>,
+    top: &VdevTree<'_>,
+    offset: u64,
+    length: u64,
+    maps: &VdevMaps,
+    depth: usize,
+) -> Result<DvaMap, DvaMapError> {
+    if depth >= INDIRECT_DEPTH_MAX {
+        return Err(DvaMapError::InvalidIndirectDepth { depth: depth });
+    }
+
+    let mapping = match maps.indirect.iter().find(|mapping| mapping.vdev == top.id) {
+        Some(v) => v,
+        None => return Err(DvaMapError::MissingIndirectMapping { vdev: top.id }),
+    };
+
+    ////////////////////////////////
+    // Map each segment, which may itself be on an indirect vdev.
+    let mut segments = Vec::new();
+    for segment in mapping.remap(offset, length)? {
+        segments.push(map_vdev_id(
+            vdev_tree,
+            segment.vdev,
+            segment.offset,
+            segment.length,
+            maps,
+            depth + 1,
+        )?);
+    }
+
+    match segments.len() {
+        1 => Ok(segments.remove(0)),
+        _ => Ok(DvaMap::Indirect(segments)),
+    }
+}
+
+/** Maps a byte range of a RAID-Z vdev to its columns.
+ *
+ * - C reference: `vdev_raidz_map_alloc`
+ */
+fn raidz_map(
+    children: &VdevChildren<'_>,
+    n_parity: u64,
+    a_shift: u64,
+    offset: u64,
+    length: u64,
+    draid: &[DraidPermutations],
+) -> Result<RaidZMap, DvaMapError> {
+    // Decode the children once, as each column maps one of them.
+    let children = children.iter().collect::<Result<Vec<_>, _>>()?;
+    let n_children = children.len() as u64;
+    let n_data = n_children - n_parity;
+
+    ////////////////////////////////
+    // Sectors of the block, and of the children.
+    let sector = offset >> a_shift;
+    let sectors = length >> a_shift;
+    let first = sector % n_children;
+    let child_offset = (sector / n_children) << a_shift;
+
+    ////////////////////////////////
+    // Every column has `quotient` sectors, and the first `big_columns`
+    // columns have one more.
+    let quotient = sectors / n_data;
+    let remainder = sectors - quotient * n_data;
+    let big_columns = if remainder == 0 {
+        0
+    } else {
+        remainder + n_parity
+    };
+    let column_count = if quotient == 0 {
+        big_columns
+    } else {
+        n_children
+    };
+
+    let mut layout = Vec::with_capacity(column_count as usize);
+    for column in 0..column_count {
+        let mut child = first + column;
+        let mut offset = child_offset;
+        if child >= n_children {
+            child -= n_children;
+            offset += 1 << a_shift;
+        }
+
+        let length = if column < big_columns {
+            (quotient + 1) << a_shift
+        } else {
+            quotient << a_shift
+        };
+
+        layout.push((child, offset, length));
+    }
+
+    ////////////////////////////////
+    // Swap the parity and first data column for RAID-Z1, so that parity is
+    // not always on the same child.
+    if n_parity == 1 && (offset & RAIDZ_SWAP_MASK) != 0 {
+        let (child, offset, _) = layout[0];
+        layout[0].0 = layout[1].0;
+        layout[0].1 = layout[1].1;
+        layout[1].0 = child;
+        layout[1].1 = offset;
+    }
+
+    ////////////////////////////////
+    // Map columns to the children.
+    let mut columns = Vec::with_capacity(layout.len());
+    for (child, offset, length) in layout {
+        let child = child as usize;
+        columns.push(RaidZColumn {
+            child: child,
+            offset: offset,
+            length: length,
+            map: DvaMap::from_vdev(&children[child], a_shift, offset, length, draid)?,
+        });
+    }
+
+    ////////////////////////////////
+    // Allocations are rounded up to a multiple of parity + 1 sectors, so
+    // that the free space left is never too small for a block.
+    let total = sectors + n_parity * (quotient + if remainder == 0 { 0 } else { 1 });
+    let skip_sectors = total.div_ceil(n_parity + 1) * (n_parity + 1) - total;
+
+    Ok(RaidZMap {
+        n_parity: n_parity as usize,
+        columns: columns,
+        skip_sectors: skip_sectors,
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum DvaMapError {
+    /** Checksum of the permutations of a dRAID map does not match.
+     *
+     * - `checksum` - Expected checksum.
+     * - `computed` - Computed checksum.
+     */
+    DraidChecksumMismatch { checksum: u64, computed: u64 },
+
+    /** Invalid sector shift of the top level vdev.
+     *
+     * - `a_shift` - Sector shift.
+     */
+    InvalidAShift { a_shift: u64 },
+
+    /** Block is not at the start of a stripe of a dRAID group.
+     *
+     * - `offset` - Offset in the dRAID vdev.
+     */
+    InvalidDraidOffset { offset: u64 },
+
+    /** Invalid dRAID map.
+     *
+     * - `children` - Number of children.
+     * - `n_perms`  - Number of permutations.
+     */
+    InvalidDraidPermutations { children: u64, n_perms: u64 },
+
+    /** Invalid depth of remapping through indirect vdevs.
+     *
+     * - `depth` - Depth.
+     */
+    InvalidIndirectDepth { depth: usize },
+
+    /** Entries of an indirect mapping are not sorted, or overlap.
+     *
+     * - `vdev`  - Top level vdev ID of the indirect vdev.
+     * - `index` - Index of the entry.
+     */
+    InvalidIndirectMapping { vdev: u64, index: u64 },
+
+    /** Indirect mapping decode error.
+     *
+     * - `err` - [`IndirectMappingDecodeError`]
+     */
+    IndirectMappingDecodeError { err: IndirectMappingDecodeError },
+
+    /** Invalid block size.
+     *
+     * - `size` - Size in bytes.
+     */
+    InvalidSize { size: u64 },
+
+    /** Top level vdev does not have a sector shift.
+     *
+     * - `vdev` - Top level vdev ID.
+     */
+    MissingAShift { vdev: u32 },
+
+    /** Permutations of a dRAID vdev are missing.
+     *
+     * - `children` - Number of children of the dRAID vdev.
+     */
+    MissingDraidPermutations { children: u64 },
+
+    /** Mapping of an indirect vdev is missing.
+     *
+     * - `vdev` - Top level vdev ID of the indirect vdev.
+     */
+    MissingIndirectMapping { vdev: u64 },
+
+    /** Offset of an indirect vdev is not mapped.
+     *
+     * - `vdev`   - Top level vdev ID of the indirect vdev.
+     * - `offset` - Byte offset in the indirect vdev.
+     */
+    UnmappedIndirectOffset { vdev: u64, offset: u64 },
+
+    /** Vdev type cannot be mapped.
+     *
+     * - `vdev_type` - Vdev type.
+     */
+    Unsupported { vdev_type: &'static str },
+
+    /** Vdev decode error.
+     *
+     * - `err` - [`VdevDecodeError`]
+     */
+    VdevDecodeError { err: VdevDecodeError },
+
+    /** Top level vdev not found.
+     *
+     * - `vdev` - Top level vdev ID.
+     */
+    VdevNotFound { vdev: u32 },
+}
+
+impl From<IndirectMappingDecodeError> for DvaMapError {
+    fn from(value: IndirectMappingDecodeError) -> Self {
+        DvaMapError::IndirectMappingDecodeError { err: value }
+    }
+}
+
+impl From<VdevDecodeError> for DvaMapError {
+    fn from(value: VdevDecodeError) -> Self {
+        DvaMapError::VdevDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for DvaMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DvaMapError::DraidChecksumMismatch { checksum, computed } => {
+                write!(
+                    f,
+                    "DVA map error: dRAID map checksum mismatch, expected {checksum:#016x}, computed {computed:#016x}"
+                )
+            }
+            DvaMapError::InvalidAShift { a_shift } => {
+                write!(f, "DVA map error: invalid ashift {a_shift}")
+            }
+            DvaMapError::InvalidDraidOffset { offset } => {
+                write!(f, "DVA map error: invalid dRAID offset {offset}")
+            }
+            DvaMapError::InvalidDraidPermutations { children, n_perms } => {
+                write!(
+                    f,
+                    "DVA map error: invalid dRAID map with {children} children and {n_perms} permutations"
+                )
+            }
+            DvaMapError::InvalidIndirectDepth { depth } => {
+                write!(f, "DVA map error: invalid indirect depth {depth}")
+            }
+            DvaMapError::InvalidIndirectMapping { vdev, index } => {
+                write!(
+                    f,
+                    "DVA map error: invalid indirect mapping for vdev {vdev}, entry {index} is out of order"
+                )
+            }
+            DvaMapError::IndirectMappingDecodeError { err } => {
+                write!(f, "DVA map Indirect Mapping decode error: {err}")
+            }
+            DvaMapError::InvalidSize { size } => {
+                write!(f, "DVA map error: invalid size {size}")
+            }
+            DvaMapError::MissingAShift { vdev } => {
+                write!(f, "DVA map error: missing ashift for vdev {vdev}")
+            }
+            DvaMapError::MissingDraidPermutations { children } => {
+                write!(
+                    f,
+                    "DVA map error: missing dRAID permutations for {children} children"
+                )
+            }
+            DvaMapError::MissingIndirectMapping { vdev } => {
+                write!(f, "DVA map error: missing indirect mapping for vdev {vdev}")
+            }
+            DvaMapError::UnmappedIndirectOffset { vdev, offset } => {
+                write!(
+                    f,
+                    "DVA map error: offset {offset} of indirect vdev {vdev} is not mapped"
+                )
+            }
+            DvaMapError::Unsupported { vdev_type } => {
+                write!(f, "DVA map error: unsupported vdev type '{vdev_type}'")
+            }
+            DvaMapError::VdevDecodeError { err } => {
+                write!(f, "DVA map Vdev decode error: {err}")
+            }
+            DvaMapError::VdevNotFound { vdev } => {
+                write!(f, "DVA map error: vdev {vdev} not found")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DvaMapError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DvaMapError::IndirectMappingDecodeError { err } => Some(err),
+            DvaMapError::VdevDecodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
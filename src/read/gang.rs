@@ -0,0 +1,227 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::checksum::{gang_verifier, gang_verify};
+use crate::phys::{BlockPointer, BlockPointerRegular, Dva, GangHeader, VdevTree};
+use crate::read::block::{check_checksum_type, checksum_matches};
+use crate::read::{read_map, DvaMap, LeafDevices, ReadError, VdevMaps};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Maximum depth of nested gang headers, to stop on loops in damaged pools.
+const GANG_DEPTH_MAX: usize = 16;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Reads a block from a gang DVA, by reading its gang header and members.
+ *
+ * - C reference: `zio_gang_tree_assemble`, `zio_gang_issue`
+ * - `ptr` is the block pointer, and `dva` is one of its DVAs, which must be a
+ *   gang DVA. The gang header is verified using the first DVA of `ptr`.
+ * - Each gang member is read from its first DVA that verifies. Members may
+ *   themselves be gang blocks.
+ * - The data of the members is the physical data of the block, which is
+ *   verified with the checksum of `ptr`. It is not decompressed.
+ * - `maps` are the maps of dRAID and indirect vdevs, as in
+ *   [`DvaMap::from_dva_maps`].
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if the gang header or a member cannot be read, or the
+ * data does not match the checksum of `ptr`.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::checksum::{block_checksum, gang_verifier};
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{
+ *     BlockPointer, BlockPointerRegular, ChecksumType, CompressionType, DmuType, Dva,
+ *     GangHeader, VdevTree,
+ * };
+ * use zfs::read::{read_gang, LeafDevices, LeafRead, VdevMaps};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "file" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 201 },
+ *         "path": { "string": "/a" },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * let mut devices = LeafDevices::new();
+ * devices.insert(201, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ *
+ * // Use closures for cleaner code.
+ * let dva = |offset: u64, asize: u32, is_gang: bool| Dva {
+ *     vdev: 0,
+ *     grid: 0,
+ *     asize: asize,
+ *     offset: offset,
+ *     is_gang: is_gang,
+ * };
+ * let ptr = |dva: Dva, data: &[u8]| BlockPointerRegular {
+ *     checksum_type: ChecksumType::Fletcher4,
+ *     checksum_value: block_checksum(data, Endian::Little, ChecksumType::Fletcher4).unwrap(),
+ *     compression: CompressionType::Off,
+ *     dedup: false,
+ *     dmu: DmuType::PlainFileContents,
+ *     dvas: [dva, Dva { vdev: 0, grid: 0, asize: 0, offset: 0, is_gang: false },
+ *            Dva { vdev: 0, grid: 0, asize: 0, offset: 0, is_gang: false }],
+ *     endian: Endian::Little,
+ *     fill_count: 1,
+ *     level: 0,
+ *     logical_birth_txg: 42,
+ *     logical_size: (data.len() / 512 - 1) as u16,
+ *     physical_birth_txg: 0,
+ *     physical_size: (data.len() / 512 - 1) as u16,
+ * };
+ *
+ * // A block of 1024 bytes, in two gang members at sectors 10 and 11.
+ * let block: Vec<u8> = (0..1024).map(|i| (i * 7) as u8).collect();
+ * devices.write(&LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 5120, length: 512 }, &block[..512]).unwrap();
+ * devices.write(&LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 5632, length: 512 }, &block[512..]).unwrap();
+ *
+ * // The gang header is at sector 0, and the third member is a hole.
+ * let parent = ptr(dva(0, 1, true), &block);
+ * let header = GangHeader {
+ *     block_pointers: [
+ *         BlockPointer::Regular(ptr(dva(10, 1, false), &block[..512])),
+ *         BlockPointer::Regular(ptr(dva(11, 1, false), &block[512..])),
+ *         BlockPointer::Regular(ptr(dva(0, 0, false), &block[..512])),
+ *     ],
+ * };
+ * let verifier = gang_verifier(&parent.dvas[0], parent.birth_txg());
+ * let mut bytes = [0u8; GangHeader::LENGTH];
+ * header.to_bytes(&mut bytes, &verifier, Endian::Little).unwrap();
+ * devices.write(&LeafRead { guid: 201, offset: Dva::LEAF_OFFSET, length: 512 }, &bytes).unwrap();
+ *
+ * let data = read_gang(&vdev_tree, &parent, &parent.dvas[0], &devices, &VdevMaps::default()).unwrap();
+ * assert_eq!(data, block);
+ *
+ * // A damaged member does not match the checksum.
+ * devices.write(&LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + 5632, length: 512 }, &[0u8; 512]).unwrap();
+ * assert!(read_gang(&vdev_tree, &parent, &parent.dvas[0], &devices, &VdevMaps::default()).is_err());
+ * ```
+ */
+pub fn read_gang<D: BlockDevice>(
+    vdev_tree: &VdevTree<'_>,
+    ptr: &BlockPointerRegular,
+    dva: &Dva,
+    devices: &LeafDevices<D>,
+    maps: &VdevMaps,
+) -> Result<Vec<u8>, ReadError> {
+    read_gang_at_depth(vdev_tree, ptr, dva, devices, maps, 0)
+}
+
+/// Reads a block from a gang DVA, at a depth of nested gang headers.
+fn read_gang_at_depth<D: BlockDevice>(
+    vdev_tree: &VdevTree<'_>,
+    ptr: &BlockPointerRegular,
+    dva: &Dva,
+    devices: &LeafDevices<D>,
+    maps: &VdevMaps,
+    depth: usize,
+) -> Result<Vec<u8>, ReadError> {
+    if depth >= GANG_DEPTH_MAX {
+        return Err(ReadError::InvalidGangDepth { depth: depth });
+    }
+
+    check_checksum_type(ptr)?;
+
+    ////////////////////////////////
+    // Read the gang header.
+    let verifier = gang_verifier(&ptr.dvas[0], ptr.birth_txg());
+    let map = DvaMap::from_dva_maps(vdev_tree, dva, GangHeader::LENGTH as u64, maps)?;
+    let verify_header = |data: &[u8]| gang_verify(&data[..GangHeader::LENGTH], &verifier).is_ok();
+    let bytes = read_map(&map, devices, &verify_header)?;
+    let header =
+        GangHeader::from_bytes(bytes[..GangHeader::LENGTH].try_into().unwrap(), &verifier)?;
+
+    ////////////////////////////////
+    // Read the members.
+    let mut data = Vec::new();
+    for member in &header.block_pointers {
+        match member {
+            BlockPointer::Regular(member) if member.is_hole() => (),
+            BlockPointer::Regular(member) => {
+                data.extend_from_slice(&read_member(vdev_tree, member, devices, maps, depth)?)
+            }
+            BlockPointer::Embedded(_) => {
+                return Err(ReadError::UnsupportedBlockPointer { kind: "embedded" })
+            }
+            BlockPointer::Encrypted(_) => {
+                return Err(ReadError::UnsupportedBlockPointer { kind: "encrypted" })
+            }
+        }
+    }
+
+    ////////////////////////////////
+    // Verify the data of the block.
+    let length = ptr.physical_size_bytes();
+    if data.len() as u64 != length {
+        return Err(ReadError::InvalidLength {
+            length: data.len(),
+            expected: length,
+        });
+    }
+
+    if !checksum_matches(ptr, &data) {
+        return Err(ReadError::ChecksumMismatch {});
+    }
+
+    Ok(data)
+}
+
+/** Reads the physical data of a gang member from its first DVA that
+ * verifies.
+ *
+ * - Returns the error of the last DVA if none verify.
+ */
+fn read_member<D: BlockDevice>(
+    vdev_tree: &VdevTree<'_>,
+    member: &BlockPointerRegular,
+    devices: &LeafDevices<D>,
+    maps: &VdevMaps,
+    depth: usize,
+) -> Result<Vec<u8>, ReadError> {
+    check_checksum_type(member)?;
+
+    let length = member.physical_size_bytes();
+    let verify = |data: &[u8]| checksum_matches(member, data);
+
+    let mut result = Err(ReadError::MissingCopies {});
+    for dva in member.dvas.iter().filter(|dva| dva.asize != 0) {
+        result = match dva.is_gang {
+            true => read_gang_at_depth(vdev_tree, member, dva, devices, maps, depth + 1),
+            false => match DvaMap::from_dva_maps(vdev_tree, dva, length, maps) {
+                Ok(map) => read_map(&map, devices, &verify),
+                Err(err) => Err(err.into()),
+            },
+        };
+
+        if let Ok(data) = &mut result {
+            data.truncate(length as usize);
+            break;
+        }
+    }
+
+    result
+}
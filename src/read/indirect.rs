@@ -0,0 +1,295 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::endian::{Decoder, Endian};
+use crate::phys::{IndirectBirthEntry, IndirectMappingEntry, IndirectMappingHeader};
+use crate::read::{read_map, DvaMap, DvaMapError, LeafDevices, ReadError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A segment of a remapped range, on another top level vdev.
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub struct IndirectSegment {
+    /// Top level vdev ID.
+    pub vdev: u32,
+
+    /// Byte offset in the vdev, not including the labels and boot block.
+    pub offset: u64,
+
+    /// Byte length.
+    pub length: u64,
+}
+
+/** Mapping of a removed top level vdev to the new locations of its data.
+ *
+ * - C reference: `vdev_indirect_mapping_t`, `vdev_indirect_remap`
+ * - The entries are the `indirect_object` of the [`crate::phys::VdevIndirect`],
+ *   and `vdev` is its top level vdev ID.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::endian::{Encoder, Endian};
+ * use zfs::phys::{Dva, IndirectMappingEntry, IndirectMappingHeader};
+ * use zfs::read::{IndirectMapping, IndirectSegment};
+ *
+ * // Sectors 0 to 7 of vdev 1 are at sector 100 of vdev 0, and sectors 8 to
+ * // 11 are at sector 40 of vdev 2.
+ * let entries = [
+ *     IndirectMappingEntry {
+ *         src_offset: 0,
+ *         mark: false,
+ *         dst: Dva { vdev: 0, grid: 0, asize: 8, offset: 100, is_gang: false },
+ *     },
+ *     IndirectMappingEntry {
+ *         src_offset: 8,
+ *         mark: false,
+ *         dst: Dva { vdev: 2, grid: 0, asize: 4, offset: 40, is_gang: false },
+ *     },
+ * ];
+ *
+ * let mut data = [0u8; 2 * IndirectMappingEntry::LENGTH];
+ * let mut encoder = Encoder::to_bytes(&mut data, Endian::Little);
+ * for entry in &entries {
+ *     entry.to_encoder(&mut encoder).unwrap();
+ * }
+ *
+ * let header = IndirectMappingHeader {
+ *     max_offset: 12 * 512,
+ *     bytes_mapped: 12 * 512,
+ *     num_entries: 2,
+ *     counts_object: None,
+ * };
+ * let mapping = IndirectMapping::from_bytes(1, &header, &data, Endian::Little).unwrap();
+ *
+ * // A block at sectors 6 to 9 is split over both entries.
+ * assert_eq!(
+ *     mapping.remap(6 * 512, 4 * 512).unwrap(),
+ *     [
+ *         IndirectSegment { vdev: 0, offset: 106 * 512, length: 1024 },
+ *         IndirectSegment { vdev: 2, offset: 40 * 512, length: 1024 },
+ *     ],
+ * );
+ *
+ * // Sector 12 is not mapped.
+ * assert!(mapping.remap(12 * 512, 512).is_err());
+ * ```
+ */
+#[derive(Debug)]
+pub struct IndirectMapping {
+    /// Top level vdev ID of the removed vdev.
+    pub vdev: u64,
+
+    /// Entries, sorted by source offset.
+    pub entries: Vec<IndirectMappingEntry>,
+}
+
+impl IndirectMapping {
+    /** Decodes an [`IndirectMapping`] from the data of an `indirect_object`.
+     *
+     * - `header` is the bonus buffer of the object.
+     * - `endian` is the endian of the block pointers of the object.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the data is too short, or the entries are not
+     * sorted.
+     */
+    pub fn from_bytes(
+        vdev: u64,
+        header: &IndirectMappingHeader,
+        data: &[u8],
+        endian: Endian,
+    ) -> Result<IndirectMapping, DvaMapError> {
+        let decoder = Decoder::from_bytes(data, endian);
+
+        let mut entries: Vec<IndirectMappingEntry> = Vec::new();
+        for index in 0..header.num_entries {
+            let entry = IndirectMappingEntry::from_decoder(&decoder)?;
+
+            if let Some(prev) = entries.last() {
+                if entry.src_offset < prev.src_offset + prev.dst.asize as u64 {
+                    return Err(DvaMapError::InvalidIndirectMapping {
+                        vdev: vdev,
+                        index: index,
+                    });
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(IndirectMapping {
+            vdev: vdev,
+            entries: entries,
+        })
+    }
+
+    /** Remaps a byte range of the removed vdev to segments on other vdevs.
+     *
+     * - C reference: `vdev_indirect_mapping_entry_for_offset`,
+     *   `vdev_indirect_remap`
+     * - A range may be split over consecutive entries, so the segments are in
+     *   order of the range.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if part of the range is not mapped.
+     */
+    pub fn remap(&self, offset: u64, length: u64) -> Result<Vec<IndirectSegment>, DvaMapError> {
+        let mut segments = Vec::new();
+        let mut offset = offset;
+        let mut remaining = length;
+
+        while remaining > 0 {
+            ////////////////////////////////
+            // Find the entry that contains the offset.
+            let index = self
+                .entries
+                .partition_point(|entry| entry.src_offset_bytes() <= offset);
+            let entry = match index.checked_sub(1).map(|index| &self.entries[index]) {
+                Some(entry) if offset < entry.src_offset_bytes() + entry.dst.asize_bytes() => entry,
+                _ => {
+                    return Err(DvaMapError::UnmappedIndirectOffset {
+                        vdev: self.vdev,
+                        offset: offset,
+                    })
+                }
+            };
+
+            ////////////////////////////////
+            // Map the part of the range in the entry.
+            let inner_offset = offset - entry.src_offset_bytes();
+            let inner_length = remaining.min(entry.dst.asize_bytes() - inner_offset);
+
+            segments.push(IndirectSegment {
+                vdev: entry.dst.vdev,
+                offset: entry.dst.offset_bytes() + inner_offset,
+                length: inner_length,
+            });
+
+            offset += inner_length;
+            remaining -= inner_length;
+        }
+
+        Ok(segments)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Transaction groups in which the parts of a removed vdev were copied.
+ *
+ * - C reference: `vdev_indirect_births_t`
+ * - The entries are the `indirect_births` object of the
+ *   [`crate::phys::VdevIndirect`].
+ * - A block pointer that is remapped to the new location of its data gets
+ *   the physical birth of the part that it was in.
+ */
+#[derive(Debug)]
+pub struct IndirectBirths {
+    /// Entries, sorted by offset.
+    pub entries: Vec<IndirectBirthEntry>,
+}
+
+impl IndirectBirths {
+    /** Decodes [`IndirectBirths`] from the data of an `indirect_births`
+     * object.
+     *
+     * - `count` is the number of entries, from the bonus buffer of the object.
+     *
+     * # Errors
+     *
+     * Returns [`DvaMapError`] if the data is too short.
+     */
+    pub fn from_bytes(
+        data: &[u8],
+        count: u64,
+        endian: Endian,
+    ) -> Result<IndirectBirths, DvaMapError> {
+        let decoder = Decoder::from_bytes(data, endian);
+
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            entries.push(IndirectBirthEntry::from_decoder(&decoder)?);
+        }
+
+        Ok(IndirectBirths { entries: entries })
+    }
+
+    /** Returns the transaction group in which the byte offset of the removed
+     * vdev was copied.
+     *
+     * - C reference: `vdev_indirect_births_physbirth`
+     * - Returns [`None`] if the offset was not copied.
+     */
+    pub fn physical_birth(&self, offset: u64) -> Option<u64> {
+        let index = self
+            .entries
+            .partition_point(|entry| entry.max_offset <= offset);
+
+        self.entries
+            .get(index)
+            .map(|entry| entry.physical_birth_txg)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Reads a block from the segments of a remapped range.
+ *
+ * - C reference: `vdev_indirect_io_start`, `vdev_indirect_reconstruct_io_done`
+ * - The segments are the children of a [`DvaMap::Indirect`].
+ * - Each segment is first read without verifying it, and the data of the
+ *   block is verified. If it does not verify, one segment at a time is read
+ *   again, verifying the block with the data of that segment, so that copies
+ *   of a damaged segment are tried.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if a segment cannot be read, or the block does not
+ * verify.
+ */
+pub fn read_indirect<D: BlockDevice>(
+    segments: &[DvaMap],
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<Vec<u8>, ReadError> {
+    ////////////////////////////////
+    // Read each segment.
+    let accept = |_: &[u8]| true;
+    let mut parts = Vec::with_capacity(segments.len());
+    for segment in segments {
+        parts.push(read_map(segment, devices, &accept)?);
+    }
+
+    let data = parts.concat();
+    if verify(&data) {
+        return Ok(data);
+    }
+
+    ////////////////////////////////
+    // Read each segment again, verifying the block.
+    for (index, segment) in segments.iter().enumerate() {
+        let verify_segment = |part: &[u8]| {
+            let mut data = Vec::with_capacity(parts.iter().map(|part| part.len()).sum());
+            for (other, other_part) in parts.iter().enumerate() {
+                data.extend_from_slice(if other == index { part } else { other_part });
+            }
+            verify(&data)
+        };
+
+        if let Ok(part) = read_map(segment, devices, &verify_segment) {
+            parts[index] = part;
+            return Ok(parts.concat());
+        }
+    }
+
+    Err(ReadError::ChecksumMismatch {})
+}
@@ -0,0 +1,316 @@
+use core::fmt;
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::block_device::{BlockDevice, BlockDeviceError};
+use crate::compression::DecompressError;
+use crate::phys::{ChecksumType, GangHeaderDecodeError};
+use crate::read::{DvaMapError, LeafRead};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** The block devices of the leaf vdevs of a pool, by GUID.
+ *
+ * - Leaf vdevs without a device are missing, and their data is read from
+ *   other copies, or reconstructed.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::phys::Dva;
+ * use zfs::read::{LeafDevices, LeafRead};
+ *
+ * let mut devices = LeafDevices::new();
+ * devices.insert(201, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ *
+ * let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET, length: 512 };
+ * devices.write(&read, &[7u8; 512]).unwrap();
+ * assert_eq!(devices.read(&read).unwrap(), [7u8; 512]);
+ *
+ * let read = LeafRead { guid: 202, offset: Dva::LEAF_OFFSET, length: 512 };
+ * assert!(devices.read(&read).is_err());
+ * ```
+ */
+#[derive(Debug)]
+pub struct LeafDevices<D> {
+    devices: BTreeMap<u64, D>,
+}
+
+impl<D: BlockDevice> LeafDevices<D> {
+    /// Creates an empty [`LeafDevices`].
+    pub fn new() -> LeafDevices<D> {
+        LeafDevices {
+            devices: BTreeMap::new(),
+        }
+    }
+
+    /** Inserts the device of a leaf vdev.
+     *
+     * - Returns the previous device of the leaf vdev, if any.
+     */
+    pub fn insert(&mut self, guid: u64, device: D) -> Option<D> {
+        self.devices.insert(guid, device)
+    }
+
+    /// Removes the device of a leaf vdev.
+    pub fn remove(&mut self, guid: u64) -> Option<D> {
+        self.devices.remove(&guid)
+    }
+
+    /// Gets the device of a leaf vdev.
+    pub fn get(&self, guid: u64) -> Option<&D> {
+        self.devices.get(&guid)
+    }
+
+    /// Gets the mutable device of a leaf vdev.
+    pub fn get_mut(&mut self, guid: u64) -> Option<&mut D> {
+        self.devices.get_mut(&guid)
+    }
+
+    /** Reads a [`LeafRead`].
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if the device is missing, or the read fails.
+     */
+    pub fn read(&self, read: &LeafRead) -> Result<Vec<u8>, ReadError> {
+        let device = match self.devices.get(&read.guid) {
+            Some(v) => v,
+            None => return Err(ReadError::MissingDevice { guid: read.guid }),
+        };
+
+        let mut data = vec![0u8; read.length as usize];
+        device.read_at(read.offset, &mut data)?;
+
+        Ok(data)
+    }
+
+    /** Writes `data` to the range of a [`LeafRead`].
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if the device is missing, the length of `data` is
+     * not the length of the read, or the write fails.
+     */
+    pub fn write(&mut self, read: &LeafRead, data: &[u8]) -> Result<(), ReadError> {
+        if data.len() as u64 != read.length {
+            return Err(ReadError::InvalidLength {
+                length: data.len(),
+                expected: read.length,
+            });
+        }
+
+        let device = match self.devices.get_mut(&read.guid) {
+            Some(v) => v,
+            None => return Err(ReadError::MissingDevice { guid: read.guid }),
+        };
+
+        Ok(device.write_at(read.offset, data)?)
+    }
+}
+
+impl<D: BlockDevice> Default for LeafDevices<D> {
+    fn default() -> Self {
+        LeafDevices::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum ReadError {
+    /** Block device error.
+     *
+     * - `err` - [`BlockDeviceError`]
+     */
+    BlockDeviceError { err: BlockDeviceError },
+
+    /// No copy or reconstruction of the block has a valid checksum.
+    ChecksumMismatch {},
+
+    /** Decompress error.
+     *
+     * - `err` - [`DecompressError`]
+     */
+    DecompressError { err: DecompressError },
+
+    /** All DVAs of a block pointer failed.
+     *
+     * - `errors` - Index and error of each DVA that was tried.
+     */
+    DvaErrors { errors: Vec<(usize, ReadError)> },
+
+    /** DVA map error.
+     *
+     * - `err` - [`DvaMapError`]
+     */
+    DvaMapError { err: DvaMapError },
+
+    /** Gang header decode error.
+     *
+     * - `err` - [`GangHeaderDecodeError`]
+     */
+    GangHeaderDecodeError { err: GangHeaderDecodeError },
+
+    /** Gang headers are nested too deep.
+     *
+     * - `depth` - Depth of the gang header.
+     */
+    InvalidGangDepth { depth: usize },
+
+    /** Index of a block pointer is out of range.
+     *
+     * - `index` - Index of the block pointer.
+     * - `count` - Number of block pointers.
+     */
+    InvalidIndex { index: usize, count: usize },
+
+    /** Invalid length of data.
+     *
+     * - `length`   - Length of data.
+     * - `expected` - Expected length.
+     */
+    InvalidLength { length: usize, expected: u64 },
+
+    /// All copies of the block are missing.
+    MissingCopies {},
+
+    /** Device of a leaf vdev is missing.
+     *
+     * - `guid` - GUID of the leaf vdev.
+     */
+    MissingDevice { guid: u64 },
+
+    /** More columns are missing than can be reconstructed from parity.
+     *
+     * - `missing`  - Number of missing columns.
+     * - `n_parity` - Number of parity columns.
+     */
+    Unrecoverable { missing: usize, n_parity: usize },
+
+    /** Block pointer kind is not supported.
+     *
+     * - `kind` - Kind of block pointer.
+     */
+    UnsupportedBlockPointer { kind: &'static str },
+
+    /** Checksum type is not supported.
+     *
+     * - `checksum_type` - [`ChecksumType`]
+     */
+    UnsupportedChecksum { checksum_type: ChecksumType },
+
+    /// A worker thread panicked while reading the block.
+    WorkerFailed {},
+}
+
+impl From<BlockDeviceError> for ReadError {
+    fn from(value: BlockDeviceError) -> Self {
+        ReadError::BlockDeviceError { err: value }
+    }
+}
+
+impl From<DecompressError> for ReadError {
+    fn from(value: DecompressError) -> Self {
+        ReadError::DecompressError { err: value }
+    }
+}
+
+impl From<DvaMapError> for ReadError {
+    fn from(value: DvaMapError) -> Self {
+        ReadError::DvaMapError { err: value }
+    }
+}
+
+impl From<GangHeaderDecodeError> for ReadError {
+    fn from(value: GangHeaderDecodeError) -> Self {
+        ReadError::GangHeaderDecodeError { err: value }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::BlockDeviceError { err } => {
+                write!(f, "Read Block Device error: {err}")
+            }
+            ReadError::ChecksumMismatch {} => {
+                write!(f, "Read error: checksum mismatch")
+            }
+            ReadError::DecompressError { err } => {
+                write!(f, "Read Decompress error: {err}")
+            }
+            ReadError::DvaErrors { errors } => {
+                write!(f, "Read error: all DVAs failed")?;
+                for (index, err) in errors {
+                    write!(f, ", dva[{index}]: {err}")?;
+                }
+                Ok(())
+            }
+            ReadError::DvaMapError { err } => {
+                write!(f, "Read DVA map error: {err}")
+            }
+            ReadError::GangHeaderDecodeError { err } => {
+                write!(f, "Read Gang Header decode error: {err}")
+            }
+            ReadError::InvalidGangDepth { depth } => {
+                write!(f, "Read error: gang headers nested {depth} deep")
+            }
+            ReadError::InvalidIndex { index, count } => {
+                write!(
+                    f,
+                    "Read error: block pointer index {index} out of range for {count} block pointers"
+                )
+            }
+            ReadError::InvalidLength { length, expected } => {
+                write!(
+                    f,
+                    "Read error: invalid length {length}, expected {expected}"
+                )
+            }
+            ReadError::MissingCopies {} => {
+                write!(f, "Read error: all copies are missing")
+            }
+            ReadError::MissingDevice { guid } => {
+                write!(f, "Read error: missing device for vdev {guid}")
+            }
+            ReadError::Unrecoverable { missing, n_parity } => {
+                write!(
+                    f,
+                    "Read error: {missing} missing columns, but only {n_parity} parity columns"
+                )
+            }
+            ReadError::UnsupportedBlockPointer { kind } => {
+                write!(f, "Read error: unsupported {kind} block pointer")
+            }
+            ReadError::UnsupportedChecksum { checksum_type } => {
+                write!(f, "Read error: unsupported checksum type {checksum_type}")
+            }
+            ReadError::WorkerFailed {} => {
+                write!(f, "Read error: worker thread panicked")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ReadError::BlockDeviceError { err } => Some(err),
+            ReadError::DecompressError { err } => Some(err),
+            ReadError::DvaMapError { err } => Some(err),
+            ReadError::GangHeaderDecodeError { err } => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::read::{DvaMap, LeafDevices, LeafRead, ReadError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A block read from a mirror vdev.
+ */
+#[derive(Debug)]
+pub struct MirrorRead {
+    /// Data of the block.
+    pub data: Vec<u8>,
+
+    /// Copy that has the data.
+    pub good_copy: LeafRead,
+
+    /// Copies that failed or had bad data. [`read_mirror`] only reads the
+    /// copies before the good copy, and [`scrub_mirror`] reads all of them.
+    pub bad_copies: Vec<LeafRead>,
+}
+
+impl MirrorRead {
+    /** Writes the good data over the bad copies.
+     *
+     * - C reference: `vdev_mirror_io_done` (self healing)
+     * - Returns the number of copies written.
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if a write fails, for example if a device is read
+     * only. The copies before it were written.
+     */
+    pub fn repair<D: BlockDevice>(&self, devices: &mut LeafDevices<D>) -> Result<usize, ReadError> {
+        for copy in &self.bad_copies {
+            devices.write(copy, &self.data)?;
+        }
+
+        Ok(self.bad_copies.len())
+    }
+}
+
+/** Reads a block from the copies of a mirror vdev.
+ *
+ * - C reference: `vdev_mirror_io_start`, `vdev_mirror_io_done`
+ * - The copies are the children of a [`DvaMap::Mirror`], and are tried in
+ *   order until one verifies. Copies of nested replacing or spare vdevs are
+ *   also tried.
+ * - `verify` checks the data of the block, usually with the checksum of the
+ *   block pointer. The data is rounded up to the sector size, so it may need
+ *   to be truncated to the physical size of the block.
+ * - Copies without a device are skipped, and are not bad copies, since they
+ *   cannot be repaired.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if all copies are missing, or no copy verifies.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::{BlockDevice, MemoryDevice};
+ * use zfs::checksum::{block_checksum, block_verify};
+ * use zfs::endian::Endian;
+ * use zfs::phys::{ChecksumType, Dva};
+ * use zfs::read::{read_mirror, DvaMap, LeafDevices, LeafRead};
+ *
+ * let copies: Vec<DvaMap> = (201..=203)
+ *     .map(|guid| DvaMap::Leaf(LeafRead { guid: guid, offset: Dva::LEAF_OFFSET, length: 512 }))
+ *     .collect();
+ *
+ * let mut devices = LeafDevices::new();
+ * for guid in 201..=203 {
+ *     devices.insert(guid, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ * }
+ *
+ * // The first copy is damaged, the second is good, and the third is missing.
+ * let block = [5u8; 512];
+ * let bad = [6u8; 512];
+ * devices.get_mut(201).unwrap().write_at(Dva::LEAF_OFFSET, &bad).unwrap();
+ * devices.get_mut(202).unwrap().write_at(Dva::LEAF_OFFSET, &block).unwrap();
+ * devices.remove(203);
+ *
+ * let checksum = block_checksum(&block, Endian::Little, ChecksumType::Fletcher4).unwrap();
+ * let verify = |data: &[u8]| {
+ *     block_verify(data, Endian::Little, ChecksumType::Fletcher4, &checksum).is_ok()
+ * };
+ * let read = read_mirror(&copies, &devices, &verify).unwrap();
+ * assert_eq!(read.data, block);
+ * assert_eq!(read.good_copy.guid, 202);
+ * assert_eq!(read.bad_copies.iter().map(|c| c.guid).collect::<Vec<u64>>(), [201]);
+ *
+ * // Write the good data over the damaged copy.
+ * assert_eq!(read.repair(&mut devices).unwrap(), 1);
+ * let read = read_mirror(&copies, &devices, &verify).unwrap();
+ * assert_eq!(read.good_copy.guid, 201);
+ * assert!(read.bad_copies.is_empty());
+ * ```
+ */
+pub fn read_mirror<D: BlockDevice>(
+    copies: &[DvaMap],
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<MirrorRead, ReadError> {
+    read_copies(copies, devices, verify, false)
+}
+
+/** Reads and verifies every copy of a block from a mirror vdev.
+ *
+ * - C reference: `vdev_mirror_io_start` (`ZIO_FLAG_SCRUB`)
+ * - Like [`read_mirror`], but every copy is read, even after a copy
+ *   verifies, so that [`MirrorRead::bad_copies`] has all the bad copies, and
+ *   [`MirrorRead::repair`] repairs all of them.
+ * - The good copy is the first copy that verifies.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if all copies are missing, or no copy verifies.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::{BlockDevice, MemoryDevice};
+ * use zfs::checksum::{block_checksum, block_verify};
+ * use zfs::endian::Endian;
+ * use zfs::phys::{ChecksumType, Dva};
+ * use zfs::read::{read_mirror, scrub_mirror, DvaMap, LeafDevices, LeafRead};
+ *
+ * let copies: Vec<DvaMap> = (201..=203)
+ *     .map(|guid| DvaMap::Leaf(LeafRead { guid: guid, offset: Dva::LEAF_OFFSET, length: 512 }))
+ *     .collect();
+ *
+ * let mut devices = LeafDevices::new();
+ * for guid in 201..=203 {
+ *     devices.insert(guid, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ * }
+ *
+ * // The second copy is damaged.
+ * let block = [5u8; 512];
+ * let bad = [6u8; 512];
+ * devices.get_mut(201).unwrap().write_at(Dva::LEAF_OFFSET, &block).unwrap();
+ * devices.get_mut(202).unwrap().write_at(Dva::LEAF_OFFSET, &bad).unwrap();
+ * devices.get_mut(203).unwrap().write_at(Dva::LEAF_OFFSET, &block).unwrap();
+ *
+ * let checksum = block_checksum(&block, Endian::Little, ChecksumType::Fletcher4).unwrap();
+ * let verify = |data: &[u8]| {
+ *     block_verify(data, Endian::Little, ChecksumType::Fletcher4, &checksum).is_ok()
+ * };
+ *
+ * // A read stops at the first copy, a scrub finds the damaged copy.
+ * let read = read_mirror(&copies, &devices, &verify).unwrap();
+ * assert!(read.bad_copies.is_empty());
+ *
+ * let read = scrub_mirror(&copies, &devices, &verify).unwrap();
+ * assert_eq!(read.data, block);
+ * assert_eq!(read.good_copy.guid, 201);
+ * assert_eq!(read.bad_copies.iter().map(|c| c.guid).collect::<Vec<u64>>(), [202]);
+ *
+ * // Write the good data over the damaged copy.
+ * assert_eq!(read.repair(&mut devices).unwrap(), 1);
+ * let read = scrub_mirror(&copies, &devices, &verify).unwrap();
+ * assert!(read.bad_copies.is_empty());
+ * ```
+ */
+pub fn scrub_mirror<D: BlockDevice>(
+    copies: &[DvaMap],
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<MirrorRead, ReadError> {
+    read_copies(copies, devices, verify, true)
+}
+
+/** Reads the copies of a mirror, until one verifies, or all of them if
+ * `scrub` is true.
+ */
+fn read_copies<D: BlockDevice>(
+    copies: &[DvaMap],
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+    scrub: bool,
+) -> Result<MirrorRead, ReadError> {
+    ////////////////////////////////
+    // Find the copies of the leaf vdevs.
+    let mut leaves = Vec::new();
+    for copy in copies {
+        push_leaves(copy, &mut leaves);
+    }
+
+    ////////////////////////////////
+    // Try each copy.
+    let mut good = None;
+    let mut bad_copies = Vec::new();
+
+    for leaf in leaves {
+        match devices.read(&leaf) {
+            Ok(data) if verify(&data) => {
+                if good.is_none() {
+                    good = Some((data, leaf));
+                }
+                if !scrub {
+                    break;
+                }
+            }
+            Err(ReadError::MissingDevice { guid: _ }) => (),
+            _ => bad_copies.push(leaf),
+        }
+    }
+
+    match good {
+        Some((data, good_copy)) => Ok(MirrorRead {
+            data: data,
+            good_copy: good_copy,
+            bad_copies: bad_copies,
+        }),
+        None if bad_copies.is_empty() => Err(ReadError::MissingCopies {}),
+        None => Err(ReadError::ChecksumMismatch {}),
+    }
+}
+
+/// Pushes the leaf reads of the copies of a mirror.
+fn push_leaves(copy: &DvaMap, leaves: &mut Vec<LeafRead>) {
+    match copy {
+        DvaMap::Leaf(read) => leaves.push(*read),
+        DvaMap::Missing { guid: _ } => (),
+        DvaMap::Mirror(copies) => {
+            for copy in copies {
+                push_leaves(copy, leaves);
+            }
+        }
+        DvaMap::RaidZ(_) | DvaMap::Draid(_) | DvaMap::Indirect(_) => (),
+    }
+}
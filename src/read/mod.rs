@@ -0,0 +1,46 @@
+/*! Reading blocks from the vdevs of a pool.
+ *
+ * - [`DvaMap`] maps a [`crate::phys::Dva`] to the reads of the leaf vdevs
+ *   of a [`crate::phys::VdevTree`].
+ * - [`LeafDevices`] are the block devices of the leaf vdevs.
+ * - [`read_mirror`] reads a block from the copies of a mirror vdev, and
+ *   [`scrub_mirror`] reads and verifies all of them.
+ * - [`read_raidz`] reads a block from a RAID-Z vdev, with reconstruction.
+ * - [`read_draid`] reads a block from a dRAID vdev, with reconstruction.
+ * - [`read_indirect`] reads a block from the segments of a removed vdev,
+ *   remapped with its [`IndirectMapping`].
+ * - [`read_map`] reads a block from any [`DvaMap`].
+ * - [`read_block`] reads the logical data of a block pointer, trying each
+ *   DVA.
+ * - [`read_gang`] reads a block from the members of a gang DVA.
+ * - With the `std` feature, [`BlockCache`] caches the logical data of
+ *   blocks, with a budget of bytes, and [`BlockReader`] reads many blocks
+ *   concurrently on a pool of threads, and reads ahead the children of
+ *   indirect blocks.
+ * - The rest of the module only needs the `alloc` feature.
+ */
+mod block;
+#[cfg(feature = "std")]
+mod cache;
+mod draid;
+mod dva_map;
+mod gang;
+mod indirect;
+mod leaf;
+mod mirror;
+#[cfg(feature = "std")]
+mod parallel;
+mod raidz;
+
+pub use block::{indirect_block_pointers, read_block, read_map};
+#[cfg(feature = "std")]
+pub use cache::{BlockCache, BlockCacheStats, BlockKey};
+pub use draid::{read_draid, DraidGeometry, DraidMap, DraidPermutations, DraidRead};
+pub use dva_map::{DvaMap, DvaMapError, LeafRead, RaidZColumn, RaidZMap, VdevMaps};
+pub use gang::read_gang;
+pub use indirect::{read_indirect, IndirectBirths, IndirectMapping, IndirectSegment};
+pub use leaf::{LeafDevices, ReadError};
+pub use mirror::{read_mirror, scrub_mirror, MirrorRead};
+#[cfg(feature = "std")]
+pub use parallel::BlockReader;
+pub use raidz::{read_raidz, RaidZRead};
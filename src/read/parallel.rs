@@ -0,0 +1,577 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use std::collections::{BTreeSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::phys::{BlockPointer, BlockPointerRegular, VdevTree};
+use crate::read::block::{check_checksum_type, read_dva_map};
+use crate::read::{read_block, BlockCache, BlockKey, DvaMap, LeafDevices, ReadError, VdevMaps};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Default number of threads of a [`BlockReader`].
+const THREADS_DEFAULT: usize = 8;
+
+/// Default number of block pointers read ahead by [`BlockReader::read_child`].
+const PREFETCH_DEFAULT: usize = 32;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Index and result of a block read by a [`ReadJob`].
+type ReadJobResult = (usize, Result<Arc<[u8]>, ReadError>);
+
+/// A block to read on a worker thread, with the maps of its DVAs.
+#[derive(Debug)]
+struct ReadJob {
+    /// Index of the block pointer.
+    index: usize,
+
+    ptr: BlockPointerRegular,
+
+    /// Index and map of each DVA that is mapped.
+    maps: Vec<(usize, DvaMap)>,
+
+    /// Index and error of each DVA that is not mapped.
+    errors: Vec<(usize, ReadError)>,
+
+    /// Sender of the result, or [`None`] for a prefetch.
+    sender: Option<Sender<ReadJobResult>>,
+
+    /// Key of the block for a prefetch, removed from the prefetching keys
+    /// when the job is done.
+    key: Option<BlockKey>,
+}
+
+impl ReadJob {
+    /** Reads the block from its first DVA that is read, matches the
+     * checksum, and decompresses, like [`read_block`].
+     *
+     * - The block is cached, and the result is sent, if there is a sender.
+     */
+    fn run<D: BlockDevice>(mut self, shared: &Shared<D>) {
+        let mut errors = Vec::new();
+        let mut data = None;
+        for (index, map) in &self.maps {
+            match read_dva_map(&self.ptr, map, &shared.devices) {
+                Ok(v) => {
+                    data = Some(v);
+                    break;
+                }
+                Err(err) => errors.push((*index, err)),
+            }
+        }
+
+        let ptr = BlockPointer::Regular(self.ptr);
+        let result = match data {
+            Some(data) => {
+                let data: Arc<[u8]> = data.into();
+                shared.cache.insert(&ptr, data.clone());
+                Ok(data)
+            }
+            None => {
+                errors.append(&mut self.errors);
+                errors.sort_by_key(|(index, _)| *index);
+                Err(ReadError::DvaErrors { errors: errors })
+            }
+        };
+
+        if let Some(sender) = self.sender {
+            // The caller may have stopped waiting.
+            let _ = sender.send((self.index, result));
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Jobs of the worker threads.
+#[derive(Debug, Default)]
+struct JobQueue {
+    /// Jobs that are not started.
+    jobs: VecDeque<ReadJob>,
+
+    /// Number of jobs that are not done.
+    pending: usize,
+
+    /// Keys of the blocks that are queued or read by a prefetch.
+    prefetching: BTreeSet<BlockKey>,
+
+    /// The worker threads exit when set.
+    is_shutdown: bool,
+}
+
+/// State shared by a [`BlockReader`] and its worker threads.
+#[derive(Debug)]
+struct Shared<D> {
+    devices: Arc<LeafDevices<D>>,
+    cache: Arc<BlockCache>,
+    queue: Mutex<JobQueue>,
+
+    /// Notified when a job is queued, or on shutdown.
+    ready: Condvar,
+
+    /// Notified when there are no pending jobs.
+    idle: Condvar,
+}
+
+impl<D> Shared<D> {
+    /// Locks the queue, ignoring a panic of another thread that held it.
+    fn lock(&self) -> MutexGuard<'_, JobQueue> {
+        match self.queue.lock() {
+            Ok(v) => v,
+            Err(err) => err.into_inner(),
+        }
+    }
+
+    /// Queues jobs for the worker threads.
+    fn push(&self, jobs: Vec<ReadJob>) {
+        if jobs.is_empty() {
+            return;
+        }
+
+        let mut queue = self.lock();
+        queue.pending += jobs.len();
+        queue.jobs.extend(jobs);
+        self.ready.notify_all();
+    }
+}
+
+/** Runs the jobs of the queue, until shutdown.
+ *
+ * - A job that panics drops its sender, so the caller waiting for it gets
+ *   [`ReadError::WorkerFailed`]. The key of a prefetch is removed whether
+ *   the job panics or not, so the block can be prefetched again.
+ */
+fn work<D: BlockDevice>(shared: &Shared<D>) {
+    loop {
+        ////////////////////////////////
+        // Wait for a job.
+        let job = {
+            let mut queue = shared.lock();
+            loop {
+                if queue.is_shutdown {
+                    return;
+                }
+                if let Some(job) = queue.jobs.pop_front() {
+                    break job;
+                }
+                queue = match shared.ready.wait(queue) {
+                    Ok(v) => v,
+                    Err(err) => err.into_inner(),
+                };
+            }
+        };
+
+        ////////////////////////////////
+        // Run it, and notify when all jobs are done.
+        let key = job.key;
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| job.run(shared)));
+
+        let mut queue = shared.lock();
+        if let Some(key) = key {
+            queue.prefetching.remove(&key);
+        }
+        queue.pending -= 1;
+        if queue.pending == 0 {
+            shared.idle.notify_all();
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** Reads many blocks concurrently, on a pool of worker threads.
+ *
+ * - Reads are issued from up to `threads` worker threads at a time, which
+ *   keeps many reads in flight on devices with a deep queue, such as SSDs
+ *   and arrays, and spreads checksums and decompression over the cores.
+ * - The worker threads are started by the first read, take the jobs from a
+ *   queue, and run until the [`BlockReader`] is dropped.
+ * - The DVAs are mapped on the calling thread, and the leaf vdevs are read,
+ *   verified, and decompressed on the worker threads. Gang blocks, holes,
+ *   and embedded block pointers are read on the calling thread with
+ *   [`read_block`].
+ * - Blocks are read through the [`BlockCache`], so blocks that are cached
+ *   are not read again, and blocks that are read are cached.
+ * - [`BlockReader::prefetch`] queues blocks to read into the cache, and
+ *   returns without waiting for them. [`BlockReader::read_child`] prefetches
+ *   the block pointers of an indirect block that follow the one that is
+ *   read, so that reading the children in order hits the cache.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use std::sync::Arc;
+ *
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::checksum::block_checksum;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{
+ *     BlockPointer, BlockPointerRegular, ChecksumType, CompressionType, DmuType, Dva, VdevTree,
+ * };
+ * use zfs::read::{BlockCache, BlockReader, LeafDevices, LeafRead, ReadError, VdevMaps};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "file" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 201 },
+ *         "path": { "string": "/a" },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * let mut devices = LeafDevices::new();
+ * devices.insert(201, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ *
+ * // Use closures for cleaner code.
+ * let empty = || Dva { vdev: 0, grid: 0, asize: 0, offset: 0, is_gang: false };
+ * let ptr = |sector: u64, data: &[u8]| BlockPointer::Regular(BlockPointerRegular {
+ *     checksum_type: ChecksumType::Fletcher4,
+ *     checksum_value: block_checksum(data, Endian::Little, ChecksumType::Fletcher4).unwrap(),
+ *     compression: CompressionType::Off,
+ *     dedup: false,
+ *     dmu: DmuType::PlainFileContents,
+ *     dvas: [Dva { vdev: 0, grid: 0, asize: 1, offset: sector, is_gang: false }, empty(), empty()],
+ *     endian: Endian::Little,
+ *     fill_count: 1,
+ *     level: 0,
+ *     logical_birth_txg: 42,
+ *     logical_size: 0,
+ *     physical_birth_txg: 0,
+ *     physical_size: 0,
+ * });
+ *
+ * // 64 blocks of one sector, and a block pointer that does not match.
+ * let mut ptrs = Vec::new();
+ * for sector in 0..64 {
+ *     let block = [sector as u8; 512];
+ *     let read = LeafRead { guid: 201, offset: Dva::LEAF_OFFSET + sector * 512, length: 512 };
+ *     devices.write(&read, &block).unwrap();
+ *     ptrs.push(ptr(sector, &block));
+ * }
+ * ptrs.push(ptr(0, &[1u8; 512]));
+ *
+ * let devices = Arc::new(devices);
+ * let maps = VdevMaps::default();
+ * let cache = Arc::new(BlockCache::new(1024 * 1024));
+ * let reader = BlockReader::new(&vdev_tree, devices.clone(), &maps, cache).with_threads(4);
+ *
+ * let results = reader.read_blocks(&ptrs);
+ * for sector in 0..64 {
+ *     assert_eq!(**results[sector].as_ref().unwrap(), [sector as u8; 512]);
+ * }
+ * assert!(results[64].is_err());
+ *
+ * // The first child of the block pointers is read, and the children after
+ * // it are prefetched, so the second child is cached.
+ * let cache = Arc::new(BlockCache::new(1024 * 1024));
+ * let reader = BlockReader::new(&vdev_tree, devices, &maps, cache.clone()).with_prefetch(8);
+ * assert_eq!(*reader.read_child(&ptrs, 0).unwrap(), [0u8; 512]);
+ * reader.wait();
+ * assert_eq!(*reader.read_child(&ptrs, 1).unwrap(), [1u8; 512]);
+ * assert_eq!((cache.stats().hits, cache.stats().entries), (1, 8));
+ *
+ * assert!(matches!(
+ *     reader.read_child(&ptrs, 65),
+ *     Err(ReadError::InvalidIndex { index: 65, count: 65 }),
+ * ));
+ * ```
+ */
+#[derive(Debug)]
+pub struct BlockReader<'a, 'b, D> {
+    vdev_tree: &'a VdevTree<'b>,
+    maps: &'a VdevMaps,
+    shared: Arc<Shared<D>>,
+    workers: OnceLock<Vec<JoinHandle<()>>>,
+    threads: usize,
+    prefetch: usize,
+}
+
+impl<'a, 'b, D: BlockDevice + Send + Sync + 'static> BlockReader<'a, 'b, D> {
+    /** Creates a [`BlockReader`] with the default number of threads (8), and
+     * of block pointers to read ahead (32).
+     *
+     * - `devices` and `cache` are shared with the worker threads.
+     */
+    pub fn new(
+        vdev_tree: &'a VdevTree<'b>,
+        devices: Arc<LeafDevices<D>>,
+        maps: &'a VdevMaps,
+        cache: Arc<BlockCache>,
+    ) -> BlockReader<'a, 'b, D> {
+        BlockReader {
+            vdev_tree: vdev_tree,
+            maps: maps,
+            shared: Arc::new(Shared {
+                devices: devices,
+                cache: cache,
+                queue: Mutex::new(JobQueue::default()),
+                ready: Condvar::new(),
+                idle: Condvar::new(),
+            }),
+            workers: OnceLock::new(),
+            threads: THREADS_DEFAULT,
+            prefetch: PREFETCH_DEFAULT,
+        }
+    }
+
+    /** Sets the number of worker threads.
+     *
+     * - There is at least one worker thread.
+     */
+    pub fn with_threads(mut self, threads: usize) -> BlockReader<'a, 'b, D> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets the number of block pointers read by [`BlockReader::read_child`],
+    /// including the child.
+    pub fn with_prefetch(mut self, prefetch: usize) -> BlockReader<'a, 'b, D> {
+        self.prefetch = prefetch.max(1);
+        self
+    }
+
+    /** Reads the logical data of blocks concurrently.
+     *
+     * - Returns the result of each block pointer, in order, as with
+     *   [`read_block`].
+     * - The result of a block is [`ReadError::WorkerFailed`] if its worker
+     *   thread panics while reading it.
+     */
+    pub fn read_blocks(&self, ptrs: &[BlockPointer]) -> Vec<Result<Arc<[u8]>, ReadError>> {
+        let mut results: Vec<Option<Result<Arc<[u8]>, ReadError>>> =
+            ptrs.iter().map(|_| None).collect();
+
+        ////////////////////////////////
+        // Check the cache, and map the DVAs of the blocks to read.
+        let (sender, receiver) = mpsc::channel();
+        let mut jobs = Vec::new();
+        for (index, ptr) in ptrs.iter().enumerate() {
+            if let Some(data) = self.shared.cache.get(ptr) {
+                results[index] = Some(Ok(data));
+                continue;
+            }
+
+            match self.job(index, ptr, Some(sender.clone())) {
+                Some(job) => jobs.push(job),
+                None => results[index] = Some(self.read_block(ptr)),
+            }
+        }
+        drop(sender);
+
+        ////////////////////////////////
+        // Read the blocks on the worker threads.
+        // The receiver is disconnected when every job has sent its result, or
+        // dropped its sender by panicking.
+        self.push(jobs);
+
+        while let Ok((index, result)) = receiver.recv() {
+            results[index] = Some(result);
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(ReadError::WorkerFailed {})))
+            .collect()
+    }
+
+    /** Queues the blocks to read into the cache, and returns without waiting
+     * for them.
+     *
+     * - C reference: `dbuf_prefetch`
+     * - Blocks that are cached, or already being prefetched, are skipped.
+     *   Gang blocks, holes, and embedded block pointers are not prefetched.
+     * - Errors are ignored.
+     */
+    pub fn prefetch(&self, ptrs: &[BlockPointer]) {
+        let mut jobs = Vec::new();
+        for (index, ptr) in ptrs.iter().enumerate() {
+            let key = match BlockKey::from_ptr(ptr) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            if self.shared.cache.contains(ptr) || self.shared.lock().prefetching.contains(&key) {
+                continue;
+            }
+
+            if let Some(job) = self.job(index, ptr, None) {
+                self.shared.lock().prefetching.insert(key);
+                jobs.push(job);
+            }
+        }
+
+        self.push(jobs);
+    }
+
+    /** Reads a child of an indirect block, and prefetches the children after
+     * it.
+     *
+     * - C reference: `dmu_zfetch`
+     * - `ptrs` are the block pointers of an indirect block (see
+     *   [`crate::read::indirect_block_pointers`]), and `index` is the child to read.
+     * - The children after `index`, up to the prefetch count of children
+     *   including it, are queued with [`BlockReader::prefetch`] after the
+     *   child.
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if the child cannot be read, `index` is out of
+     * range, or the worker thread panics while reading the child. Errors of
+     * the other children are ignored.
+     */
+    pub fn read_child(&self, ptrs: &[BlockPointer], index: usize) -> Result<Arc<[u8]>, ReadError> {
+        let ptr = match ptrs.get(index) {
+            Some(v) => v,
+            None => {
+                return Err(ReadError::InvalidIndex {
+                    index: index,
+                    count: ptrs.len(),
+                })
+            }
+        };
+
+        if let Some(data) = self.shared.cache.get(ptr) {
+            return Ok(data);
+        }
+
+        ////////////////////////////////
+        // Queue the child before the children after it.
+        let (sender, receiver) = mpsc::channel();
+        let job = match self.job(0, ptr, Some(sender)) {
+            Some(v) => v,
+            None => return self.read_block(ptr),
+        };
+        self.push(Vec::from([job]));
+
+        let end = ptrs.len().min(index + self.prefetch);
+        self.prefetch(&ptrs[index + 1..end]);
+
+        match receiver.recv() {
+            Ok((_, result)) => result,
+            Err(_) => Err(ReadError::WorkerFailed {}),
+        }
+    }
+
+    /// Waits until the worker threads have read all queued blocks, including
+    /// prefetches.
+    pub fn wait(&self) {
+        let mut queue = self.shared.lock();
+        while queue.pending > 0 {
+            queue = match self.shared.idle.wait(queue) {
+                Ok(v) => v,
+                Err(err) => err.into_inner(),
+            };
+        }
+    }
+
+    /// Reads a block on the calling thread, and caches it.
+    fn read_block(&self, ptr: &BlockPointer) -> Result<Arc<[u8]>, ReadError> {
+        let data: Arc<[u8]> =
+            read_block(self.vdev_tree, ptr, &self.shared.devices, self.maps)?.into();
+        self.shared.cache.insert(ptr, data.clone());
+
+        Ok(data)
+    }
+
+    /// Queues jobs, and starts the worker threads if they are not started.
+    fn push(&self, jobs: Vec<ReadJob>) {
+        if jobs.is_empty() {
+            return;
+        }
+
+        self.workers.get_or_init(|| {
+            (0..self.threads)
+                .map(|_| {
+                    let shared = self.shared.clone();
+                    thread::spawn(move || work(&shared))
+                })
+                .collect()
+        });
+
+        self.shared.push(jobs);
+    }
+
+    /** Returns the [`ReadJob`] of a block pointer, or [`None`] if it is read
+     * on the calling thread.
+     */
+    fn job(
+        &self,
+        index: usize,
+        ptr: &BlockPointer,
+        sender: Option<Sender<ReadJobResult>>,
+    ) -> Option<ReadJob> {
+        let key = match sender {
+            Some(_) => None,
+            None => BlockKey::from_ptr(ptr),
+        };
+
+        let ptr = match ptr {
+            BlockPointer::Regular(ptr) if !ptr.is_hole() => ptr,
+            _ => return None,
+        };
+
+        if check_checksum_type(ptr).is_err() || ptr.dvas.iter().any(|dva| dva.is_gang) {
+            return None;
+        }
+
+        let mut maps = Vec::new();
+        let mut errors = Vec::new();
+        for (index, dva) in ptr.dvas.iter().enumerate() {
+            if dva.asize == 0 {
+                continue;
+            }
+
+            match DvaMap::from_dva_maps(self.vdev_tree, dva, ptr.physical_size_bytes(), self.maps) {
+                Ok(map) => maps.push((index, map)),
+                Err(err) => errors.push((index, err.into())),
+            }
+        }
+
+        Some(ReadJob {
+            index: index,
+            ptr: ptr.clone(),
+            maps: maps,
+            errors: errors,
+            sender: sender,
+            key: key,
+        })
+    }
+}
+
+impl<D> Drop for BlockReader<'_, '_, D> {
+    /// Stops the worker threads, and drops the jobs that are not started.
+    fn drop(&mut self) {
+        {
+            let mut queue = self.shared.lock();
+            queue.is_shutdown = true;
+            queue.pending -= queue.jobs.len();
+            queue.jobs.clear();
+        }
+        self.shared.ready.notify_all();
+
+        if let Some(workers) = self.workers.take() {
+            for worker in workers {
+                let _ = worker.join();
+            }
+        }
+    }
+}
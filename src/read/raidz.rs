@@ -0,0 +1,545 @@
+use core::result::Result;
+use core::result::Result::{Err, Ok};
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::block_device::BlockDevice;
+use crate::read::{DvaMap, LeafDevices, RaidZMap, ReadError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Reduction polynomial of GF(2^8), without the x^8 term.
+const GF_POLYNOMIAL: u8 = 0x1d;
+
+/// Generators of the P, Q, and R parity columns.
+const PARITY_GENERATORS: [u8; 3] = [1, 2, 4];
+
+/// Exponent and logarithm tables of GF(2^8).
+const GF_TABLES: ([u8; 512], [u8; 256]) = gf_tables();
+
+const fn gf_tables() -> ([u8; 512], [u8; 256]) {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u8 = 1;
+    let mut i = 0;
+
+    while i < 255 {
+        exp[i] = x;
+        exp[i + 255] = x;
+        log[x as usize] = i as u8;
+        x = (x << 1) ^ if (x & 0x80) != 0 { GF_POLYNOMIAL } else { 0 };
+        i += 1;
+    }
+
+    (exp, log)
+}
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let (exp, log) = &GF_TABLES;
+    exp[log[a as usize] as usize + log[b as usize] as usize]
+}
+
+fn gf_inv(a: u8) -> u8 {
+    let (exp, log) = &GF_TABLES;
+    exp[255 - log[a as usize] as usize]
+}
+
+fn gf_pow(a: u8, n: usize) -> u8 {
+    let (exp, log) = &GF_TABLES;
+    exp[(log[a as usize] as usize * n) % 255]
+}
+
+/** Returns the coefficient of a data column in a parity column.
+ *
+ * - The first data column has the highest power, as parity is generated by
+ *   multiplying by the generator before adding each data column.
+ */
+fn coefficient(parity: usize, n_data: usize, data: usize) -> u8 {
+    gf_pow(PARITY_GENERATORS[parity], n_data - 1 - data)
+}
+
+/// Adds `factor * source` to `target`, up to the shorter length.
+fn add_mul(target: &mut [u8], source: &[u8], factor: u8) {
+    for (t, s) in target.iter_mut().zip(source.iter()) {
+        *t ^= gf_mul(factor, *s);
+    }
+}
+
+/// Inverts a square matrix, or returns [`None`] if it is singular.
+fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<u8>> = (0..n)
+        .map(|r| (0..n).map(|c| if r == c { 1 } else { 0 }).collect())
+        .collect();
+
+    for column in 0..n {
+        let pivot = (column..n).find(|r| matrix[*r][column] != 0)?;
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = gf_inv(matrix[column][column]);
+        for c in 0..n {
+            matrix[column][c] = gf_mul(matrix[column][c], scale);
+            inverse[column][c] = gf_mul(inverse[column][c], scale);
+        }
+
+        for row in 0..n {
+            let factor = matrix[row][column];
+            if row == column || factor == 0 {
+                continue;
+            }
+            for c in 0..n {
+                let (m, i) = (matrix[column][c], inverse[column][c]);
+                matrix[row][c] ^= gf_mul(factor, m);
+                inverse[row][c] ^= gf_mul(factor, i);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/** Calls `f` for each combination of `k` items, until it returns true.
+ *
+ * - Returns true if `f` returned true.
+ */
+fn for_each_combination(items: &[usize], k: usize, f: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+    fn recurse(
+        items: &[usize],
+        k: usize,
+        start: usize,
+        chosen: &mut Vec<usize>,
+        f: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if chosen.len() == k {
+            return f(chosen);
+        }
+
+        for (index, item) in items.iter().enumerate().skip(start) {
+            chosen.push(*item);
+            if recurse(items, k, index + 1, chosen, f) {
+                return true;
+            }
+            chosen.pop();
+        }
+
+        false
+    }
+
+    recurse(items, k, 0, &mut Vec::with_capacity(k), f)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl RaidZMap {
+    /// Returns the byte length of the data columns.
+    pub fn data_length(&self) -> u64 {
+        self.data_columns().iter().map(|column| column.length).sum()
+    }
+
+    /** Generates the parity columns of the data of a block.
+     *
+     * - C reference: `vdev_raidz_generate_parity`
+     * - `data` has the length of [`RaidZMap::data_length`].
+     *
+     * # Errors
+     *
+     * Returns [`ReadError`] if `data` has the wrong length.
+     */
+    pub fn parity(&self, data: &[u8]) -> Result<Vec<Vec<u8>>, ReadError> {
+        let expected = self.data_length();
+        if data.len() as u64 != expected {
+            return Err(ReadError::InvalidLength {
+                length: data.len(),
+                expected: expected,
+            });
+        }
+
+        let data_columns = self.split(data);
+        let n_data = data_columns.len();
+
+        Ok((0..self.n_parity)
+            .map(|parity| {
+                let mut column = vec![0u8; self.columns[parity].length as usize];
+                for (index, data_column) in data_columns.iter().enumerate() {
+                    add_mul(&mut column, data_column, coefficient(parity, n_data, index));
+                }
+                column
+            })
+            .collect())
+    }
+
+    /// Splits the data of a block into its data columns.
+    fn split<'a>(&self, data: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut columns = Vec::with_capacity(self.columns.len() - self.n_parity);
+        let mut start = 0;
+
+        for column in self.data_columns() {
+            let end = start + column.length as usize;
+            columns.push(&data[start..end]);
+            start = end;
+        }
+
+        columns
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** A block read from a RAID-Z vdev.
+ */
+#[derive(Debug)]
+pub struct RaidZRead {
+    /// Data of the block, which is the data columns in order.
+    pub data: Vec<u8>,
+
+    /// Indices of the columns of the map that were missing or damaged.
+    pub bad_columns: Vec<usize>,
+}
+
+/** Reads a block from a RAID-Z vdev, and reconstructs damaged columns using
+ * parity.
+ *
+ * - C reference: `vdev_raidz_combrec`, `vdev_raidz_reconstruct_general`
+ * - `verify` checks the data of the block, usually with the checksum of the
+ *   block pointer. The data has the length of [`RaidZMap::data_length`], so
+ *   it may need to be truncated to the physical size of the block.
+ * - Columns that cannot be read are missing. If the data does not verify,
+ *   then combinations of up to `n_parity` missing or damaged columns are
+ *   reconstructed, until the data verifies.
+ *
+ * # Errors
+ *
+ * Returns [`ReadError`] if too many columns are missing, or no
+ * reconstruction verifies.
+ *
+ * # Examples
+ *
+ * Basic usage:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Dva, VdevTree};
+ * use zfs::read::{read_raidz, DvaMap, LeafDevices};
+ *
+ * let text = r#"{
+ *     "vdev_tree": { "nvlist": {
+ *         "type": { "string": "raidz" },
+ *         "id": { "uint64": 0 },
+ *         "guid": { "uint64": 100 },
+ *         "nparity": { "uint64": 2 },
+ *         "ashift": { "uint64": 9 },
+ *         "asize": { "uint64": 1073741824 },
+ *         "metaslab_array": { "uint64": 256 },
+ *         "metaslab_shift": { "uint64": 29 },
+ *         "children": { "nvlist_array": [
+ *             { "type": { "string": "file" }, "id": { "uint64": 0 },
+ *               "guid": { "uint64": 101 }, "path": { "string": "/a" } },
+ *             { "type": { "string": "file" }, "id": { "uint64": 1 },
+ *               "guid": { "uint64": 102 }, "path": { "string": "/b" } },
+ *             { "type": { "string": "file" }, "id": { "uint64": 2 },
+ *               "guid": { "uint64": 103 }, "path": { "string": "/c" } },
+ *             { "type": { "string": "file" }, "id": { "uint64": 3 },
+ *               "guid": { "uint64": 104 }, "path": { "string": "/d" } }
+ *         ] }
+ *     } }
+ * }"#;
+ *
+ * let data = nv_json::to_bytes(text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * // Map a block of 1024 bytes.
+ * let dva = Dva { vdev: 0, grid: 0, asize: 4, offset: 0, is_gang: false };
+ * let map = match DvaMap::from_dva(&vdev_tree, &dva, 1024).unwrap() {
+ *     DvaMap::RaidZ(v) => v,
+ *     _ => panic!("expected a raidz"),
+ * };
+ *
+ * // Write the parity and data columns.
+ * let block: Vec<u8> = (0..1024).map(|i| (i * 7 + i / 256) as u8).collect();
+ * let mut columns = map.parity(&block).unwrap();
+ * columns.push(block[..512].to_vec());
+ * columns.push(block[512..].to_vec());
+ *
+ * let mut devices = LeafDevices::new();
+ * for guid in 101..=104 {
+ *     devices.insert(guid, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ * }
+ * for (column, bytes) in map.columns.iter().zip(columns.iter()) {
+ *     match &column.map {
+ *         DvaMap::Leaf(read) => devices.write(read, bytes).unwrap(),
+ *         _ => panic!("expected a leaf"),
+ *     }
+ * }
+ *
+ * // Lose the device of the first data column, and damage the second.
+ * devices.remove(103);
+ * devices.get_mut(104).unwrap().data_mut()[Dva::LEAF_OFFSET as usize] ^= 0xff;
+ *
+ * let verify = |data: &[u8]| data == &block[..];
+ * let read = read_raidz(&map, &devices, &verify).unwrap();
+ * assert_eq!(read.data, block);
+ * assert_eq!(read.bad_columns, [2, 3]);
+ * ```
+ *
+ * RAID-Z3 reconstructs three damaged data columns, with the P, Q, and R
+ * parity:
+ *
+ * ```
+ * use zfs::block_device::MemoryDevice;
+ * use zfs::endian::Endian;
+ * use zfs::nv::{Decoder, Encoding, Unique};
+ * use zfs::nv_json;
+ * use zfs::phys::{Dva, VdevTree};
+ * use zfs::read::{read_raidz, DvaMap, LeafDevices};
+ *
+ * let children: Vec<String> = (0..6)
+ *     .map(|id| {
+ *         format!(
+ *             r#"{{ "type": {{ "string": "file" }}, "id": {{ "uint64": {id} }},
+ *                 "guid": {{ "uint64": {} }}, "path": {{ "string": "/{id}" }} }}"#,
+ *             101 + id,
+ *         )
+ *     })
+ *     .collect();
+ * let text = format!(
+ *     r#"{{
+ *         "vdev_tree": {{ "nvlist": {{
+ *             "type": {{ "string": "raidz" }},
+ *             "id": {{ "uint64": 0 }},
+ *             "guid": {{ "uint64": 100 }},
+ *             "nparity": {{ "uint64": 3 }},
+ *             "ashift": {{ "uint64": 9 }},
+ *             "asize": {{ "uint64": 1073741824 }},
+ *             "metaslab_array": {{ "uint64": 256 }},
+ *             "metaslab_shift": {{ "uint64": 29 }},
+ *             "children": {{ "nvlist_array": [{}] }}
+ *         }} }}
+ *     }}"#,
+ *     children.join(", "),
+ * );
+ *
+ * let data = nv_json::to_bytes(&text, Encoding::Xdr, Endian::Little, Unique::Name).unwrap();
+ * let decoder = Decoder::from_bytes(&data).unwrap();
+ * let vdev_tree = VdevTree::from_decoder(&decoder).unwrap();
+ *
+ * // Map a block of three sectors, one for each data column.
+ * let dva = Dva { vdev: 0, grid: 0, asize: 8, offset: 0, is_gang: false };
+ * let map = match DvaMap::from_dva(&vdev_tree, &dva, 1536).unwrap() {
+ *     DvaMap::RaidZ(v) => v,
+ *     _ => panic!("expected a raidz"),
+ * };
+ * assert_eq!((map.n_parity, map.data_columns().len()), (3, 3));
+ *
+ * // Write the parity and data columns.
+ * let block: Vec<u8> = (0..1536).map(|i| (i * 13 + i / 256) as u8).collect();
+ * let mut columns = map.parity(&block).unwrap();
+ * columns.extend(block.chunks(512).map(|v| v.to_vec()));
+ *
+ * let mut devices = LeafDevices::new();
+ * for guid in 101..=106 {
+ *     devices.insert(guid, MemoryDevice::new(vec![0u8; 8 * 1024 * 1024]));
+ * }
+ * for (column, bytes) in map.columns.iter().zip(columns.iter()) {
+ *     match &column.map {
+ *         DvaMap::Leaf(read) => devices.write(read, bytes).unwrap(),
+ *         _ => panic!("expected a leaf"),
+ *     }
+ * }
+ *
+ * // Damage all three data columns.
+ * for guid in 104..=106 {
+ *     devices.get_mut(guid).unwrap().data_mut()[Dva::LEAF_OFFSET as usize + 100] ^= 0x5a;
+ * }
+ *
+ * let verify = |data: &[u8]| data == &block[..];
+ * let read = read_raidz(&map, &devices, &verify).unwrap();
+ * assert_eq!(read.data, block);
+ * assert_eq!(read.bad_columns, [3, 4, 5]);
+ * ```
+ */
+pub fn read_raidz<D: BlockDevice>(
+    map: &RaidZMap,
+    devices: &LeafDevices<D>,
+    verify: &dyn Fn(&[u8]) -> bool,
+) -> Result<RaidZRead, ReadError> {
+    ////////////////////////////////
+    // Read columns.
+    let columns: Vec<Option<Vec<u8>>> = map
+        .columns
+        .iter()
+        .map(|column| read_column(&column.map, devices))
+        .collect();
+
+    let missing: Vec<usize> = (0..columns.len())
+        .filter(|index| columns[*index].is_none())
+        .collect();
+    let present: Vec<usize> = (0..columns.len())
+        .filter(|index| columns[*index].is_some())
+        .collect();
+
+    if missing.len() > map.n_parity {
+        return Err(ReadError::Unrecoverable {
+            missing: missing.len(),
+            n_parity: map.n_parity,
+        });
+    }
+
+    ////////////////////////////////
+    // Reconstruct the missing columns, and then also combinations of more
+    // and more columns that were read, but may be damaged.
+    for extra in 0..=(map.n_parity - missing.len()) {
+        let mut found: Option<Vec<u8>> = None;
+
+        for_each_combination(&present, extra, &mut |combination| {
+            let mut bad = missing.clone();
+            bad.extend_from_slice(combination);
+
+            // Without data columns to reconstruct, the data is as read.
+            if extra > 0 && bad.iter().all(|index| *index < map.n_parity) {
+                return false;
+            }
+
+            match reconstruct(map, &columns, &bad) {
+                Some(data) if verify(&data) => {
+                    found = Some(data);
+                    true
+                }
+                _ => false,
+            }
+        });
+
+        if let Some(data) = found {
+            let bad_columns = find_bad_columns(map, &columns, &data);
+            return Ok(RaidZRead {
+                data: data,
+                bad_columns: bad_columns,
+            });
+        }
+    }
+
+    Err(ReadError::ChecksumMismatch {})
+}
+
+/** Reads a column, or returns [`None`] if it cannot be read.
+ *
+ * - For a column on a replacing or spare vdev, the first copy that can be
+ *   read is used, as a column cannot be verified by itself.
+ */
+fn read_column<D: BlockDevice>(map: &DvaMap, devices: &LeafDevices<D>) -> Option<Vec<u8>> {
+    match map {
+        DvaMap::Leaf(read) => devices.read(read).ok(),
+        DvaMap::Missing { guid: _ } => None,
+        DvaMap::Mirror(copies) => copies.iter().find_map(|copy| read_column(copy, devices)),
+        DvaMap::RaidZ(_) | DvaMap::Draid(_) | DvaMap::Indirect(_) => None,
+    }
+}
+
+/** Reconstructs the data of a block, assuming the `bad` columns are missing
+ * or damaged.
+ *
+ * - Returns [`None`] if there is not enough parity.
+ */
+fn reconstruct(map: &RaidZMap, columns: &[Option<Vec<u8>>], bad: &[usize]) -> Option<Vec<u8>> {
+    let n_parity = map.n_parity;
+    let n_data = columns.len() - n_parity;
+
+    ////////////////////////////////
+    // Data columns to reconstruct, and parity columns to use.
+    let mut targets: Vec<usize> = bad
+        .iter()
+        .filter(|index| **index >= n_parity)
+        .copied()
+        .collect();
+    targets.sort_unstable();
+
+    let rows: Vec<usize> = (0..n_parity)
+        .filter(|index| !bad.contains(index))
+        .take(targets.len())
+        .collect();
+
+    if rows.len() < targets.len() {
+        return None;
+    }
+
+    ////////////////////////////////
+    // Good data columns.
+    let mut data_columns: Vec<Vec<u8>> = (n_parity..columns.len())
+        .map(|index| match &columns[index] {
+            Some(column) if !targets.contains(&index) => column.clone(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    if !targets.is_empty() {
+        ////////////////////////////////
+        // Remove the good data columns from the parity columns, which leaves
+        // the sum of the target columns.
+        let mut syndromes: Vec<Vec<u8>> = Vec::with_capacity(rows.len());
+        for parity in &rows {
+            let mut syndrome = columns[*parity].clone()?;
+            for (data, column) in data_columns.iter().enumerate() {
+                if !targets.contains(&(data + n_parity)) {
+                    add_mul(&mut syndrome, column, coefficient(*parity, n_data, data));
+                }
+            }
+            syndromes.push(syndrome);
+        }
+
+        ////////////////////////////////
+        // Solve for the target columns.
+        let matrix = rows
+            .iter()
+            .map(|parity| {
+                targets
+                    .iter()
+                    .map(|target| coefficient(*parity, n_data, target - n_parity))
+                    .collect()
+            })
+            .collect();
+        let inverse = invert(matrix)?;
+
+        for (t, target) in targets.iter().enumerate() {
+            let mut column = vec![0u8; syndromes[0].len()];
+            for (r, syndrome) in syndromes.iter().enumerate() {
+                add_mul(&mut column, syndrome, inverse[t][r]);
+            }
+            column.truncate(map.columns[*target].length as usize);
+            data_columns[target - n_parity] = column;
+        }
+    }
+
+    Some(data_columns.concat())
+}
+
+/** Returns the indices of the columns that were missing, or differ from the
+ * verified data and its parity.
+ */
+fn find_bad_columns(map: &RaidZMap, columns: &[Option<Vec<u8>>], data: &[u8]) -> Vec<usize> {
+    let parity = match map.parity(data) {
+        Ok(v) => v,
+        Err(_) => return (0..columns.len()).collect(),
+    };
+
+    let data_columns = map.split(data);
+    let expected = parity
+        .iter()
+        .map(|column| column.as_slice())
+        .chain(data_columns);
+
+    columns
+        .iter()
+        .zip(expected)
+        .enumerate()
+        .filter(|(_, (column, expected))| column.as_deref() != Some(*expected))
+        .map(|(index, _)| index)
+        .collect()
+}
@@ -10,6 +10,8 @@
  *   padded to a multiple of four. The length does not include the padding.
  * - [`Decoder`] uses an internal [`Cell`] field for the `offset` field
  *   in order to implement a split borrow.
+ * - [`Decoder::from_bytes`] is lenient, and does not check the contents of
+ *   padding bytes. [`Decoder::from_bytes_strict`] errors on non-zero padding.
  */
 
 use core::cell::Cell;
@@ -26,9 +28,11 @@ use std::error;
 
 /** An XDR decoder.
  */
+#[derive(Clone)]
 pub struct Decoder<'a> {
     data: &'a [u8],
     offset: Cell<usize>,
+    strict: bool,
 }
 
 impl fmt::Debug for Decoder<'_> {
@@ -37,11 +41,12 @@ impl fmt::Debug for Decoder<'_> {
         f.debug_struct("Decoder")
             .field("length", &self.data.len())
             .field("offset", &self.offset.get())
+            .field("strict", &self.strict)
             .finish()
     }
 }
 
-impl Decoder<'_> {
+impl<'a> Decoder<'a> {
     /** Instantiate a [`Decoder`] from a slice of bytes.
      *
      * # Examples
@@ -75,6 +80,40 @@ impl Decoder<'_> {
         Decoder {
             data: data,
             offset: Cell::new(0),
+            strict: false,
+        }
+    }
+
+    /** Instantiate a strict [`Decoder`] from a slice of bytes.
+     *
+     * A strict decoder returns [`DecodeError::NonZeroPadding`] if the padding
+     * bytes used for alignment are not all zero.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Decoder;
+     *
+     * // String "abc" with non-zero padding.
+     * let data = &[0x00, 0x00, 0x00, 0x03, 0x61, 0x62, 0x63, 0xff];
+     *
+     * // Lenient decoder ignores padding.
+     * let decoder = Decoder::from_bytes(data);
+     * assert_eq!(decoder.get_str().unwrap(), "abc");
+     *
+     * // Strict decoder checks padding.
+     * let decoder = Decoder::from_bytes_strict(data);
+     * assert!(decoder.is_strict());
+     * assert!(decoder.get_str().is_err());
+     * ```
+     */
+    pub fn from_bytes_strict(data: &[u8]) -> Decoder<'_> {
+        Decoder {
+            data: data,
+            offset: Cell::new(0),
+            strict: true,
         }
     }
 
@@ -112,7 +151,13 @@ impl Decoder<'_> {
         // Check bounds for padding.
         self.check_need(padding)?;
 
-        // TODO(cybojanek): Validate that padding is all zeros?
+        // Check that padding is all zeros.
+        if self.strict && self.data[offset..offset + padding].iter().any(|x| *x != 0) {
+            return Err(DecodeError::NonZeroPadding {
+                offset: offset,
+                length: padding,
+            });
+        }
 
         // Skip the padding.
         self.offset.set(offset + padding);
@@ -150,6 +195,11 @@ impl Decoder<'_> {
         self.data.len()
     }
 
+    /// Returns the source data.
+    pub(crate) fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
     /** Returns the current offset into the source data.
      *
      * # Examples
@@ -204,6 +254,12 @@ impl Decoder<'_> {
         self.len() == 0
     }
 
+    /** Returns true if the decoder was created with [`Decoder::from_bytes_strict`].
+     */
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
     /** Returns length of bytes remaining to be processed.
      *
      * # Examples
@@ -560,7 +616,7 @@ impl Decoder<'_> {
      * assert!(decoder.get_n_bytes(8).is_err());
      * ```
      */
-    pub fn get_n_bytes(&self, length: usize) -> Result<&[u8], DecodeError> {
+    pub fn get_n_bytes(&self, length: usize) -> Result<&'a [u8], DecodeError> {
         // Check bounds for length.
         self.check_need(length)?;
 
@@ -704,7 +760,7 @@ impl Decoder<'_> {
      * assert!(decoder.get_bytes().is_err());
      * ```
      */
-    pub fn get_bytes(&self) -> Result<&[u8], DecodeError> {
+    pub fn get_bytes(&self) -> Result<&'a [u8], DecodeError> {
         let length = self.get_usize()?;
         self.get_n_bytes(length)
     }
@@ -1332,7 +1388,7 @@ impl Decoder<'_> {
      * assert!(decoder.get_str().is_err());
      * ```
      */
-    pub fn get_str(&self) -> Result<&str, DecodeError> {
+    pub fn get_str(&self) -> Result<&'a str, DecodeError> {
         let length = self.get_usize()?;
         let offset = self.offset.get();
         let data = self.get_n_bytes(length)?;
@@ -1488,6 +1544,614 @@ impl GetFromDecoder for usize {
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/** An XDR encoder.
+ */
+pub struct Encoder<'a> {
+    data: &'a mut [u8],
+    offset: usize,
+}
+
+impl fmt::Debug for Encoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Change debug printing to print length instead of raw data.
+        f.debug_struct("Encoder")
+            .field("length", &self.data.len())
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl Encoder<'_> {
+    /** Instantiate an [`Encoder`] for a mutable slice of bytes.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0; 8];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     * assert_eq!(encoder.available(), 8);
+     *
+     * // Encode values.
+     * encoder.put_bool(true).unwrap();
+     * encoder.put_bool(false).unwrap();
+     *
+     * assert_eq!(encoder.is_full(), true);
+     * assert_eq!(data, &[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+     * ```
+     */
+    pub fn to_bytes(data: &mut [u8]) -> Encoder<'_> {
+        Encoder {
+            data: data,
+            offset: 0,
+        }
+    }
+
+    /** Checks if there is enough space in data slice to encode.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    fn check_need(&self, count: usize) -> Result<(), EncodeError> {
+        if self.available() >= count {
+            Ok(())
+        } else {
+            Err(EncodeError::EndOfOutput {
+                offset: self.offset,
+                length: self.data.len(),
+                count: count,
+            })
+        }
+    }
+
+    /** Puts zero padding to align offset to a multiple of 4.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    fn put_padding(&mut self) -> Result<(), EncodeError> {
+        // Compute padding.
+        let remainder = self.offset % 4;
+        let padding = if remainder == 0 { 0 } else { 4 - remainder };
+
+        // Check bounds for padding.
+        self.check_need(padding)?;
+
+        // Put the padding.
+        let start = self.offset;
+        let end = start + padding;
+
+        self.data[start..end].fill(0);
+        self.offset = end;
+
+        Ok(())
+    }
+
+    /** Returns the number of bytes still available for encoding in data slice.
+     */
+    pub fn available(&self) -> usize {
+        // Gracefully handle offset errors, and just return 0.
+        match self.data.len().checked_sub(self.offset) {
+            Some(v) => v,
+            None => 0,
+        }
+    }
+
+    /** Returns the destination data capacity.
+     *
+     * Remains unchanged while encoding values.
+     */
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /** Returns true if there is no more space for values to be encoded.
+     */
+    pub fn is_full(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    /** Returns true if no values have been encoded.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.offset == 0
+    }
+
+    /** Returns the length of the encoded values.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * let data = &mut [0; 32];
+     * let mut encoder = Encoder::to_bytes(data);
+     * assert_eq!(encoder.len(), 0);
+     *
+     * encoder.put_str("abc").unwrap();
+     * assert_eq!(encoder.len(), 8);
+     *
+     * encoder.put_u64(0xfedcba9876543210).unwrap();
+     * assert_eq!(encoder.len(), 16);
+     * ```
+     */
+    pub fn len(&self) -> usize {
+        self.offset
+    }
+
+    /** Seeks the encoder to the specified offset of the data.
+     *
+     * Used to overwrite previously encoded values, such as sizes that are
+     * only known after encoding the values that follow them.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if the offset is past the end of the data, or is
+     * not a multiple of 4.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0; 8];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     *
+     * // Encode values.
+     * encoder.put_u32(0).unwrap();
+     * encoder.put_u32(2).unwrap();
+     *
+     * // Overwrite first value.
+     * encoder.seek(0).unwrap();
+     * encoder.put_u32(1).unwrap();
+     *
+     * assert_eq!(data, &[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02]);
+     * ```
+     */
+    pub fn seek(&mut self, offset: usize) -> Result<(), EncodeError> {
+        if offset > self.data.len() || offset & 3 != 0 {
+            return Err(EncodeError::InvalidOffset {
+                offset: offset,
+                length: self.data.len(),
+            });
+        }
+
+        self.offset = offset;
+
+        Ok(())
+    }
+
+    /** Encodes 4 bytes.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    fn put_4_bytes(&mut self, data: [u8; 4]) -> Result<(), EncodeError> {
+        self.check_need(4)?;
+
+        let start = self.offset;
+        let end = start + 4;
+
+        self.offset = end;
+
+        self.data[start..end].copy_from_slice(&data);
+
+        Ok(())
+    }
+
+    /** Encodes 8 bytes.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    fn put_8_bytes(&mut self, data: [u8; 8]) -> Result<(), EncodeError> {
+        self.check_need(8)?;
+
+        let start = self.offset;
+        let end = start + 8;
+
+        self.offset = end;
+
+        self.data[start..end].copy_from_slice(&data);
+
+        Ok(())
+    }
+
+    /** Encodes bytes, without a length.
+     *
+     * Puts zero padding if length is not a multiple of 4.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0xff; 8];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     *
+     * // Encode value.
+     * encoder.put_n_bytes(&[0x12, 0x34, 0x56, 0x78, 0x61]).unwrap();
+     *
+     * assert_eq!(data, &[0x12, 0x34, 0x56, 0x78, 0x61, 0x00, 0x00, 0x00]);
+     * ```
+     *
+     * Truncated padding:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0; 7];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     *
+     * // Need 1 more byte for padding.
+     * assert!(encoder.put_n_bytes(&[0x12, 0x34, 0x56, 0x78, 0x61]).is_err());
+     * ```
+     */
+    pub fn put_n_bytes(&mut self, data: &[u8]) -> Result<(), EncodeError> {
+        // Check bounds for length.
+        let length = data.len();
+        self.check_need(length)?;
+
+        // Start and end of bytes.
+        let start = self.offset;
+        let end = start + length;
+
+        // Put bytes.
+        self.data[start..end].copy_from_slice(data);
+        self.offset = end;
+
+        // Put padding.
+        self.put_padding()
+    }
+
+    /** Encodes a [`bool`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_bool(&mut self, value: bool) -> Result<(), EncodeError> {
+        self.put_u32(match value {
+            true => 1,
+            false => 0,
+        })
+    }
+
+    /** Encodes a [`&[u8]`], preceded by its length.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available, or the
+     * length does not fit in a [`u32`].
+     */
+    pub fn put_bytes(&mut self, data: &[u8]) -> Result<(), EncodeError> {
+        self.put_usize(data.len())?;
+        self.put_n_bytes(data)
+    }
+
+    /** Encodes an [`f32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_f32(&mut self, value: f32) -> Result<(), EncodeError> {
+        self.put_4_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes an [`f64`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_f64(&mut self, value: f64) -> Result<(), EncodeError> {
+        self.put_8_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes an [`i8`] as an [`i32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0; 8];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     *
+     * // Encode values.
+     * encoder.put_i8(-128).unwrap();
+     * encoder.put_i8(127).unwrap();
+     *
+     * assert_eq!(data, &[0xff, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0x7f]);
+     * ```
+     */
+    pub fn put_i8(&mut self, value: i8) -> Result<(), EncodeError> {
+        self.put_i32(i32::from(value))
+    }
+
+    /** Encodes an [`i16`] as an [`i32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i16(&mut self, value: i16) -> Result<(), EncodeError> {
+        self.put_i32(i32::from(value))
+    }
+
+    /** Encodes an [`i32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i32(&mut self, value: i32) -> Result<(), EncodeError> {
+        self.put_4_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes an [`i64`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_i64(&mut self, value: i64) -> Result<(), EncodeError> {
+        self.put_8_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes a [`u8`] as a [`u32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_u8(&mut self, value: u8) -> Result<(), EncodeError> {
+        self.put_u32(u32::from(value))
+    }
+
+    /** Encodes a [`u16`] as a [`u32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_u16(&mut self, value: u16) -> Result<(), EncodeError> {
+        self.put_u32(u32::from(value))
+    }
+
+    /** Encodes a [`u32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_u32(&mut self, value: u32) -> Result<(), EncodeError> {
+        self.put_4_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes a [`u64`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     */
+    pub fn put_u64(&mut self, value: u64) -> Result<(), EncodeError> {
+        self.put_8_bytes(value.to_be_bytes())
+    }
+
+    /** Encodes a [`usize`] as a [`u32`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available, or
+     * casting would be out of range for [`u32`].
+     */
+    pub fn put_usize(&mut self, value: usize) -> Result<(), EncodeError> {
+        match u32::try_from(value) {
+            Ok(v) => self.put_u32(v),
+            Err(e) => Err(EncodeError::UsizeConversion {
+                offset: self.offset,
+                value: value,
+                err: e,
+            }),
+        }
+    }
+
+    /** Encodes a [`str`].
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] if there are not enough bytes available.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::Encoder;
+     *
+     * // Some bytes.
+     * let data = &mut [0; 16];
+     *
+     * // Create encoder.
+     * let mut encoder = Encoder::to_bytes(data);
+     *
+     * // Encode values.
+     * encoder.put_str("abc").unwrap();
+     * encoder.put_str("de").unwrap();
+     *
+     * assert_eq!(
+     *     data,
+     *     &[
+     *         0x00, 0x00, 0x00, 0x03, 0x61, 0x62, 0x63, 0x00,
+     *         0x00, 0x00, 0x00, 0x02, 0x64, 0x65, 0x00, 0x00,
+     *     ]
+     * );
+     * ```
+     */
+    pub fn put_str(&mut self, value: &str) -> Result<(), EncodeError> {
+        self.put_bytes(value.as_bytes())
+    }
+
+    /** Encodes a value using the [`PutToEncoder`] trait for F.
+     *
+     * # Errors
+     *
+     * Returns [`EncodeError`] in case of encoding errors.
+     *
+     * # Examples
+     *
+     * Basic usage:
+     *
+     * ```
+     * use zfs::xdr::{Decoder, Encoder};
+     *
+     * // Some bytes.
+     * let data = &mut [0; 16];
+     *
+     * // Encode values.
+     * let mut encoder = Encoder::to_bytes(data);
+     * encoder.put(true).unwrap();
+     * encoder.put(-128i8).unwrap();
+     * encoder.put(0xf23456789abcdef0u64).unwrap();
+     *
+     * // Decode values.
+     * let decoder = Decoder::from_bytes(data);
+     * let a: bool = decoder.get().unwrap();
+     * let b: i8 = decoder.get().unwrap();
+     * let c: u64 = decoder.get().unwrap();
+     *
+     * assert_eq!(a, true);
+     * assert_eq!(b, -128);
+     * assert_eq!(c, 0xf23456789abcdef0);
+     * ```
+     */
+    pub fn put<F: PutToEncoder>(&mut self, value: F) -> Result<(), EncodeError> {
+        value.put_to_encoder(self)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/** [`PutToEncoder`] is a trait that puts the type to the [`Encoder`].
+ */
+pub trait PutToEncoder: Sized {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError>;
+}
+
+impl PutToEncoder for bool {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_bool(self)
+    }
+}
+
+impl PutToEncoder for f32 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_f32(self)
+    }
+}
+
+impl PutToEncoder for f64 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_f64(self)
+    }
+}
+
+impl PutToEncoder for i8 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_i8(self)
+    }
+}
+
+impl PutToEncoder for i16 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_i16(self)
+    }
+}
+
+impl PutToEncoder for i32 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_i32(self)
+    }
+}
+
+impl PutToEncoder for i64 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_i64(self)
+    }
+}
+
+impl PutToEncoder for u8 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_u8(self)
+    }
+}
+
+impl PutToEncoder for u16 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_u16(self)
+    }
+}
+
+impl PutToEncoder for u32 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_u32(self)
+    }
+}
+
+impl PutToEncoder for u64 {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_u64(self)
+    }
+}
+
+impl PutToEncoder for usize {
+    fn put_to_encoder(self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encoder.put_usize(self)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 #[derive(Debug)]
 pub enum DecodeError {
     /** End of input data.
@@ -1530,6 +2194,13 @@ pub enum DecodeError {
         err: core::str::Utf8Error,
     },
 
+    /** Non-zero padding bytes (strict mode only).
+     *
+     * - `offset` - Byte offset of padding.
+     * - `length` - Length of padding.
+     */
+    NonZeroPadding { offset: usize, length: usize },
+
     /** Size conversion error from [`i32`] to [`i8`].
      *
      * - `offset` - Byte offset of data.
@@ -1622,6 +2293,12 @@ impl fmt::Display for DecodeError {
                     "XDR invalid UTF8 str of length {length} at offset {offset} err {err}"
                 )
             }
+            DecodeError::NonZeroPadding { offset, length } => {
+                write!(
+                    f,
+                    "XDR non-zero padding of length {length} at offset {offset}"
+                )
+            }
             DecodeError::I8Conversion { offset, value, err } => {
                 write!(
                     f,
@@ -1697,3 +2374,78 @@ impl error::Error for DecodeError {
         }
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum EncodeError {
+    /** End of output data.
+     *
+     * - `offset` - Byte offset of data.
+     * - `length` - Total length of data.
+     * - `count`  - Number of bytes needed.
+     */
+    EndOfOutput {
+        offset: usize,
+        length: usize,
+        count: usize,
+    },
+
+    /** Invalid offset is past data, or not aligned.
+     *
+     * - `offset` - Byte offset of data.
+     * - `length` - Total length of data.
+     */
+    InvalidOffset { offset: usize, length: usize },
+
+    /** Size conversion error from [`usize`] to [`u32`].
+     *
+     * - `offset` - Byte offset of data.
+     * - `value`  - Value of failed conversion.
+     */
+    UsizeConversion {
+        offset: usize,
+        value: usize,
+        err: num::TryFromIntError,
+    },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::EndOfOutput {
+                offset,
+                length,
+                count,
+            } => {
+                write!(
+                    f,
+                    "XDR end of output at offset {offset}, need {count} bytes, total length {length}"
+                )
+            }
+            EncodeError::InvalidOffset { offset, length } => {
+                write!(f, "XDR invalid offset {offset}, total length {length}")
+            }
+            EncodeError::UsizeConversion { offset, value, err } => {
+                write!(
+                    f,
+                    "XDR usize conversion error at offset {offset}, value {value} err {err}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            EncodeError::UsizeConversion {
+                offset: _,
+                value: _,
+                err,
+            } => Some(err),
+            _ => None,
+        }
+    }
+}